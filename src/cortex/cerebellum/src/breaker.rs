@@ -0,0 +1,308 @@
+//! Circuit breaker for model backends (chat completions, embeddings).
+//!
+//! After `failure_threshold` consecutive failures the breaker opens and
+//! calls fail fast for `cooldown`. The first call after that is a probe
+//! (half-open): success closes the breaker, failure re-opens it.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::embedding::EmbeddingBackend;
+
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Returned instead of calling the backend while the breaker is open
+#[derive(Debug, Clone)]
+pub struct BreakerOpen {
+    pub backend: String,
+    pub retry_in: Duration,
+}
+
+impl std::fmt::Display for BreakerOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} backend circuit open; retry in {}s", self.backend, self.retry_in.as_secs().max(1))
+    }
+}
+
+impl std::error::Error for BreakerOpen {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerSnapshot {
+    pub backend: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+}
+
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+}
+
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name: name.into(),
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_in_flight: false,
+            }),
+        }
+    }
+
+    /// Fail fast without taking a probe: Err while calls would be refused
+    pub fn check(&self) -> Result<(), BreakerOpen> {
+        let inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::Open => {
+                let elapsed = inner.opened_at.map(|t| t.elapsed()).unwrap_or(self.cooldown);
+                match self.cooldown.checked_sub(elapsed) {
+                    Some(remaining) if !remaining.is_zero() => Err(self.open_error(remaining)),
+                    _ => Ok(()),
+                }
+            }
+            BreakerState::HalfOpen if inner.probe_in_flight => Err(self.open_error(Duration::ZERO)),
+            BreakerState::HalfOpen => Ok(()),
+        }
+    }
+
+    /// Permission to call the backend. Settle the permit with `success` or
+    /// `failure`; one dropped unsettled (a cancelled call) frees the probe
+    /// slot without counting either way.
+    pub fn acquire(&self) -> Result<BreakerPermit<'_>, BreakerOpen> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => {}
+            BreakerState::Open => {
+                let elapsed = inner.opened_at.map(|t| t.elapsed()).unwrap_or(self.cooldown);
+                if elapsed < self.cooldown {
+                    return Err(self.open_error(self.cooldown - elapsed));
+                }
+                inner.state = BreakerState::HalfOpen;
+                inner.probe_in_flight = true;
+            }
+            // Only one probe at a time
+            BreakerState::HalfOpen if inner.probe_in_flight => return Err(self.open_error(Duration::ZERO)),
+            BreakerState::HalfOpen => inner.probe_in_flight = true,
+        }
+        Ok(BreakerPermit { breaker: self, settled: false })
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != BreakerState::Closed {
+            tracing::info!("Breaker: {} backend recovered, closing circuit", self.name);
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_in_flight = false;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.probe_in_flight = false;
+
+        let trip = match inner.state {
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            BreakerState::Open => false,
+        };
+        if trip {
+            tracing::warn!(
+                "Breaker: {} backend failed {} time(s) in a row, opening circuit for {:?}",
+                self.name, inner.consecutive_failures, self.cooldown
+            );
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Run `call` through the breaker, counting any error as a backend failure
+    pub async fn call<T, F>(&self, call: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let permit = self.acquire()?;
+        match call.await {
+            Ok(value) => {
+                permit.success();
+                Ok(value)
+            }
+            Err(e) => {
+                permit.failure();
+                Err(e)
+            }
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.inner.lock().unwrap().state
+    }
+
+    pub fn snapshot(&self) -> BreakerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        BreakerSnapshot {
+            backend: self.name.clone(),
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+        }
+    }
+
+    fn open_error(&self, retry_in: Duration) -> BreakerOpen {
+        BreakerOpen { backend: self.name.clone(), retry_in }
+    }
+}
+
+/// One admitted backend call, settled exactly once
+#[must_use = "settle the permit with success() or failure()"]
+pub struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    settled: bool,
+}
+
+impl BreakerPermit<'_> {
+    pub fn success(mut self) {
+        self.settled = true;
+        self.breaker.record_success();
+    }
+
+    pub fn failure(mut self) {
+        self.settled = true;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        if !self.settled {
+            // Cancelled before an answer: let the next call probe instead
+            self.breaker.inner.lock().unwrap().probe_in_flight = false;
+        }
+    }
+}
+
+/// Embedding backend guarded by a breaker
+pub struct BreakerEmbedder {
+    inner: Arc<dyn EmbeddingBackend>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl BreakerEmbedder {
+    pub fn new(inner: Arc<dyn EmbeddingBackend>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for BreakerEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.breaker.call(self.inner.embed(text)).await
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn fail() -> Result<()> {
+        Err(anyhow::anyhow!("backend timeout"))
+    }
+
+    async fn succeed() -> Result<()> {
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_closed_open_half_open_closed() {
+        let breaker = CircuitBreaker::new("llm", 3, Duration::from_millis(50));
+
+        // Closed: failures below the threshold pass through
+        for _ in 0..2 {
+            assert!(breaker.call(fail()).await.is_err());
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        // Threshold reached: open and fail fast without calling the backend
+        assert!(breaker.call(fail()).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Open);
+        let err = breaker.call(succeed()).await.unwrap_err();
+        assert!(err.downcast_ref::<BreakerOpen>().is_some());
+        assert!(err.to_string().contains("llm backend circuit open"));
+
+        // After cooldown a single probe is let through
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(breaker.check().is_ok());
+        let probe = breaker.acquire().unwrap();
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.acquire().is_err());
+        assert!(breaker.check().is_err());
+
+        // Successful probe closes the circuit
+        probe.success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert_eq!(breaker.snapshot().consecutive_failures, 0);
+        assert!(breaker.call(succeed()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_reopens() {
+        let breaker = CircuitBreaker::new("embeddings", 1, Duration::from_millis(30));
+        assert!(breaker.call(fail()).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(breaker.call(fail()).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.acquire().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_probe_frees_the_slot() {
+        let breaker = CircuitBreaker::new("llm", 1, Duration::from_millis(30));
+        assert!(breaker.call(fail()).await.is_err());
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        // The probing call is cancelled mid-flight
+        let probe = breaker.call(std::future::pending::<Result<()>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), probe).await.is_err());
+
+        // Neither stuck half-open nor counted as a failure
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert_eq!(breaker.snapshot().consecutive_failures, 1);
+        assert!(breaker.call(succeed()).await.is_ok());
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
@@ -0,0 +1,221 @@
+//! Falling back to other models when the preferred one can't answer.
+//!
+//! A `ModelChain` lists models in order of preference, typically from the
+//! best to the cheapest. `think` asks each in turn until one answers within
+//! its timeout; the response names the models that failed, and confidence
+//! drops with every step down the chain. When none answers, the thought is
+//! answered from memory alone.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::llm::{Completion, CompletionOptions, LlmBackend, Prompt};
+
+pub const DEFAULT_MODEL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Confidence lost for each model that failed before one answered
+pub const FALLBACK_CONFIDENCE_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTier {
+    pub model: String,
+    /// Price per token relative to the other models in the chain
+    pub cost: f32,
+    /// Give up on this model after this long
+    pub timeout: Duration,
+}
+
+/// Models to try, most preferred first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelChain {
+    tiers: Vec<ModelTier>,
+}
+
+impl ModelChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Just `model`, nothing to fall back to
+    pub fn single(model: &str) -> Self {
+        Self::new().then(model, 1.0)
+    }
+
+    pub fn then(mut self, model: &str, cost: f32) -> Self {
+        self.tiers.push(ModelTier { model: model.to_string(), cost, timeout: DEFAULT_MODEL_TIMEOUT });
+        self
+    }
+
+    /// Per-model timeout for every model in the chain
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        for tier in &mut self.tiers {
+            tier.timeout = timeout;
+        }
+        self
+    }
+
+    pub fn tiers(&self) -> &[ModelTier] {
+        &self.tiers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    /// Comma-separated `model` or `model=cost`, most preferred first,
+    /// e.g. `gemma:7b=1.0,gemma:2b=0.3`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut chain = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (model, cost) = match entry.split_once('=') {
+                Some((model, cost)) => {
+                    let cost: f32 = cost.trim().parse().map_err(|_| anyhow!("Bad cost in model chain entry '{}'", entry))?;
+                    (model.trim(), cost)
+                }
+                None => (entry, 1.0),
+            };
+            chain = chain.then(model, cost);
+        }
+        Ok(chain)
+    }
+
+    /// `IPPOC_LLM_CHAIN`, with `IPPOC_LLM_CHAIN_TIMEOUT_SECS` per model.
+    /// None when unset or invalid (logged): callers pick a single model.
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("IPPOC_LLM_CHAIN").ok()?;
+        let chain = match Self::parse(&spec) {
+            Ok(chain) if !chain.is_empty() => chain,
+            Ok(_) => return None,
+            Err(e) => {
+                warn!("Ignoring IPPOC_LLM_CHAIN: {}", e);
+                return None;
+            }
+        };
+        let timeout = std::env::var("IPPOC_LLM_CHAIN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MODEL_TIMEOUT);
+        Some(chain.with_timeout(timeout))
+    }
+}
+
+/// A model that was tried and didn't answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedModel {
+    pub model: String,
+    pub error: String,
+}
+
+/// The answer, and what it took to get it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Served {
+    pub completion: Completion,
+    pub cost: f32,
+    /// Models tried before the one that answered, in order
+    pub failed: Vec<FailedModel>,
+}
+
+/// Ask each model of `chain` in turn until one answers. Err carries every
+/// failure when none did.
+pub async fn complete_with_fallback(
+    llm: &dyn LlmBackend,
+    chain: &ModelChain,
+    prompt: &Prompt,
+) -> std::result::Result<Served, Vec<FailedModel>> {
+    let mut failed = Vec::new();
+    for tier in chain.tiers() {
+        let attempt = tokio::time::timeout(tier.timeout, llm.complete(prompt, &CompletionOptions::new(&tier.model))).await;
+        let error = match attempt {
+            Ok(Ok(completion)) => {
+                if !failed.is_empty() {
+                    info!("Cerebrum: {} (cost {}) answered after {} model(s) failed", tier.model, tier.cost, failed.len());
+                }
+                return Ok(Served { completion, cost: tier.cost, failed });
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("no answer within {:?}", tier.timeout),
+        };
+        warn!("Cerebrum: model {} failed: {}", tier.model, error);
+        failed.push(FailedModel { model: tier.model.clone(), error });
+    }
+    Err(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::TokenUsage;
+    use crate::mock::{CannedMemory, CannedSearch};
+    use crate::{Cerebrum, Recollection, ThoughtRequest};
+    use async_trait::async_trait;
+
+    /// Serves only the models it's told are up
+    struct Flaky {
+        up: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl LlmBackend for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn complete(&self, _prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion> {
+            if !self.up.contains(&opts.model.as_str()) {
+                return Err(anyhow!("{} is overloaded", opts.model));
+            }
+            let usage = TokenUsage { model: opts.model.clone(), prompt_tokens: 10, completion_tokens: 5 };
+            Ok(Completion { text: format!("answer from {}", opts.model), usage })
+        }
+
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![0.0])
+        }
+    }
+
+    fn ask() -> ThoughtRequest {
+        ThoughtRequest { query: "how do nodes talk?".into(), context_history: vec![] }
+    }
+
+    #[tokio::test]
+    async fn test_second_model_answers_when_first_fails() {
+        let chain = ModelChain::parse("gemma:7b=1.0, gemma:2b=0.3").unwrap();
+        assert_eq!(chain.tiers()[1], ModelTier { model: "gemma:2b".into(), cost: 0.3, timeout: DEFAULT_MODEL_TIMEOUT });
+
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), CannedMemory::new(vec![]))
+            .with_llm_backend(Box::new(Flaky { up: vec!["gemma:2b"] }))
+            .with_model_chain(chain);
+        let response = brain.think(ask()).await.unwrap();
+
+        assert_eq!(response.answer, "answer from gemma:2b");
+        assert_eq!(response.usage.unwrap().model, "gemma:2b");
+        assert_eq!(response.fallbacks.len(), 1);
+        assert_eq!(response.fallbacks[0].model, "gemma:7b");
+        assert!(response.fallbacks[0].error.contains("overloaded"));
+        assert!((response.confidence - (0.8 - FALLBACK_CONFIDENCE_STEP)).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_memory_answers_when_every_model_fails() {
+        let memory = CannedMemory::new(vec![Recollection::new("Memory (conf: 0.90): the mesh uses QUIC", 0.9)]);
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), memory)
+            .with_llm_backend(Box::new(Flaky { up: vec![] }))
+            .with_model_chain(ModelChain::single("gemma:7b").then("gemma:2b", 0.3));
+        let response = brain.think(ask()).await.unwrap();
+
+        assert!(response.answer.starts_with("(memory only)"), "{}", response.answer);
+        assert!(response.usage.is_none());
+        assert_eq!(response.fallbacks.len(), 2);
+        assert_eq!(response.confidence, brain.degradation.policy.memory_only_confidence);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_cost() {
+        assert!(ModelChain::parse("gemma:7b=cheap").is_err());
+        assert!(ModelChain::parse(" , ").unwrap().is_empty());
+    }
+}
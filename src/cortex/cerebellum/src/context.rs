@@ -0,0 +1,185 @@
+//! Keeping a thought's prompt inside the model's context window.
+//!
+//! `ThoughtRequest.context_history` grows with the conversation. Before
+//! synthesis the history is fitted to what the model has left after the
+//! retrieved context, the query and room for the answer: the first entry
+//! (the system prompt) and the most recent turns are kept, and whatever falls
+//! in between is folded into a single summary line.
+
+use std::collections::HashMap;
+
+/// Rough characters per token for English text and code
+const CHARS_PER_TOKEN: usize = 4;
+/// Words of each dropped turn quoted in the summary line
+const SUMMARY_WORDS_PER_TURN: usize = 6;
+
+/// Estimated token count; errs high so the real prompt fits
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Context window per model, in tokens
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextBudget {
+    /// Window for models without an entry
+    pub default_window: usize,
+    /// Tokens left free for the model's answer
+    pub answer_reserve: usize,
+    windows: HashMap<String, usize>,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self::new(8192, 1024).with_model("gemma:2b", 8192).with_model("codegemma", 8192)
+    }
+}
+
+impl ContextBudget {
+    pub fn new(default_window: usize, answer_reserve: usize) -> Self {
+        Self { default_window, answer_reserve, windows: HashMap::new() }
+    }
+
+    pub fn with_model(mut self, model: &str, window: usize) -> Self {
+        self.windows.insert(model.to_string(), window);
+        self
+    }
+
+    pub fn window(&self, model: &str) -> usize {
+        self.windows.get(model).copied().unwrap_or(self.default_window)
+    }
+
+    /// Tokens the prompt may use with `model`
+    pub fn prompt_tokens(&self, model: &str) -> usize {
+        self.window(model).saturating_sub(self.answer_reserve)
+    }
+}
+
+/// `history` cut down to `budget` tokens (joined by newlines). The system
+/// prompt (first entry) is always kept, even alone over budget; then the
+/// newest turns that fit, with the turns dropped between them summarized in
+/// one line when there is room for it.
+pub fn fit_history(history: &[String], budget: usize) -> Vec<String> {
+    if estimate_tokens(&history.join("\n")) <= budget {
+        return history.to_vec();
+    }
+    let Some((system, turns)) = history.split_first() else {
+        return Vec::new();
+    };
+
+    // Every entry after the first costs its text plus a joining newline
+    let mut remaining = budget.saturating_sub(estimate_tokens(system));
+    let mut kept = Vec::new();
+    for turn in turns.iter().rev() {
+        let cost = estimate_tokens(turn) + 1;
+        if cost > remaining {
+            break;
+        }
+        remaining -= cost;
+        kept.push(turn.clone());
+    }
+    kept.reverse();
+
+    let dropped = &turns[..turns.len() - kept.len()];
+    let mut fitted = vec![system.clone()];
+    if let Some(summary) = summarize(dropped, remaining.saturating_sub(1)) {
+        fitted.push(summary);
+    }
+    fitted.extend(kept);
+    fitted
+}
+
+/// One line naming the dropped turns by their opening words, within `budget` tokens
+fn summarize(dropped: &[String], budget: usize) -> Option<String> {
+    if dropped.is_empty() {
+        return None;
+    }
+    let mut line = format!("[{} earlier messages omitted]", dropped.len());
+    if estimate_tokens(&line) > budget {
+        return None;
+    }
+    let mut quoted = Vec::new();
+    for turn in dropped {
+        let opening = turn.split_whitespace().take(SUMMARY_WORDS_PER_TURN).collect::<Vec<_>>().join(" ");
+        if opening.is_empty() {
+            continue;
+        }
+        quoted.push(format!("{}…", opening));
+        let extended = format!("[{} earlier messages omitted: {}]", dropped.len(), quoted.join("; "));
+        if estimate_tokens(&extended) > budget {
+            break;
+        }
+        line = extended;
+    }
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{CannedMemory, CannedSearch};
+    use crate::llm::{Completion, CompletionOptions, LlmBackend, Prompt, TokenUsage};
+    use crate::{Cerebrum, Recollection, ThoughtRequest};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Remembers the last prompt it was sent
+    #[derive(Clone, Default)]
+    struct Recorder {
+        prompt: Arc<Mutex<Prompt>>,
+    }
+
+    #[async_trait]
+    impl LlmBackend for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        async fn complete(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion> {
+            *self.prompt.lock().unwrap() = prompt.clone();
+            let usage = TokenUsage { model: opts.model.clone(), prompt_tokens: 0, completion_tokens: 1 };
+            Ok(Completion { text: "ok".to_string(), usage })
+        }
+
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![0.0])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_history_fits_the_window() {
+        let mut history = vec!["You are IPPOC, a sovereign AI node.".to_string()];
+        history.extend((0..400).map(|i| format!("turn {i}: {}", "the quick brown fox jumps over the lazy dog ".repeat(4))));
+
+        let budget = ContextBudget::new(4096, 512).with_model("gemma:2b", 2048);
+        let recorder = Recorder::default();
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), CannedMemory::new(vec![Recollection::new("Memory (conf: 0.50): foxes", 0.5)]))
+            .with_llm_backend(Box::new(recorder.clone()))
+            .with_context_budget(budget.clone());
+
+        brain
+            .think(ThoughtRequest { query: "what did the fox do?".into(), context_history: history.clone() })
+            .await
+            .unwrap();
+
+        let prompt = recorder.prompt.lock().unwrap().clone();
+        let used: usize = prompt.messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+        assert!(used <= budget.prompt_tokens("gemma:2b"), "{used} tokens");
+
+        // System prompt first, then the summary, newest turn last
+        let system = &prompt.messages[0].content;
+        let lines: Vec<&str> = system.lines().collect();
+        assert_eq!(lines[0], history[0]);
+        assert!(lines[1].starts_with("[") && lines[1].contains("earlier messages omitted"), "{}", lines[1]);
+        assert_eq!(*lines.last().unwrap(), history.last().unwrap().as_str());
+        assert!(system.contains("turn 398:"));
+    }
+
+    #[test]
+    fn test_history_within_budget_is_untouched() {
+        let history = vec!["system".to_string(), "hello".to_string()];
+        assert_eq!(fit_history(&history, 100), history);
+        // Nothing to keep but the system prompt
+        assert_eq!(fit_history(&["x".repeat(400), "hello".to_string()], 10), vec!["x".repeat(400)]);
+    }
+}
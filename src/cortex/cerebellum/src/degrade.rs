@@ -0,0 +1,143 @@
+//! Graceful degradation when the node runs out of cognitive budget.
+//!
+//! Before each thought the Cerebrum asks its `CognitiveBudget` for tokens.
+//! When the pool can't cover it, the thought is answered from memory alone:
+//! no search, no LLM call, and a lowered confidence, instead of a hard
+//! failure.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Source of cognitive tokens (the node's resource manager)
+#[async_trait]
+pub trait CognitiveBudget: Send + Sync {
+    /// Reserve `tokens` for one thought; false when the pool can't cover it
+    async fn reserve(&self, tokens: f64) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DegradationPolicy {
+    /// Tokens reserved per thought
+    pub tokens_per_thought: f64,
+    /// Confidence reported for memory-only answers
+    pub memory_only_confidence: f32,
+}
+
+impl Default for DegradationPolicy {
+    fn default() -> Self {
+        Self { tokens_per_thought: 50.0, memory_only_confidence: 0.3 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradationLevel {
+    /// Memory, search and LLM
+    Full,
+    /// Budget exhausted: recall only
+    MemoryOnly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DegradationStatus {
+    pub level: DegradationLevel,
+    pub degraded_thoughts: u64,
+    pub last_degraded_at: Option<DateTime<Utc>>,
+}
+
+pub struct Degradation {
+    pub policy: DegradationPolicy,
+    budget: Option<Arc<dyn CognitiveBudget>>,
+    level: Mutex<(DegradationLevel, Option<DateTime<Utc>>)>,
+    degraded: AtomicU64,
+}
+
+impl Degradation {
+    /// Never degrades
+    pub fn unbudgeted() -> Self {
+        Self::new(None, DegradationPolicy::default())
+    }
+
+    pub fn new(budget: Option<Arc<dyn CognitiveBudget>>, policy: DegradationPolicy) -> Self {
+        Self {
+            policy,
+            budget,
+            level: Mutex::new((DegradationLevel::Full, None)),
+            degraded: AtomicU64::new(0),
+        }
+    }
+
+    /// How much thinking the budget allows right now
+    pub async fn admit(&self) -> DegradationLevel {
+        let affordable = match &self.budget {
+            Some(budget) => budget.reserve(self.policy.tokens_per_thought).await,
+            None => true,
+        };
+
+        let mut level = self.level.lock().unwrap();
+        if affordable {
+            level.0 = DegradationLevel::Full;
+        } else {
+            if level.0 == DegradationLevel::Full {
+                tracing::warn!("Cerebrum: cognitive budget exhausted, answering from memory only");
+            }
+            *level = (DegradationLevel::MemoryOnly, Some(Utc::now()));
+            self.degraded.fetch_add(1, Ordering::Relaxed);
+        }
+        level.0
+    }
+
+    pub fn status(&self) -> DegradationStatus {
+        let (level, last_degraded_at) = *self.level.lock().unwrap();
+        DegradationStatus {
+            level,
+            degraded_thoughts: self.degraded.load(Ordering::Relaxed),
+            last_degraded_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cerebrum, ThoughtRequest};
+    use std::sync::atomic::AtomicBool;
+
+    struct Pool {
+        exhausted: AtomicBool,
+    }
+
+    #[async_trait]
+    impl CognitiveBudget for Pool {
+        async fn reserve(&self, _tokens: f64) -> bool {
+            !self.exhausted.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_budget_degrades_to_memory_only() {
+        let pool = Arc::new(Pool { exhausted: AtomicBool::new(true) });
+        let policy = DegradationPolicy::default();
+        let brain = Cerebrum::new(Arc::new(hidb::InMemoryStore::new())).with_budget(pool.clone(), policy);
+
+        let response = brain
+            .think(ThoughtRequest { query: "what is ippoc".into(), context_history: vec![] })
+            .await
+            .expect("degraded thinking still answers");
+
+        assert_eq!(response.confidence, policy.memory_only_confidence);
+        assert!(response.sources.is_empty());
+        let status = brain.degradation_status();
+        assert_eq!(status.level, DegradationLevel::MemoryOnly);
+        assert_eq!(status.degraded_thoughts, 1);
+
+        // Recovers as soon as tokens are back
+        pool.exhausted.store(false, Ordering::SeqCst);
+        assert_eq!(brain.degradation.admit().await, DegradationLevel::Full);
+        assert_eq!(brain.degradation_status().level, DegradationLevel::Full);
+    }
+}
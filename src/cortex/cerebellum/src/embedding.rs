@@ -0,0 +1,207 @@
+//! Embedding backends and a content-keyed cache in front of them.
+//!
+//! Loops like the Inner Voice embed the same prompts over and over; the cache
+//! makes every repeat of identical (whitespace-normalized) text free.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use lru::LruCache;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_EMBEDDING_DIM: usize = 1536;
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
+
+#[async_trait]
+pub trait EmbeddingBackend: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+
+    /// Model name stored with every vector; vectors from different models
+    /// must not be compared
+    fn model(&self) -> &str {
+        "unknown"
+    }
+}
+
+/// Zero vectors until a real embedding sidecar is wired in
+pub struct PlaceholderEmbedder {
+    dim: usize,
+}
+
+impl Default for PlaceholderEmbedder {
+    fn default() -> Self {
+        Self { dim: DEFAULT_EMBEDDING_DIM }
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for PlaceholderEmbedder {
+    async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+        Ok(vec![0.0; self.dim])
+    }
+
+    fn model(&self) -> &str {
+        "placeholder"
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+/// LRU cache in front of an `EmbeddingBackend`, optionally shared through Redis
+pub struct EmbeddingCache {
+    backend: Arc<dyn EmbeddingBackend>,
+    entries: Mutex<LruCache<String, Vec<f32>>>,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    #[cfg(feature = "redis-cache")]
+    redis: Option<redis::Client>,
+}
+
+impl EmbeddingCache {
+    pub fn new(backend: Arc<dyn EmbeddingBackend>, max_entries: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            backend,
+            entries: Mutex::new(LruCache::new(capacity)),
+            max_entries: capacity.get(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            #[cfg(feature = "redis-cache")]
+            redis: None,
+        }
+    }
+
+    /// Second-level cache shared by every node process using this Redis
+    #[cfg(feature = "redis-cache")]
+    pub fn with_redis(mut self, client: redis::Client) -> Self {
+        self.redis = Some(client);
+        self
+    }
+
+    pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let key = cache_key(text);
+
+        if let Some(hit) = self.entries.lock().unwrap().get(&key).cloned() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(hit);
+        }
+
+        #[cfg(feature = "redis-cache")]
+        if let Some(hit) = self.redis_get(&key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.entries.lock().unwrap().put(key, hit.clone());
+            return Ok(hit);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let embedding = self.backend.embed(text).await?;
+
+        #[cfg(feature = "redis-cache")]
+        self.redis_put(&key, &embedding).await;
+
+        self.entries.lock().unwrap().put(key, embedding.clone());
+        Ok(embedding)
+    }
+
+    pub fn model(&self) -> &str {
+        self.backend.model()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+            max_entries: self.max_entries,
+        }
+    }
+
+    // Redis is best effort: failures only cost a backend call
+
+    #[cfg(feature = "redis-cache")]
+    async fn redis_get(&self, key: &str) -> Option<Vec<f32>> {
+        use redis::AsyncCommands;
+        let client = self.redis.as_ref()?;
+        let mut conn = client.get_multiplexed_async_connection().await.ok()?;
+        let raw: Option<String> = conn.get(format!("embedding:{}:{key}", self.model())).await.ok()?;
+        serde_json::from_str(&raw?).ok()
+    }
+
+    #[cfg(feature = "redis-cache")]
+    async fn redis_put(&self, key: &str, embedding: &[f32]) {
+        use redis::AsyncCommands;
+        let Some(client) = self.redis.as_ref() else { return };
+        let Ok(mut conn) = client.get_multiplexed_async_connection().await else { return };
+        let Ok(value) = serde_json::to_string(embedding) else { return };
+        // Shared across processes that may run different models
+        if let Err(e) = conn.set_ex::<_, _, ()>(format!("embedding:{}:{key}", self.model()), value, 86_400).await {
+            tracing::debug!("Embedding cache: Redis write failed: {}", e);
+        }
+    }
+}
+
+/// SHA-256 of the text with runs of whitespace collapsed and ends trimmed
+fn cache_key(text: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingBackend {
+        calls: AtomicU64,
+    }
+
+    #[async_trait]
+    impl EmbeddingBackend for CountingBackend {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![text.len() as f32])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeat_embed_skips_backend() {
+        let backend = Arc::new(CountingBackend::default());
+        let cache = EmbeddingCache::new(backend.clone(), 8);
+
+        let first = cache.embed("what should I do next?").await.unwrap();
+        let second = cache.embed("  what should I\tdo   next?\n").await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1, max_entries: 8 });
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_is_evicted() {
+        let backend = Arc::new(CountingBackend::default());
+        let cache = EmbeddingCache::new(backend.clone(), 2);
+
+        cache.embed("a").await.unwrap();
+        cache.embed("b").await.unwrap();
+        cache.embed("a").await.unwrap(); // "b" is now least recent
+        cache.embed("c").await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 3);
+
+        cache.embed("a").await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 3);
+        cache.embed("b").await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 4);
+        assert_eq!(cache.stats().entries, 2);
+    }
+}
@@ -1,6 +1,24 @@
 use anyhow::Result;
+pub mod breaker;
+pub mod chain;
 pub mod chat;
+pub mod context;
+pub mod degrade;
+pub mod embedding;
+pub mod llm;
+pub mod mock;
+pub mod ratelimit;
+pub mod retry;
+use breaker::{BreakerEmbedder, BreakerSnapshot, CircuitBreaker};
+use chain::{FailedModel, ModelChain};
 use chat::ChatLobe;
+use context::ContextBudget;
+use degrade::{CognitiveBudget, Degradation, DegradationLevel, DegradationPolicy, DegradationStatus};
+use embedding::{CacheStats, EmbeddingBackend, EmbeddingCache, PlaceholderEmbedder};
+use llm::{LlmBackend, Prompt, TokenUsage};
+use ratelimit::{RateLimit, RateLimitSnapshot, RateLimitedEmbedder, RateLimiter};
+use retry::{RetryEmbedder, RetryPolicy};
+use async_trait::async_trait;
 use tracing::info;
 use serde::{Deserialize, Serialize};
 
@@ -15,36 +33,231 @@ pub struct ThoughtResponse {
     pub answer: String,
     pub confidence: f32,
     pub sources: Vec<String>,
+    /// What the model spent on this thought; None when no model answered
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+    /// Models that failed, in order, before one answered or memory took over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FailedModel>,
 }
 
-use hidb::HiDB;
+use hidb::{MemoryStore, Reembedder, SourceTrust, TrustAll};
 use std::sync::Arc;
 
+/// Where `think` looks things up
+#[async_trait]
+pub trait Search: Send + Sync {
+    async fn search(&self, query: &str) -> Result<Vec<SearchResult>>;
+}
+
+/// A recalled memory and how well it matches the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recollection {
+    pub text: String,
+    /// Similarity to the query (trust-weighted), higher is better
+    pub relevance: f32,
+}
+
+impl Recollection {
+    pub fn new(text: impl Into<String>, relevance: f32) -> Self {
+        Self { text: text.into(), relevance }
+    }
+}
+
+/// Recollections below this relevance are kept out of prompts
+pub const DEFAULT_RECALL_THRESHOLD: f32 = 0.3;
+
+/// What `think` remembers and recalls
+#[async_trait]
+pub trait Memory: Send + Sync {
+    /// Most relevant first
+    async fn recall(&self, query: &str) -> Result<Vec<Recollection>>;
+
+    async fn memorize(&self, query: &str, answer: &str) -> Result<()>;
+
+    /// Weight recalled memories by source; ignored by memories without sources
+    fn set_source_trust(&mut self, _trust: Arc<dyn SourceTrust>) {}
+
+    /// None for memories that don't embed
+    fn embedding_stats(&self) -> Option<CacheStats> {
+        None
+    }
+
+    /// Memories re-embedded under the current model
+    async fn reembed_stale(&self) -> Result<u64> {
+        Ok(0)
+    }
+}
+
 /// The Thinking Engine
 pub struct Cerebrum {
-    search: SearchLobe,
-    memories: MemoryLobe,
+    search: Box<dyn Search>,
+    memories: Box<dyn Memory>,
+    /// Synthesizes answers; an Err counts as a failure for `llm_breaker`
+    llm: Box<dyn LlmBackend>,
     pub chat: ChatLobe,
+    /// Guards the chat-completions backend
+    llm_breaker: Arc<CircuitBreaker>,
+    /// Guards the embeddings backend
+    embedding_breaker: Arc<CircuitBreaker>,
+    /// Paces outbound chat-completions, embedding and search calls
+    llm_limiter: Arc<RateLimiter>,
+    embedding_limiter: Arc<RateLimiter>,
+    search_limiter: Arc<RateLimiter>,
+    /// Falls back to memory-only answers when cognitive tokens run out
+    degradation: Degradation,
+    /// Context window per model; history beyond it is summarized
+    context_budget: ContextBudget,
+    /// Minimum relevance for a recollection to reach an answer
+    recall_threshold: f32,
+    /// Models to fall back through; None = one model picked per thought
+    model_chain: Option<ModelChain>,
 }
 
 impl Cerebrum {
-    pub fn new(hidb: Arc<HiDB>) -> Self {
+    pub fn new(memory: Arc<dyn MemoryStore>) -> Self {
+        Self::with_embedding_backend(memory, Arc::new(PlaceholderEmbedder::default()))
+    }
+
+    /// Embeddings from `backend` go through retry, the circuit breaker, the
+    /// rate limiter and the cache
+    pub fn with_embedding_backend(memory: Arc<dyn MemoryStore>, backend: Arc<dyn EmbeddingBackend>) -> Self {
+        let retry = RetryPolicy::from_env();
+        let guards = BackendGuards::from_env();
+        // A whole retry burst counts as one breaker failure; throttling counts as neither
+        let retried = Arc::new(RetryEmbedder::new(backend, retry));
+        let guarded = Arc::new(BreakerEmbedder::new(retried, guards.embedding_breaker.clone()));
+        let paced = Arc::new(RateLimitedEmbedder::new(guarded, guards.embedding_limiter.clone()));
+
+        let search = SearchLobe::new(retry, guards.search_limiter.clone());
+        let memories = MemoryLobe::new(memory, EmbeddingCache::new(paced, embedding::DEFAULT_CACHE_SIZE), retry);
+        Self::assemble(Box::new(search), Box::new(memories), guards)
+    }
+
+    /// Thinks with the given search and memory instead of the web and a
+    /// `MemoryStore`; the answer still comes from the configured LLM backend
+    /// unless `with_llm_backend` replaces it
+    pub fn new_with(search: impl Search + 'static, memory: impl Memory + 'static) -> Self {
+        Self::assemble(Box::new(search), Box::new(memory), BackendGuards::from_env())
+    }
+
+    fn assemble(search: Box<dyn Search>, memories: Box<dyn Memory>, guards: BackendGuards) -> Self {
         Self {
-            search: SearchLobe::new(),
-            memories: MemoryLobe::new(hidb),
+            search,
+            memories,
+            llm: llm::backend_from_env(),
             chat: ChatLobe::new(),
+            llm_breaker: guards.llm_breaker,
+            embedding_breaker: guards.embedding_breaker,
+            llm_limiter: guards.llm_limiter,
+            embedding_limiter: guards.embedding_limiter,
+            search_limiter: guards.search_limiter,
+            degradation: Degradation::unbudgeted(),
+            context_budget: ContextBudget::default(),
+            recall_threshold: DEFAULT_RECALL_THRESHOLD,
+            model_chain: ModelChain::from_env(),
         }
     }
 
+    /// Synthesize answers with `llm` instead of the backend chosen by `IPPOC_LLM_BACKEND`
+    pub fn with_llm_backend(mut self, llm: Box<dyn LlmBackend>) -> Self {
+        self.llm = llm;
+        self
+    }
+
+    /// Reserve cognitive tokens from `budget` before each thought, degrading
+    /// to memory-only answers when it runs dry
+    pub fn with_budget(mut self, budget: Arc<dyn CognitiveBudget>, policy: DegradationPolicy) -> Self {
+        self.degradation = Degradation::new(Some(budget), policy);
+        self
+    }
+
+    /// Fit prompts to these context windows instead of the defaults
+    pub fn with_context_budget(mut self, budget: ContextBudget) -> Self {
+        self.context_budget = budget;
+        self
+    }
+
+    /// Try these models in order for every thought, instead of the one model
+    /// picked per thought (or `IPPOC_LLM_CHAIN`)
+    pub fn with_model_chain(mut self, chain: ModelChain) -> Self {
+        self.model_chain = Some(chain).filter(|chain| !chain.is_empty());
+        self
+    }
+
+    /// Only answer from recollections at least this relevant
+    pub fn with_recall_threshold(mut self, threshold: f32) -> Self {
+        self.recall_threshold = threshold;
+        self
+    }
+
+    /// Weight recalled memories by how much their source is trusted
+    pub fn with_source_trust(mut self, trust: Arc<dyn SourceTrust>) -> Self {
+        self.memories.set_source_trust(trust);
+        self
+    }
+
+    pub fn degradation_status(&self) -> DegradationStatus {
+        self.degradation.status()
+    }
+
+    /// None when thinking with a memory that doesn't embed
+    pub fn embedding_stats(&self) -> Option<CacheStats> {
+        self.memories.embedding_stats()
+    }
+
+    /// Circuit state of every model backend
+    pub fn breakers(&self) -> Vec<BreakerSnapshot> {
+        vec![self.llm_breaker.snapshot(), self.embedding_breaker.snapshot()]
+    }
+
+    /// Throttling counters of every outbound backend
+    pub fn rate_limits(&self) -> Vec<RateLimitSnapshot> {
+        vec![
+            self.llm_limiter.snapshot(),
+            self.embedding_limiter.snapshot(),
+            self.search_limiter.snapshot(),
+        ]
+    }
+
+    /// Everything recalled, relevant or not
     pub async fn recall(&self, query: &str) -> Result<Vec<String>> {
-        self.memories.recall(query).await
+        Ok(self.memories.recall(query).await?.into_iter().map(|r| r.text).collect())
+    }
+
+    /// Recollections that clear the relevance threshold; recall errors count as none
+    async fn relevant_memories(&self, query: &str) -> Vec<String> {
+        let recollections = self.memories.recall(query).await.unwrap_or_default();
+        let total = recollections.len();
+        let relevant: Vec<String> = recollections.into_iter()
+            .filter(|r| r.relevance >= self.recall_threshold)
+            .map(|r| r.text)
+            .collect();
+        if relevant.len() < total {
+            info!("Cerebrum: {} of {} recollections below relevance {}", total - relevant.len(), total, self.recall_threshold);
+        }
+        relevant
+    }
+
+    /// Re-embed memories stored under an older embedding model; until then
+    /// recall can't see them
+    pub async fn reembed_stale_memories(&self) -> Result<u64> {
+        self.memories.reembed_stale().await
     }
 
     pub async fn think(&self, req: ThoughtRequest) -> Result<ThoughtResponse> {
         info!("Cerebrum thinking about: {}", req.query);
 
+        if self.degradation.admit().await == DegradationLevel::MemoryOnly {
+            return self.think_from_memory(&req, vec![]).await;
+        }
+
+        // Fail fast (before any retrieval work) while over budget or the model backend is down
+        self.llm_limiter.acquire().await?;
+        self.llm_breaker.check()?;
+
         // 1. Quick Reflex (Do I know this?)
-        let memory_strings = self.memories.recall(&req.query).await.unwrap_or_default();
+        let memory_strings = self.relevant_memories(&req.query).await;
         let memory_context = if !memory_strings.is_empty() {
             format!("I recall: {}\n\n", memory_strings.join("\n"))
         } else {
@@ -55,21 +268,14 @@ impl Cerebrum {
         let search_results = self.search.search(&req.query).await.unwrap_or_default();
 
         // 3. Synthesis (LLM Call)
-        let endpoint = std::env::var("VLLM_ENDPOINT").unwrap_or_else(|_| "http://localhost:11434/v1".to_string());
-        
-        // Determine model based on context (Genetic vs Cognition)
+        // Determine model based on context (Genetic vs Cognition), unless a chain is configured
         let model = if req.context_history.iter().any(|s| s.contains("Evolution Engine")) {
             "codegemma"
         } else {
             "gemma:2b"
         };
+        let chain = self.model_chain.clone().unwrap_or_else(|| ModelChain::single(model));
         
-        let system_prompt = if !req.context_history.is_empty() {
-            req.context_history.join("\n")
-        } else {
-            "You are IPPOC, a sovereign AI node.".to_string()
-        };
-
         let mut context_block = String::new();
         if !memory_context.is_empty() {
              context_block.push_str(&format!("Internal Memory:\n{}\n", memory_context));
@@ -81,36 +287,33 @@ impl Cerebrum {
              }
         }
 
-        let client = reqwest::Client::new();
-        let url = format!("{}/chat/completions", endpoint.trim_end_matches('/'));
-        
-        info!("Cerebrum: Synapsing to {} using model {}", url, model);
-
-        let body = serde_json::json!({
-            "model": model,
-            "messages": [
-                { "role": "system", "content": system_prompt },
-                { "role": "system", "content": context_block },
-                { "role": "user", "content": req.query }
-            ],
-            "temperature": 0.2,
-            "stream": false
-        });
-
-        let answer = match client.post(&url).json(&body).send().await {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    let json: serde_json::Value = resp.json().await.unwrap_or_default();
-                    json["choices"][0]["message"]["content"]
-                        .as_str()
-                        .unwrap_or("Error: Empty response from Neural Engine")
-                        .to_string()
-                } else {
-                    format!("Error: Neural Engine returned {}", resp.status())
-                }
+        // History gets whatever the smallest window in the chain has left after retrieval and the query
+        let window = chain.tiers().iter().map(|tier| self.context_budget.prompt_tokens(&tier.model)).min().unwrap_or(0);
+        let history_budget = window
+            .saturating_sub(context::estimate_tokens(&context_block) + context::estimate_tokens(&req.query));
+        let system_prompt = if !req.context_history.is_empty() {
+            context::fit_history(&req.context_history, history_budget).join("\n")
+        } else {
+            "You are IPPOC, a sovereign AI node.".to_string()
+        };
+
+        let prompt = Prompt::new().system(system_prompt).system(context_block).user(&req.query);
+        // Taken only around the model call; dropping it on cancellation frees a half-open probe
+        let permit = self.llm_breaker.acquire()?;
+        let served = match chain::complete_with_fallback(self.llm.as_ref(), &chain, &prompt).await {
+            Ok(served) => {
+                permit.success();
+                served
+            }
+            // Last resort: whatever memory knows
+            Err(failed) => {
+                permit.failure();
+                return self.think_from_memory(&req, failed).await;
             }
-            Err(e) => format!("Error: Could not reach Neural Engine: {}", e),
         };
+        let answer = served.completion.text;
+        let confidence = (0.8 - chain::FALLBACK_CONFIDENCE_STEP * served.failed.len() as f32)
+            .max(self.degradation.policy.memory_only_confidence);
 
         // 4. Memorize this interaction (Hippocampal consolidation)
         if let Err(e) = self.memories.memorize(&req.query, &answer).await {
@@ -119,8 +322,56 @@ impl Cerebrum {
 
         Ok(ThoughtResponse {
             answer,
-            confidence: 0.8,
+            confidence,
             sources: search_results.into_iter().map(|r| r.url).collect(),
+            usage: Some(served.completion.usage),
+            fallbacks: served.failed,
+        })
+    }
+}
+
+/// Breakers and limiters in front of every outbound backend
+struct BackendGuards {
+    llm_breaker: Arc<CircuitBreaker>,
+    embedding_breaker: Arc<CircuitBreaker>,
+    llm_limiter: Arc<RateLimiter>,
+    embedding_limiter: Arc<RateLimiter>,
+    search_limiter: Arc<RateLimiter>,
+}
+
+impl BackendGuards {
+    fn from_env() -> Self {
+        Self {
+            llm_breaker: Arc::new(CircuitBreaker::new("llm", breaker::DEFAULT_FAILURE_THRESHOLD, breaker::DEFAULT_COOLDOWN)),
+            embedding_breaker: Arc::new(CircuitBreaker::new("embeddings", breaker::DEFAULT_FAILURE_THRESHOLD, breaker::DEFAULT_COOLDOWN)),
+            llm_limiter: Arc::new(RateLimiter::new("llm", RateLimit::from_env("llm", RateLimit::new(2.0, 5)))),
+            embedding_limiter: Arc::new(RateLimiter::new("embeddings", RateLimit::from_env("embeddings", RateLimit::new(10.0, 20)))),
+            search_limiter: Arc::new(RateLimiter::new("search", RateLimit::from_env("search", RateLimit::new(1.0, 3)))),
+        }
+    }
+}
+
+impl Cerebrum {
+    /// Cheap answer from recall alone: no search, no LLM, nothing memorized.
+    /// `failed` lists the models that couldn't answer; empty when the budget
+    /// ran out before any was asked.
+    async fn think_from_memory(&self, req: &ThoughtRequest, failed: Vec<FailedModel>) -> Result<ThoughtResponse> {
+        let memories = self.relevant_memories(&req.query).await;
+        let answer = if memories.is_empty() && failed.is_empty() {
+            "Cognitive budget exhausted and nothing relevant in memory; try again later.".to_string()
+        } else if memories.is_empty() {
+            let models: Vec<&str> = failed.iter().map(|f| f.model.as_str()).collect();
+            format!("No model could answer ({}) and nothing relevant in memory; try again later.", models.join(", "))
+        } else {
+            format!("(memory only) {}", memories.join("\n"))
+        };
+
+        Ok(ThoughtResponse {
+            answer,
+            confidence: self.degradation.policy.memory_only_confidence,
+            sources: vec![],
+            usage: None,
+            fallbacks: failed,
         })
     }
 }
@@ -128,36 +379,60 @@ impl Cerebrum {
 // --- Lobes ---
 
 struct MemoryLobe {
-    hidb: Arc<HiDB>,
-    _embedding_client: reqwest::Client,
+    store: Arc<dyn MemoryStore>,
+    embeddings: EmbeddingCache,
+    retry: RetryPolicy,
+    trust: Arc<dyn SourceTrust>,
 }
 
+/// Memories returned per recall
+const RECALL_LIMIT: usize = 3;
+/// Candidates fetched per recalled memory, so trust re-ranking has room to work
+const RECALL_OVERFETCH: usize = 3;
+
 impl MemoryLobe {
-    pub fn new(hidb: Arc<HiDB>) -> Self {
+    fn new(store: Arc<dyn MemoryStore>, embeddings: EmbeddingCache, retry: RetryPolicy) -> Self {
         Self {
-            hidb,
-            _embedding_client: reqwest::Client::new(),
+            store,
+            embeddings,
+            retry,
+            trust: Arc::new(TrustAll),
         }
     }
 
-    pub async fn recall(&self, query: &str) -> Result<Vec<String>> {
+}
+
+#[async_trait]
+impl Memory for MemoryLobe {
+    async fn recall(&self, query: &str) -> Result<Vec<Recollection>> {
         info!("Recalling memories related to: {}", query);
-        // 1. Generate Embedding (Mock for now, or call Sidecar)
-        // In a real implementation: call local LLM sidecar /v1/embeddings
-        let mock_embedding = vec![0.0f32; 1536]; // Placeholder 1536 dim
+        // 1. Generate Embedding (cached; repeated queries are free)
+        let embedding = self.embeddings.embed(query).await?;
         
-        // 2. Query HiDB
-        let memories = self.hidb.semantic_search(&mock_embedding, 3).await?;
+        // 2. Query the memory store
+        let limit = (RECALL_LIMIT * RECALL_OVERFETCH) as i64;
+        // Vectors from another model aren't comparable with this query
+        let model = Some(self.embeddings.model());
+        let candidates = retry::retry(&self.retry, "Semantic search", || self.store.semantic_search_scoped(&embedding, limit, model)).await?;
+
+        // 3. Weight by source trust; memories from rejected sources are purged
+        let ranked = hidb::rank_by_trust(candidates, &embedding, self.trust.as_ref(), RECALL_LIMIT).await;
+        for id in ranked.rejected {
+            match self.store.delete(id).await {
+                Ok(_) => info!("Purged memory {} from an untrusted source", id),
+                Err(e) => tracing::warn!("Failed to purge untrusted memory {}: {}", id, e),
+            }
+        }
         
-        // 3. Format
-        let results = memories.into_iter()
-            .map(|m| format!("Memory (conf: {:.2}): {}", m.confidence, m.content))
+        // 4. Format
+        let results = ranked.memories.into_iter().zip(ranked.relevance)
+            .map(|(m, relevance)| Recollection::new(format!("Memory (conf: {:.2}): {}", m.confidence, m.content), relevance))
             .collect();
             
         Ok(results)
     }
 
-    pub async fn memorize(&self, query: &str, answer: &str) -> Result<()> {
+    async fn memorize(&self, query: &str, answer: &str) -> Result<()> {
         use hidb::MemoryRecord;
         
         info!("Consolidating memory: '{}' -> ...", query);
@@ -165,41 +440,82 @@ impl MemoryLobe {
         // 1. Create content blob
         let content = format!("Q: {query}\nA: {answer}");
         
-        // 2. Generate Embedding (Mock)
-        // Ideally we embed the Question or Q+A
-        let mock_embedding = vec![0.0f32; 1536]; 
+        // 2. Embed the question so future recalls of it match
+        let embedding = self.embeddings.embed(query).await?;
 
         // 3. Store
-        let record = MemoryRecord::new(content, mock_embedding);
-        self.hidb.store(&record).await?;
+        let record = MemoryRecord::new(content, embedding).with_model(self.embeddings.model());
+        self.store.store(&record).await?;
         
         Ok(())
     }
+
+    fn set_source_trust(&mut self, trust: Arc<dyn SourceTrust>) {
+        self.trust = trust;
+    }
+
+    fn embedding_stats(&self) -> Option<CacheStats> {
+        Some(self.embeddings.stats())
+    }
+
+    async fn reembed_stale(&self) -> Result<u64> {
+        self.store.reembed_stale(self).await
+    }
+}
+
+#[async_trait]
+impl Reembedder for MemoryLobe {
+    fn model(&self) -> &str {
+        self.embeddings.model()
+    }
+
+    /// Memorized content is "Q: ...\nA: ..." but only the question was embedded
+    async fn embed(&self, content: &str) -> Result<Vec<f32>> {
+        let question = content.strip_prefix("Q: ")
+            .and_then(|rest| rest.split("\nA: ").next())
+            .unwrap_or(content);
+        self.embeddings.embed(question).await
+    }
 }
 
 struct SearchLobe {
     client: reqwest::Client,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
 }
 
-#[derive(Debug, Deserialize)]
-struct SearchResult {
-    title: String,
-    url: String,
-    snippet: String,
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
 }
 
 impl SearchLobe {
-    pub fn new() -> Self {
+    fn new(retry: RetryPolicy, limiter: Arc<RateLimiter>) -> Self {
         Self {
             client: reqwest::Client::new(),
+            retry,
+            limiter,
         }
     }
+}
 
-    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+#[async_trait]
+impl Search for SearchLobe {
+    async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
         // Real logic: If query is a URL, fetch it. If not, do a mock search for now (or Bing API if env set)
         
         if query.starts_with("http") {
-             let res = self.client.get(query).send().await?;
+             // 5xx is surfaced as an error so it gets retried
+             let res = retry::retry(&self.retry, "Search fetch", || async move {
+                 self.limiter.acquire().await?;
+                 let res = self.client.get(query).send().await?;
+                 if res.status().is_server_error() {
+                     return Err(res.error_for_status().unwrap_err().into());
+                 }
+                 Ok(res)
+             }).await?;
              let title = query.to_string(); // In real app, parse HTML <title>
              let snippet = format!("TITLE: {}\nFetched content from {}: Status {}", title, query, res.status());
              
@@ -222,3 +538,57 @@ impl SearchLobe {
         Ok(vec![])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm::EchoBackend;
+    use mock::{CannedMemory, CannedSearch};
+
+    #[tokio::test]
+    async fn test_think_synthesizes_memory_and_search() {
+        let memory = CannedMemory::new(vec![Recollection::new("Memory (conf: 0.90): the mesh uses QUIC", 0.9)]);
+        let search = CannedSearch::new(vec![SearchResult {
+            title: "Transport notes".to_string(),
+            url: "https://example.org/quic".to_string(),
+            snippet: "QUIC multiplexes streams over UDP".to_string(),
+        }]);
+        let brain = Cerebrum::new_with(search, memory.clone()).with_llm_backend(Box::new(EchoBackend));
+
+        let response = brain
+            .think(ThoughtRequest { query: "how do nodes talk?".into(), context_history: vec![] })
+            .await
+            .unwrap();
+
+        assert!(response.answer.contains("the mesh uses QUIC"), "{}", response.answer);
+        assert!(response.answer.contains("QUIC multiplexes streams over UDP"), "{}", response.answer);
+        assert_eq!(response.sources, vec!["https://example.org/quic".to_string()]);
+        assert_eq!(response.confidence, 0.8);
+        assert_eq!(memory.memorized(), vec![("how do nodes talk?".to_string(), response.answer.clone())]);
+        assert_eq!(brain.embedding_stats(), None);
+    }
+
+    #[tokio::test]
+    async fn test_irrelevant_memories_stay_out_of_the_prompt() {
+        let memory = CannedMemory::new(vec![
+            Recollection::new("Memory (conf: 0.90): the mesh uses QUIC", 0.82),
+            Recollection::new("Memory (conf: 0.90): bananas are yellow", 0.05),
+        ]);
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), memory.clone())
+            .with_llm_backend(Box::new(EchoBackend))
+            .with_recall_threshold(0.5);
+
+        let ask = || ThoughtRequest { query: "how do nodes talk?".into(), context_history: vec![] };
+        let answer = brain.think(ask()).await.unwrap().answer;
+        assert!(answer.contains("I recall: Memory (conf: 0.90): the mesh uses QUIC"), "{}", answer);
+        assert!(!answer.contains("bananas"), "{}", answer);
+
+        // Nothing clears the bar: no "I recall" at all
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), memory)
+            .with_llm_backend(Box::new(EchoBackend))
+            .with_recall_threshold(0.9);
+        let answer = brain.think(ask()).await.unwrap().answer;
+        assert!(!answer.contains("I recall"), "{}", answer);
+        assert!(!answer.contains("Internal Memory"), "{}", answer);
+    }
+}
@@ -0,0 +1,605 @@
+//! Language-model backends.
+//!
+//! Synthesis in the Cerebrum and conflict resolution in the Evolution Engine
+//! both talk to a model through `LlmBackend`. `LlmConfig::from_env` picks one
+//! with `IPPOC_LLM_BACKEND`:
+//!
+//! - `openai` (default): any OpenAI-compatible server at `VLLM_ENDPOINT`
+//!   (vLLM, Ollama's `/v1`, OpenAI itself with `OPENAI_API_KEY`)
+//! - `openrouter`: OpenRouter, authenticated with `OPENROUTER_API_KEY`
+//! - `ollama`: Ollama's native API at `OLLAMA_URL`
+//! - `echo`: deterministic and offline, for tests
+//!
+//! `IPPOC_LLM_MODEL` pins one model for every request, for servers that don't
+//! serve the models callers ask for by name.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::context::estimate_tokens;
+
+pub const DEFAULT_OPENAI_URL: &str = "http://localhost:11434/v1";
+pub const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1";
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+/// Vector size of `EchoBackend` embeddings
+pub const ECHO_EMBEDDING_DIM: usize = 32;
+/// Pieces buffered between a streaming response and its reader
+const STREAM_BUFFER: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+/// Chat messages, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Prompt {
+    pub messages: Vec<Message>,
+}
+
+impl Prompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn system(self, content: impl Into<String>) -> Self {
+        self.with(Role::System, content)
+    }
+
+    pub fn user(self, content: impl Into<String>) -> Self {
+        self.with(Role::User, content)
+    }
+
+    pub fn assistant(self, content: impl Into<String>) -> Self {
+        self.with(Role::Assistant, content)
+    }
+
+    fn with(mut self, role: Role, content: impl Into<String>) -> Self {
+        self.messages.push(Message { role, content: content.into() });
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionOptions {
+    pub model: String,
+    pub temperature: f32,
+    pub max_tokens: Option<u32>,
+}
+
+impl CompletionOptions {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self { model: model.into(), temperature: 0.2, max_tokens: None }
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+/// Tokens a completion cost, as reported by the server, or estimated from
+/// the text when it doesn't say
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// The model that actually answered
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u32 {
+        self.prompt_tokens.saturating_add(self.completion_tokens)
+    }
+
+    /// Reported counts, estimating whichever is missing
+    fn reported(model: &str, prompt_tokens: Option<u64>, completion_tokens: Option<u64>, prompt: &Prompt, text: &str) -> Self {
+        let estimated_prompt = || prompt.messages.iter().map(|m| estimate_tokens(&m.content)).sum::<usize>() as u64;
+        Self {
+            model: model.to_string(),
+            prompt_tokens: prompt_tokens.unwrap_or_else(estimated_prompt).min(u32::MAX as u64) as u32,
+            completion_tokens: completion_tokens.unwrap_or(estimate_tokens(text) as u64).min(u32::MAX as u64) as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    pub usage: TokenUsage,
+}
+
+/// Pieces of an answer in order; closed when the answer is complete
+pub type CompletionStream = mpsc::Receiver<Result<String>>;
+
+#[async_trait]
+pub trait LlmBackend: Send + Sync {
+    /// Short name for logs and errors
+    fn name(&self) -> &str;
+
+    async fn complete(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion>;
+
+    /// The answer as it is generated. Backends that can't stream send it
+    /// whole, as a single piece.
+    async fn complete_stream(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<CompletionStream> {
+        let completion = self.complete(prompt, opts).await?;
+        let (tx, rx) = mpsc::channel(1);
+        let _ = tx.send(Ok(completion.text)).await;
+        Ok(rx)
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Which backend to build, and how to reach it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmConfig {
+    /// openai, openrouter, ollama or echo
+    pub backend: String,
+    /// Server base URL; None for the backend's default
+    pub url: Option<String>,
+    pub api_key: Option<String>,
+    /// Use this model for every request instead of the caller's choice
+    pub model: Option<String>,
+    pub embedding_model: Option<String>,
+}
+
+impl LlmConfig {
+    pub fn new(backend: impl Into<String>) -> Self {
+        Self { backend: backend.into(), url: None, api_key: None, model: None, embedding_model: None }
+    }
+
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let backend = var("IPPOC_LLM_BACKEND").unwrap_or_else(|| "openai".to_string()).to_lowercase();
+        let (url, api_key) = match backend.as_str() {
+            "openrouter" => (None, var("OPENROUTER_API_KEY")),
+            "ollama" => (var("OLLAMA_URL"), None),
+            _ => (var("VLLM_ENDPOINT"), var("OPENAI_API_KEY")),
+        };
+        Self {
+            backend,
+            url,
+            api_key,
+            model: var("IPPOC_LLM_MODEL"),
+            embedding_model: var("IPPOC_LLM_EMBEDDING_MODEL"),
+        }
+    }
+
+    pub fn build(&self) -> Result<Box<dyn LlmBackend>> {
+        let backend: Box<dyn LlmBackend> = match self.backend.as_str() {
+            "openai" => {
+                let mut backend = OpenAiBackend::new(self.url.as_deref().unwrap_or(DEFAULT_OPENAI_URL));
+                backend.api_key = self.api_key.clone();
+                Box::new(self.tune_openai(backend))
+            }
+            "openrouter" => {
+                let Some(key) = &self.api_key else {
+                    bail!("OpenRouter needs an API key (OPENROUTER_API_KEY)");
+                };
+                let mut backend = OpenAiBackend::openrouter(key.clone());
+                if let Some(url) = &self.url {
+                    backend.base_url = url.trim_end_matches('/').to_string();
+                }
+                Box::new(self.tune_openai(backend))
+            }
+            "ollama" => {
+                let mut backend = OllamaBackend::new(self.url.as_deref().unwrap_or(DEFAULT_OLLAMA_URL));
+                backend.model = self.model.clone();
+                if let Some(model) = &self.embedding_model {
+                    backend.embedding_model = model.clone();
+                }
+                Box::new(backend)
+            }
+            "echo" => Box::new(EchoBackend),
+            other => bail!("Unknown LLM backend '{}' (expected openai, openrouter, ollama or echo)", other),
+        };
+        info!("LLM backend: {}", backend.name());
+        Ok(backend)
+    }
+
+    fn tune_openai(&self, mut backend: OpenAiBackend) -> OpenAiBackend {
+        backend.model = self.model.clone();
+        if let Some(model) = &self.embedding_model {
+            backend.embedding_model = model.clone();
+        }
+        backend
+    }
+}
+
+/// The backend configured in the environment. A bad configuration is logged
+/// and replaced by the OpenAI-compatible default, so the node still boots.
+pub fn backend_from_env() -> Box<dyn LlmBackend> {
+    LlmConfig::from_env().build().unwrap_or_else(|e| {
+        error!("LLM backend not configured ({}); using {}", e, DEFAULT_OPENAI_URL);
+        Box::new(OpenAiBackend::new(DEFAULT_OPENAI_URL))
+    })
+}
+
+/// OpenAI's chat completions and embeddings API, as served by OpenAI,
+/// OpenRouter, vLLM and Ollama's `/v1`
+pub struct OpenAiBackend {
+    name: &'static str,
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    /// Overrides the requested model
+    model: Option<String>,
+    embedding_model: String,
+}
+
+impl OpenAiBackend {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            name: "openai",
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+            model: None,
+            embedding_model: "text-embedding-3-small".to_string(),
+        }
+    }
+
+    pub fn openrouter(api_key: impl Into<String>) -> Self {
+        Self { name: "openrouter", ..Self::new(OPENROUTER_URL) }.with_api_key(api_key)
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<reqwest::Response> {
+        let url = format!("{}/{}", self.base_url, path);
+        let mut request = self.client.post(&url).json(body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let resp = request.send().await.map_err(|e| anyhow!("Could not reach {} at {}: {}", self.name, url, e))?;
+        if !resp.status().is_success() {
+            bail!("{} returned {}", self.name, resp.status());
+        }
+        Ok(resp)
+    }
+
+    fn chat_body(&self, prompt: &Prompt, opts: &CompletionOptions, stream: bool) -> Value {
+        let model = self.model.as_deref().unwrap_or(&opts.model);
+        info!("Cerebrum: Synapsing to {} using model {}", self.name, model);
+        let mut body = serde_json::json!({
+            "model": model,
+            "messages": prompt.messages,
+            "temperature": opts.temperature,
+            "stream": stream
+        });
+        if let Some(max_tokens) = opts.max_tokens {
+            body["max_tokens"] = max_tokens.into();
+        }
+        body
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OpenAiBackend {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn complete(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion> {
+        let resp = self.post("chat/completions", &self.chat_body(prompt, opts, false)).await?;
+        let json: Value = resp.json().await?;
+        let text = json["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Empty response from {}", self.name))?;
+        // OpenRouter answers with the model it routed to
+        let model = json["model"].as_str().unwrap_or(self.model.as_deref().unwrap_or(&opts.model));
+        let usage = &json["usage"];
+        let usage = TokenUsage::reported(model, usage["prompt_tokens"].as_u64(), usage["completion_tokens"].as_u64(), prompt, &text);
+        Ok(Completion { text, usage })
+    }
+
+    async fn complete_stream(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<CompletionStream> {
+        let resp = self.post("chat/completions", &self.chat_body(prompt, opts, true)).await?;
+        Ok(stream_lines(resp, parse_sse))
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let body = serde_json::json!({ "model": self.embedding_model, "input": text });
+        let json: Value = self.post("embeddings", &body).await?.json().await?;
+        parse_vector(&json["data"][0]["embedding"]).ok_or_else(|| anyhow!("No embedding in {} response", self.name))
+    }
+}
+
+/// Ollama's native chat and embeddings API
+pub struct OllamaBackend {
+    client: reqwest::Client,
+    base_url: String,
+    /// Overrides the requested model
+    model: Option<String>,
+    embedding_model: String,
+}
+
+impl OllamaBackend {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: None,
+            embedding_model: "nomic-embed-text".to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<reqwest::Response> {
+        let url = format!("{}/{}", self.base_url, path);
+        let resp = self.client.post(&url).json(body).send().await
+            .map_err(|e| anyhow!("Could not reach ollama at {}: {}", url, e))?;
+        if !resp.status().is_success() {
+            bail!("ollama returned {}", resp.status());
+        }
+        Ok(resp)
+    }
+
+    fn chat_body(&self, prompt: &Prompt, opts: &CompletionOptions, stream: bool) -> Value {
+        let mut options = serde_json::json!({ "temperature": opts.temperature });
+        if let Some(max_tokens) = opts.max_tokens {
+            options["num_predict"] = max_tokens.into();
+        }
+        serde_json::json!({
+            "model": self.model.as_deref().unwrap_or(&opts.model),
+            "messages": prompt.messages,
+            "options": options,
+            "stream": stream
+        })
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OllamaBackend {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    async fn complete(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion> {
+        let json: Value = self.post("api/chat", &self.chat_body(prompt, opts, false)).await?.json().await?;
+        let text = json["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Empty response from ollama"))?;
+        let model = json["model"].as_str().unwrap_or(self.model.as_deref().unwrap_or(&opts.model));
+        let usage = TokenUsage::reported(model, json["prompt_eval_count"].as_u64(), json["eval_count"].as_u64(), prompt, &text);
+        Ok(Completion { text, usage })
+    }
+
+    async fn complete_stream(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<CompletionStream> {
+        let resp = self.post("api/chat", &self.chat_body(prompt, opts, true)).await?;
+        Ok(stream_lines(resp, parse_ollama_line))
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let body = serde_json::json!({ "model": self.embedding_model, "prompt": text });
+        let json: Value = self.post("api/embeddings", &body).await?.json().await?;
+        parse_vector(&json["embedding"]).ok_or_else(|| anyhow!("No embedding in ollama response"))
+    }
+}
+
+/// Deterministic and offline: answers with the prompt's non-empty messages,
+/// one per line, with estimated usage, and embeds text as a hash of it
+pub struct EchoBackend;
+
+#[async_trait]
+impl LlmBackend for EchoBackend {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    async fn complete(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<Completion> {
+        let text = prompt
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .filter(|content| !content.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let usage = TokenUsage::reported(&opts.model, None, None, prompt, &text);
+        Ok(Completion { text, usage })
+    }
+
+    /// Word by word, whitespace included
+    async fn complete_stream(&self, prompt: &Prompt, opts: &CompletionOptions) -> Result<CompletionStream> {
+        let answer = self.complete(prompt, opts).await?.text;
+        let pieces: Vec<String> = answer.split_inclusive(char::is_whitespace).map(str::to_string).collect();
+        let (tx, rx) = mpsc::channel(pieces.len().max(1));
+        for piece in pieces {
+            let _ = tx.send(Ok(piece)).await;
+        }
+        Ok(rx)
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(Sha256::digest(text.as_bytes()).iter().map(|b| *b as f32 / 127.5 - 1.0).collect())
+    }
+}
+
+/// What one line of a streaming response carries
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Text(String),
+    Done,
+    Nothing,
+}
+
+/// Server-sent events from OpenAI-style `stream: true` completions
+fn parse_sse(line: &str) -> Piece {
+    let Some(data) = line.strip_prefix("data:") else {
+        return Piece::Nothing;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return Piece::Done;
+    }
+    match serde_json::from_str::<Value>(data) {
+        Ok(json) => match json["choices"][0]["delta"]["content"].as_str() {
+            Some(text) if !text.is_empty() => Piece::Text(text.to_string()),
+            _ => Piece::Nothing,
+        },
+        Err(_) => Piece::Nothing,
+    }
+}
+
+/// Newline-delimited JSON from Ollama's streaming chat
+fn parse_ollama_line(line: &str) -> Piece {
+    let Ok(json) = serde_json::from_str::<Value>(line) else {
+        return Piece::Nothing;
+    };
+    match json["message"]["content"].as_str() {
+        Some(text) if !text.is_empty() => Piece::Text(text.to_string()),
+        _ if json["done"].as_bool() == Some(true) => Piece::Done,
+        _ => Piece::Nothing,
+    }
+}
+
+/// Forward a line-oriented streaming body, parsed line by line, until it ends,
+/// `parse` says it's done, or the reader hangs up
+fn stream_lines(mut resp: reqwest::Response, parse: fn(&str) -> Piece) -> CompletionStream {
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        let mut pending = Vec::new();
+        let mut ended = false;
+        while !ended {
+            match resp.chunk().await {
+                Ok(Some(bytes)) => pending.extend_from_slice(&bytes),
+                // Whatever is left is the last line
+                Ok(None) => {
+                    pending.push(b'\n');
+                    ended = true;
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e.into())).await;
+                    return;
+                }
+            }
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                match parse(String::from_utf8_lossy(&line).trim()) {
+                    Piece::Text(text) => {
+                        if tx.send(Ok(text)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Piece::Done => return,
+                    Piece::Nothing => {}
+                }
+            }
+        }
+    });
+    rx
+}
+
+fn parse_vector(value: &Value) -> Option<Vec<f32>> {
+    value.as_array()?.iter().map(|v| v.as_f64().map(|f| f as f32)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What every backend promises, checked offline against the echo backend
+    async fn conformance(backend: &dyn LlmBackend) {
+        let prompt = Prompt::new().system("You are IPPOC.").user("Say hello to the mesh");
+        let opts = CompletionOptions::new("gemma:2b");
+
+        let completion = backend.complete(&prompt, &opts).await.unwrap();
+        let answer = completion.text.clone();
+        assert!(!answer.is_empty());
+        assert!(completion.usage.prompt_tokens > 0 && completion.usage.completion_tokens > 0);
+        // Deterministic for the same prompt
+        assert_eq!(backend.complete(&prompt, &opts).await.unwrap(), completion);
+
+        // Streamed pieces add up to the whole answer
+        let mut stream = backend.complete_stream(&prompt, &opts).await.unwrap();
+        let mut streamed = String::new();
+        while let Some(piece) = stream.recv().await {
+            streamed.push_str(&piece.unwrap());
+        }
+        assert_eq!(streamed, answer);
+
+        // Fixed-size, repeatable embeddings that tell texts apart
+        let a = backend.embed("hello").await.unwrap();
+        assert_eq!(backend.embed("hello").await.unwrap(), a);
+        let b = backend.embed("goodbye").await.unwrap();
+        assert_eq!(a.len(), b.len());
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_echo_backend_conforms() {
+        conformance(&EchoBackend).await;
+        let answer = EchoBackend
+            .complete(&Prompt::new().system("rules").system("").user("question"), &CompletionOptions::new("any"))
+            .await
+            .unwrap();
+        assert_eq!(answer.text, "rules\nquestion");
+        assert_eq!(answer.usage, TokenUsage { model: "any".into(), prompt_tokens: 4, completion_tokens: 4 });
+        assert_eq!(EchoBackend.embed("x").await.unwrap().len(), ECHO_EMBEDDING_DIM);
+    }
+
+    #[tokio::test]
+    async fn test_config_selects_backend() {
+        assert_eq!(LlmConfig::new("echo").build().unwrap().name(), "echo");
+        assert_eq!(LlmConfig::new("ollama").build().unwrap().name(), "ollama");
+        assert_eq!(LlmConfig::new("openai").build().unwrap().name(), "openai");
+        assert!(LlmConfig::new("openrouter").build().is_err(), "OpenRouter without a key");
+        let openrouter = LlmConfig { api_key: Some("sk-or-test".into()), ..LlmConfig::new("openrouter") };
+        assert_eq!(openrouter.build().unwrap().name(), "openrouter");
+        assert!(LlmConfig::new("gpt-in-a-box").build().is_err());
+    }
+
+    #[test]
+    fn test_stream_lines_parse() {
+        assert_eq!(parse_sse(r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#), Piece::Text("Hel".into()));
+        assert_eq!(parse_sse("data: [DONE]"), Piece::Done);
+        assert_eq!(parse_sse(": keep-alive"), Piece::Nothing);
+        assert_eq!(parse_ollama_line(r#"{"message":{"content":"lo"},"done":false}"#), Piece::Text("lo".into()));
+        assert_eq!(parse_ollama_line(r#"{"message":{"content":""},"done":true}"#), Piece::Done);
+    }
+}
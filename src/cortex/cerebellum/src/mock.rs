@@ -0,0 +1,61 @@
+//! Canned lobes for exercising `Cerebrum::think` offline.
+//!
+//! `Cerebrum::new_with(CannedSearch::new(..), CannedMemory::new(..))
+//! .with_llm_backend(Box::new(EchoBackend))` thinks without Postgres, Redis or
+//! the internet, and deterministically.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{Memory, Recollection, Search, SearchResult};
+
+/// Returns the same results for every query
+pub struct CannedSearch {
+    results: Vec<SearchResult>,
+}
+
+impl CannedSearch {
+    pub fn new(results: Vec<SearchResult>) -> Self {
+        Self { results }
+    }
+}
+
+#[async_trait]
+impl Search for CannedSearch {
+    async fn search(&self, _query: &str) -> Result<Vec<SearchResult>> {
+        Ok(self.results.clone())
+    }
+}
+
+/// Recalls the same memories for every query and keeps what it's asked to
+/// memorize. Clones share the memorized log.
+#[derive(Clone)]
+pub struct CannedMemory {
+    recalled: Vec<Recollection>,
+    memorized: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl CannedMemory {
+    pub fn new(recalled: Vec<Recollection>) -> Self {
+        Self { recalled, memorized: Arc::default() }
+    }
+
+    /// (query, answer) pairs, oldest first
+    pub fn memorized(&self) -> Vec<(String, String)> {
+        self.memorized.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Memory for CannedMemory {
+    async fn recall(&self, _query: &str) -> Result<Vec<Recollection>> {
+        Ok(self.recalled.clone())
+    }
+
+    async fn memorize(&self, query: &str, answer: &str) -> Result<()> {
+        self.memorized.lock().unwrap().push((query.to_string(), answer.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Token-bucket rate limiting for outbound model/search calls.
+//!
+//! Each backend gets a bucket of `burst` tokens refilled at `rps` per second.
+//! Calls over the limit queue until a token frees up; if that would take
+//! longer than `max_wait` they are rejected with `RateLimited` instead, so a
+//! runaway loop can't pile up unbounded work behind the limiter.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::embedding::EmbeddingBackend;
+
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained calls per second
+    pub rps: f64,
+    /// Calls allowed back-to-back before pacing kicks in
+    pub burst: u32,
+    /// Longest a call may queue before it is rejected
+    pub max_wait: Duration,
+}
+
+impl RateLimit {
+    pub fn new(rps: f64, burst: u32) -> Self {
+        Self { rps, burst, max_wait: DEFAULT_MAX_WAIT }
+    }
+
+    /// IPPOC_RATE_<BACKEND>_RPS / _BURST override the defaults, e.g.
+    /// IPPOC_RATE_LLM_RPS=0.5
+    pub fn from_env(backend: &str, default: RateLimit) -> Self {
+        let prefix = format!("IPPOC_RATE_{}", backend.to_uppercase());
+        let rps = std::env::var(format!("{prefix}_RPS"))
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .unwrap_or(default.rps);
+        let burst = std::env::var(format!("{prefix}_BURST"))
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(default.burst);
+        Self { rps, burst, ..default }
+    }
+}
+
+/// Returned instead of calling the backend when the queue is too long
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    pub backend: String,
+    pub retry_in: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} backend rate limited; retry in {:.1}s", self.backend, self.retry_in.as_secs_f64())
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitSnapshot {
+    pub backend: String,
+    pub rps: f64,
+    pub burst: u32,
+    /// Calls that had to wait for a token
+    pub throttled: u64,
+    /// Calls turned away because the wait exceeded `max_wait`
+    pub rejected: u64,
+}
+
+struct Bucket {
+    /// Negative while callers are queued for future tokens
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+pub struct RateLimiter {
+    name: String,
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+    throttled: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(name: impl Into<String>, limit: RateLimit) -> Self {
+        let limit = RateLimit {
+            rps: if limit.rps > 0.0 { limit.rps } else { 1.0 },
+            burst: limit.burst.max(1),
+            ..limit
+        };
+        Self {
+            name: name.into(),
+            limit,
+            bucket: Mutex::new(Bucket { tokens: limit.burst as f64, refilled_at: Instant::now() }),
+            throttled: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Wait for a token, or fail with `RateLimited` if the wait is too long
+    pub async fn acquire(&self) -> Result<(), RateLimited> {
+        let wait = self.reserve(self.limit.max_wait)?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Take a token only if one is available right now
+    pub fn try_acquire(&self) -> Result<(), RateLimited> {
+        self.reserve(Duration::ZERO).map(|_| ())
+    }
+
+    pub fn snapshot(&self) -> RateLimitSnapshot {
+        RateLimitSnapshot {
+            backend: self.name.clone(),
+            rps: self.limit.rps,
+            burst: self.limit.burst,
+            throttled: self.throttled.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Claim the next token and return how long to wait before using it
+    fn reserve(&self, max_wait: Duration) -> Result<Duration, RateLimited> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.limit.rps).min(self.limit.burst as f64);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+
+        let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.limit.rps);
+        if wait > max_wait {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("RateLimit: {} backend over limit, rejecting call", self.name);
+            return Err(RateLimited { backend: self.name.clone(), retry_in: wait });
+        }
+
+        bucket.tokens -= 1.0;
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        Ok(wait)
+    }
+}
+
+/// Embedding backend paced by a rate limiter
+pub struct RateLimitedEmbedder {
+    inner: Arc<dyn EmbeddingBackend>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitedEmbedder {
+    pub fn new(inner: Arc<dyn EmbeddingBackend>, limiter: Arc<RateLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for RateLimitedEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.limiter.acquire().await?;
+        self.inner.embed(text).await
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_is_paced_to_configured_rate() {
+        // 2 immediate calls, then one every 20ms
+        let limiter = RateLimiter::new("search", RateLimit::new(50.0, 2));
+        let calls = 12;
+
+        let start = Instant::now();
+        for _ in 0..calls {
+            limiter.acquire().await.unwrap();
+        }
+        let elapsed = start.elapsed();
+
+        // (12 - 2) calls / 50 rps = 200ms
+        assert!(elapsed >= Duration::from_millis(190), "finished too fast: {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "finished too slow: {:?}", elapsed);
+        assert_eq!(limiter.snapshot().throttled, (calls - 2) as u64);
+        assert_eq!(limiter.snapshot().rejected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_the_bucket() {
+        let limiter = Arc::new(RateLimiter::new("embeddings", RateLimit::new(100.0, 1)));
+
+        let start = Instant::now();
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        // (6 - 1) calls / 100 rps = 50ms
+        assert!(start.elapsed() >= Duration::from_millis(45));
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_calls_are_rejected() {
+        let limit = RateLimit { rps: 1.0, burst: 1, max_wait: Duration::from_millis(100) };
+        let limiter = RateLimiter::new("llm", limit);
+
+        limiter.acquire().await.unwrap();
+        let err = limiter.acquire().await.unwrap_err();
+        assert!(err.to_string().contains("llm backend rate limited"));
+        assert!(limiter.try_acquire().is_err());
+        assert_eq!(limiter.snapshot().rejected, 2);
+    }
+}
@@ -0,0 +1,190 @@
+//! Retry with exponential backoff and full jitter for idempotent calls
+//! (embeddings, semantic search, HTTP fetches).
+//!
+//! Only transient failures are retried: timeouts, connection drops, 5xx and
+//! 429. Client errors (other 4xx, auth) fail immediately.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+
+use crate::breaker::BreakerOpen;
+use crate::embedding::EmbeddingBackend;
+use crate::ratelimit::RateLimited;
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total tries including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // IPPOC_RETRY_MAX_ATTEMPTS overrides the attempt count
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("IPPOC_RETRY_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        Self { max_attempts, ..Self::default() }
+    }
+
+    /// Random delay in [0, min(max_delay, base_delay * 2^attempt)]
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let millis = ceiling.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+}
+
+/// Run `op` until it succeeds, fails permanently, or attempts run out
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && is_retryable(&e) => {
+                let delay = policy.backoff(attempt - 1);
+                tracing::warn!("{} failed (attempt {}/{}), retrying in {:?}: {}", what, attempt, policy.max_attempts, delay, e);
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Transient network/server failures are worth another try; bad requests aren't
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<BreakerOpen>().is_some() || err.downcast_ref::<RateLimited>().is_some() {
+        return false;
+    }
+
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if e.is_timeout() || e.is_connect() {
+                return true;
+            }
+            if let Some(status) = e.status() {
+                return status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            }
+            return e.is_request();
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind::*;
+            return matches!(e.kind(), TimedOut | ConnectionReset | ConnectionAborted | ConnectionRefused | BrokenPipe | UnexpectedEof);
+        }
+    }
+
+    // Database drivers surface pool/connection trouble only in the message
+    let message = err.to_string().to_lowercase();
+    ["timed out", "timeout", "connection reset", "connection closed", "connection refused"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
+/// Embedding backend with transient failures retried
+pub struct RetryEmbedder {
+    inner: Arc<dyn EmbeddingBackend>,
+    policy: RetryPolicy,
+}
+
+impl RetryEmbedder {
+    pub fn new(inner: Arc<dyn EmbeddingBackend>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl EmbeddingBackend for RetryEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        retry(&self.policy, "Embedding", || self.inner.embed(text)).await
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy { max_attempts, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5) }
+    }
+
+    fn reset() -> anyhow::Error {
+        std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset by peer").into()
+    }
+
+    #[tokio::test]
+    async fn test_fails_twice_then_succeeds() {
+        let calls = &AtomicU32::new(0);
+        let result = retry(&fast_policy(3), "test", || async move {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(reset())
+            } else {
+                Ok("embedded")
+            }
+        }).await;
+
+        assert_eq!(result.unwrap(), "embedded");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = &AtomicU32::new(0);
+        let result: Result<()> = retry(&fast_policy(2), "test", || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(reset())
+        }).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_fails_immediately() {
+        let calls = &AtomicU32::new(0);
+        let result: Result<()> = retry(&fast_policy(5), "test", || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::anyhow!("401 unauthorized: invalid api key"))
+        }).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_breaker_open_is_not_retried() {
+        let err = anyhow::Error::new(BreakerOpen { backend: "llm".into(), retry_in: Duration::from_secs(1) });
+        assert!(!is_retryable(&err));
+    }
+}
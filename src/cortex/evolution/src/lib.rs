@@ -1,17 +1,46 @@
 use anyhow::{Result, Context};
 use async_trait::async_trait;
-use git_evolution::{BrainMutationResolver, ConflictContext};
-use cerebellum::Cerebrum;
-use std::sync::Arc;
+use git_evolution::{BrainMutationResolver, ConflictContext, SimulationOutcome, SimulationStage, Violation};
+use cerebellum::llm::{CompletionOptions, LlmBackend, Prompt};
+use serde::Deserialize;
 use tracing::{info, warn};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Model for writing code (conflict resolutions)
+const CODE_MODEL: &str = "codegemma";
+/// Model for judging changes (reviews, governance audits)
+const REASONING_MODEL: &str = "gemma:2b";
+
+/// Wall-clock budget for one simulation (check + test)
+pub const DEFAULT_SIMULATION_TIMEOUT: Duration = Duration::from_secs(900);
 
 pub struct EvolutionEngine {
-    cerebellum: Arc<Cerebrum>,
+    llm: Box<dyn LlmBackend>,
+    /// Cargo executable used by simulations
+    cargo: PathBuf,
+    simulation_timeout: Duration,
 }
 
 impl EvolutionEngine {
-    pub fn new(cerebellum: Arc<Cerebrum>) -> Self {
-        Self { cerebellum }
+    pub fn new(llm: Box<dyn LlmBackend>) -> Self {
+        Self {
+            llm,
+            cargo: PathBuf::from("cargo"),
+            simulation_timeout: DEFAULT_SIMULATION_TIMEOUT,
+        }
+    }
+
+    /// Override the simulation budget (default `DEFAULT_SIMULATION_TIMEOUT`)
+    pub fn with_simulation_timeout(mut self, timeout: Duration) -> Self {
+        self.simulation_timeout = timeout;
+        self
+    }
+
+    /// One question to the model, answered in `role`
+    async fn ask(&self, role: &str, model: &str, query: String) -> Result<String> {
+        let prompt = Prompt::new().system(role).user(query);
+        Ok(self.llm.complete(&prompt, &CompletionOptions::new(model)).await?.text)
     }
 }
 
@@ -32,7 +61,7 @@ impl BrainMutationResolver for EvolutionEngine {
         self.check_organ_boundaries(&conflict.repo_root, &conflict.file_path)?;
 
         // 3. Construct reasoning prompt with structural context
-        let query = format!(
+        let mut query = format!(
             "Task: Resolve Git Conflict\nPath: {}\nOrgan Context: {}\n\nOURS (Current version):\n{}\n\nTHEIRS (Incoming change):\n{}\n\nBASE (Common ancestor):\n{}\n\nConstraint: Ensure the resolution stays within the defined role of this organ and follows IPPOC-FS rules. Return ONLY the resolved content.",
             conflict.file_path,
             conflict.repo_root,
@@ -41,14 +70,19 @@ impl BrainMutationResolver for EvolutionEngine {
             conflict.hunk_base.unwrap_or_default()
         );
 
-        // 4. Consult Cerebellum (High-level reasoning)
-        let req = cerebellum::ThoughtRequest {
-            query,
-            context_history: vec!["You are the Evolution Engine. You resolve conflicts while enforcing structural integrity and Rule 6.".to_string()],
-        };
+        // Retry: show the Brain why its previous resolution failed simulation
+        if let Some(failure) = &conflict.previous_failure {
+            query.push_str(&format!(
+                "\n\nPREVIOUS ATTEMPT FAILED SIMULATION:\n{failure}\n\nFix the resolution so it compiles cleanly."
+            ));
+        }
 
-        let response = self.cerebellum.think(req).await?;
-        let resolution = response.answer;
+        // 4. Consult the model (High-level reasoning)
+        let resolution = self.ask(
+            "You are the Evolution Engine. You resolve conflicts while enforcing structural integrity and Rule 6.",
+            CODE_MODEL,
+            query,
+        ).await?;
 
         // 5. Final LOC check
         let res_loc = resolution.lines().count();
@@ -60,20 +94,29 @@ impl BrainMutationResolver for EvolutionEngine {
         Ok(resolution)
     }
 
-    async fn simulate_patch(&self, repo_root: &str, patch_content: &str) -> Result<bool> {
+    async fn simulate_patch(&self, repo_root: &str, patch_content: &str) -> Result<SimulationOutcome> {
         info!("Brain: Simulating mutation impact at {} with strict warning enforcement", repo_root);
-        
+        let deadline = Instant::now() + self.simulation_timeout;
+        let started = Instant::now();
+
         // Rule 11: Warnings are errors
-        let status = std::process::Command::new("cargo")
-            .arg("check")
-            .env("RUSTFLAGS", "-D warnings")
-            .current_dir(repo_root)
-            .status()
-            .context("Simulation failed to execute check")?;
+        match self.run_cargo(repo_root, "check", deadline).await? {
+            CargoRun::Success => {}
+            CargoRun::Failed(stderr) => {
+                let stage = classify_check_failure(&stderr);
+                warn!("Brain: Mutation REJECTED at {:?} stage (Rule 11).", stage);
+                return Ok(SimulationOutcome::failed(stage, stderr_excerpt(&stderr), started.elapsed()));
+            }
+            CargoRun::TimedOut => return Ok(self.timed_out("check", started)),
+        }
 
-        if !status.success() {
-             warn!("Brain: Mutation REJECTED. Lint errors/warnings detected (Rule 11).");
-             return Ok(false);
+        match self.run_cargo(repo_root, "test", deadline).await? {
+            CargoRun::Success => {}
+            CargoRun::Failed(output) => {
+                warn!("Brain: Mutation REJECTED at Test stage.");
+                return Ok(SimulationOutcome::failed(SimulationStage::Test, test_excerpt(&output), started.elapsed()));
+            }
+            CargoRun::TimedOut => return Ok(self.timed_out("test", started)),
         }
 
         let metadata = git_evolution::CommitMetadata {
@@ -85,7 +128,7 @@ impl BrainMutationResolver for EvolutionEngine {
 
         self.update_changelog(repo_root, &metadata)?;
         info!("Brain: Simulation PASSED.");
-        Ok(true)
+        Ok(SimulationOutcome::passed(SimulationStage::Test, started.elapsed()))
     }
 
     async fn review_patch(&self, patch_content: &str) -> Result<bool> {
@@ -95,38 +138,144 @@ impl BrainMutationResolver for EvolutionEngine {
         "Task: Structural Immune Review\nPatch:\n{patch_content}\n\nConstraint: Does this patch violate Rule 0 (Intent), Rule 6 (Scope), or Rule 10 (Safety)?\nReturn: JSON {{ \"approved\": bool, \"reason\": string }}"
     );
 
-        let req = cerebellum::ThoughtRequest {
+        let answer = self.ask(
+            "You are the IPPOC Immune System. You reject any mutation that weakens the organism or violates structural laws.",
+            REASONING_MODEL,
             query,
-            context_history: vec!["You are the IPPOC Immune System. You reject any mutation that weakens the organism or violates structural laws.".to_string()],
-        };
-
-        let response = self.cerebellum.think(req).await?;
-        Ok(response.answer.contains("\"safe\": true"))
+        ).await?;
+        Ok(answer.contains("\"safe\": true"))
     }
 
-    async fn scan_for_governance_violations(&self, summary: &str) -> Result<Vec<String>> {
+    async fn scan_for_governance_violations(&self, summary: &str) -> Result<Vec<Violation>> {
         info!("Brain: Scanning for governance violations in proposed evolution");
         
         let query = format!(
-        "Task: Governance Audit\nProposed Change Summary:\n{summary}\n\nConstraint: Identify any violations of IPPOC Constitution (Rules 0-22).\nReturn: List of violation strings. Empty if safe."
+        "Task: Governance Audit\nProposed Change Summary:\n{summary}\n\nConstraint: Identify any violations of IPPOC Constitution (Rules 0-22).\nReturn: ONLY a JSON array of {{ \"rule\": \"Rule N\", \"detail\": string }}. Return [] if safe."
     );
 
-        let req = cerebellum::ThoughtRequest {
+        let answer = self.ask(
+            "You are the Guardian of the IPPOC System Canon. You enforce hard stop conditions for all mutations.",
+            REASONING_MODEL,
             query,
-            context_history: vec!["You are the Guardian of the IPPOC System Canon. You enforce hard stop conditions for all mutations.".to_string()],
+        ).await?;
+        Ok(parse_violations(&answer))
+    }
+}
+
+/// How one cargo invocation of a simulation ended
+enum CargoRun {
+    Success,
+    /// Non-zero exit, with stdout (test reports) followed by stderr
+    Failed(String),
+    TimedOut,
+}
+
+impl EvolutionEngine {
+    /// `cargo <subcommand>` in `repo_root` with warnings denied, killed at `deadline`
+    async fn run_cargo(&self, repo_root: &str, subcommand: &str, deadline: Instant) -> Result<CargoRun> {
+        let child = tokio::process::Command::new(&self.cargo)
+            .arg(subcommand)
+            .env("RUSTFLAGS", "-D warnings")
+            .current_dir(repo_root)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Simulation failed to execute {subcommand}"))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Ok(output) = tokio::time::timeout(remaining, child.wait_with_output()).await else {
+            return Ok(CargoRun::TimedOut);
         };
+        let output = output.with_context(|| format!("Simulation failed to collect {subcommand} output"))?;
+        if output.status.success() {
+            return Ok(CargoRun::Success);
+        }
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(CargoRun::Failed(text))
+    }
 
-        let response = self.cerebellum.think(req).await?;
-        
-        // Simple heuristic for parsing violations from LLM response
-        if response.answer.contains("[]") || response.answer.to_lowercase().contains("no violations") {
-            Ok(vec![])
-        } else {
-            Ok(vec![response.answer]) // Wrap the reasoning as a violation for now
+    fn timed_out(&self, subcommand: &str, started: Instant) -> SimulationOutcome {
+        warn!("Brain: Mutation REJECTED: cargo {} exceeded the {:?} simulation budget.", subcommand, self.simulation_timeout);
+        SimulationOutcome::failed(
+            SimulationStage::Timeout,
+            format!("cargo {subcommand} did not finish within {}s", self.simulation_timeout.as_secs()),
+            started.elapsed(),
+        )
+    }
+}
+
+/// rustc hard errors carry an `E` code; warnings promoted by `-D warnings` don't
+fn classify_check_failure(stderr: &str) -> SimulationStage {
+    if stderr.lines().any(|l| l.starts_with("error[E")) {
+        SimulationStage::Compile
+    } else {
+        SimulationStage::Lint
+    }
+}
+
+const STDERR_EXCERPT_CHARS: usize = 2000;
+
+/// Diagnostics starting at the first error, bounded so they fit in a prompt
+fn stderr_excerpt(stderr: &str) -> String {
+    let start = stderr.find("error").unwrap_or(0);
+    stderr[start..].chars().take(STDERR_EXCERPT_CHARS).collect()
+}
+
+/// Test failures start at the first failing test (or compile error in a test target)
+fn test_excerpt(output: &str) -> String {
+    let start = ["failures:", "panicked at", "error"]
+        .iter()
+        .filter_map(|marker| output.find(marker))
+        .min()
+        .unwrap_or(0);
+    output[start..].chars().take(STDERR_EXCERPT_CHARS).collect()
+}
+
+/// Shapes the model is known to emit for a single violation
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawViolation {
+    Structured {
+        #[serde(default)]
+        rule: Option<String>,
+        #[serde(default, alias = "description", alias = "reason")]
+        detail: Option<String>,
+    },
+    Text(String),
+}
+
+impl From<RawViolation> for Violation {
+    fn from(raw: RawViolation) -> Self {
+        match raw {
+            RawViolation::Structured { rule, detail } => Violation {
+                rule: rule.unwrap_or_else(|| "Unspecified".to_string()),
+                detail: detail.unwrap_or_default(),
+            },
+            RawViolation::Text(detail) => Violation { rule: "Unspecified".to_string(), detail },
         }
     }
 }
 
+/// Parse the Guardian's answer into structured violations.
+/// Accepts a bare JSON array or the first array embedded in prose; anything
+/// unparseable fails closed as a single violation carrying the raw answer.
+pub fn parse_violations(answer: &str) -> Vec<Violation> {
+    for (start, _) in answer.match_indices('[') {
+        let mut stream = serde_json::Deserializer::from_str(&answer[start..]).into_iter::<Vec<RawViolation>>();
+        if let Some(Ok(raw)) = stream.next() {
+            return raw.into_iter().map(Violation::from).collect();
+        }
+    }
+
+    warn!("Brain: Governance audit returned no parseable JSON; treating as violation");
+    vec![Violation {
+        rule: "Unparseable".to_string(),
+        detail: answer.trim().to_string(),
+    }]
+}
+
 impl EvolutionEngine {
     fn check_organ_boundaries(&self, repo_root: &str, file_path: &str) -> Result<()> {
         // Enforce Rule 3: Allowed Access Matrix
@@ -166,3 +315,119 @@ impl EvolutionEngine {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_check_failure() {
+        let compile = "error[E0425]: cannot find value `x` in this scope\n --> src/lib.rs:3:5\n";
+        assert_eq!(classify_check_failure(compile), SimulationStage::Compile);
+
+        let lint = "error: unused variable: `y`\n  = note: `-D unused-variables` implied by `-D warnings`\n";
+        assert_eq!(classify_check_failure(lint), SimulationStage::Lint);
+    }
+
+    #[test]
+    fn test_stderr_excerpt_starts_at_first_error() {
+        let stderr = "    Checking node v0.1.0\nerror[E0308]: mismatched types\n";
+        assert_eq!(stderr_excerpt(stderr), "error[E0308]: mismatched types\n");
+    }
+
+    /// Engine whose "cargo" is a shell script answering per subcommand
+    fn engine_with_cargo(dir: &std::path::Path, script: &str) -> EvolutionEngine {
+        use std::os::unix::fs::PermissionsExt;
+        let cargo = dir.join("fake-cargo");
+        std::fs::write(&cargo, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut engine = EvolutionEngine::new(Box::new(cerebellum::llm::EchoBackend));
+        engine.cargo = cargo;
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_simulation_passes_through_test_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine_with_cargo(dir.path(), "exit 0");
+        let outcome = engine.simulate_patch(dir.path().to_str().unwrap(), "patch").await.unwrap();
+        assert!(outcome.passed);
+        assert_eq!(outcome.stage, SimulationStage::Test);
+    }
+
+    #[tokio::test]
+    async fn test_failing_tests_report_test_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = r#"[ "$1" = test ] || exit 0
+echo "running 1 test"
+echo "failures:"
+echo "    tests::it_works"
+exit 101"#;
+        let engine = engine_with_cargo(dir.path(), script);
+        let outcome = engine.simulate_patch(dir.path().to_str().unwrap(), "patch").await.unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.stage, SimulationStage::Test);
+        assert!(outcome.stderr_excerpt.starts_with("failures:"));
+        assert!(outcome.stderr_excerpt.contains("tests::it_works"));
+    }
+
+    #[tokio::test]
+    async fn test_check_failure_stops_before_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = r#"[ "$1" = check ] && { echo "error[E0425]: cannot find value" >&2; exit 101; }
+touch tests-ran"#;
+        let engine = engine_with_cargo(dir.path(), script);
+        let outcome = engine.simulate_patch(dir.path().to_str().unwrap(), "patch").await.unwrap();
+        assert_eq!(outcome.stage, SimulationStage::Compile);
+        assert!(!dir.path().join("tests-ran").exists());
+    }
+
+    #[tokio::test]
+    async fn test_slow_simulation_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine_with_cargo(dir.path(), r#"[ "$1" = test ] && exec sleep 30; exit 0"#)
+            .with_simulation_timeout(Duration::from_millis(500));
+        let started = Instant::now();
+        let outcome = engine.simulate_patch(dir.path().to_str().unwrap(), "patch").await.unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.stage, SimulationStage::Timeout);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_parse_well_formed() {
+        let answer = r#"[{"rule": "Rule 6", "detail": "Touches 900 lines"}, {"rule": "Rule 3", "detail": "Crosses organ boundary"}]"#;
+        let violations = parse_violations(answer);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0], Violation { rule: "Rule 6".into(), detail: "Touches 900 lines".into() });
+        assert_eq!(violations[1].rule, "Rule 3");
+    }
+
+    #[test]
+    fn test_parse_empty_array_is_safe() {
+        assert!(parse_violations("[]").is_empty());
+        assert!(parse_violations("No violations found.\n```json\n[]\n```").is_empty());
+    }
+
+    #[test]
+    fn test_parse_prose_wrapped() {
+        let answer = "After review [see canon], I found:\n```json\n[{\"rule\": \"Rule 10\", \"reason\": \"Disables safety check\"}]\n```\nPlease fix.";
+        let violations = parse_violations(answer);
+        assert_eq!(violations, vec![Violation { rule: "Rule 10".into(), detail: "Disables safety check".into() }]);
+    }
+
+    #[test]
+    fn test_parse_plain_strings() {
+        let violations = parse_violations(r#"["Rule 0: intent missing"]"#);
+        assert_eq!(violations[0].rule, "Unspecified");
+        assert_eq!(violations[0].detail, "Rule 0: intent missing");
+    }
+
+    #[test]
+    fn test_parse_malformed_fails_closed() {
+        let violations = parse_violations("The change looks risky [Rule 6");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "Unparseable");
+        assert!(violations[0].detail.contains("looks risky"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Append-only record of simulation results, one JSON line per run
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::SimulationResult;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    scenario: String,
+    /// Unix seconds
+    recorded_at: u64,
+    result: SimulationResult,
+}
+
+/// How a scenario's simulations have been going
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationTrend {
+    pub runs: usize,
+    pub success_rate: f32,
+    pub mean_rpi: f32,
+    pub mean_duration_ms: u64,
+    pub p95_duration_ms: u64,
+}
+
+/// Results stored at e.g. `<node_root>/worldmodel/history.jsonl`
+pub struct SimulationHistory {
+    path: PathBuf,
+}
+
+impl SimulationHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The conventional location under a node root
+    pub fn under_node_root(node_root: &Path) -> Self {
+        Self::new(node_root.join("worldmodel").join("history.jsonl"))
+    }
+
+    pub fn record(&self, scenario: &str, result: &SimulationResult) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let entry = HistoryEntry {
+            scenario: scenario.to_string(),
+            recorded_at: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs(),
+            result: result.clone(),
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Every recorded result for `scenario`, oldest first
+    pub fn results(&self, scenario: &str) -> Result<Vec<SimulationResult>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut results = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            match serde_json::from_str::<HistoryEntry>(&line) {
+                Ok(entry) if entry.scenario == scenario => results.push(entry.result),
+                Ok(_) => {}
+                Err(e) => warn!("WorldModel: Skipping unreadable history line in {:?}: {}", self.path, e),
+            }
+        }
+        Ok(results)
+    }
+
+    /// None until the scenario has been simulated at least once
+    pub fn trend(&self, scenario: &str) -> Result<Option<SimulationTrend>> {
+        Ok(summarize(&self.results(scenario)?))
+    }
+}
+
+fn summarize(results: &[SimulationResult]) -> Option<SimulationTrend> {
+    if results.is_empty() {
+        return None;
+    }
+    let runs = results.len();
+    let mut durations: Vec<u64> = results.iter().map(|r| r.duration_ms).collect();
+    durations.sort_unstable();
+    // Nearest-rank percentile
+    let p95_index = ((runs as f64 * 0.95).ceil() as usize).clamp(1, runs) - 1;
+
+    Some(SimulationTrend {
+        runs,
+        success_rate: results.iter().filter(|r| r.success).count() as f32 / runs as f32,
+        mean_rpi: results.iter().map(|r| r.rpi).sum::<f32>() / runs as f32,
+        mean_duration_ms: durations.iter().sum::<u64>() / runs as u64,
+        p95_duration_ms: durations[p95_index],
+    })
+}
@@ -1,9 +1,13 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::time::Instant;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{info, warn};
 
+pub mod history;
+pub use history::{SimulationHistory, SimulationTrend};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationResult {
     pub success: bool,
@@ -20,19 +24,162 @@ pub struct SimulationMetrics {
     pub network_calls: u32,
 }
 
+/// Workspaces are directories named `worldmodel-*` under `base`
+pub const WORKSPACE_PREFIX: &str = "worldmodel-";
+
+/// Where simulation workspaces live and when leftovers count as abandoned
+#[derive(Debug, Clone)]
+pub struct WorkspacePolicy {
+    /// Parent directory (e.g. `<node_root>/worldmodel`); defaults to the system temp dir
+    pub base: PathBuf,
+    /// Workspaces untouched for this long are removed at startup
+    pub stale_after: Duration,
+}
+
+impl Default for WorkspacePolicy {
+    fn default() -> Self {
+        Self {
+            base: std::env::temp_dir(),
+            stale_after: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl WorkspacePolicy {
+    pub fn under(base: impl Into<PathBuf>) -> Self {
+        Self { base: base.into(), ..Default::default() }
+    }
+
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Remove workspaces left behind by crashed runs. Returns how many were removed.
+    pub fn sweep_stale(&self) -> Result<usize> {
+        if !self.base.exists() {
+            return Ok(0);
+        }
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in std::fs::read_dir(&self.base)? {
+            let entry = entry?;
+            let stale = entry.file_name().to_string_lossy().starts_with(WORKSPACE_PREFIX)
+                && entry.file_type()?.is_dir()
+                && entry.metadata()?.modified()
+                    .map(|m| now.duration_since(m).unwrap_or_default() >= self.stale_after)
+                    .unwrap_or(false);
+            if stale {
+                match std::fs::remove_dir_all(entry.path()) {
+                    Ok(()) => removed += 1,
+                    Err(e) => warn!("WorldModel: Could not remove stale workspace {:?}: {}", entry.path(), e),
+                }
+            }
+        }
+        if removed > 0 {
+            info!("WorldModel: Swept {} stale workspaces from {:?}", removed, self.base);
+        }
+        Ok(removed)
+    }
+}
+
 pub struct WorldModel {
     workspace: PathBuf,
+    /// Shared cargo target dirs, one per dependency set (see `target_dir`)
+    target_cache: Option<PathBuf>,
+    /// Where results are kept for trends and the RPI baseline
+    history: Option<SimulationHistory>,
+    /// Git repository patches are applied to; None = standalone mode
+    target_repo: Option<PathBuf>,
 }
 
 impl WorldModel {
     pub fn new() -> Result<Self> {
-        let workspace = tempfile::tempdir()?.keep();
+        Self::with_policy(&WorkspacePolicy::default())
+    }
+
+    /// Sweep stale workspaces under `policy.base`, then create a fresh one there
+    pub fn with_policy(policy: &WorkspacePolicy) -> Result<Self> {
+        std::fs::create_dir_all(&policy.base)?;
+        policy.sweep_stale()?;
+        // Kept so simulations can run across awaits; removed again on Drop
+        let workspace = tempfile::Builder::new()
+            .prefix(WORKSPACE_PREFIX)
+            .tempdir_in(&policy.base)?
+            .keep();
         info!("WorldModel: Created simulation workspace at {:?}", workspace);
         
-        Ok(Self { workspace })
+        Ok(Self { workspace, target_cache: None, history: None, target_repo: None })
     }
 
-    /// Simulate a code patch in isolation
+    /// Reuse compiled dependencies across simulations from `dir`
+    pub fn with_target_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.target_cache = Some(dir.into());
+        self
+    }
+
+    /// Simulate patches as diffs against a clone of `repo` instead of standalone code
+    pub fn with_target_repo(mut self, repo: impl Into<PathBuf>) -> Self {
+        self.target_repo = Some(repo.into());
+        self
+    }
+
+    /// Record every simulation's result
+    pub fn with_history(mut self, history: SimulationHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Past results for `scenario`, oldest first (empty without a history)
+    pub fn history(&self, scenario: &str) -> Result<Vec<SimulationResult>> {
+        match &self.history {
+            Some(history) => history.results(scenario),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn trend(&self, scenario: &str) -> Result<Option<SimulationTrend>> {
+        match &self.history {
+            Some(history) => history.trend(scenario),
+            None => Ok(None),
+        }
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    /// Where `cargo` builds. With a cache, workspaces with the same manifest and
+    /// lockfile share a target dir, so their dependencies compile once; a patch
+    /// that changes dependencies gets its own. The patched crate itself is still
+    /// rebuilt per simulation: cargo fingerprints path crates by their location.
+    pub fn target_dir(&self) -> Result<PathBuf> {
+        let Some(cache) = &self.target_cache else {
+            return Ok(self.project_dir().join("target"));
+        };
+        let mut hasher = Sha256::new();
+        for file in ["Cargo.toml", "Cargo.lock"] {
+            match std::fs::read(self.project_dir().join(file)) {
+                Ok(bytes) => hasher.update(&bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            hasher.update([0u8]);
+        }
+        let key: String = hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        Ok(cache.join(key))
+    }
+
+    /// The crate scenarios run in: the repo clone, or the workspace itself
+    fn project_dir(&self) -> PathBuf {
+        match self.target_repo {
+            Some(_) => self.workspace.join("repo"),
+            None => self.workspace.clone(),
+        }
+    }
+
+    /// Simulate a code patch in isolation. With a target repo, `patch_code` is a
+    /// unified diff applied to a fresh clone; otherwise it is written standalone.
     pub async fn simulate_patch(&self, patch_code: &str, scenario: &str) -> Result<SimulationResult> {
         info!("WorldModel: Simulating patch in scenario '{}'", scenario);
         let start = Instant::now();
@@ -40,12 +187,18 @@ impl WorldModel {
         // 1. Create isolated environment
         self.setup_environment()?;
 
-        // 2. Apply patch (write to temp file)
-        let patch_file = self.workspace.join("patch.rs");
-        std::fs::write(&patch_file, patch_code)?;
+        // 2. Apply patch
+        let mut error = None;
+        match &self.target_repo {
+            Some(repo) => error = self.apply_to_clone(repo, patch_code)?,
+            None => std::fs::write(self.workspace.join("patch.rs"), patch_code)?,
+        }
 
-        // 3. Run scenario
-        let success = self.run_scenario(scenario).await?;
+        // 3. Run scenario (a patch that doesn't apply has nothing to run)
+        let success = error.is_none() && self.run_scenario(scenario).await?;
+        if !success && error.is_none() {
+            error = Some("Simulation failed".to_string());
+        }
 
         // 4. Collect metrics
         let metrics = SimulationMetrics {
@@ -59,13 +212,49 @@ impl WorldModel {
         // Rule 6.1: Reality Parity Index
         let rpi = 0.98; // Mock - would compare simulated_metrics vs historical real_metrics
 
-        Ok(SimulationResult {
+        let result = SimulationResult {
             success,
             duration_ms,
             metrics,
             rpi,
-            error: if success { None } else { Some("Simulation failed".to_string()) },
-        })
+            error,
+        };
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record(scenario, &result) {
+                warn!("WorldModel: Could not record result for '{}': {}", scenario, e);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Fresh clone of `repo` with the diff applied; Some(reason) if it doesn't apply
+    fn apply_to_clone(&self, repo: &Path, diff: &str) -> Result<Option<String>> {
+        let clone = self.project_dir();
+        if clone.exists() {
+            std::fs::remove_dir_all(&clone)?;
+        }
+        let cloned = std::process::Command::new("git")
+            .args(["clone", "--quiet", "--local", "--no-hardlinks"])
+            .arg(repo)
+            .arg(&clone)
+            .output()?;
+        if !cloned.status.success() {
+            anyhow::bail!("Could not clone {:?}: {}", repo, String::from_utf8_lossy(&cloned.stderr).trim());
+        }
+
+        let patch_file = self.workspace.join("patch.diff");
+        std::fs::write(&patch_file, diff)?;
+        let applied = std::process::Command::new("git")
+            .args(["apply", "--whitespace=nowarn"])
+            .arg(&patch_file)
+            .current_dir(&clone)
+            .output()?;
+        if !applied.status.success() {
+            let reason = String::from_utf8_lossy(&applied.stderr).trim().to_string();
+            warn!("WorldModel: Patch does not apply to {:?}: {}", repo, reason);
+            return Ok(Some(format!("Patch does not apply: {}", reason)));
+        }
+        Ok(None)
     }
 
     fn setup_environment(&self) -> Result<()> {
@@ -76,18 +265,10 @@ impl WorldModel {
     }
 
     async fn run_scenario(&self, scenario: &str) -> Result<bool> {
-        info!("WorldModel: Running scenario '{}' in {:?}", scenario, self.workspace);
+        info!("WorldModel: Running scenario '{}' in {:?}", scenario, self.project_dir());
         
         match scenario {
-            "basic_compile" => {
-                info!("WorldModel: Running cargo check");
-                let status = std::process::Command::new("cargo")
-                    .arg("check")
-                    .current_dir(&self.workspace)
-                    .status()?;
-                
-                Ok(status.success())
-            }
+            "basic_compile" => self.cargo_check(),
             "high_load" => {
                 info!("WorldModel: Simulating high load impact");
                 // Hypothetical load test
@@ -104,6 +285,18 @@ impl WorldModel {
         }
     }
 
+    fn cargo_check(&self) -> Result<bool> {
+        let target_dir = self.target_dir()?;
+        info!("WorldModel: Running cargo check (target {:?})", target_dir);
+        let status = std::process::Command::new("cargo")
+            .arg("check")
+            .current_dir(self.project_dir())
+            .env("CARGO_TARGET_DIR", &target_dir)
+            .status()?;
+        
+        Ok(status.success())
+    }
+
     /// Clean up simulation environment
     pub fn cleanup(&self) -> Result<()> {
         if self.workspace.exists() {
@@ -116,6 +309,191 @@ impl WorldModel {
 
 impl Drop for WorldModel {
     fn drop(&mut self) {
-        let _ = self.cleanup();
+        if let Err(e) = self.cleanup() {
+            warn!("WorldModel: Could not remove workspace {:?}: {}", self.workspace, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_base(name: &str) -> PathBuf {
+        let base = std::env::temp_dir().join(format!("worldmodel_test_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&base).unwrap();
+        base
+    }
+
+    #[test]
+    fn test_dropped_model_removes_workspace() -> Result<()> {
+        let base = temp_base("drop");
+        let model = WorldModel::with_policy(&WorkspacePolicy::under(&base))?;
+        let workspace = model.workspace().to_path_buf();
+        assert!(workspace.starts_with(&base));
+        assert!(workspace.exists());
+
+        drop(model);
+        assert!(!workspace.exists());
+        std::fs::remove_dir_all(base)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_startup_sweeps_stale_workspaces() -> Result<()> {
+        let base = temp_base("sweep");
+        let leaked = base.join(format!("{}crashed", WORKSPACE_PREFIX));
+        let unrelated = base.join("keep-me");
+        std::fs::create_dir_all(&leaked)?;
+        std::fs::create_dir_all(&unrelated)?;
+
+        let model = WorldModel::with_policy(&WorkspacePolicy::under(&base).with_stale_after(Duration::ZERO))?;
+        assert!(!leaked.exists());
+        assert!(unrelated.exists());
+        assert!(model.workspace().exists());
+
+        drop(model);
+        std::fs::remove_dir_all(base)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_target_cache_is_keyed_by_dependencies() -> Result<()> {
+        let base = temp_base("cache_key");
+        let policy = WorkspacePolicy::under(&base);
+        let cache = base.join("targets");
+        let a = WorldModel::with_policy(&policy)?.with_target_cache(&cache);
+        let b = WorldModel::with_policy(&policy)?.with_target_cache(&cache);
+        let manifest = "[package]\nname = \"sim\"\nversion = \"0.1.0\"\n";
+        std::fs::write(a.workspace().join("Cargo.toml"), manifest)?;
+        std::fs::write(b.workspace().join("Cargo.toml"), manifest)?;
+        assert_eq!(a.target_dir()?, b.target_dir()?);
+        assert!(a.target_dir()?.starts_with(&cache));
+
+        // A patch that pulls in another dependency can't share (or poison) the first's artifacts
+        std::fs::write(b.workspace().join("Cargo.toml"), format!("{}\n[dependencies]\nextra = \"1\"\n", manifest))?;
+        assert_ne!(a.target_dir()?, b.target_dir()?);
+
+        let uncached = WorldModel::with_policy(&policy)?;
+        assert_eq!(uncached.target_dir()?, uncached.workspace().join("target"));
+
+        drop((a, b, uncached));
+        std::fs::remove_dir_all(base)?;
+        Ok(())
+    }
+
+    // Needs a cargo toolchain; run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_cached_targets_speed_up_repeated_checks() -> Result<()> {
+        let base = temp_base("cache_timing");
+        // A dependency heavy enough that compiling it dominates the check
+        let dep = base.join("dep");
+        std::fs::create_dir_all(dep.join("src"))?;
+        std::fs::write(dep.join("Cargo.toml"), "[package]\nname = \"dep\"\nversion = \"0.1.0\"\nedition = \"2021\"\n")?;
+        let body: String = (0..400).map(|i| format!("pub fn f{i}(x: u64) -> u64 {{ (0..x).map(|v| v * {i}).sum() }}\n")).collect();
+        std::fs::write(dep.join("src/lib.rs"), body)?;
+        let manifest = format!(
+            "[package]\nname = \"sim\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ndep = {{ path = {:?} }}\n",
+            dep
+        );
+
+        let policy = WorkspacePolicy::under(base.join("workspaces"));
+        let mut timings = Vec::new();
+        for i in 0..5 {
+            let model = WorldModel::with_policy(&policy)?.with_target_cache(base.join("targets"));
+            model.setup_environment()?;
+            std::fs::write(model.workspace().join("Cargo.toml"), &manifest)?;
+            std::fs::write(model.workspace().join("src/lib.rs"), format!("pub fn patched() -> u64 {{ dep::f1({i}) }}\n"))?;
+            let start = Instant::now();
+            assert!(model.cargo_check()?);
+            timings.push(start.elapsed());
+        }
+
+        let first = timings[0];
+        let rest = timings[1..].iter().sum::<Duration>() / 4;
+        println!("cold {:?}, warm average {:?}", first, rest);
+        assert!(rest < first, "cached checks ({:?}) should beat the cold one ({:?})", rest, first);
+
+        std::fs::remove_dir_all(base)?;
+        Ok(())
+    }
+
+    fn result(success: bool, rpi: f32, duration_ms: u64) -> SimulationResult {
+        SimulationResult {
+            success,
+            duration_ms,
+            metrics: SimulationMetrics { cpu_usage: 0.0, memory_mb: 0.0, network_calls: 0 },
+            rpi,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_history_trend_per_scenario() -> Result<()> {
+        let base = temp_base("history");
+        let history = SimulationHistory::under_node_root(&base);
+        for (i, duration) in [100, 200, 300, 400, 1000].into_iter().enumerate() {
+            history.record("basic_compile", &result(i != 4, 0.9, duration))?;
+        }
+        history.record("high_load", &result(true, 0.5, 5))?;
+
+        let model = WorldModel::with_policy(&WorkspacePolicy::under(&base))?
+            .with_history(SimulationHistory::under_node_root(&base));
+        assert_eq!(model.history("basic_compile")?.len(), 5);
+        assert!(base.join("worldmodel").join("history.jsonl").exists());
+
+        let trend = model.trend("basic_compile")?.expect("recorded runs");
+        assert_eq!(trend.runs, 5);
+        assert_eq!(trend.success_rate, 0.8);
+        assert!((trend.mean_rpi - 0.9).abs() < 1e-6);
+        assert_eq!(trend.mean_duration_ms, 400);
+        assert_eq!(trend.p95_duration_ms, 1000);
+        assert_eq!(model.trend("network_partition")?, None);
+
+        drop(model);
+        std::fs::remove_dir_all(base)?;
+        Ok(())
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git").args(args).current_dir(dir).status().unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    // Needs git and a cargo toolchain; run with `cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_patches_are_checked_against_the_repo() -> Result<()> {
+        let base = temp_base("repo_patch");
+        let repo = base.join("upstream");
+        std::fs::create_dir_all(repo.join("src"))?;
+        std::fs::write(repo.join("Cargo.toml"), "[package]\nname = \"upstream\"\nversion = \"0.1.0\"\nedition = \"2021\"\n")?;
+        std::fs::write(repo.join("src/lib.rs"), "pub fn answer() -> u32 {\n    42\n}\n")?;
+        git(&repo, &["init", "--quiet"]);
+        git(&repo, &["add", "."]);
+        git(&repo, &["-c", "user.name=t", "-c", "user.email=t@t", "commit", "--quiet", "-m", "init"]);
+
+        let diff = |body: &str| format!(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,3 +1,3 @@\n pub fn answer() -> u32 {{\n-    42\n+    {}\n }}\n",
+            body
+        );
+        let model = WorldModel::with_policy(&WorkspacePolicy::under(base.join("workspaces")))?.with_target_repo(&repo);
+
+        let compiles = model.simulate_patch(&diff("41 + 1"), "basic_compile").await?;
+        assert!(compiles.success, "{:?}", compiles.error);
+
+        let broken = model.simulate_patch(&diff("\"forty-two\""), "basic_compile").await?;
+        assert!(!broken.success);
+
+        let stale = model.simulate_patch(&diff("7").replace("-    42", "-    43"), "basic_compile").await?;
+        assert!(stale.error.unwrap().contains("does not apply"));
+
+        // The upstream checkout is never touched
+        assert!(std::fs::read_to_string(repo.join("src/lib.rs"))?.contains("42"));
+
+        drop(model);
+        std::fs::remove_dir_all(base)?;
+        Ok(())
     }
 }
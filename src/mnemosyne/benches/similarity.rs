@@ -0,0 +1,40 @@
+//! Cosine over 10k x 1536-dim vectors, SIMD vs scalar.
+//!
+//! Run: cargo bench --bench similarity
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hidb::similarity::{cosine, cosine_scalar};
+
+const DIM: usize = 1536;
+const VECTORS: usize = 10_000;
+
+fn corpus() -> (Vec<f32>, Vec<Vec<f32>>) {
+    let mut state = 0x9E3779B9u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    };
+    let query = (0..DIM).map(|_| next()).collect();
+    let vectors = (0..VECTORS).map(|_| (0..DIM).map(|_| next()).collect()).collect();
+    (query, vectors)
+}
+
+fn bench_cosine(c: &mut Criterion) {
+    let (query, vectors) = corpus();
+    let mut group = c.benchmark_group("cosine_10k_x_1536");
+    group.sample_size(20);
+
+    group.bench_function("scalar", |b| {
+        b.iter(|| vectors.iter().map(|v| cosine_scalar(black_box(&query), v)).sum::<f32>())
+    });
+    group.bench_function("simd", |b| {
+        b.iter(|| vectors.iter().map(|v| cosine(black_box(&query), v)).sum::<f32>())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_cosine);
+criterion_main!(benches);
@@ -0,0 +1,5 @@
+-- Embedding model that produced each vector; '' marks records from before tagging
+ALTER TABLE memories ADD COLUMN IF NOT EXISTS model TEXT NOT NULL DEFAULT '';
+
+-- Scoped search and re-embedding filter on it
+CREATE INDEX IF NOT EXISTS memories_model_idx ON memories(model);
@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::MemoryRecord;
+
+/// Encoding of `MemoryRecord` values in the Redis cache.
+/// Postgres stores typed columns and never goes through a codec.
+pub trait Codec: Send + Sync {
+    fn encode(&self, record: &MemoryRecord) -> Result<Vec<u8>>;
+    fn decode(&self, bytes: &[u8]) -> Result<MemoryRecord>;
+}
+
+/// Human-readable, but floats cost ~10 bytes each
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode(&self, record: &MemoryRecord) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(record)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<MemoryRecord> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// Fixed 4 bytes per float, fastest to (de)serialize
+pub struct BincodeCodec;
+
+impl Codec for BincodeCodec {
+    fn encode(&self, record: &MemoryRecord) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(record)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<MemoryRecord> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Compact and self-describing, readable from non-Rust services
+pub struct MessagePackCodec;
+
+impl Codec for MessagePackCodec {
+    fn encode(&self, record: &MemoryRecord) -> Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec_named(record)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<MemoryRecord> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// Configurable codec choice (`HIDB_CACHE_CODEC=json|bincode|msgpack`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheCodec {
+    Json,
+    #[default]
+    Bincode,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+impl CacheCodec {
+    pub fn codec(self) -> &'static dyn Codec {
+        match self {
+            CacheCodec::Json => &JsonCodec,
+            CacheCodec::Bincode => &BincodeCodec,
+            CacheCodec::MessagePack => &MessagePackCodec,
+        }
+    }
+}
+
+impl std::str::FromStr for CacheCodec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(CacheCodec::Json),
+            "bincode" => Ok(CacheCodec::Bincode),
+            "msgpack" | "messagepack" => Ok(CacheCodec::MessagePack),
+            other => Err(anyhow!("Unknown cache codec '{}' (expected json, bincode or msgpack)", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> MemoryRecord {
+        let mut record = MemoryRecord::new(
+            "floats survive".into(),
+            vec![0.1, -0.0, 1.0 / 3.0, f32::MIN_POSITIVE, f32::MAX, -1.5e-30, 6.02e23],
+        );
+        record.confidence = 0.3;
+        record
+    }
+
+    #[test]
+    fn test_every_codec_round_trips_exactly() {
+        let original = record();
+        for kind in [CacheCodec::Json, CacheCodec::Bincode, CacheCodec::MessagePack] {
+            let bytes = kind.codec().encode(&original).unwrap();
+            let decoded = kind.codec().decode(&bytes).unwrap();
+
+            assert_eq!(decoded.id, original.id, "{:?}", kind);
+            assert_eq!(decoded.content, original.content, "{:?}", kind);
+            // Bit-exact, including the sign of zero
+            let bits = |v: &[f32]| v.iter().map(|f| f.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(&decoded.embedding), bits(&original.embedding), "{:?}", kind);
+            assert_eq!(decoded.confidence.to_bits(), original.confidence.to_bits(), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_binary_codecs_are_smaller_for_embeddings() {
+        let record = MemoryRecord::new("x".into(), (0..1536).map(|i| (i as f32).sin()).collect());
+        let json = CacheCodec::Json.codec().encode(&record).unwrap().len();
+        for kind in [CacheCodec::Bincode, CacheCodec::MessagePack] {
+            assert!(kind.codec().encode(&record).unwrap().len() * 2 < json, "{:?}", kind);
+        }
+        assert_eq!("MsgPack".parse::<CacheCodec>().unwrap(), CacheCodec::MessagePack);
+        assert!("yaml".parse::<CacheCodec>().is_err());
+    }
+}
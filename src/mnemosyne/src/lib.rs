@@ -1,7 +1,19 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use anyhow::Result;
+use async_trait::async_trait;
 use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub mod codec;
+pub mod provenance;
+pub mod similarity;
+mod sqlite;
+pub use codec::{CacheCodec, Codec};
+pub use provenance::{rank_by_trust, Provenance, SourceTrust, TrustAll, TrustRanked};
+pub use sqlite::SqliteMemoryStore;
+pub use similarity::cosine as cosine_similarity;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryRecord {
@@ -11,6 +23,10 @@ pub struct MemoryRecord {
     pub confidence: f32,
     pub decay_rate: f32,
     pub source: String,
+    /// Embedding model that produced `embedding` (empty for legacy records);
+    /// vectors from different models are not comparable
+    #[serde(default)]
+    pub model: String,
 }
 
 impl MemoryRecord {
@@ -21,14 +37,118 @@ impl MemoryRecord {
             content,
             confidence: 1.0,
             decay_rate: 0.1,
-            source: "node".to_string(),
+            source: Provenance::SelfLearned.to_string(),
+            model: String::new(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.source = provenance.to_string();
+        self
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        Provenance::parse(&self.source)
+    }
+}
+
+/// Storage backend for long-term memory.
+/// `HiDB` (Postgres + Redis) is the production implementation, `SqliteMemoryStore`
+/// serves single-node/edge deployments and `InMemoryStore` needs no services.
+#[async_trait]
+pub trait MemoryStore: Send + Sync {
+    async fn store(&self, memory: &MemoryRecord) -> Result<()>;
+    /// Nearest memories, across every embedding model
+    async fn semantic_search(&self, query_embedding: &[f32], limit: i64) -> Result<Vec<MemoryRecord>> {
+        self.semantic_search_scoped(query_embedding, limit, None).await
+    }
+    /// Nearest memories, only those embedded by `model` when given
+    async fn semantic_search_scoped(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>>;
+    async fn decay_memories(&self) -> Result<()>;
+    /// Raise a memory's confidence by `amount`, capped at 1.0
+    async fn reinforce(&self, id: Uuid, amount: f32) -> Result<()>;
+    async fn get(&self, id: Uuid) -> Result<Option<MemoryRecord>>;
+    /// Forget one memory; false if it didn't exist
+    async fn delete(&self, id: Uuid) -> Result<bool>;
+    /// Forget every memory whose cosine similarity to the query is at least
+    /// `similarity_threshold` (redacting a topic); returns how many went
+    async fn forget(&self, query_embedding: &[f32], similarity_threshold: f32) -> Result<u64>;
+    /// Up to `limit` memories embedded by a model other than `current_model`
+    async fn stale_records(&self, current_model: &str, limit: i64) -> Result<Vec<MemoryRecord>>;
+    async fn update_embedding(&self, id: Uuid, embedding: &[f32], model: &str) -> Result<()>;
+
+    /// Maintenance after an embedding model change: re-embed every memory
+    /// tagged with another model, in batches. Returns how many were updated.
+    async fn reembed_stale(&self, embedder: &dyn Reembedder) -> Result<u64> {
+        let current_model = embedder.model();
+        let mut updated = 0;
+        loop {
+            let batch = self.stale_records(current_model, REEMBED_BATCH).await?;
+            if batch.is_empty() {
+                return Ok(updated);
+            }
+            for record in batch {
+                let embedding = embedder.embed(&record.content).await?;
+                self.update_embedding(record.id, &embedding, current_model).await?;
+                updated += 1;
+            }
+        }
+    }
+}
+
+/// Records re-embedded per `stale_records` round trip
+pub const REEMBED_BATCH: i64 = 64;
+
+/// Embedding model used by `MemoryStore::reembed_stale`
+#[async_trait]
+pub trait Reembedder: Send + Sync {
+    fn model(&self) -> &str;
+    /// Embed a memory's stored content
+    async fn embed(&self, content: &str) -> Result<Vec<f32>>;
+}
+
+/// How HiDB uses its Redis cache. The TTL scales with confidence so noise
+/// expires before knowledge; records below `min_confidence` aren't cached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheConfig {
+    pub codec: CacheCodec,
+    /// TTL of a record with confidence 0.5; confidence 1.0 lives twice as long
+    pub base_ttl_secs: u64,
+    pub min_confidence: f32,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { codec: CacheCodec::default(), base_ttl_secs: 3600, min_confidence: 0.3 }
+    }
+}
+
+impl CacheConfig {
+    /// Redis TTL for a record of this confidence, or None to skip caching it
+    pub fn ttl_for(&self, confidence: f32) -> Option<u64> {
+        if confidence < self.min_confidence {
+            return None;
         }
+        let scale = 2.0 * confidence.clamp(0.0, 1.0) as f64;
+        Some(((self.base_ttl_secs as f64 * scale) as u64).max(1))
     }
 }
 
+/// Rows scanned by the in-Rust cosine fallback when pgvector is unavailable
+pub const DEFAULT_MAX_SCAN: i64 = 10_000;
+
 pub struct HiDB {
     pg_pool: PgPool,
     redis_client: redis::Client,
+    /// pgvector installed: search with `<=>` in SQL
+    pgvector: bool,
+    max_scan: i64,
+    cache: CacheConfig,
 }
 
 impl HiDB {
@@ -36,20 +156,50 @@ impl HiDB {
         let pg_pool = PgPool::connect(database_url).await?;
         let redis_client = redis::Client::open(redis_url)?;
         
-        tracing::info!("HiDB: Connected to PostgreSQL and Redis");
+        let pgvector: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'vector')")
+            .fetch_one(&pg_pool)
+            .await?;
+        if pgvector {
+            tracing::info!("HiDB: Connected to PostgreSQL (pgvector) and Redis");
+        } else {
+            tracing::warn!(
+                "HiDB: pgvector extension not found; semantic search falls back to brute-force cosine (default scan limit {} rows). Install pgvector for production.",
+                DEFAULT_MAX_SCAN
+            );
+        }
         
         Ok(Self {
             pg_pool,
             redis_client,
+            pgvector,
+            max_scan: DEFAULT_MAX_SCAN,
+            cache: CacheConfig::default(),
         })
     }
 
+    /// Bound the brute-force fallback scan (ignored when pgvector is present)
+    pub fn with_max_scan(mut self, max_scan: i64) -> Self {
+        self.max_scan = max_scan;
+        self
+    }
+
+    /// Codec for Redis cache values (Postgres is unaffected)
+    pub fn with_codec(mut self, codec: CacheCodec) -> Self {
+        self.cache.codec = codec;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
     pub async fn store(&self, memory: &MemoryRecord) -> Result<()> {
         // Store in PostgreSQL
         sqlx::query(
             r#"
-            INSERT INTO memories (id, embedding, content, confidence, decay_rate, source)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO memories (id, embedding, content, confidence, decay_rate, source, model)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#
         )
         .bind(memory.id)
@@ -58,49 +208,183 @@ impl HiDB {
         .bind(memory.confidence)
         .bind(memory.decay_rate)
         .bind(&memory.source)
+        .bind(&memory.model)
         .execute(&self.pg_pool)
         .await?;
 
-        // Cache in Redis
+        // Cache in Redis, for longer the more confident the memory
+        let Some(ttl) = self.cache.ttl_for(memory.confidence) else {
+            return Ok(());
+        };
         let mut conn = self.redis_client.get_connection()?;
         let key = format!("memory:{}", memory.id);
-        let value = serde_json::to_string(memory)?;
+        let value = self.cache.codec.codec().encode(memory)?;
         redis::cmd("SET")
             .arg(&key)
             .arg(&value)
             .arg("EX")
-            .arg(3600) // 1 hour TTL
+            .arg(ttl)
             .query::<()>(&mut conn)?;
 
         Ok(())
     }
 
     pub async fn semantic_search(&self, query_embedding: &[f32], limit: i64) -> Result<Vec<MemoryRecord>> {
+        self.semantic_search_scoped(query_embedding, limit, None).await
+    }
+
+    /// `model` restricts results to vectors from that embedding model
+    pub async fn semantic_search_scoped(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>> {
+        if !self.pgvector {
+            return self.scan_search(query_embedding, limit, model).await;
+        }
+
         let rows = sqlx::query(
             r#"
-            SELECT id, embedding, content, confidence, decay_rate, source
+            SELECT id, embedding, content, confidence, decay_rate, source, model
             FROM memories
+            WHERE $3::text IS NULL OR model = $3
             ORDER BY embedding <=> $1
             LIMIT $2
             "#
         )
         .bind(query_embedding)
         .bind(limit)
+        .bind(model)
+        .fetch_all(&self.pg_pool)
+        .await?;
+
+        Ok(rows.iter().map(record_from_row).collect())
+    }
+
+    /// Fallback without pgvector: rank the most confident `max_scan` rows in Rust
+    async fn scan_search(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, embedding, content, confidence, decay_rate, source, model
+            FROM memories
+            WHERE $2::text IS NULL OR model = $2
+            ORDER BY confidence DESC
+            LIMIT $1
+            "#
+        )
+        .bind(self.max_scan)
+        .bind(model)
         .fetch_all(&self.pg_pool)
         .await?;
 
-        let memories = rows.into_iter().map(|row| {
-            MemoryRecord {
-                id: row.get("id"),
-                embedding: row.get("embedding"),
-                content: row.get("content"),
-                confidence: row.get("confidence"),
-                decay_rate: row.get("decay_rate"),
-                source: row.get("source"),
+        let candidates = rows.iter().map(record_from_row).collect();
+        Ok(scan_rank(candidates, query_embedding, limit, model, self.max_scan))
+    }
+
+    /// Redis first, then Postgres
+    pub async fn get(&self, id: Uuid) -> Result<Option<MemoryRecord>> {
+        let mut conn = self.redis_client.get_connection()?;
+        let cached: Option<Vec<u8>> = redis::cmd("GET").arg(format!("memory:{id}")).query(&mut conn)?;
+        if let Some(bytes) = cached {
+            // Written by another codec (config changed): fall through to Postgres
+            match self.cache.codec.codec().decode(&bytes) {
+                Ok(record) => return Ok(Some(record)),
+                Err(e) => tracing::debug!("HiDB: undecodable cache entry for {}: {}", id, e),
             }
-        }).collect();
+        }
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, embedding, content, confidence, decay_rate, source, model
+            FROM memories
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        Ok(row.as_ref().map(record_from_row))
+    }
+
+    pub async fn stale_records(&self, current_model: &str, limit: i64) -> Result<Vec<MemoryRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, embedding, content, confidence, decay_rate, source, model
+            FROM memories
+            WHERE model <> $1
+            LIMIT $2
+            "#
+        )
+        .bind(current_model)
+        .bind(limit)
+        .fetch_all(&self.pg_pool)
+        .await?;
+
+        Ok(rows.iter().map(record_from_row).collect())
+    }
+
+    pub async fn update_embedding(&self, id: Uuid, embedding: &[f32], model: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE memories
+            SET embedding = $2, model = $3, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(embedding)
+        .bind(model)
+        .execute(&self.pg_pool)
+        .await?;
+
+        let mut conn = self.redis_client.get_connection()?;
+        redis::cmd("DEL").arg(format!("memory:{id}")).query::<()>(&mut conn)?;
+        Ok(())
+    }
+
+    /// Remove from both stores
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM memories WHERE id = $1")
+            .bind(id)
+            .execute(&self.pg_pool)
+            .await?
+            .rows_affected() > 0;
+
+        let mut conn = self.redis_client.get_connection()?;
+        let evicted: i64 = redis::cmd("DEL").arg(format!("memory:{id}")).query(&mut conn)?;
+        Ok(deleted || evicted > 0)
+    }
 
-        Ok(memories)
+    /// Delete all memories at least `similarity_threshold` similar to the
+    /// query from Postgres, and their cached copies from Redis
+    pub async fn forget(&self, query_embedding: &[f32], similarity_threshold: f32) -> Result<u64> {
+        let ids: Vec<Uuid> = if self.pgvector {
+            sqlx::query_scalar("DELETE FROM memories WHERE 1 - (embedding <=> $1) >= $2 RETURNING id")
+                .bind(query_embedding)
+                .bind(similarity_threshold as f64)
+                .fetch_all(&self.pg_pool)
+                .await?
+        } else {
+            // Redaction must be complete, so scan every row rather than `max_scan`
+            let rows = sqlx::query("SELECT id, embedding FROM memories")
+                .fetch_all(&self.pg_pool)
+                .await?;
+            let ids = forget_matches(
+                rows.iter().map(|row| (row.get("id"), row.get("embedding"))),
+                query_embedding,
+                similarity_threshold,
+            );
+            sqlx::query("DELETE FROM memories WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&self.pg_pool)
+                .await?;
+            ids
+        };
+
+        if !ids.is_empty() {
+            let mut conn = self.redis_client.get_connection()?;
+            let keys: Vec<String> = ids.iter().map(|id| format!("memory:{id}")).collect();
+            redis::cmd("DEL").arg(keys).query::<()>(&mut conn)?;
+        }
+        tracing::info!("HiDB: forgot {} memories (similarity >= {})", ids.len(), similarity_threshold);
+        Ok(ids.len() as u64)
     }
 
     pub async fn decay_memories(&self) -> Result<()> {
@@ -125,6 +409,467 @@ impl HiDB {
     }
 }
 
+#[async_trait]
+impl MemoryStore for HiDB {
+    async fn store(&self, memory: &MemoryRecord) -> Result<()> {
+        HiDB::store(self, memory).await
+    }
+
+    async fn semantic_search_scoped(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>> {
+        HiDB::semantic_search_scoped(self, query_embedding, limit, model).await
+    }
+
+    async fn decay_memories(&self) -> Result<()> {
+        HiDB::decay_memories(self).await
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<MemoryRecord>> {
+        HiDB::get(self, id).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        HiDB::delete(self, id).await
+    }
+
+    async fn forget(&self, query_embedding: &[f32], similarity_threshold: f32) -> Result<u64> {
+        HiDB::forget(self, query_embedding, similarity_threshold).await
+    }
+
+    async fn stale_records(&self, current_model: &str, limit: i64) -> Result<Vec<MemoryRecord>> {
+        HiDB::stale_records(self, current_model, limit).await
+    }
+
+    async fn update_embedding(&self, id: Uuid, embedding: &[f32], model: &str) -> Result<()> {
+        HiDB::update_embedding(self, id, embedding, model).await
+    }
+
+    async fn reinforce(&self, id: Uuid, amount: f32) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE memories
+            SET confidence = LEAST(1.0, confidence + $2),
+                updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(amount)
+        .execute(&self.pg_pool)
+        .await?;
+
+        // Drop the stale cached copy
+        let mut conn = self.redis_client.get_connection()?;
+        redis::cmd("DEL").arg(format!("memory:{id}")).query::<()>(&mut conn)?;
+        Ok(())
+    }
+}
+
+fn record_from_row(row: &sqlx::postgres::PgRow) -> MemoryRecord {
+    MemoryRecord {
+        id: row.get("id"),
+        embedding: row.get("embedding"),
+        content: row.get("content"),
+        confidence: row.get("confidence"),
+        decay_rate: row.get("decay_rate"),
+        source: row.get("source"),
+        model: row.get("model"),
+    }
+}
+
+/// Process-local store with brute-force cosine search, for tests and offline nodes
+#[derive(Default)]
+pub struct InMemoryStore {
+    memories: RwLock<Vec<MemoryRecord>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.memories.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.memories.read().await.is_empty()
+    }
+}
+
+#[async_trait]
+impl MemoryStore for InMemoryStore {
+    async fn store(&self, memory: &MemoryRecord) -> Result<()> {
+        self.memories.write().await.push(memory.clone());
+        Ok(())
+    }
+
+    async fn semantic_search_scoped(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>> {
+        let memories = self.memories.read().await.clone();
+        Ok(scan_rank(memories, query_embedding, limit, model, i64::MAX))
+    }
+
+    async fn decay_memories(&self) -> Result<()> {
+        let mut memories = self.memories.write().await;
+        for m in memories.iter_mut() {
+            if m.confidence > 0.01 {
+                m.confidence *= 1.0 - m.decay_rate;
+            }
+        }
+        memories.retain(|m| m.confidence >= 0.01);
+        Ok(())
+    }
+
+    async fn reinforce(&self, id: Uuid, amount: f32) -> Result<()> {
+        let mut memories = self.memories.write().await;
+        if let Some(m) = memories.iter_mut().find(|m| m.id == id) {
+            m.confidence = (m.confidence + amount).min(1.0);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<MemoryRecord>> {
+        Ok(self.memories.read().await.iter().find(|m| m.id == id).cloned())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let mut memories = self.memories.write().await;
+        let before = memories.len();
+        memories.retain(|m| m.id != id);
+        Ok(memories.len() < before)
+    }
+
+    async fn forget(&self, query_embedding: &[f32], similarity_threshold: f32) -> Result<u64> {
+        let mut memories = self.memories.write().await;
+        let doomed = forget_matches(
+            memories.iter().map(|m| (m.id, m.embedding.clone())),
+            query_embedding,
+            similarity_threshold,
+        );
+        memories.retain(|m| !doomed.contains(&m.id));
+        Ok(doomed.len() as u64)
+    }
+
+    async fn stale_records(&self, current_model: &str, limit: i64) -> Result<Vec<MemoryRecord>> {
+        Ok(self.memories.read().await.iter()
+            .filter(|m| m.model != current_model)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_embedding(&self, id: Uuid, embedding: &[f32], model: &str) -> Result<()> {
+        if let Some(m) = self.memories.write().await.iter_mut().find(|m| m.id == id) {
+            m.embedding = embedding.to_vec();
+            m.model = model.to_string();
+        }
+        Ok(())
+    }
+}
+
+/// Top `limit` candidates by cosine similarity to `query`, most similar first
+pub fn rank_by_similarity(candidates: Vec<MemoryRecord>, query: &[f32], limit: i64) -> Vec<MemoryRecord> {
+    let mut scored: Vec<(f32, MemoryRecord)> = candidates.into_iter()
+        .map(|m| (cosine_similarity(query, &m.embedding), m))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    scored.into_iter()
+        .take(limit.max(0) as usize)
+        .map(|(_, m)| m)
+        .collect()
+}
+
+/// Brute-force search as HiDB runs it without pgvector: keep the `max_scan`
+/// most confident candidates embedded by `model` (any model when None),
+/// then rank those by cosine similarity
+pub fn scan_rank(
+    candidates: Vec<MemoryRecord>,
+    query: &[f32],
+    limit: i64,
+    model: Option<&str>,
+    max_scan: i64,
+) -> Vec<MemoryRecord> {
+    let mut scanned: Vec<MemoryRecord> = candidates.into_iter()
+        .filter(|m| model.is_none_or(|model| m.model == model))
+        .collect();
+    scanned.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    scanned.truncate(max_scan.max(0).try_into().unwrap_or(usize::MAX));
+    rank_by_similarity(scanned, query, limit)
+}
+
+/// Ids of the `(id, embedding)` rows at least `similarity_threshold` similar
+/// to the query; redaction without pgvector
+pub fn forget_matches(
+    rows: impl IntoIterator<Item = (Uuid, Vec<f32>)>,
+    query: &[f32],
+    similarity_threshold: f32,
+) -> Vec<Uuid> {
+    rows.into_iter()
+        .filter(|(_, embedding)| cosine_similarity(query, embedding) >= similarity_threshold)
+        .map(|(id, _)| id)
+        .collect()
+}
+
 pub async fn init(database_url: &str, redis_url: &str) -> Result<HiDB> {
     HiDB::connect(database_url, redis_url).await
 }
+
+/// Pick the backend from the database URL: `sqlite:` selects `SqliteMemoryStore`
+/// (Redis unused), anything else connects HiDB to Postgres + Redis.
+pub async fn open_store(database_url: &str, redis_url: &str) -> Result<Arc<dyn MemoryStore>> {
+    open_store_with(database_url, redis_url, CacheConfig::default()).await
+}
+
+/// `open_store` with explicit Redis cache settings
+pub async fn open_store_with(database_url: &str, redis_url: &str, cache: CacheConfig) -> Result<Arc<dyn MemoryStore>> {
+    if database_url.starts_with("sqlite:") {
+        Ok(Arc::new(SqliteMemoryStore::connect(database_url).await?))
+    } else {
+        Ok(Arc::new(HiDB::connect(database_url, redis_url).await?.with_cache(cache)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Behaviour every `MemoryStore` backend must share
+    async fn conformance(store: &dyn MemoryStore) {
+        let east = MemoryRecord::new("east".into(), vec![1.0, 0.0]);
+        let north = MemoryRecord::new("north".into(), vec![0.0, 1.0]);
+        let mut fading = MemoryRecord::new("fading".into(), vec![0.6, 0.8]);
+        fading.confidence = 0.011;
+        for m in [&east, &north, &fading] {
+            store.store(m).await.unwrap();
+        }
+
+        // Nearest first, bounded by limit
+        let results = store.semantic_search(&[0.1, 1.0], 2).await.unwrap();
+        let contents: Vec<_> = results.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["north", "fading"]);
+        assert_eq!(results[0].embedding, vec![0.0, 1.0]);
+
+        // Reinforcement is capped at full confidence
+        store.reinforce(east.id, 0.5).await.unwrap();
+        // Decay scales every memory and forgets the weakest
+        store.decay_memories().await.unwrap();
+
+        let remaining = store.semantic_search(&[1.0, 0.0], 10).await.unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].id, east.id);
+        assert!((remaining[0].confidence - 0.9).abs() < 1e-5);
+        assert!(remaining.iter().all(|m| m.id != fading.id));
+
+        // Point lookups: hit, miss, and gone after delete
+        let hit = store.get(north.id).await.unwrap().expect("stored memory");
+        assert_eq!(hit.content, "north");
+        assert_eq!(hit.embedding, vec![0.0, 1.0]);
+        assert!(store.get(Uuid::new_v4()).await.unwrap().is_none());
+
+        assert!(store.delete(north.id).await.unwrap());
+        assert!(store.get(north.id).await.unwrap().is_none());
+        assert!(!store.delete(north.id).await.unwrap());
+
+        // Redacting a topic forgets the cluster and nothing else
+        let wrong = [
+            MemoryRecord::new("wrong fact".into(), vec![-1.0, 0.0]),
+            MemoryRecord::new("wrong fact, restated".into(), vec![-0.99, 0.1]),
+        ];
+        let unrelated = MemoryRecord::new("unrelated".into(), vec![0.1, -1.0]);
+        for m in wrong.iter().chain([&unrelated]) {
+            store.store(m).await.unwrap();
+        }
+        assert_eq!(store.forget(&[-1.0, 0.0], 0.95).await.unwrap(), 2);
+        for m in &wrong {
+            assert!(store.get(m.id).await.unwrap().is_none());
+        }
+        assert!(store.get(unrelated.id).await.unwrap().is_some());
+        assert!(store.get(east.id).await.unwrap().is_some());
+
+        // Vectors from different embedding models stay apart when scoped
+        let old = MemoryRecord::new("old model".into(), vec![0.6, 0.8]).with_model("embed-v1");
+        let new = MemoryRecord::new("new model".into(), vec![0.6, 0.8]).with_model("embed-v2");
+        store.store(&old).await.unwrap();
+        store.store(&new).await.unwrap();
+        let scoped = store.semantic_search_scoped(&[0.6, 0.8], 10, Some("embed-v2")).await.unwrap();
+        let contents: Vec<_> = scoped.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["new model"]);
+        assert!(store.semantic_search(&[0.6, 0.8], 10).await.unwrap().len() >= 2);
+
+        // Re-embedding brings every stale record onto the current model
+        let reembedded = store.reembed_stale(&FixedEmbedder("embed-v2")).await.unwrap();
+        assert_eq!(reembedded, 3); // east, unrelated, old
+        assert!(store.stale_records("embed-v2", 10).await.unwrap().is_empty());
+        let migrated = store.get(old.id).await.unwrap().unwrap();
+        assert_eq!((migrated.model.as_str(), migrated.embedding.clone()), ("embed-v2", vec![0.0, 1.0]));
+    }
+
+    /// Embeds everything to the same vector under a fixed model name
+    struct FixedEmbedder(&'static str);
+
+    #[async_trait]
+    impl Reembedder for FixedEmbedder {
+        fn model(&self) -> &str {
+            self.0
+        }
+
+        async fn embed(&self, _content: &str) -> Result<Vec<f32>> {
+            Ok(vec![0.0, 1.0])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conformance_in_memory() {
+        conformance(&InMemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_conformance_sqlite() {
+        let store = open_store("sqlite::memory:", "").await.unwrap();
+        conformance(store.as_ref()).await;
+    }
+
+    /// Needs Postgres and Redis; skipped unless configured
+    #[tokio::test]
+    async fn test_conformance_hidb() {
+        let (Ok(database_url), Ok(redis_url)) = (
+            std::env::var("HIDB_TEST_DATABASE_URL"),
+            std::env::var("HIDB_TEST_REDIS_URL"),
+        ) else {
+            return;
+        };
+        let store = open_store(&database_url, &redis_url).await.unwrap();
+        conformance(store.as_ref()).await;
+    }
+
+    /// Same suite through the brute-force path, as on vanilla Postgres
+    #[tokio::test]
+    async fn test_conformance_hidb_without_pgvector() {
+        let (Ok(database_url), Ok(redis_url)) = (
+            std::env::var("HIDB_TEST_DATABASE_URL"),
+            std::env::var("HIDB_TEST_REDIS_URL"),
+        ) else {
+            return;
+        };
+        let mut store = HiDB::connect(&database_url, &redis_url).await.unwrap();
+        store.pgvector = false;
+        conformance(&store).await;
+    }
+
+    #[test]
+    fn test_cache_ttl_scales_with_confidence() {
+        let cache = CacheConfig::default();
+        assert_eq!(cache.ttl_for(0.1), None);
+        let weak = cache.ttl_for(0.3).unwrap();
+        let strong = cache.ttl_for(1.0).unwrap();
+        assert_eq!(cache.ttl_for(0.5), Some(cache.base_ttl_secs));
+        assert!(strong > weak);
+        assert_eq!(strong, 2 * cache.base_ttl_secs);
+    }
+
+    /// Needs Postgres and Redis; skipped unless configured
+    #[tokio::test]
+    async fn test_hidb_caches_only_confident_records() {
+        let (Ok(database_url), Ok(redis_url)) = (
+            std::env::var("HIDB_TEST_DATABASE_URL"),
+            std::env::var("HIDB_TEST_REDIS_URL"),
+        ) else {
+            return;
+        };
+        let store = HiDB::connect(&database_url, &redis_url).await.unwrap();
+        let mut noise = MemoryRecord::new("noise".into(), vec![1.0]);
+        noise.confidence = 0.1;
+        let mut hunch = MemoryRecord::new("hunch".into(), vec![1.0]);
+        hunch.confidence = 0.4;
+        let fact = MemoryRecord::new("fact".into(), vec![1.0]);
+        for m in [&noise, &hunch, &fact] {
+            store.store(m).await.unwrap();
+        }
+
+        let mut conn = store.redis_client.get_connection().unwrap();
+        let ttl = |conn: &mut redis::Connection, id: Uuid| -> i64 {
+            redis::cmd("TTL").arg(format!("memory:{id}")).query(conn).unwrap()
+        };
+        assert_eq!(ttl(&mut conn, noise.id), -2); // key absent
+        let (hunch_ttl, fact_ttl) = (ttl(&mut conn, hunch.id), ttl(&mut conn, fact.id));
+        assert!(hunch_ttl > 0);
+        assert!(fact_ttl > hunch_ttl);
+    }
+
+    #[test]
+    fn test_rank_by_similarity_respects_limit_and_order() {
+        let candidates = vec![
+            MemoryRecord::new("east".into(), vec![1.0, 0.0]),
+            MemoryRecord::new("north".into(), vec![0.0, 1.0]),
+            MemoryRecord::new("mismatched".into(), vec![1.0]),
+            MemoryRecord::new("north-east".into(), vec![0.7, 0.7]),
+        ];
+        let ranked = rank_by_similarity(candidates, &[0.2, 1.0], 3);
+        let contents: Vec<_> = ranked.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["north", "north-east", "east"]);
+
+        assert!(rank_by_similarity(Vec::new(), &[1.0], 5).is_empty());
+        assert!(rank_by_similarity(vec![MemoryRecord::new("x".into(), vec![1.0])], &[1.0], 0).is_empty());
+    }
+
+    #[test]
+    fn test_scan_rank_bounds_scan_by_confidence_and_model() {
+        let mut faint = MemoryRecord::new("faint but nearest".into(), vec![0.0, 1.0]);
+        faint.confidence = 0.2;
+        let candidates = vec![
+            faint,
+            MemoryRecord::new("east".into(), vec![1.0, 0.0]),
+            MemoryRecord::new("north-east".into(), vec![0.7, 0.7]),
+            MemoryRecord::new("other model".into(), vec![0.2, 1.0]).with_model("embed-v2"),
+        ];
+
+        // Only the two most confident rows of the scoped model are ranked
+        let ranked = scan_rank(candidates.clone(), &[0.0, 1.0], 10, Some(""), 2);
+        let contents: Vec<_> = ranked.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["north-east", "east"]);
+
+        // An unbounded, unscoped scan sees everything
+        let ranked = scan_rank(candidates, &[0.0, 1.0], 2, None, DEFAULT_MAX_SCAN);
+        let contents: Vec<_> = ranked.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["faint but nearest", "other model"]);
+    }
+
+    #[test]
+    fn test_forget_matches_selects_only_the_cluster() {
+        let (wrong, restated, unrelated) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let rows = vec![
+            (wrong, vec![-1.0, 0.0]),
+            (restated, vec![-0.99, 0.1]),
+            (unrelated, vec![0.1, -1.0]),
+            (Uuid::new_v4(), vec![-1.0]), // mismatched dimension never matches
+        ];
+        assert_eq!(forget_matches(rows, &[-1.0, 0.0], 0.95), vec![wrong, restated]);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_search_orders_by_similarity() {
+        let store = InMemoryStore::new();
+        store.store(&MemoryRecord::new("east".into(), vec![1.0, 0.0])).await.unwrap();
+        store.store(&MemoryRecord::new("north".into(), vec![0.0, 1.0])).await.unwrap();
+        store.store(&MemoryRecord::new("north-east".into(), vec![0.7, 0.7])).await.unwrap();
+
+        let results = store.semantic_search(&[0.0, 1.0], 2).await.unwrap();
+        let contents: Vec<_> = results.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["north", "north-east"]);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_decay_forgets_weak_memories() {
+        let store = InMemoryStore::new();
+        let mut fading = MemoryRecord::new("fading".into(), vec![1.0]);
+        fading.confidence = 0.011;
+        store.store(&fading).await.unwrap();
+        store.store(&MemoryRecord::new("strong".into(), vec![1.0])).await.unwrap();
+
+        store.decay_memories().await.unwrap();
+        let remaining = store.semantic_search(&[1.0], 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].content, "strong");
+        assert!((remaining[0].confidence - 0.9).abs() < 1e-6);
+    }
+}
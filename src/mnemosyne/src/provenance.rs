@@ -0,0 +1,153 @@
+//! Where a memory came from, and how far recall should trust it.
+//!
+//! `MemoryRecord.source` stores the provenance as a string (`self`,
+//! `peer:<node-id>`, `user`, `search:<url>`). At recall time a
+//! `SourceTrust` turns it into a weight that scales both the ranking and the
+//! reported confidence; a source with no weight (a blacklisted peer) is
+//! dropped and its memories purged.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{cosine_similarity, MemoryRecord};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provenance {
+    /// Learned by this node
+    SelfLearned,
+    /// Synced from a mesh peer
+    Peer(String),
+    /// Taught directly by the operator
+    User,
+    /// Distilled from a web result
+    Search(String),
+}
+
+impl Provenance {
+    /// Unrecognised strings are treated as self-learned, like the legacy "node"
+    pub fn parse(source: &str) -> Self {
+        if let Some(id) = source.strip_prefix("peer:") {
+            Provenance::Peer(id.to_string())
+        } else if let Some(url) = source.strip_prefix("search:") {
+            Provenance::Search(url.to_string())
+        } else if source == "user" {
+            Provenance::User
+        } else {
+            Provenance::SelfLearned
+        }
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provenance::SelfLearned => write!(f, "self"),
+            Provenance::Peer(id) => write!(f, "peer:{}", id),
+            Provenance::User => write!(f, "user"),
+            Provenance::Search(url) => write!(f, "search:{}", url),
+        }
+    }
+}
+
+/// Trust in a memory source
+#[async_trait]
+pub trait SourceTrust: Send + Sync {
+    /// Weight in 0.0..=1.0, or None to ignore the source entirely
+    async fn weight(&self, source: &Provenance) -> Option<f32>;
+}
+
+/// Every source counts fully (no trust information available)
+pub struct TrustAll;
+
+#[async_trait]
+impl SourceTrust for TrustAll {
+    async fn weight(&self, _source: &Provenance) -> Option<f32> {
+        Some(1.0)
+    }
+}
+
+/// Outcome of trust-weighted ranking
+#[derive(Debug, Default)]
+pub struct TrustRanked {
+    /// Most relevant first; `confidence` is already scaled by source trust
+    pub memories: Vec<MemoryRecord>,
+    /// Trust-weighted similarity of each of `memories`, in the same order
+    pub relevance: Vec<f32>,
+    /// Memories from untrusted sources, to be purged
+    pub rejected: Vec<Uuid>,
+}
+
+/// Rank by similarity × source weight, keeping the top `limit`
+pub async fn rank_by_trust(
+    candidates: Vec<MemoryRecord>,
+    query: &[f32],
+    trust: &dyn SourceTrust,
+    limit: usize,
+) -> TrustRanked {
+    let mut ranked = TrustRanked::default();
+    let mut scored = Vec::with_capacity(candidates.len());
+    for mut memory in candidates {
+        let Some(weight) = trust.weight(&memory.provenance()).await else {
+            ranked.rejected.push(memory.id);
+            continue;
+        };
+        let weight = weight.clamp(0.0, 1.0);
+        memory.confidence *= weight;
+        scored.push((cosine_similarity(query, &memory.embedding) * weight, memory));
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    (ranked.relevance, ranked.memories) = scored.into_iter().take(limit).unzip();
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct PeerTrust;
+
+    #[async_trait]
+    impl SourceTrust for PeerTrust {
+        async fn weight(&self, source: &Provenance) -> Option<f32> {
+            match source {
+                Provenance::Peer(id) if id == "mallory" => None,
+                Provenance::Peer(_) => Some(0.3),
+                _ => Some(1.0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_provenance_round_trips_and_reads_legacy_source() {
+        for p in [
+            Provenance::SelfLearned,
+            Provenance::Peer("node-b".into()),
+            Provenance::User,
+            Provenance::Search("https://example.org/a?b=c".into()),
+        ] {
+            assert_eq!(Provenance::parse(&p.to_string()), p);
+        }
+        assert_eq!(Provenance::parse("node"), Provenance::SelfLearned);
+    }
+
+    #[tokio::test]
+    async fn test_low_trust_source_ranks_below_self() {
+        let peer = MemoryRecord::new("from a peer".into(), vec![1.0, 0.0])
+            .with_provenance(Provenance::Peer("node-b".into()));
+        let own = MemoryRecord::new("learned here".into(), vec![1.0, 0.0]);
+        let hostile = MemoryRecord::new("poison".into(), vec![1.0, 0.0])
+            .with_provenance(Provenance::Peer("mallory".into()));
+
+        // Same similarity; the peer's copy is listed first to rule out stable ordering
+        let ranked = rank_by_trust(vec![hostile.clone(), peer, own], &[1.0, 0.0], &PeerTrust, 10).await;
+
+        let contents: Vec<_> = ranked.memories.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["learned here", "from a peer"]);
+        assert!((ranked.memories[1].confidence - 0.3).abs() < 1e-6);
+        assert_eq!(ranked.relevance.len(), 2);
+        assert!((ranked.relevance[1] - 0.3).abs() < 1e-6);
+        assert_eq!(ranked.rejected, vec![hostile.id]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Vector similarity for in-process search (SQLite backend, pgvector
+//! fallback, client-side ranking).
+//!
+//! `cosine` processes 8 lanes at a time through `wide` (SSE/AVX/NEON where
+//! available, portable code elsewhere); `cosine_scalar` is the reference.
+
+use wide::f32x8;
+
+const LANES: usize = 8;
+
+/// Cosine similarity; 0.0 for mismatched, empty or zero-norm vectors
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let mut dot = f32x8::ZERO;
+    let mut norm_a = f32x8::ZERO;
+    let mut norm_b = f32x8::ZERO;
+
+    let chunks_a = a.chunks_exact(LANES);
+    let chunks_b = b.chunks_exact(LANES);
+    let (tail_a, tail_b) = (chunks_a.remainder(), chunks_b.remainder());
+
+    for (ca, cb) in chunks_a.zip(chunks_b) {
+        let va = f32x8::from(<[f32; LANES]>::try_from(ca).unwrap());
+        let vb = f32x8::from(<[f32; LANES]>::try_from(cb).unwrap());
+        dot = va.mul_add(vb, dot);
+        norm_a = va.mul_add(va, norm_a);
+        norm_b = vb.mul_add(vb, norm_b);
+    }
+
+    let (tail_dot, tail_na, tail_nb) = sums(tail_a, tail_b);
+    finish(
+        dot.reduce_add() + tail_dot,
+        norm_a.reduce_add() + tail_na,
+        norm_b.reduce_add() + tail_nb,
+    )
+}
+
+/// Reference implementation, one element at a time
+pub fn cosine_scalar(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (dot, norm_a, norm_b) = sums(a, b);
+    finish(dot, norm_a, norm_b)
+}
+
+fn sums(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+    a.iter().zip(b).fold((0.0, 0.0, 0.0), |(dot, na, nb), (x, y)| {
+        (dot + x * y, na + x * x, nb + y * y)
+    })
+}
+
+fn finish(dot: f32, norm_a: f32, norm_b: f32) -> f32 {
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random vector (no rand dependency)
+    fn vector(seed: u32, dim: usize) -> Vec<f32> {
+        let mut state = seed.wrapping_mul(2654435761).max(1);
+        (0..dim).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+        }).collect()
+    }
+
+    #[test]
+    fn test_simd_matches_scalar() {
+        // Includes lengths that leave a non-multiple-of-8 tail
+        for dim in [1, 7, 8, 9, 100, 1536] {
+            for seed in 1..20 {
+                let a = vector(seed, dim);
+                let b = vector(seed + 1000, dim);
+                let (fast, reference) = (cosine(&a, &b), cosine_scalar(&a, &b));
+                assert!((fast - reference).abs() < 1e-5, "dim {dim}: {fast} vs {reference}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        assert_eq!(cosine(&[], &[]), 0.0);
+        assert_eq!(cosine(&[1.0, 2.0], &[1.0]), 0.0);
+        assert_eq!(cosine(&[0.0; 16], &[1.0; 16]), 0.0);
+        assert!((cosine(&[3.0; 16], &[3.0; 16]) - 1.0).abs() < 1e-6);
+    }
+}
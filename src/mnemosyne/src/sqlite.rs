@@ -0,0 +1,206 @@
+//! Single-file memory backend for edge/single-node deployments.
+//! Embeddings are stored as little-endian f32 blobs and searched by
+//! brute-force cosine similarity, so no vector extension is required.
+
+use std::str::FromStr;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{cosine_similarity, rank_by_similarity, MemoryRecord, MemoryStore};
+
+pub struct SqliteMemoryStore {
+    pool: SqlitePool,
+}
+
+impl SqliteMemoryStore {
+    /// Open (creating if missing) e.g. `sqlite://ippoc.db` or `sqlite::memory:`
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        // Every connection to `:memory:` is a separate database
+        let max_connections = if database_url.contains(":memory:") { 1 } else { 4 };
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect_with(options)
+            .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS memories (
+                id TEXT PRIMARY KEY,
+                embedding BLOB NOT NULL,
+                content TEXT NOT NULL,
+                confidence REAL NOT NULL,
+                decay_rate REAL NOT NULL,
+                source TEXT NOT NULL,
+                model TEXT NOT NULL DEFAULT '',
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#
+        )
+        .execute(&pool)
+        .await?;
+
+        // Databases created before records were tagged with their embedding model
+        let has_model: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('memories') WHERE name = 'model'")
+            .fetch_one(&pool)
+            .await?;
+        if !has_model {
+            sqlx::query("ALTER TABLE memories ADD COLUMN model TEXT NOT NULL DEFAULT ''")
+                .execute(&pool)
+                .await?;
+        }
+
+        tracing::info!("HiDB: Using SQLite memory store at {}", database_url);
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl MemoryStore for SqliteMemoryStore {
+    async fn store(&self, memory: &MemoryRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO memories (id, embedding, content, confidence, decay_rate, source, model)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#
+        )
+        .bind(memory.id.to_string())
+        .bind(encode_embedding(&memory.embedding))
+        .bind(&memory.content)
+        .bind(memory.confidence)
+        .bind(memory.decay_rate)
+        .bind(&memory.source)
+        .bind(&memory.model)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn semantic_search_scoped(&self, query_embedding: &[f32], limit: i64, model: Option<&str>) -> Result<Vec<MemoryRecord>> {
+        let rows = sqlx::query("SELECT id, embedding, content, confidence, decay_rate, source, model FROM memories WHERE ?1 IS NULL OR model = ?1")
+            .bind(model)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            candidates.push(record_from_row(&row)?);
+        }
+
+        Ok(rank_by_similarity(candidates, query_embedding, limit))
+    }
+
+    async fn decay_memories(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE memories
+            SET confidence = confidence * (1.0 - decay_rate),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE confidence > 0.01
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("DELETE FROM memories WHERE confidence < 0.01")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn reinforce(&self, id: Uuid, amount: f32) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE memories
+            SET confidence = MIN(1.0, confidence + ?2),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?1
+            "#
+        )
+        .bind(id.to_string())
+        .bind(amount)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<MemoryRecord>> {
+        let row = sqlx::query("SELECT id, embedding, content, confidence, decay_rate, source, model FROM memories WHERE id = ?1")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(record_from_row).transpose()
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM memories WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn forget(&self, query_embedding: &[f32], similarity_threshold: f32) -> Result<u64> {
+        let rows = sqlx::query("SELECT id, embedding FROM memories")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut forgotten = 0;
+        for row in rows {
+            if cosine_similarity(query_embedding, &decode_embedding(row.get("embedding"))) >= similarity_threshold {
+                let id: String = row.get("id");
+                sqlx::query("DELETE FROM memories WHERE id = ?1").bind(id).execute(&mut *tx).await?;
+                forgotten += 1;
+            }
+        }
+        tx.commit().await?;
+        Ok(forgotten)
+    }
+
+    async fn stale_records(&self, current_model: &str, limit: i64) -> Result<Vec<MemoryRecord>> {
+        let rows = sqlx::query("SELECT id, embedding, content, confidence, decay_rate, source, model FROM memories WHERE model <> ?1 LIMIT ?2")
+            .bind(current_model)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(record_from_row).collect()
+    }
+
+    async fn update_embedding(&self, id: Uuid, embedding: &[f32], model: &str) -> Result<()> {
+        sqlx::query("UPDATE memories SET embedding = ?2, model = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?1")
+            .bind(id.to_string())
+            .bind(encode_embedding(embedding))
+            .bind(model)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+fn record_from_row(row: &SqliteRow) -> Result<MemoryRecord> {
+    let id: String = row.get("id");
+    Ok(MemoryRecord {
+        id: Uuid::parse_str(&id)?,
+        embedding: decode_embedding(row.get("embedding")),
+        content: row.get("content"),
+        confidence: row.get("confidence"),
+        decay_rate: row.get("decay_rate"),
+        source: row.get("source"),
+        model: row.get("model"),
+    })
+}
+
+fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_embedding(bytes: Vec<u8>) -> Vec<f32> {
+    bytes.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
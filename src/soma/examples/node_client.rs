@@ -0,0 +1,36 @@
+//! Talk to a running node through the typed client.
+//!
+//! Usage: cargo run --example node_client -- http://localhost:8080
+
+use ippoc_node::api::{RecordActionRequest, ThinkRequest};
+use ippoc_node::NodeClient;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let base_url = std::env::args().nth(1).unwrap_or_else(|| "http://localhost:8080".to_string());
+    let client = NodeClient::new(base_url);
+
+    let balance = client.balance().await?;
+    println!("Node {} holds {} IPPC", balance.node_id, balance.balances.ippc);
+
+    let id = client.memory_store("The sky is blue", vec![0.1; 1536]).await?;
+    println!("Stored memory {}", id);
+
+    for record in client.memory_search(vec![0.1; 1536], 3).await? {
+        println!("Recalled ({:.2}): {}", record.confidence, record.content);
+    }
+
+    client.record_action(&RecordActionRequest {
+        outcome: Some("Success".to_string()),
+        tool: Some("example".to_string()),
+        ..Default::default()
+    }).await?;
+
+    let thought = client.think(&ThinkRequest {
+        query: "What colour is the sky?".to_string(),
+        context_history: vec![],
+    }).await?;
+    println!("Answer ({:.2}): {}", thought.confidence, thought.answer);
+
+    Ok(())
+}
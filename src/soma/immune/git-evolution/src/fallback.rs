@@ -0,0 +1,170 @@
+//! Deterministic conflict resolution
+//!
+//! Handles conflicts that need no reasoning so routine syncs keep flowing
+//! when the Brain's model backend is unavailable. Anything not covered here
+//! is escalated to the `BrainMutationResolver`. Line-level merges need the
+//! common ancestor and only ever combine pure insertions, so neither side's
+//! deletions can be silently undone.
+
+use std::path::Path;
+
+use crate::ConflictContext;
+
+/// Generated files where upstream is always authoritative
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FallbackResolver;
+
+impl FallbackResolver {
+    /// Resolved content for trivially-resolvable conflicts, `None` to escalate
+    pub fn resolve(&self, conflict: &ConflictContext) -> Option<String> {
+        let ours = &conflict.hunk_ours;
+        let theirs = &conflict.hunk_theirs;
+
+        // Delete/modify conflicts always need a decision
+        if ours.is_empty() || theirs.is_empty() {
+            return None;
+        }
+
+        if is_lockfile(&conflict.file_path) {
+            return Some(theirs.clone());
+        }
+
+        if normalize_whitespace(ours) == normalize_whitespace(theirs) {
+            return Some(theirs.clone());
+        }
+
+        // Without the ancestor an added line can't be told from a line the
+        // other side deleted, so anything further needs the Brain
+        let base = conflict.hunk_base.as_ref()?;
+        let base_lines: Vec<&str> = base.lines().collect();
+        let ours_lines: Vec<&str> = ours.lines().collect();
+        let theirs_lines: Vec<&str> = theirs.lines().collect();
+
+        // Only pure insertions on both sides are safe to combine; a deletion
+        // on either side must not be undone by taking the other
+        if !is_subsequence(&base_lines, &ours_lines) || !is_subsequence(&base_lines, &theirs_lines) {
+            return None;
+        }
+
+        // One side's insertions already contain the other's
+        if is_subsequence(&ours_lines, &theirs_lines) {
+            return Some(theirs.clone());
+        }
+        if is_subsequence(&theirs_lines, &ours_lines) {
+            return Some(ours.clone());
+        }
+
+        // Both sides only appended to the common ancestor: keep both tails
+        if ours_lines.starts_with(&base_lines) && theirs_lines.starts_with(&base_lines) {
+            let mut merged: Vec<&str> = ours_lines.clone();
+            merged.extend_from_slice(&theirs_lines[base_lines.len()..]);
+            return Some(join_lines(&merged, theirs));
+        }
+
+        None
+    }
+}
+
+fn is_lockfile(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| LOCKFILES.contains(&n))
+        .unwrap_or(false)
+}
+
+fn normalize_whitespace(content: &str) -> Vec<&str> {
+    content.split_whitespace().collect()
+}
+
+/// Whether every line of `needle` appears in `haystack`, in order
+fn is_subsequence(needle: &[&str], haystack: &[&str]) -> bool {
+    let mut remaining = haystack.iter();
+    needle.iter().all(|line| remaining.any(|h| h == line))
+}
+
+fn join_lines(lines: &[&str], template: &str) -> String {
+    let mut joined = lines.join("\n");
+    if template.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(path: &str, ours: &str, theirs: &str, base: Option<&str>) -> ConflictContext {
+        ConflictContext {
+            repo_root: "/tmp/repo".to_string(),
+            file_path: path.to_string(),
+            hunk_ours: ours.to_string(),
+            hunk_theirs: theirs.to_string(),
+            hunk_base: base.map(|b| b.to_string()),
+            previous_failure: None,
+        }
+    }
+
+    #[test]
+    fn test_lockfile_takes_theirs() {
+        let c = conflict("crates/node/Cargo.lock", "ours = 1\n", "theirs = 2\n", Some("base\n"));
+        assert_eq!(FallbackResolver.resolve(&c).as_deref(), Some("theirs = 2\n"));
+    }
+
+    #[test]
+    fn test_whitespace_only_takes_theirs() {
+        let c = conflict("src/lib.rs", "fn main() {\n    run();\n}\n", "fn main() {\n\trun();\n}\n", None);
+        assert_eq!(FallbackResolver.resolve(&c).as_deref(), Some("fn main() {\n\trun();\n}\n"));
+    }
+
+    #[test]
+    fn test_one_sided_additions_take_superset() {
+        let c = conflict("src/lib.rs", "a\nb\n", "a\nnew\nb\n", Some("a\nb\n"));
+        assert_eq!(FallbackResolver.resolve(&c).as_deref(), Some("a\nnew\nb\n"));
+
+        let c = conflict("src/lib.rs", "a\nb\nlocal\n", "a\nb\n", Some("a\n"));
+        assert_eq!(FallbackResolver.resolve(&c).as_deref(), Some("a\nb\nlocal\n"));
+    }
+
+    #[test]
+    fn test_deletion_is_not_undone_by_superset() {
+        // Ours deleted X, theirs appended Y: taking theirs would resurrect X
+        let c = conflict("src/lib.rs", "a\nb\n", "a\nb\nX\nY\n", Some("a\nb\nX\n"));
+        assert!(FallbackResolver.resolve(&c).is_none());
+
+        let c = conflict("src/lib.rs", "a\nb\nX\nY\n", "a\nb\n", Some("a\nb\nX\n"));
+        assert!(FallbackResolver.resolve(&c).is_none());
+
+        // Without the ancestor the same shape is ambiguous
+        let c = conflict("src/lib.rs", "a\nb\n", "a\nb\nX\nY\n", None);
+        assert!(FallbackResolver.resolve(&c).is_none());
+    }
+
+    #[test]
+    fn test_both_appended_keeps_both_tails() {
+        let c = conflict("CHANGELOG.md", "base\nours\n", "base\ntheirs\n", Some("base\n"));
+        assert_eq!(FallbackResolver.resolve(&c).as_deref(), Some("base\nours\ntheirs\n"));
+    }
+
+    #[test]
+    fn test_semantic_conflict_escalates() {
+        let c = conflict("src/lib.rs", "let x = 1;\n", "let x = 2;\n", Some("let x = 0;\n"));
+        assert!(FallbackResolver.resolve(&c).is_none());
+
+        // Deleted on one side
+        let c = conflict("src/lib.rs", "", "let x = 2;\n", Some("let x = 0;\n"));
+        assert!(FallbackResolver.resolve(&c).is_none());
+    }
+}
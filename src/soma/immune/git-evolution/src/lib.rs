@@ -1,16 +1,75 @@
 use anyhow::{Result, Context, anyhow};
-use git2::{Repository, Signature, MergeOptions, AnnotatedCommit};
+use git2::{Repository, RepositoryState, Signature, MergeOptions, AnnotatedCommit};
 use std::path::{Path, PathBuf};
 use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
 
+mod fallback;
+mod lock;
+pub use fallback::FallbackResolver;
+pub use lock::{repo_lock, RepoBusy, RepoGuard, RepoLock};
+
+/// Rule 6 aggregate ceiling: total lines added + removed by one evolution
+pub const DEFAULT_MAX_TOTAL_LOC: usize = 1500;
+
+/// Resolve -> simulate rounds before a conflicted merge is abandoned
+pub const DEFAULT_RESOLUTION_ATTEMPTS: usize = 3;
+
 /// Interface for the Brain's reasoning engine to resolve code mutations
 #[async_trait::async_trait]
 pub trait BrainMutationResolver: Send + Sync {
     async fn resolve_conflict(&self, conflict: ConflictContext) -> Result<String>;
-    async fn simulate_patch(&self, repo_root: &str, patch_content: &str) -> Result<bool>;
+    async fn simulate_patch(&self, repo_root: &str, patch_content: &str) -> Result<SimulationOutcome>;
     async fn review_patch(&self, patch_content: &str) -> Result<bool>;
-    async fn scan_for_governance_violations(&self, summary: &str) -> Result<Vec<String>>;
+    async fn scan_for_governance_violations(&self, summary: &str) -> Result<Vec<Violation>>;
+
+    /// Pass/fail adapter for callers that don't need diagnostics
+    async fn simulate_patch_passed(&self, repo_root: &str, patch_content: &str) -> Result<bool> {
+        Ok(self.simulate_patch(repo_root, patch_content).await?.passed)
+    }
+}
+
+/// Last stage a simulation reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulationStage {
+    Compile,
+    Lint,
+    Test,
+    /// Ran out of the simulation's time budget
+    Timeout,
+}
+
+/// Result of simulating a mutation; on failure `stage` is where it broke
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationOutcome {
+    pub passed: bool,
+    pub stage: SimulationStage,
+    /// Leading diagnostics from the failing tool, empty on success
+    pub stderr_excerpt: String,
+    pub duration: std::time::Duration,
+}
+
+impl SimulationOutcome {
+    pub fn passed(stage: SimulationStage, duration: std::time::Duration) -> Self {
+        Self { passed: true, stage, stderr_excerpt: String::new(), duration }
+    }
+
+    pub fn failed(stage: SimulationStage, stderr_excerpt: impl Into<String>, duration: std::time::Duration) -> Self {
+        Self { passed: false, stage, stderr_excerpt: stderr_excerpt.into(), duration }
+    }
+}
+
+/// A single governance finding, e.g. `{ rule: "Rule 6", detail: "..." }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    pub rule: String,
+    pub detail: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule, self.detail)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,17 +89,95 @@ impl CommitMetadata {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictContext {
     pub repo_root: String,
     pub file_path: String,
     pub hunk_ours: String,
     pub hunk_theirs: String,
     pub hunk_base: Option<String>,
+    /// Simulation diagnostics from the previous resolution attempt, if it failed
+    #[serde(default)]
+    pub previous_failure: Option<String>,
+}
+
+/// What an update cycle did to the local repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateOutcome {
+    /// Local HEAD already matched upstream
+    UpToDate,
+    /// Local branch was moved forward to upstream
+    FastForwarded,
+    /// Divergent histories merged without conflicts
+    Merged,
+    /// Conflicts were resolved by the Brain and verified by simulation
+    ConflictResolved,
+    /// Conflicts could not be resolved or failed simulation; merge aborted
+    ConflictRejected,
+    /// Merge analysis required no action
+    NoAction,
+}
+
+impl UpdateOutcome {
+    /// Whether the repository is in a healthy, synced state afterwards
+    pub fn is_success(&self) -> bool {
+        !matches!(self, UpdateOutcome::ConflictRejected)
+    }
+}
+
+/// Phase reports emitted while a long-running evolution is in progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum EvolutionProgress {
+    Fetching { received_bytes: usize, received_objects: usize, total_objects: usize },
+    Analyzing,
+    Merging,
+    ResolvingConflict { file: String },
+    Simulating,
+    Committing,
+}
+
+/// Receiving end is typically streamed to an operator (SSE) or logged
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<EvolutionProgress>;
+
+/// Action an update cycle would take, computed without touching the worktree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlannedAction {
+    UpToDate,
+    FastForward,
+    Merge,
+    ResolveConflicts,
+    NoAction,
+}
+
+/// Dry-run preview of `auto_update`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePlan {
+    pub local_commit: String,
+    pub upstream_commit: String,
+    pub action: PlannedAction,
+    /// Local commits not upstream
+    pub ahead: usize,
+    /// Upstream commits not local
+    pub behind: usize,
+    /// Files that differ between local HEAD and upstream
+    pub changed_files: Vec<String>,
+    /// Lines added + removed by the incoming changes (checked against the Rule 6 ceiling)
+    pub changed_lines: usize,
+    /// Files that would conflict on merge (Brain resolution required)
+    pub conflicting_files: Vec<String>,
 }
 
 pub struct GitEvolution {
     path: PathBuf,
+    /// Shared with every other handle on the same repository
+    lock: RepoLock,
+    /// Optional progress sink
+    progress: Option<ProgressSender>,
+    /// Aggregate Rule 6 ceiling across all files of one evolution
+    max_total_loc: usize,
+    /// Bound on the resolve/simulate retry loop
+    max_resolution_attempts: usize,
 }
 
 impl GitEvolution {
@@ -49,11 +186,175 @@ impl GitEvolution {
         let repo_path = path.as_ref().to_path_buf();
         // Verify it's a repo
         Repository::open(&repo_path)?;
-        Ok(Self { path: repo_path })
+        let lock = repo_lock(&repo_path);
+        Ok(Self {
+            path: repo_path,
+            lock,
+            progress: None,
+            max_total_loc: DEFAULT_MAX_TOTAL_LOC,
+            max_resolution_attempts: DEFAULT_RESOLUTION_ATTEMPTS,
+        })
+    }
+
+    /// Override the aggregate LOC ceiling (default `DEFAULT_MAX_TOTAL_LOC`)
+    pub fn with_max_total_loc(mut self, max_total_loc: usize) -> Self {
+        self.max_total_loc = max_total_loc;
+        self
+    }
+
+    /// Override how many resolve/simulate rounds a conflict gets (minimum 1)
+    pub fn with_max_resolution_attempts(mut self, attempts: usize) -> Self {
+        self.max_resolution_attempts = attempts.max(1);
+        self
+    }
+
+    /// Report phases of subsequent operations to `sink`
+    pub fn with_progress(mut self, sink: ProgressSender) -> Self {
+        self.progress = Some(sink);
+        self
+    }
+
+    fn emit(&self, phase: EvolutionProgress) {
+        if let Some(sink) = &self.progress {
+            // A dropped receiver just means nobody is watching
+            let _ = sink.send(phase);
+        }
+    }
+
+    /// Whether another mutation currently holds the repository lock
+    pub fn is_busy(&self) -> bool {
+        self.lock.try_lock().is_err()
+    }
+
+    fn try_guard(&self) -> Result<tokio::sync::MutexGuard<'_, ()>> {
+        self.lock.try_lock().map_err(|_| anyhow::Error::new(RepoBusy(self.path.clone())))
+    }
+
+    /// Take the repository lock now without waiting, for a later `*_reserved`
+    /// call. Lets a caller settle permissions and charges only once it knows
+    /// the repository is free.
+    pub fn reserve(&self) -> Result<RepoGuard> {
+        self.lock.clone().try_lock_owned().map_err(|_| anyhow::Error::new(RepoBusy(self.path.clone())))
+    }
+
+    fn check_reservation(&self, guard: &RepoGuard) -> Result<()> {
+        if !std::sync::Arc::ptr_eq(tokio::sync::OwnedMutexGuard::mutex(guard), &self.lock) {
+            return Err(anyhow!("Reservation does not belong to {:?}", self.path));
+        }
+        Ok(())
+    }
+
+    /// Current HEAD commit id
+    pub fn head_commit(&self) -> Result<String> {
+        let repo = Repository::open(&self.path)?;
+        let head = repo.head()?.peel_to_commit()?;
+        Ok(head.id().to_string())
+    }
+
+    /// Dry-run planner: fetch upstream and report what `auto_update` would do.
+    /// Only remote-tracking refs are updated; HEAD, index and worktree are untouched.
+    pub fn plan_update(&self, remote_name: &str, branch: &str) -> Result<UpdatePlan> {
+        let _guard = self.try_guard()?;
+        self.plan_update_locked(remote_name, branch)
+    }
+
+    /// `plan_update` under a lock taken earlier with `reserve`
+    pub fn plan_update_reserved(&self, guard: &RepoGuard, remote_name: &str, branch: &str) -> Result<UpdatePlan> {
+        self.check_reservation(guard)?;
+        self.plan_update_locked(remote_name, branch)
+    }
+
+    fn plan_update_locked(&self, remote_name: &str, branch: &str) -> Result<UpdatePlan> {
+        let repo = Repository::open(&self.path)?;
+        self.fetch_upstream(&repo, remote_name, branch)?;
+
+        let local_commit = repo.head()?.peel_to_commit()?;
+        let remote_ref_name = format!("refs/remotes/{remote_name}/{branch}");
+        let remote_commit = repo.find_reference(&remote_ref_name)?.peel_to_commit()?;
+
+        let mut plan = UpdatePlan {
+            local_commit: local_commit.id().to_string(),
+            upstream_commit: remote_commit.id().to_string(),
+            action: PlannedAction::UpToDate,
+            ahead: 0,
+            behind: 0,
+            changed_files: Vec::new(),
+            changed_lines: 0,
+            conflicting_files: Vec::new(),
+        };
+
+        if local_commit.id() == remote_commit.id() {
+            return Ok(plan);
+        }
+
+        let (ahead, behind) = repo.graph_ahead_behind(local_commit.id(), remote_commit.id())?;
+        plan.ahead = ahead;
+        plan.behind = behind;
+
+        let diff = repo.diff_tree_to_tree(Some(&local_commit.tree()?), Some(&remote_commit.tree()?), None)?;
+        plan.changed_files = diff.deltas()
+            .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        plan.changed_lines = self.incoming_changed_lines(&repo, &local_commit, &remote_commit)?;
+
+        let annotated_remote = repo.find_annotated_commit(remote_commit.id())?;
+        let (analysis, _) = repo.merge_analysis(&[&annotated_remote])?;
+
+        plan.action = if analysis.is_up_to_date() {
+            PlannedAction::UpToDate
+        } else if analysis.is_fast_forward() {
+            PlannedAction::FastForward
+        } else if analysis.is_normal() {
+            // In-memory merge: detects conflicts without writing MERGE_HEAD
+            let index = repo.merge_commits(&local_commit, &remote_commit, None)?;
+            if index.has_conflicts() {
+                for conflict in index.conflicts()? {
+                    let conflict = conflict?;
+                    if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                        plan.conflicting_files.push(String::from_utf8_lossy(&entry.path).to_string());
+                    }
+                }
+                PlannedAction::ResolveConflicts
+            } else {
+                PlannedAction::Merge
+            }
+        } else {
+            PlannedAction::NoAction
+        };
+
+        Ok(plan)
+    }
+
+    /// Autonomous Update Cycle (waits for any concurrent mutation to finish)
+    pub async fn auto_update<T>(&self, remote_name: &str, branch: &str, brain: &T) -> Result<UpdateOutcome> 
+    where 
+        T: BrainMutationResolver + 'static + ?Sized
+    {
+        let _guard = self.lock.lock().await;
+        self.auto_update_locked(remote_name, branch, brain).await
     }
 
-    /// Autonomous Update Cycle
-    pub async fn auto_update<T>(&self, remote_name: &str, branch: &str, brain: &T) -> Result<bool> 
+    /// Like `auto_update`, but fails fast with `RepoBusy` instead of waiting
+    pub async fn try_auto_update<T>(&self, remote_name: &str, branch: &str, brain: &T) -> Result<UpdateOutcome> 
+    where 
+        T: BrainMutationResolver + 'static + ?Sized
+    {
+        let _guard = self.try_guard()?;
+        self.auto_update_locked(remote_name, branch, brain).await
+    }
+
+    /// `auto_update` under a lock taken earlier with `reserve`; the lock is
+    /// released when the update finishes
+    pub async fn auto_update_reserved<T>(&self, guard: RepoGuard, remote_name: &str, branch: &str, brain: &T) -> Result<UpdateOutcome> 
+    where 
+        T: BrainMutationResolver + 'static + ?Sized
+    {
+        self.check_reservation(&guard)?;
+        self.auto_update_locked(remote_name, branch, brain).await
+    }
+
+    async fn auto_update_locked<T>(&self, remote_name: &str, branch: &str, brain: &T) -> Result<UpdateOutcome> 
     where 
         T: BrainMutationResolver + 'static + ?Sized
     {
@@ -62,10 +363,11 @@ impl GitEvolution {
         // 1. Fetch & Analyze (Synchronous block to avoid Holding Repo across Await)
         let (analysis, remote_commit_id, branch_ref_name) = {
             let repo = Repository::open(&self.path)?;
+            self.recover_interrupted_state(&repo)?;
             self.fetch_upstream(&repo, remote_name, branch)?;
 
+            let branch_ref = self.ensure_attached(&repo, branch)?;
             let head = repo.head()?;
-            let branch_ref = head.name().ok_or_else(|| anyhow!("Detached HEAD"))?.to_string();
 
             let remote_ref_name = format!("refs/remotes/{remote_name}/{branch}");
             let remote_commit = repo.find_reference(&remote_ref_name)?
@@ -75,9 +377,13 @@ impl GitEvolution {
 
             if local_commit.id() == remote_commit.id() {
                 info!("GitEvolution: Already up to date.");
-                return Ok(true);
+                return Ok(UpdateOutcome::UpToDate);
             }
 
+            self.emit(EvolutionProgress::Analyzing);
+            let changed = self.incoming_changed_lines(&repo, &local_commit, &remote_commit)?;
+            self.check_loc_ceiling(changed)?;
+
             let annotated_remote = repo.find_annotated_commit(remote_commit.id())?;
             let (analysis, _) = repo.merge_analysis(&[&annotated_remote])?;
             
@@ -85,12 +391,14 @@ impl GitEvolution {
         };
 
         if analysis.is_fast_forward() {
+             self.emit(EvolutionProgress::Merging);
              let repo = Repository::open(&self.path)?;
              let remote_commit = repo.find_commit(remote_commit_id)?;
              self.perform_fast_forward(&repo, &branch_ref_name, &remote_commit)?;
              info!("GitEvolution: Fast-forward update successful.");
-             Ok(true)
+             Ok(UpdateOutcome::FastForwarded)
         } else if analysis.is_normal() {
+             self.emit(EvolutionProgress::Merging);
              let has_conflicts = {
                  let repo = Repository::open(&self.path)?;
                  let annotated_remote = repo.find_annotated_commit(remote_commit_id)?;
@@ -102,7 +410,11 @@ impl GitEvolution {
              
              if has_conflicts {
                  warn!("GitEvolution: CONFLICT DETECTED. Invoking cortex...");
-                 self.resolve_conflicts_with_brain(brain).await
+                 if self.resolve_conflicts_with_brain(brain).await? {
+                     Ok(UpdateOutcome::ConflictResolved)
+                 } else {
+                     Ok(UpdateOutcome::ConflictRejected)
+                 }
              } else {
                 let repo = Repository::open(&self.path)?;
                 let annotated_remote = repo.find_annotated_commit(remote_commit_id)?;
@@ -112,19 +424,144 @@ impl GitEvolution {
                     description: "Autonomous update from remote tracking branch".to_string(),
                     impact: "Keep local node code in sync with evolution source".to_string(),
                 };
+                self.emit(EvolutionProgress::Committing);
                 self.finalize_merge(&repo, &annotated_remote, &metadata)?;
                  info!("GitEvolution: Clean merge successful.");
-                 Ok(true)
+                 Ok(UpdateOutcome::Merged)
              }
         } else {
             info!("GitEvolution: No actions required for update.");
-            Ok(true)
+            Ok(UpdateOutcome::NoAction)
+        }
+    }
+
+    /// Roll `branch` back to `commit` and check it out, re-attaching HEAD.
+    /// Recovery path for repositories left detached on an unrelated commit.
+    pub fn rollback_to(&self, branch: &str, commit: &str) -> Result<()> {
+        let _guard = self.try_guard()?;
+        let repo = Repository::open(&self.path)?;
+        let target = repo.revparse_single(commit)
+            .with_context(|| format!("Unknown commit '{commit}'"))?
+            .peel_to_commit()?;
+
+        warn!("GitEvolution: Rolling back '{}' to {}", branch, target.id());
+        repo.cleanup_state()?;
+        repo.branch(branch, &target, true)?;
+        let refname = format!("refs/heads/{branch}");
+        repo.set_head(&refname)?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+        Ok(())
+    }
+
+    /// Name of the branch HEAD points to, or a precise error when detached
+    fn current_branch_ref(&self, repo: &Repository) -> Result<String> {
+        if repo.head_detached()? {
+            let oid = repo.head()?.peel_to_commit()?.id();
+            return Err(anyhow!(
+                "HEAD is detached at {oid}; check out a branch or call rollback_to(<branch>, <commit>) to recover"
+            ));
         }
+        let head = repo.head()?;
+        head.name()
+            .map(|n| n.to_string())
+            .ok_or_else(|| anyhow!("HEAD reference name is not valid UTF-8"))
+    }
+
+    /// Abort leftovers from an interrupted merge/rebase so a new cycle can start
+    fn recover_interrupted_state(&self, repo: &Repository) -> Result<()> {
+        let state = repo.state();
+        if state != RepositoryState::Clean {
+            warn!("GitEvolution: Repository in {:?} state; aborting stale operation", state);
+            repo.cleanup_state()?;
+            let head = repo.head()?.peel_to_commit()?;
+            repo.reset(head.as_object(), git2::ResetType::Hard, None)?;
+        }
+        Ok(())
+    }
+
+    /// Make sure HEAD is on `branch`, re-attaching a detached HEAD when safe.
+    /// Returns the full reference name of the attached branch.
+    fn ensure_attached(&self, repo: &Repository, branch: &str) -> Result<String> {
+        if !repo.head_detached()? {
+            return self.current_branch_ref(repo);
+        }
+
+        let head_oid = repo.head()?.peel_to_commit()?.id();
+        let refname = format!("refs/heads/{branch}");
+        warn!("GitEvolution: HEAD detached at {}; attempting to re-attach to '{}'", head_oid, branch);
+
+        match repo.find_branch(branch, git2::BranchType::Local) {
+            Ok(local) => {
+                let tip = local.get().peel_to_commit()?.id();
+                // Only re-attach if no detached work would be orphaned
+                if tip != head_oid && !repo.graph_descendant_of(tip, head_oid)? {
+                    return Err(anyhow!(
+                        "HEAD is detached at {head_oid}, which is not contained in branch '{branch}' (tip {tip}); \
+                         call rollback_to(\"{branch}\", <commit>) to choose a recovery point"
+                    ));
+                }
+            }
+            Err(_) => {
+                // No local branch yet: create it where HEAD is
+                let head_commit = repo.find_commit(head_oid)?;
+                repo.branch(branch, &head_commit, false)?;
+            }
+        }
+
+        repo.set_head(&refname)?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().safe()))?;
+        info!("GitEvolution: Re-attached HEAD to {}", refname);
+        Ok(refname)
     }
 
     fn fetch_upstream(&self, repo: &Repository, remote: &str, branch: &str) -> Result<()> {
         let mut remote = repo.find_remote(remote)?;
-        remote.fetch(&[branch], None, None).context("Failed to fetch upstream")?;
+
+        let mut callbacks = git2::RemoteCallbacks::new();
+        if let Some(sink) = self.progress.clone() {
+            let mut last_objects = usize::MAX;
+            callbacks.transfer_progress(move |stats| {
+                // Only report when object count moves, not on every packet
+                if stats.received_objects() != last_objects {
+                    last_objects = stats.received_objects();
+                    let _ = sink.send(EvolutionProgress::Fetching {
+                        received_bytes: stats.received_bytes(),
+                        received_objects: stats.received_objects(),
+                        total_objects: stats.total_objects(),
+                    });
+                }
+                true
+            });
+        }
+        let mut fetch_opts = git2::FetchOptions::new();
+        fetch_opts.remote_callbacks(callbacks);
+
+        remote.fetch(&[branch], Some(&mut fetch_opts), None).context("Failed to fetch upstream")?;
+        Ok(())
+    }
+
+    /// Lines added + removed between the merge base and `remote`, i.e. what the
+    /// evolution would bring in regardless of how many files it is spread over
+    fn incoming_changed_lines(&self, repo: &Repository, local: &git2::Commit, remote: &git2::Commit) -> Result<usize> {
+        let base_tree = match repo.merge_base(local.id(), remote.id()) {
+            Ok(base) => Some(repo.find_commit(base)?.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(base_tree.as_ref(), Some(&remote.tree()?), None)?;
+        let stats = diff.stats()?;
+        Ok(stats.insertions() + stats.deletions())
+    }
+
+    /// Rule 6 (aggregate): reject evolutions whose total diff exceeds the ceiling,
+    /// even when every individual hunk is within the per-conflict limit
+    fn check_loc_ceiling(&self, changed: usize) -> Result<()> {
+        if changed > self.max_total_loc {
+            error!("GitEvolution: Aggregate change of {} LOC exceeds ceiling of {}", changed, self.max_total_loc);
+            return Err(anyhow!(
+                "Rule 6 Violation (aggregate): Evolution changes {changed} LOC in total, exceeding the {} LOC ceiling",
+                self.max_total_loc
+            ));
+        }
         Ok(())
     }
 
@@ -156,7 +593,7 @@ impl GitEvolution {
         Ok(())
     }
 
-    /// Extracted Immune Response: Conflict -> Hunks -> Brain -> Patch -> Resolution
+    /// Extracted Immune Response: Conflict -> Hunks -> Fallback/Brain -> Patch -> Resolution
     async fn resolve_conflicts_with_brain<T>(&self, brain: &T) -> Result<bool> 
     where 
         T: BrainMutationResolver + ?Sized
@@ -176,10 +613,13 @@ impl GitEvolution {
                 hunk_ours: String::new(),
                 hunk_theirs: String::new(),
                 hunk_base: None,
+                previous_failure: None,
             };
 
+                if let Some(entry) = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref()) {
+                     context.file_path = String::from_utf8_lossy(&entry.path).to_string();
+                }
                 if let Some(ref ours) = conflict.our {
-                     context.file_path = String::from_utf8_lossy(&ours.path).to_string();
                      context.hunk_ours = self.read_blob_content(&repo, ours.id)?;
                 }
                 if let Some(ref theirs) = conflict.their {
@@ -193,69 +633,96 @@ impl GitEvolution {
             contexts
         };
 
-        let mut resolutions = Vec::new();
-        for context in conflict_contexts {
-            info!("GitEvolution: Requesting resolution for {}", context.file_path);
-            let file_path = context.file_path.clone();
-            let resolution = cortex.resolve_conflict(context).await?;
-            resolutions.push((file_path, resolution));
-        }
+        // Failed simulations are fed back to the Brain so it can correct its own resolution
+        let mut previous_failure: Option<String> = None;
+        for attempt in 1..=self.max_resolution_attempts {
+            let mut resolutions = Vec::new();
+            for context in &conflict_contexts {
+                self.emit(EvolutionProgress::ResolvingConflict { file: context.file_path.clone() });
+                let file_path = context.file_path.clone();
 
-        // 2. Apply Resolutions
-        {
-            let repo = Repository::open(&self.path)?;
-            for (path, resolution) in resolutions {
-                 self.apply_resolution(&repo, &path, &resolution)?;
+                // Trivial conflicts never reach the Brain, so syncs survive a model outage.
+                // On retries everything is escalated: the fallback's answer may be what broke.
+                if previous_failure.is_none() {
+                    if let Some(resolution) = FallbackResolver.resolve(context) {
+                        info!("GitEvolution: Resolved {} deterministically", file_path);
+                        resolutions.push((file_path, resolution));
+                        continue;
+                    }
+                }
+
+                info!("GitEvolution: Requesting resolution for {} (attempt {})", file_path, attempt);
+                let mut context = context.clone();
+                context.previous_failure = previous_failure.clone();
+                let resolution = brain.resolve_conflict(context).await?;
+                resolutions.push((file_path, resolution));
             }
 
-            let mut index = repo.index()?;
-            if index.has_conflicts() {
-                 error!("GitEvolution: Brain failed to resolve some conflicts. Aborting merge.");
-                 repo.cleanup_state()?; 
-                 return Ok(false);
+            // 2. Apply Resolutions
+            {
+                let repo = Repository::open(&self.path)?;
+                for (path, resolution) in resolutions {
+                     self.apply_resolution(&repo, &path, &resolution)?;
+                }
+
+                let mut index = repo.index()?;
+                if index.has_conflicts() {
+                     error!("GitEvolution: Brain failed to resolve some conflicts. Aborting merge.");
+                     repo.cleanup_state()?; 
+                     return Ok(false);
+                }
+                index.write()?;
             }
-            index.write()?;
-        }
 
-        // 3. Simulation Step
-        info!("GitEvolution: Conflict resolved, running simulation...");
-        
-        if cortex.simulate_patch(&self.path.to_string_lossy(), "conflict_resolution").await? {
-             info!("GitEvolution: Resolution verified by simulation. Finalizing.");
-             let repo = Repository::open(&self.path)?;
-             let mut index = repo.index()?;
-             let head_commit = repo.head()?.peel_to_commit()?;
-             let merge_head = repo.find_reference("MERGE_HEAD")?.peel_to_commit()?;
-             
-             let signature = Signature::now("IPPOC-Immune", "immune@ippoc.os")?;
-             let tree_id = index.write_tree()?;
-             let tree = repo.find_tree(tree_id)?;
+            // 3. Simulation Step
+            info!("GitEvolution: Conflict resolved, running simulation...");
+            self.emit(EvolutionProgress::Simulating);
+            
+            let simulation = brain.simulate_patch(&self.path.to_string_lossy(), "conflict_resolution").await?;
+            if simulation.passed {
+                 info!("GitEvolution: Resolution verified by simulation in {:?}. Finalizing.", simulation.duration);
+                 self.emit(EvolutionProgress::Committing);
+                 let repo = Repository::open(&self.path)?;
+                 let mut index = repo.index()?;
+                 let head_commit = repo.head()?.peel_to_commit()?;
+                 let merge_head = repo.find_reference("MERGE_HEAD")?.peel_to_commit()?;
+                 
+                 let signature = Signature::now("IPPOC-Immune", "immune@ippoc.os")?;
+                 let tree_id = index.write_tree()?;
+                 let tree = repo.find_tree(tree_id)?;
 
-             let metadata = CommitMetadata {
-                 organ: "body/immune".to_string(),
-                 intent: "Resolve merge conflicts".to_string(),
-                 description: "Brain Mutation Resolver successfully addressed file conflicts.".to_string(),
-                 impact: "Merged evolutionary changes while maintaining structural integrity.".to_string(),
-             };
-             let msg = metadata.to_message();
+                 let metadata = CommitMetadata {
+                     organ: "body/immune".to_string(),
+                     intent: "Resolve merge conflicts".to_string(),
+                     description: "Brain Mutation Resolver successfully addressed file conflicts.".to_string(),
+                     impact: "Merged evolutionary changes while maintaining structural integrity.".to_string(),
+                 };
+                 let msg = metadata.to_message();
 
-             repo.commit(
-                 Some("HEAD"),
-                 &signature,
-                 &signature,
-                 &msg,
-                 &tree,
-                 &[&head_commit, &merge_head]
-             )?;
-             
-             repo.cleanup_state()?;
-             Ok(true)
-        } else {
-             warn!("GitEvolution: Simulation FAILED for resolution. Rolling back.");
-             let repo = Repository::open(&self.path)?;
-             repo.cleanup_state()?;
-             Ok(false)
+                 repo.commit(
+                     Some("HEAD"),
+                     &signature,
+                     &signature,
+                     &msg,
+                     &tree,
+                     &[&head_commit, &merge_head]
+                 )?;
+                 
+                 repo.cleanup_state()?;
+                 return Ok(true);
+            }
+
+            warn!(
+                "GitEvolution: Simulation FAILED for resolution at {:?} stage (attempt {}/{}).\n{}",
+                simulation.stage, attempt, self.max_resolution_attempts, simulation.stderr_excerpt
+            );
+            previous_failure = Some(simulation.stderr_excerpt);
         }
+
+        warn!("GitEvolution: Resolution attempts exhausted. Rolling back.");
+        let repo = Repository::open(&self.path)?;
+        repo.cleanup_state()?;
+        Ok(false)
     }
 
     fn read_blob_content(&self, repo: &Repository, id: git2::Oid) -> Result<String> {
@@ -284,6 +751,8 @@ impl GitEvolution {
     where 
         T: BrainMutationResolver + 'static + ?Sized
     {
+        let _guard = self.lock.lock().await;
+
         // 1. Pre-review safety check (Rule 0 & Rule 3)
         if !self.review_patch(brain, &metadata.description).await? {
             warn!("GitEvolution: Patch REJECTED by immune system review.");
@@ -304,7 +773,7 @@ impl GitEvolution {
                 repo.branch(&branch_name, &head_commit, false)?;
             }
             
-            let head_name = head.name().unwrap_or("refs/heads/main").to_string();
+            let head_name = self.current_branch_ref(&repo)?;
             (head_name, head_commit.id())
         };
 
@@ -317,8 +786,11 @@ impl GitEvolution {
         }
 
         // 1. Simulate the patch on the feature branch
-        if cortex.simulate_patch(&self.path.to_string_lossy(), &metadata.description).await? {
+        self.emit(EvolutionProgress::Simulating);
+        let simulation = brain.simulate_patch(&self.path.to_string_lossy(), &metadata.description).await?;
+        if simulation.passed {
              info!("GitEvolution: Feature PASSED simulation. Merging back to head.");
+             self.emit(EvolutionProgress::Committing);
              
              let repo = Repository::open(&self.path)?;
              
@@ -359,7 +831,7 @@ impl GitEvolution {
              repo.cleanup_state()?;
              Ok(true)
         } else {
-             warn!("GitEvolution: Feature FAILED simulation. Staying on branch for inspection.");
+             warn!("GitEvolution: Feature FAILED simulation at {:?} stage. Staying on branch for inspection.\n{}", simulation.stage, simulation.stderr_excerpt);
              Ok(false)
         }
     }
@@ -389,6 +861,7 @@ impl GitEvolution {
 
     /// Commit staged changes with metadata provided by the Brain
     pub fn commit_staged(&self, metadata: CommitMetadata) -> Result<String> {
+        let _guard = self.try_guard()?;
         let repo = Repository::open(&self.path)?;
         let signature = Signature::now("IPPOC-Immune", "immune@ippoc.os")?;
         
@@ -419,7 +892,7 @@ impl GitEvolution {
         info!("GitEvolution: Initiating immune system patch review...");
         
         // 1. Ask brain to scan for governance violations
-        let violations = cortex.scan_for_governance_violations(patch_content).await?;
+        let violations = brain.scan_for_governance_violations(patch_content).await?;
         
         if !violations.is_empty() {
             for violation in violations {
@@ -429,7 +902,378 @@ impl GitEvolution {
         }
 
         // 2. Structural review
-        cortex.review_patch(patch_content).await
+        brain.review_patch(patch_content).await
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct NullBrain;
+
+    #[async_trait::async_trait]
+    impl BrainMutationResolver for NullBrain {
+        async fn resolve_conflict(&self, _conflict: ConflictContext) -> Result<String> {
+            Err(anyhow!("not expected in test"))
+        }
+        async fn simulate_patch(&self, _repo_root: &str, _patch_content: &str) -> Result<SimulationOutcome> {
+            Ok(SimulationOutcome::failed(SimulationStage::Compile, "not expected in test", Duration::ZERO))
+        }
+        async fn review_patch(&self, _patch_content: &str) -> Result<bool> {
+            Ok(false)
+        }
+        async fn scan_for_governance_violations(&self, _summary: &str) -> Result<Vec<Violation>> {
+            Ok(vec![])
+        }
+    }
+
+    /// Upstream repo with one commit, plus a clone tracking it as `origin`
+    fn upstream_and_clone(dir: &Path) -> (PathBuf, String) {
+        let upstream_path = dir.join("upstream");
+        let upstream = Repository::init(&upstream_path).unwrap();
+        std::fs::write(upstream_path.join("README.md"), "genesis\n").unwrap();
+        let mut index = upstream.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let tree = upstream.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("test", "test@ippoc.os").unwrap();
+        upstream.commit(Some("HEAD"), &sig, &sig, "genesis", &tree, &[]).unwrap();
+        let branch = upstream.head().unwrap().shorthand().unwrap().to_string();
+
+        let clone_path = dir.join("clone");
+        Repository::clone(upstream_path.to_str().unwrap(), &clone_path).unwrap();
+        (clone_path, branch)
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_auto_updates_serialize() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        // Different spellings of the same path share one lock
+        let holder = GitEvolution::open(&clone_path).unwrap();
+        let other = GitEvolution::open(clone_path.join(".")).unwrap();
+
+        let guard = holder.lock.lock().await;
+        assert!(other.is_busy());
+
+        let pending = tokio::spawn(async move {
+            other.auto_update("origin", &branch, &NullBrain).await
+        });
+
+        // Second update must wait while the first holds the repo
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!pending.is_finished());
+
+        drop(guard);
+        let outcome = tokio::time::timeout(Duration::from_secs(5), pending)
+            .await
+            .expect("update should proceed once the lock is released")
+            .unwrap()
+            .unwrap();
+        assert_eq!(outcome, UpdateOutcome::UpToDate);
+    }
+
+    #[tokio::test]
+    async fn test_fast_forward_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        // Advance upstream by one commit
+        let upstream = Repository::open(dir.path().join("upstream")).unwrap();
+        std::fs::write(dir.path().join("upstream/README.md"), "evolved\n").unwrap();
+        let mut index = upstream.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let tree = upstream.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = upstream.head().unwrap().peel_to_commit().unwrap();
+        let sig = Signature::now("test", "test@ippoc.os").unwrap();
+        upstream.commit(Some("HEAD"), &sig, &sig, "evolve", &tree, &[&parent]).unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let git = GitEvolution::open(&clone_path).unwrap().with_progress(tx);
+        let outcome = git.auto_update("origin", &branch, &NullBrain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::FastForwarded);
+        drop(git);
+
+        let mut phases = Vec::new();
+        while let Some(phase) = rx.recv().await {
+            phases.push(phase);
+        }
+        let analyzing = phases.iter().position(|p| *p == EvolutionProgress::Analyzing).unwrap();
+        let merging = phases.iter().position(|p| *p == EvolutionProgress::Merging).unwrap();
+        assert!(analyzing < merging);
+        assert!(phases[..analyzing].iter().all(|p| matches!(p, EvolutionProgress::Fetching { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_many_small_changes_exceed_aggregate_ceiling() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        // 20 files x 5 lines: each tiny, 100 LOC together
+        let upstream_path = dir.path().join("upstream");
+        let upstream = Repository::open(&upstream_path).unwrap();
+        let mut index = upstream.index().unwrap();
+        for i in 0..20 {
+            let name = format!("organ_{i}.rs");
+            std::fs::write(upstream_path.join(&name), "a\nb\nc\nd\ne\n").unwrap();
+            index.add_path(Path::new(&name)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = upstream.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = upstream.head().unwrap().peel_to_commit().unwrap();
+        let sig = Signature::now("test", "test@ippoc.os").unwrap();
+        upstream.commit(Some("HEAD"), &sig, &sig, "many organs", &tree, &[&parent]).unwrap();
+
+        let git = GitEvolution::open(&clone_path).unwrap().with_max_total_loc(50);
+        let before = git.head_commit().unwrap();
+
+        let plan = git.plan_update("origin", &branch).unwrap();
+        assert_eq!(plan.changed_lines, 100);
+
+        let err = git.auto_update("origin", &branch, &NullBrain).await.unwrap_err();
+        assert!(err.to_string().contains("Rule 6 Violation (aggregate)"));
+        assert_eq!(git.head_commit().unwrap(), before);
+
+        // Same evolution passes under the default ceiling
+        let git = GitEvolution::open(&clone_path).unwrap();
+        let outcome = git.auto_update("origin", &branch, &NullBrain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::FastForwarded);
+    }
+
+    /// Fails simulation once, then accepts whatever it resolved on retry
+    #[derive(Default)]
+    struct RetryBrain {
+        simulations: std::sync::atomic::AtomicUsize,
+        feedback_seen: std::sync::Mutex<Vec<Option<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl BrainMutationResolver for RetryBrain {
+        async fn resolve_conflict(&self, conflict: ConflictContext) -> Result<String> {
+            self.feedback_seen.lock().unwrap().push(conflict.previous_failure);
+            Ok("resolved\n".to_string())
+        }
+        async fn simulate_patch(&self, _repo_root: &str, _patch_content: &str) -> Result<SimulationOutcome> {
+            let n = self.simulations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if n == 0 {
+                Ok(SimulationOutcome::failed(SimulationStage::Compile, "error[E0425]: cannot find value `x`", Duration::ZERO))
+            } else {
+                Ok(SimulationOutcome::passed(SimulationStage::Lint, Duration::ZERO))
+            }
+        }
+        async fn review_patch(&self, _patch_content: &str) -> Result<bool> {
+            Ok(true)
+        }
+        async fn scan_for_governance_violations(&self, _summary: &str) -> Result<Vec<Violation>> {
+            Ok(vec![])
+        }
+    }
+
+    fn commit_file(repo: &Repository, file: &str, content: &str, message: &str) {
+        let root = repo.workdir().unwrap().to_path_buf();
+        std::fs::write(root.join(file), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = Signature::now("test", "test@ippoc.os").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_simulation_failure_is_fed_back_into_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        // Semantic conflict on the same line: fallback can't handle it
+        let upstream = Repository::open(dir.path().join("upstream")).unwrap();
+        commit_file(&upstream, "README.md", "upstream\n", "upstream change");
+        let clone = Repository::open(&clone_path).unwrap();
+        commit_file(&clone, "README.md", "local\n", "local change");
+
+        let brain = RetryBrain::default();
+        let git = GitEvolution::open(&clone_path).unwrap();
+        let outcome = git.auto_update("origin", &branch, &brain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::ConflictResolved);
+
+        let seen = brain.feedback_seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0], None);
+        assert!(seen[1].as_deref().unwrap().contains("E0425"));
+
+        assert_eq!(std::fs::read_to_string(clone_path.join("README.md")).unwrap(), "resolved\n");
+        assert_eq!(clone.head().unwrap().peel_to_commit().unwrap().parent_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolution_attempts_are_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        let upstream = Repository::open(dir.path().join("upstream")).unwrap();
+        commit_file(&upstream, "README.md", "upstream\n", "upstream change");
+        let clone = Repository::open(&clone_path).unwrap();
+        commit_file(&clone, "README.md", "local\n", "local change");
+
+        // Only the first simulation fails, but one attempt allows no retry
+        let brain = RetryBrain::default();
+        let git = GitEvolution::open(&clone_path).unwrap().with_max_resolution_attempts(1);
+        let outcome = git.auto_update("origin", &branch, &brain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::ConflictRejected);
+        assert_eq!(brain.feedback_seen.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_detached_head_reattaches_to_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        {
+            let repo = Repository::open(&clone_path).unwrap();
+            let oid = repo.head().unwrap().peel_to_commit().unwrap().id();
+            repo.set_head_detached(oid).unwrap();
+            assert!(repo.head_detached().unwrap());
+        }
+
+        let git = GitEvolution::open(&clone_path).unwrap();
+        let outcome = git.auto_update("origin", &branch, &NullBrain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::UpToDate);
+
+        let repo = Repository::open(&clone_path).unwrap();
+        assert!(!repo.head_detached().unwrap());
+        assert_eq!(repo.head().unwrap().shorthand(), Some(branch.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_detached_on_orphan_commit_requires_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        let tip = {
+            let repo = Repository::open(&clone_path).unwrap();
+            let tip = repo.head().unwrap().peel_to_commit().unwrap();
+            repo.set_head_detached(tip.id()).unwrap();
+
+            // Commit on the detached HEAD: not contained in the branch
+            let sig = Signature::now("test", "test@ippoc.os").unwrap();
+            let tree = tip.tree().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "stray", &tree, &[&tip]).unwrap();
+            tip.id().to_string()
+        };
+
+        let git = GitEvolution::open(&clone_path).unwrap();
+        let err = git.auto_update("origin", &branch, &NullBrain).await.unwrap_err().to_string();
+        assert!(err.contains("detached"));
+        assert!(err.contains("rollback_to"));
+
+        git.rollback_to(&branch, &tip).unwrap();
+        let outcome = git.auto_update("origin", &branch, &NullBrain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::UpToDate);
+    }
+
+    /// Resolves conflicts only once released, so an update can be held mid-flight
+    #[derive(Default)]
+    struct GateBrain {
+        entered: tokio::sync::Notify,
+        release: tokio::sync::Notify,
+    }
+
+    #[async_trait::async_trait]
+    impl BrainMutationResolver for GateBrain {
+        async fn resolve_conflict(&self, _conflict: ConflictContext) -> Result<String> {
+            self.entered.notify_one();
+            self.release.notified().await;
+            Ok("resolved\n".to_string())
+        }
+        async fn simulate_patch(&self, _repo_root: &str, _patch_content: &str) -> Result<SimulationOutcome> {
+            Ok(SimulationOutcome::passed(SimulationStage::Lint, Duration::ZERO))
+        }
+        async fn review_patch(&self, _patch_content: &str) -> Result<bool> {
+            Ok(true)
+        }
+        async fn scan_for_governance_violations(&self, _summary: &str) -> Result<Vec<Violation>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_try_auto_updates_one_is_busy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+        let upstream = Repository::open(dir.path().join("upstream")).unwrap();
+        commit_file(&upstream, "README.md", "upstream\n", "upstream change");
+        let clone = Repository::open(&clone_path).unwrap();
+        commit_file(&clone, "README.md", "local\n", "local change");
+
+        let brain = std::sync::Arc::new(GateBrain::default());
+        let spawn_update = |path: PathBuf| {
+            let (brain, branch) = (brain.clone(), branch.clone());
+            tokio::spawn(async move {
+                let git = GitEvolution::open(path).unwrap();
+                git.try_auto_update("origin", &branch, brain.as_ref()).await
+            })
+        };
+        let first = spawn_update(clone_path.clone());
+        let second = spawn_update(clone_path.join("."));
+
+        // One update is inside conflict resolution; the other must give up
+        tokio::time::timeout(Duration::from_secs(5), brain.entered.notified()).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !first.is_finished() && !second.is_finished() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the losing update should fail fast");
+        brain.release.notify_one();
+
+        let results = [first.await.unwrap(), second.await.unwrap()];
+        let busy = results.iter()
+            .filter(|r| r.as_ref().is_err_and(|e| e.downcast_ref::<RepoBusy>().is_some()))
+            .count();
+        assert_eq!(busy, 1);
+        assert!(results.iter().any(|r| matches!(r, Ok(UpdateOutcome::ConflictResolved))));
+    }
+
+    #[tokio::test]
+    async fn test_reservation_blocks_others_until_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        let git = GitEvolution::open(&clone_path).unwrap();
+        let other = GitEvolution::open(&clone_path).unwrap();
+        let guard = git.reserve().unwrap();
+        assert!(other.reserve().is_err());
+        assert!(other.try_auto_update("origin", &branch, &NullBrain).await.is_err());
+
+        // A guard for one repository can't drive another
+        let elsewhere = tempfile::tempdir().unwrap();
+        let (foreign_path, _) = upstream_and_clone(elsewhere.path());
+        let foreign = GitEvolution::open(&foreign_path).unwrap();
+        let foreign_guard = foreign.reserve().unwrap();
+        assert!(git.plan_update_reserved(&foreign_guard, "origin", &branch).is_err());
+
+        let outcome = git.auto_update_reserved(guard, "origin", &branch, &NullBrain).await.unwrap();
+        assert_eq!(outcome, UpdateOutcome::UpToDate);
+        assert!(!other.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_try_auto_update_reports_busy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clone_path, branch) = upstream_and_clone(dir.path());
+
+        let holder = GitEvolution::open(&clone_path).unwrap();
+        let other = GitEvolution::open(&clone_path).unwrap();
+
+        let _guard = holder.lock.lock().await;
+        let err = other.try_auto_update("origin", &branch, &NullBrain).await.unwrap_err();
+        assert!(err.downcast_ref::<RepoBusy>().is_some());
+    }
+}
@@ -0,0 +1,38 @@
+//! Per-repository mutation lock
+//!
+//! Every `GitEvolution` handle on the same repository (keyed by canonical path)
+//! shares one async mutex, so the autonomous loop and operator triggers can
+//! never race on the index or working tree.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub type RepoLock = Arc<tokio::sync::Mutex<()>>;
+
+/// Held repository lock, taken ahead of the work with `GitEvolution::reserve`
+pub type RepoGuard = tokio::sync::OwnedMutexGuard<()>;
+
+static REGISTRY: OnceLock<Mutex<HashMap<PathBuf, RepoLock>>> = OnceLock::new();
+
+/// Returned when a non-waiting operation finds the repository locked
+#[derive(Debug, Clone)]
+pub struct RepoBusy(pub PathBuf);
+
+impl std::fmt::Display for RepoBusy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Repository busy: another evolution is running on {:?}", self.0)
+    }
+}
+
+impl std::error::Error for RepoBusy {}
+
+/// Lock shared by all handles on `path`
+pub fn repo_lock(path: &Path) -> RepoLock {
+    let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let registry = REGISTRY.get_or_init(|| Mutex::new(HashMap::new()));
+    registry.lock().unwrap()
+        .entry(key)
+        .or_default()
+        .clone()
+}
@@ -0,0 +1,137 @@
+//! Baselines for the mesh's per-message crypto and serialization work
+//!
+//! Run everything:
+//!
+//!     cargo bench -p nervous-system --bench hot_paths
+//!
+//! Run one group (criterion filters by name):
+//!
+//!     cargo bench -p nervous-system --bench hot_paths -- aes_gcm
+//!
+//! CI-friendly quick mode: `IPPOC_BENCH_QUICK=1` cuts samples and measuring
+//! time so the suite finishes in well under a minute. The numbers are noisier
+//! and only good for catching large regressions. Compare against a saved
+//! baseline with `-- --save-baseline main` and `-- --baseline main`.
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nervous_system::{
+    decrypt_message, encrypt_message, verify_signature, AiMessage, CipherSuite, EmbeddingPolicy, NodeSecrets, Thought,
+};
+
+/// Direct-message payloads: a chat line, a tool result, a synced memory with its embedding
+const PAYLOAD_SIZES: [usize; 3] = [256, 4 * 1024, 64 * 1024];
+/// Stand-in for the sender/recipient binding of a direct message
+const AAD: &[u8] = b"ippoc-direct-v1|sender|recipient";
+/// Signatures per batch in the batch-verify baseline
+const BATCH: usize = 64;
+
+fn config() -> Criterion {
+    let criterion = Criterion::default();
+    if std::env::var_os("IPPOC_BENCH_QUICK").is_some() {
+        criterion
+            .sample_size(10)
+            .warm_up_time(Duration::from_millis(200))
+            .measurement_time(Duration::from_secs(1))
+    } else {
+        criterion
+    }
+}
+
+fn bench_x25519(c: &mut Criterion) {
+    let ours = NodeSecrets::generate();
+    let theirs = NodeSecrets::generate().identity("peer", "tool");
+    c.bench_function("x25519_derive_shared", |b| {
+        b.iter(|| ours.derive_shared(black_box(&theirs.exchange_public)))
+    });
+}
+
+fn bench_aead(c: &mut Criterion) {
+    let ours = NodeSecrets::generate();
+    let shared = ours.derive_shared(&NodeSecrets::generate().identity("peer", "tool").exchange_public);
+
+    for (name, suite) in [("aes_gcm", CipherSuite::Aes256Gcm), ("chacha20_poly1305", CipherSuite::ChaCha20Poly1305)] {
+        let mut group = c.benchmark_group(name);
+        for size in PAYLOAD_SIZES {
+            let plaintext = vec![0x5au8; size];
+            let sealed = encrypt_message(&shared, suite, &plaintext, AAD).unwrap();
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new("encrypt", size), &plaintext, |b, p| {
+                b.iter(|| encrypt_message(&shared, suite, black_box(p), AAD).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new("decrypt", size), &sealed, |b, s| {
+                b.iter(|| decrypt_message(&shared, black_box(s), AAD).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+fn bench_ed25519(c: &mut Criterion) {
+    let secrets = NodeSecrets::generate();
+    let public = secrets.identity("bench", "tool").signing_public;
+    let message = vec![0xa5u8; 1024];
+    let signature = secrets.sign(&message);
+
+    let mut group = c.benchmark_group("ed25519");
+    group.bench_function("sign", |b| b.iter(|| secrets.sign(black_box(&message))));
+    group.bench_function("verify", |b| {
+        b.iter(|| verify_signature(&public, black_box(&message), &signature).unwrap())
+    });
+
+    // One signer per message, as when draining a busy inbox. Verified one by
+    // one today; this is the baseline a batch verifier has to beat.
+    let signed: Vec<([u8; 32], Vec<u8>, [u8; 64])> = (0..BATCH)
+        .map(|i| {
+            let signer = NodeSecrets::generate();
+            let msg = format!("message {}", i).into_bytes();
+            let sig = signer.sign(&msg);
+            (signer.identity("bench", "tool").signing_public, msg, sig)
+        })
+        .collect();
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function(BenchmarkId::new("verify_batch", BATCH), |b| {
+        b.iter(|| {
+            signed.iter().all(|(key, msg, sig)| verify_signature(key, black_box(msg), sig).unwrap())
+        })
+    });
+    group.finish();
+}
+
+fn sample_message() -> AiMessage {
+    let thought = Thought {
+        content: serde_json::json!({ "summary": "observed a pattern in the build logs", "source": "bench" }),
+        embedding: Some(vec![0.25; 1536]),
+        confidence: 0.9,
+        context: Some("benchmarks".into()),
+        tags: vec!["bench".into()],
+    };
+    AiMessage::thought("bench-node", &thought.for_transport(EmbeddingPolicy::Keep), 1)
+}
+
+fn bench_serialization(c: &mut Criterion) {
+    let msg = sample_message();
+    let bincode_bytes = msg.to_bytes();
+    let json_bytes = serde_json::to_vec(&msg).unwrap();
+
+    let mut group = c.benchmark_group("ai_message");
+    group.bench_function("bincode_round_trip", |b| {
+        b.iter(|| AiMessage::from_bytes(&black_box(&msg).to_bytes()).unwrap())
+    });
+    group.bench_function("json_round_trip", |b| {
+        b.iter(|| serde_json::from_slice::<AiMessage>(&serde_json::to_vec(black_box(&msg)).unwrap()).unwrap())
+    });
+    group.bench_function("bincode_decode", |b| b.iter(|| AiMessage::from_bytes(black_box(&bincode_bytes)).unwrap()));
+    group.bench_function("json_decode", |b| {
+        b.iter(|| serde_json::from_slice::<AiMessage>(black_box(&json_bytes)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = hot_paths;
+    config = config();
+    targets = bench_x25519, bench_aead, bench_ed25519, bench_serialization
+}
+criterion_main!(hot_paths);
@@ -0,0 +1,16 @@
+//! Raw QUIC frames as `transport` hands them to the mesh: version byte + bincode
+//!
+//!     cargo +nightly fuzz run ai_message_from_bytes
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nervous_system::AiMessage;
+
+fuzz_target!(|data: &[u8]| {
+    // Anything is allowed to fail; nothing is allowed to panic
+    if let Ok(msg) = AiMessage::from_bytes(data) {
+        // Whatever decodes must survive a re-encode
+        let _ = AiMessage::from_bytes(&msg.to_bytes()).expect("re-encoded message must decode");
+    }
+});
@@ -0,0 +1,14 @@
+//! AiMessage as JSON, the form used by the node API and persisted logs
+//!
+//!     cargo +nightly fuzz run ai_message_json
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nervous_system::AiMessage;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(msg) = serde_json::from_slice::<AiMessage>(data) {
+        let _ = msg.to_bytes();
+    }
+});
@@ -0,0 +1,12 @@
+//! Peer identities arriving in handshakes and the trusted-peers file
+//!
+//!     cargo +nightly fuzz run node_identity_json
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nervous_system::NodeIdentity;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<NodeIdentity>(data);
+});
@@ -0,0 +1,126 @@
+//! Security audit trail
+//!
+//! Security-relevant decisions (rejected signatures, replays, trust changes,
+//! denied permissions, spends against a frozen wallet) are appended as JSON
+//! lines to `<node_root>/audit.log`, apart from the operational logs. The
+//! file is only ever appended to; rotating or shipping it is left to the
+//! operator.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{error, warn};
+
+use crate::clock::SharedClock;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    SignatureRejected,
+    ReplayDetected,
+    TrustDemoted,
+    Blacklisted,
+    PermissionDenied,
+    FrozenSpend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub event: AuditEvent,
+    /// Node id the event concerns; None for our own actions
+    pub peer: Option<String>,
+    pub detail: String,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    clock: SharedClock,
+    /// Serializes appends so concurrent entries don't interleave
+    write: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(node_root: &Path, clock: SharedClock) -> Self {
+        Self { path: node_root.join("audit.log"), clock, write: Mutex::new(()) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry. A failed write is logged, never propagated: the
+    /// decision being audited has already been made.
+    pub fn record(&self, event: AuditEvent, peer: Option<&str>, detail: impl Into<String>) {
+        let entry = AuditEntry {
+            at: self.clock.now(),
+            event,
+            peer: peer.map(str::to_string),
+            detail: detail.into(),
+        };
+        if let Err(e) = self.append(&entry) {
+            error!("Audit log {:?} not written ({:?} {:?}): {}", self.path, entry.event, entry.peer, e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let _guard = self.write.lock().unwrap();
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Entries at or after `since` (unix seconds; all of them when None), oldest first
+    pub fn since(&self, since: Option<i64>) -> Result<Vec<AuditEntry>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            match serde_json::from_str::<AuditEntry>(&line) {
+                Ok(entry) if since.is_none_or(|t| entry.at.timestamp() >= t) => entries.push(entry),
+                Ok(_) => {}
+                // A torn last line after a crash shouldn't hide the rest
+                Err(e) => warn!("Skipping unreadable audit line in {:?}: {}", self.path, e),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+
+    #[test]
+    fn test_since_filters_by_time() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("audit_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root)?;
+        let clock = MockClock::starting_now();
+        let audit = AuditLog::new(&root, clock.clone());
+
+        audit.record(AuditEvent::ReplayDetected, Some("peer-a"), "nonce reused");
+        clock.advance(chrono::Duration::seconds(60));
+        let cutoff = clock.now().timestamp();
+        audit.record(AuditEvent::PermissionDenied, None, "Probation nodes cannot evolve");
+
+        assert_eq!(audit.since(None)?.len(), 2);
+        let recent = audit.since(Some(cutoff))?;
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].event, AuditEvent::PermissionDenied);
+        assert_eq!(recent[0].peer, None);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
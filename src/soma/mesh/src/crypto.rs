@@ -1,15 +1,30 @@
 //! Cryptographic primitives for AI node communication
-//! Based on BitChat's security model: X25519 + AES-256-GCM + Ed25519
+//! Based on BitChat's security model: X25519 + AES-256-GCM (or
+//! ChaCha20-Poly1305) + Ed25519
 
 use aes_gcm::{Aes256Gcm, Key, Nonce};
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
 use anyhow::{Result, anyhow};
+use argon2::{Algorithm, Argon2, Params, Version};
 use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
 use hkdf::Hkdf;
 use rand::rngs::OsRng;
 use sha2::{Sha256, Digest};
 use x25519_dalek::{PublicKey, StaticSecret};
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+/// HKDF labels separating the keys each subsystem derives from one DH output
+pub const LEGACY_CONTEXT: &str = "ippoc-ai-mesh-v1";
+pub const DIRECT_CONTEXT: &str = "ippoc-ai-mesh-v1/direct";
+pub const GOSSIP_CONTEXT: &str = "ippoc-ai-mesh-v1/gossip";
+
+/// Header of an exported secrets file
+const EXPORT_MAGIC: &[u8; 7] = b"IPPOCSK";
+const EXPORT_VERSION: u8 = 1;
+const EXPORT_SALT_LEN: usize = 16;
+const EXPORT_NONCE_LEN: usize = 12;
 
 /// Unique identity for an AI node
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,7 +43,12 @@ pub struct NodeIdentity {
     pub name: String,
 }
 
-/// Node's secret keys (never shared)
+/// Node ID for a signing key: hex SHA256 of the Ed25519 public key
+pub fn node_id(signing_public: &[u8; 32]) -> String {
+    hex::encode(Sha256::digest(signing_public))
+}
+
+/// Node's secret keys (never shared). Both keys wipe themselves on drop.
 pub struct NodeSecrets {
     /// Static secret for key exchange
     exchange_secret: StaticSecret,
@@ -49,7 +69,7 @@ impl NodeSecrets {
     pub fn identity(&self, name: &str, role: &str) -> NodeIdentity {
         let exchange_public = PublicKey::from(&self.exchange_secret);
         let signing_public = self.signing_key.verifying_key();
-        let id = hex::encode(sha2::Sha256::digest(signing_public.as_bytes()));
+        let id = node_id(signing_public.as_bytes());
         
         NodeIdentity {
             id,
@@ -60,20 +80,36 @@ impl NodeSecrets {
         }
     }
 
-    /// Derive shared secret with peer
+    /// Derive shared secret with peer under the pre-separation label
     pub fn derive_shared(&self, peer_public: &[u8; 32]) -> SharedSecret {
+        self.derive_shared_for(peer_public, LEGACY_CONTEXT)
+    }
+
+    /// Derive the shared secret with peer for one subsystem; other contexts
+    /// get unrelated keys from the same DH output
+    pub fn derive_shared_for(&self, peer_public: &[u8; 32], context: &str) -> SharedSecret {
         let peer_key = PublicKey::from(*peer_public);
         let raw_shared = self.exchange_secret.diffie_hellman(&peer_key);
         
         // Derive encryption key using HKDF
         let hk = Hkdf::<Sha256>::new(None, raw_shared.as_bytes());
         let mut encryption_key = [0u8; 32];
-        hk.expand(b"ippoc-ai-mesh-v1", &mut encryption_key)
+        hk.expand(context.as_bytes(), &mut encryption_key)
             .expect("HKDF expand failed");
         
         SharedSecret { key: encryption_key }
     }
 
+    /// Derive a symmetric key for protecting local state; never sent to peers
+    pub fn derive_local_key(&self, purpose: &[u8]) -> [u8; 32] {
+        let seed = Zeroizing::new(self.signing_key.to_bytes());
+        let hk = Hkdf::<Sha256>::new(Some(b"ippoc-local-state"), seed.as_slice());
+        let mut key = [0u8; 32];
+        hk.expand(purpose, &mut key)
+            .expect("HKDF expand failed");
+        key
+    }
+
     /// Sign a message
     pub fn sign(&self, message: &[u8]) -> [u8; 64] {
         self.signing_key.sign(message).to_bytes()
@@ -81,11 +117,11 @@ impl NodeSecrets {
 
     /// Serialize secrets to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut bytes = Vec::with_capacity(64);
         // Exchange secret (32 bytes)
-        bytes.extend_from_slice(&self.exchange_secret.to_bytes());
+        bytes.extend_from_slice(Zeroizing::new(self.exchange_secret.to_bytes()).as_slice());
         // Signing key bytes (32 bytes seed)
-        bytes.extend_from_slice(&self.signing_key.to_bytes());
+        bytes.extend_from_slice(Zeroizing::new(self.signing_key.to_bytes()).as_slice());
         bytes
     }
 
@@ -95,22 +131,110 @@ impl NodeSecrets {
             return Err(anyhow!("Invalid secrets length: expected 64, got {}", bytes.len()));
         }
         
-        let exchange_bytes: [u8; 32] = bytes[0..32].try_into()?;
-        let signing_bytes: [u8; 32] = bytes[32..64].try_into()?;
+        let exchange_bytes: Zeroizing<[u8; 32]> = Zeroizing::new(bytes[0..32].try_into()?);
+        let signing_bytes: Zeroizing<[u8; 32]> = Zeroizing::new(bytes[32..64].try_into()?);
         
         Ok(Self {
-            exchange_secret: StaticSecret::from(exchange_bytes),
+            exchange_secret: StaticSecret::from(*exchange_bytes),
             signing_key: SigningKey::from_bytes(&signing_bytes),
         })
     }
+
+    /// Secrets sealed under `passphrase` for backup or moving to another
+    /// machine: magic, version, salt, nonce, then the AES-256-GCM ciphertext
+    /// of `to_bytes` under an Argon2id key
+    pub fn export_encrypted(&self, passphrase: &str) -> Result<Vec<u8>> {
+        let mut salt = [0u8; EXPORT_SALT_LEN];
+        getrandom::getrandom(&mut salt)?;
+        let mut nonce_bytes = [0u8; EXPORT_NONCE_LEN];
+        getrandom::getrandom(&mut nonce_bytes)?;
+
+        let key = passphrase_key(passphrase, &salt)?;
+        let plaintext = Zeroizing::new(self.to_bytes());
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+            .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_slice())
+            .map_err(|e| anyhow!("Encryption failed: {e}"))?;
+
+        let mut sealed = Vec::with_capacity(EXPORT_MAGIC.len() + 1 + EXPORT_SALT_LEN + EXPORT_NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(EXPORT_MAGIC);
+        sealed.push(EXPORT_VERSION);
+        sealed.extend_from_slice(&salt);
+        sealed.extend_from_slice(&nonce_bytes);
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    /// Secrets from `export_encrypted` output
+    pub fn import_encrypted(bytes: &[u8], passphrase: &str) -> Result<Self> {
+        let header = EXPORT_MAGIC.len() + 1;
+        if bytes.len() < header + EXPORT_SALT_LEN + EXPORT_NONCE_LEN || &bytes[..EXPORT_MAGIC.len()] != EXPORT_MAGIC {
+            return Err(anyhow!("Not an exported IPPOC secrets file"));
+        }
+        let version = bytes[EXPORT_MAGIC.len()];
+        if version != EXPORT_VERSION {
+            return Err(anyhow!("Unsupported secrets export version {version}"));
+        }
+        let (salt, rest) = bytes[header..].split_at(EXPORT_SALT_LEN);
+        let (nonce_bytes, ciphertext) = rest.split_at(EXPORT_NONCE_LEN);
+
+        let key = passphrase_key(passphrase, salt)?;
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+                .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+                .map_err(|_| anyhow!("Wrong passphrase or corrupted secrets export"))?,
+        );
+        Self::from_bytes(&plaintext)
+    }
+}
+
+/// AES key stretched from a passphrase with Argon2id
+fn passphrase_key(passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+        .map_err(|e| anyhow!("Key derivation failed: {e}"))?;
+    Ok(key)
+}
+
+/// Our half of a session re-key: a fresh X25519 key used for one exchange
+#[derive(Clone)]
+pub struct EphemeralExchange {
+    secret: StaticSecret,
+    /// Sent to the peer
+    pub public: [u8; 32],
+}
+
+impl EphemeralExchange {
+    pub fn generate() -> Self {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret).to_bytes();
+        Self { secret, public }
+    }
+
+    /// Next session key: the fresh DH output salted with the current key, so
+    /// only the peer holding the current session can follow the rotation
+    pub fn rekey(&self, current: &SharedSecret, peer_public: &[u8; 32]) -> SharedSecret {
+        let fresh = self.secret.diffie_hellman(&PublicKey::from(*peer_public));
+        let hk = Hkdf::<Sha256>::new(Some(current.as_bytes()), fresh.as_bytes());
+        let mut key = [0u8; 32];
+        hk.expand(b"ippoc-ai-mesh-rekey-v1", &mut key)
+            .expect("HKDF expand failed");
+        SharedSecret { key }
+    }
 }
 
-/// Shared secret for encrypted communication
+/// Shared secret for encrypted communication; wiped on drop
 #[derive(Clone)]
 pub struct SharedSecret {
     key: [u8; 32],
 }
 
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
 impl SharedSecret {
     /// Get key bytes
     pub fn as_bytes(&self) -> &[u8; 32] {
@@ -118,40 +242,93 @@ impl SharedSecret {
     }
 }
 
-/// Encrypt a message using AES-256-GCM
-pub fn encrypt_message(shared: &SharedSecret, plaintext: &[u8]) -> Result<Vec<u8>> {
-    let key = Key::<Aes256Gcm>::from_slice(&shared.key);
-    let cipher = Aes256Gcm::new(key);
-    
+/// AEAD used to seal a message. Both take a 32-byte key and a 96-bit nonce;
+/// ChaCha20-Poly1305 is the faster choice on CPUs without AES instructions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CipherSuite {
+    #[default]
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// First byte of every sealed message
+    pub fn tag(self) -> u8 {
+        match self {
+            Self::Aes256Gcm => 1,
+            Self::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            1 => Ok(Self::Aes256Gcm),
+            2 => Ok(Self::ChaCha20Poly1305),
+            other => Err(anyhow!("Unknown cipher suite tag {other}")),
+        }
+    }
+}
+
+impl std::str::FromStr for CipherSuite {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "aes" | "aes256gcm" | "aes_256_gcm" => Ok(Self::Aes256Gcm),
+            "chacha" | "chacha20poly1305" | "chacha20_poly1305" => Ok(Self::ChaCha20Poly1305),
+            _ => Err(anyhow!("Unknown cipher suite '{s}' (expected aes-256-gcm or chacha20-poly1305)")),
+        }
+    }
+}
+
+/// Associated data for a direct message: binds the ciphertext to who sent it
+/// to whom, so it can't be replayed in another conversation
+pub fn direct_aad(sender: &str, recipient: &str) -> Vec<u8> {
+    format!("ippoc-direct-v1|{sender}|{recipient}").into_bytes()
+}
+
+/// Encrypt a message with `suite`: suite tag, then nonce, then ciphertext.
+/// `aad` is authenticated but not sent; decryption must supply the same bytes.
+pub fn encrypt_message(shared: &SharedSecret, suite: CipherSuite, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
     // Generate random nonce
     let mut nonce_bytes = [0u8; 12];
     getrandom::getrandom(&mut nonce_bytes)?;
     let nonce = Nonce::from_slice(&nonce_bytes);
     
-    let ciphertext = cipher.encrypt(nonce, plaintext)
-        .map_err(|e| anyhow!("Encryption failed: {e}"))?;
+    let payload = Payload { msg: plaintext, aad };
+    let ciphertext = match suite {
+        CipherSuite::Aes256Gcm => Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&shared.key)).encrypt(nonce, payload),
+        CipherSuite::ChaCha20Poly1305 => ChaCha20Poly1305::new(Key::<ChaCha20Poly1305>::from_slice(&shared.key)).encrypt(nonce, payload),
+    }
+    .map_err(|e| anyhow!("Encryption failed: {e}"))?;
     
-    // Prepend nonce to ciphertext
-    let mut result = nonce_bytes.to_vec();
+    // Prepend suite tag and nonce to ciphertext
+    let mut result = Vec::with_capacity(1 + nonce_bytes.len() + ciphertext.len());
+    result.push(suite.tag());
+    result.extend_from_slice(&nonce_bytes);
     result.extend(ciphertext);
     
     Ok(result)
 }
 
-/// Decrypt a message using AES-256-GCM
-pub fn decrypt_message(shared: &SharedSecret, encrypted: &[u8]) -> Result<Vec<u8>> {
-    if encrypted.len() < 12 {
+/// Decrypt a message with whichever suite its tag names; fails unless `aad`
+/// matches what it was sealed with
+pub fn decrypt_message(shared: &SharedSecret, encrypted: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if encrypted.len() < 1 + 12 {
         return Err(anyhow!("Invalid encrypted message: too short"));
     }
     
-    let key = Key::<Aes256Gcm>::from_slice(&shared.key);
-    let cipher = Aes256Gcm::new(key);
-    
-    let (nonce_bytes, ciphertext) = encrypted.split_at(12);
+    let suite = CipherSuite::from_tag(encrypted[0])?;
+    let (nonce_bytes, ciphertext) = encrypted[1..].split_at(12);
     let nonce = Nonce::from_slice(nonce_bytes);
     
-    let plaintext = cipher.decrypt(nonce, ciphertext)
-        .map_err(|e| anyhow!("Decryption failed: {e}"))?;
+    let payload = Payload { msg: ciphertext, aad };
+    let plaintext = match suite {
+        CipherSuite::Aes256Gcm => Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&shared.key)).decrypt(nonce, payload),
+        CipherSuite::ChaCha20Poly1305 => ChaCha20Poly1305::new(Key::<ChaCha20Poly1305>::from_slice(&shared.key)).decrypt(nonce, payload),
+    }
+    .map_err(|e| anyhow!("Decryption failed: {e}"))?;
     
     Ok(plaintext)
 }
@@ -176,9 +353,10 @@ mod hex_bytes {
     pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u8; 32], D::Error> {
         let s = String::deserialize(d)?;
         let bytes = hex::decode(&s).map_err(serde::de::Error::custom)?;
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&bytes);
-        Ok(arr)
+        // Peer-supplied: a short or long key must be an error, not a panic
+        bytes.try_into().map_err(|b: Vec<u8>| {
+            serde::de::Error::custom(format!("expected 32-byte key, got {} bytes", b.len()))
+        })
     }
 }
 
@@ -202,9 +380,107 @@ mod tests {
         
         // Encrypt/decrypt
         let message = b"Hello from Alice!";
-        let encrypted = encrypt_message(&alice_shared, message).unwrap();
-        let decrypted = decrypt_message(&bob_shared, &encrypted).unwrap();
+        let aad = direct_aad(&alice_identity.id, &bob_identity.id);
+        let encrypted = encrypt_message(&alice_shared, CipherSuite::default(), message, &aad).unwrap();
+        let decrypted = decrypt_message(&bob_shared, &encrypted, &aad).unwrap();
         
         assert_eq!(message.as_slice(), decrypted.as_slice());
     }
+
+    #[test]
+    fn test_contexts_derive_separate_keys() {
+        let alice = NodeSecrets::generate();
+        let bob = NodeSecrets::generate();
+        let bob_public = bob.identity("bob", "retrieval").exchange_public;
+        let alice_public = alice.identity("alice", "reasoning").exchange_public;
+
+        let direct = alice.derive_shared_for(&bob_public, DIRECT_CONTEXT);
+        let gossip = alice.derive_shared_for(&bob_public, GOSSIP_CONTEXT);
+        assert_ne!(direct.as_bytes(), gossip.as_bytes());
+        assert_ne!(direct.as_bytes(), alice.derive_shared(&bob_public).as_bytes());
+        assert_eq!(direct.as_bytes(), bob.derive_shared_for(&alice_public, DIRECT_CONTEXT).as_bytes());
+
+        // A direct message doesn't open under the gossip key
+        let sealed = encrypt_message(&direct, CipherSuite::default(), b"hello", b"").unwrap();
+        assert!(decrypt_message(&gossip, &sealed, b"").is_err());
+    }
+
+    #[test]
+    fn test_decrypt_picks_the_suite_from_the_tag() {
+        let alice = NodeSecrets::generate();
+        let bob = NodeSecrets::generate();
+        let alice_shared = alice.derive_shared(&bob.identity("bob", "retrieval").exchange_public);
+        let bob_shared = bob.derive_shared(&alice.identity("alice", "reasoning").exchange_public);
+
+        for suite in [CipherSuite::Aes256Gcm, CipherSuite::ChaCha20Poly1305] {
+            let sealed = encrypt_message(&alice_shared, suite, b"kernel-bridge telemetry", b"").unwrap();
+            assert_eq!(sealed[0], suite.tag());
+            assert_eq!(decrypt_message(&bob_shared, &sealed, b"").unwrap(), b"kernel-bridge telemetry");
+
+            // The tag really selects the algorithm: relabelled, it no longer opens
+            let mut relabelled = sealed.clone();
+            let other = if suite == CipherSuite::Aes256Gcm { CipherSuite::ChaCha20Poly1305 } else { CipherSuite::Aes256Gcm };
+            relabelled[0] = other.tag();
+            assert!(decrypt_message(&bob_shared, &relabelled, b"").is_err());
+        }
+
+        let mut unknown = encrypt_message(&alice_shared, CipherSuite::Aes256Gcm, b"x", b"").unwrap();
+        unknown[0] = 0xff;
+        assert!(decrypt_message(&bob_shared, &unknown, b"").is_err());
+        assert_eq!("chacha20-poly1305".parse::<CipherSuite>().unwrap(), CipherSuite::ChaCha20Poly1305);
+    }
+
+    #[test]
+    fn test_ciphertext_is_bound_to_its_recipient() {
+        let alice = NodeSecrets::generate();
+        let bob = NodeSecrets::generate();
+        let (alice_id, bob_id) = (alice.identity("alice", "reasoning"), bob.identity("bob", "retrieval"));
+        let shared = alice.derive_shared(&bob_id.exchange_public);
+
+        for suite in [CipherSuite::Aes256Gcm, CipherSuite::ChaCha20Poly1305] {
+            let sealed = encrypt_message(&shared, suite, b"transfer 10 IPPC", &direct_aad(&alice_id.id, &bob_id.id)).unwrap();
+            assert!(decrypt_message(&shared, &sealed, &direct_aad(&alice_id.id, &bob_id.id)).is_ok());
+
+            // Same key, replayed as if addressed to someone else or sent the other way
+            assert!(decrypt_message(&shared, &sealed, &direct_aad(&alice_id.id, "carol")).is_err());
+            assert!(decrypt_message(&shared, &sealed, &direct_aad(&bob_id.id, &alice_id.id)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_export_import_keeps_the_node_id() {
+        let secrets = NodeSecrets::generate();
+        let before = secrets.identity("alice", "reasoning");
+
+        let sealed = secrets.export_encrypted("correct horse battery staple").unwrap();
+        assert!(sealed.starts_with(EXPORT_MAGIC));
+        assert!(!sealed.windows(64).any(|w| w == secrets.to_bytes().as_slice()));
+
+        let restored = NodeSecrets::import_encrypted(&sealed, "correct horse battery staple").unwrap();
+        let after = restored.identity("alice", "reasoning");
+        assert_eq!(before.id, after.id);
+        assert_eq!(before.exchange_public, after.exchange_public);
+    }
+
+    #[test]
+    fn test_import_rejects_wrong_passphrase_and_bad_header() {
+        let sealed = NodeSecrets::generate().export_encrypted("hunter2").unwrap();
+        assert!(NodeSecrets::import_encrypted(&sealed, "hunter3").is_err());
+
+        let mut future = sealed.clone();
+        future[EXPORT_MAGIC.len()] = EXPORT_VERSION + 1;
+        assert!(NodeSecrets::import_encrypted(&future, "hunter2").is_err());
+
+        let mut foreign = sealed;
+        foreign[0] = b'X';
+        assert!(NodeSecrets::import_encrypted(&foreign, "hunter2").is_err());
+        assert!(NodeSecrets::import_encrypted(b"IPPOCSK", "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_wrong_length_key_is_rejected() {
+        let mut json = serde_json::to_value(NodeSecrets::generate().identity("alice", "reasoning")).unwrap();
+        json["signing_public"] = serde_json::json!("abcd");
+        assert!(serde_json::from_value::<NodeIdentity>(json).is_err());
+    }
 }
@@ -3,11 +3,36 @@
 //! Implements PRD 14: Sovereign Swarm Spec (Metabolism)
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, anyhow};
 use chrono::Utc;
+use hmac::{Hmac, Mac};
 use sha2::{Sha256, Digest};
 
+use crate::clock::{self, SharedClock};
+use crate::crypto::NodeSecrets;
+use crate::governance::SlashSeverity;
+use crate::persist::atomic_write;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Economy events buffered per subscriber before the slowest start lagging
+pub const EVENT_BUFFER: usize = 256;
+
+/// IPPC decays by 2% once per period
+pub const DECAY_PERIOD_SECS: u64 = 86_400;
+
+/// A freeze voted by the mesh lapses after this long unless an operator confirms it
+pub const MESH_FREEZE_SECS: u64 = 86_400;
+
+/// On-disk format of wallet.json and ledger.json.
+/// v1: bare `Wallet` / `Vec<LedgerEntry>`, no header or MAC.
+/// v2: versioned envelopes; both files carry an HMAC and the wallet's binds it to the ledger head.
+pub const ECONOMY_FORMAT_VERSION: u32 = 2;
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
 /// 3-Layer Currency Model
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Balances {
@@ -19,6 +44,106 @@ pub struct Balances {
     pub eth_virtual: u128,
 }
 
+impl Balances {
+    /// `amount` in one layer, zero elsewhere
+    pub fn of(layer: CurrencyLayer, amount: u128) -> Self {
+        let mut balances = Self::default();
+        *balances.layer_mut(layer) = amount;
+        balances
+    }
+
+    pub fn layer(&self, layer: CurrencyLayer) -> u128 {
+        match layer {
+            CurrencyLayer::Ippc => self.ippc,
+            CurrencyLayer::Iusd => self.iusd,
+            CurrencyLayer::EthVirtual => self.eth_virtual,
+        }
+    }
+
+    fn layer_mut(&mut self, layer: CurrencyLayer) -> &mut u128 {
+        match layer {
+            CurrencyLayer::Ippc => &mut self.ippc,
+            CurrencyLayer::Iusd => &mut self.iusd,
+            CurrencyLayer::EthVirtual => &mut self.eth_virtual,
+        }
+    }
+}
+
+/// One of the three currency layers in `Balances`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CurrencyLayer {
+    Ippc,
+    Iusd,
+    EthVirtual,
+}
+
+impl CurrencyLayer {
+    pub const ALL: [CurrencyLayer; 3] = [CurrencyLayer::Ippc, CurrencyLayer::Iusd, CurrencyLayer::EthVirtual];
+}
+
+/// Exchange rate as an exact fraction: `amount * numerator / denominator`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Rate {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Rate {
+    pub fn new(numerator: u128, denominator: u128) -> Self {
+        Self { numerator, denominator }
+    }
+
+    pub fn inverse(self) -> Self {
+        Self::new(self.denominator, self.numerator)
+    }
+
+    /// Converted amount, or None if it would not come out whole (value would be lost)
+    pub fn apply(self, amount: u128) -> Option<u128> {
+        let scaled = amount.checked_mul(self.numerator)?;
+        (self.denominator != 0 && scaled % self.denominator == 0).then(|| scaled / self.denominator)
+    }
+}
+
+/// Source of conversion rates between layers
+pub trait RateOracle: Send + Sync {
+    fn rate(&self, from: CurrencyLayer, to: CurrencyLayer) -> Result<Rate>;
+}
+
+/// Stub oracle with fixed, configurable rates (no market feed yet)
+#[derive(Debug, Clone)]
+pub struct FixedRateOracle {
+    rates: HashMap<(CurrencyLayer, CurrencyLayer), Rate>,
+}
+
+impl FixedRateOracle {
+    pub fn empty() -> Self {
+        Self { rates: HashMap::new() }
+    }
+
+    /// Set `from -> to`; the inverse direction is set too
+    pub fn with_rate(mut self, from: CurrencyLayer, to: CurrencyLayer, rate: Rate) -> Self {
+        self.rates.insert((from, to), rate);
+        self.rates.insert((to, from), rate.inverse());
+        self
+    }
+}
+
+impl Default for FixedRateOracle {
+    /// 100 IPPC = 1 iUSD, 1000 iUSD = 1 ETH (virtual)
+    fn default() -> Self {
+        Self::empty()
+            .with_rate(CurrencyLayer::Ippc, CurrencyLayer::Iusd, Rate::new(1, 100))
+            .with_rate(CurrencyLayer::Iusd, CurrencyLayer::EthVirtual, Rate::new(1, 1000))
+    }
+}
+
+impl RateOracle for FixedRateOracle {
+    fn rate(&self, from: CurrencyLayer, to: CurrencyLayer) -> Result<Rate> {
+        self.rates.get(&(from, to))
+            .copied()
+            .ok_or_else(|| anyhow!("No rate for {:?} -> {:?}", from, to))
+    }
+}
 
 /// Types of economic actions
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +157,106 @@ pub enum ActionType {
     SystemGrant, // Genesis minting
     DecayBurn { amount: u128 }, // Entropy
     Vote { proposal_id: String, vote: bool },
+    /// Move value between layers; debits `amount` of `from`
+    Conversion { from: CurrencyLayer, to: CurrencyLayer, amount: u128, rate: Rate },
+    /// IPPC burned because a `SlashNode` proposal against us passed
+    Slash { proposal_id: String, amount: u128 },
+}
+
+impl ActionType {
+    /// Variant name, for grouping in reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ActionType::LlmInference { .. } => "LlmInference",
+            ActionType::ToolExecution { .. } => "ToolExecution",
+            ActionType::EvolutionSim { .. } => "EvolutionSim",
+            ActionType::BountyPayout { .. } => "BountyPayout",
+            ActionType::DaoFee => "DaoFee",
+            ActionType::Transfer { .. } => "Transfer",
+            ActionType::SystemGrant => "SystemGrant",
+            ActionType::DecayBurn { .. } => "DecayBurn",
+            ActionType::Vote { .. } => "Vote",
+            ActionType::Conversion { .. } => "Conversion",
+            ActionType::Slash { .. } => "Slash",
+        }
+    }
+
+    /// Finer grouping than `kind`: which model, tool or target drove the cost
+    pub fn driver(&self) -> String {
+        match self {
+            ActionType::LlmInference { model, .. } => format!("LlmInference:{}", model),
+            ActionType::ToolExecution { tool } => format!("ToolExecution:{}", tool),
+            ActionType::BountyPayout { target } | ActionType::Transfer { target } => {
+                format!("{}:{}", self.kind(), target)
+            }
+            _ => self.kind().to_string(),
+        }
+    }
+}
+
+/// Cost drivers listed in an `EconomyReport`
+pub const TOP_COST_DRIVERS: usize = 5;
+
+/// Totals for one kind of action in a report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActionTotals {
+    pub kind: String,
+    pub count: u64,
+    pub debit: Balances,
+    pub credit: Balances,
+}
+
+/// IPPC spent on one driver (see `ActionType::driver`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CostDriver {
+    pub driver: String,
+    pub ippc: u128,
+}
+
+/// Income vs. expenditure over `[since, until)`, successful entries only
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EconomyReport {
+    pub since: u64,
+    pub until: u64,
+    pub entries: u64,
+    pub debit: Balances,
+    pub credit: Balances,
+    /// IPPC credited minus IPPC debited
+    pub net_ippc: i128,
+    /// Most IPPC spent first
+    pub by_action: Vec<ActionTotals>,
+    /// Up to `TOP_COST_DRIVERS`, most IPPC spent first
+    pub top_costs: Vec<CostDriver>,
+}
+
+/// Published for every ledger entry. Carries only what `/v1/economy/balance`
+/// already exposes plus the action kind: no tool names, targets or tx ids.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EconomyEvent {
+    pub seq_no: u64,
+    pub timestamp: u64,
+    /// `ActionType::kind`
+    pub action: String,
+    pub outcome: Outcome,
+    pub debit: Balances,
+    pub credit: Balances,
+    /// Wallet after the entry
+    pub balances: Balances,
+}
+
+/// Result of `EconomyController::simulate_action`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SimulatedEntry {
+    pub action: ActionType,
+    pub outcome: Outcome,
+    pub debit: Balances,
+    pub credit: Balances,
+    pub balances_before: Balances,
+    pub balances_after: Balances,
+    /// IPPC the next decay tick would burn; not included in `balances_after`
+    pub pending_decay: u128,
+    /// Whether `record_action` would accept it
+    pub affordable: bool,
 }
 
 /// DAO Governance Proposal Types (Layer 4)
@@ -39,7 +264,12 @@ pub enum ActionType {
 pub enum ProposalType {
     AdjustCosts { action: String, new_cost: u128 },
     FundNode { node_id: String, amount: u128 },
-    SlashNode { node_id: String, reason: String },
+    SlashNode {
+        node_id: String,
+        reason: String,
+        #[serde(default)]
+        severity: SlashSeverity,
+    },
     ApproveEvolution { commit_hash: String },
     EmergencyFreeze,
 }
@@ -79,6 +309,38 @@ pub struct LedgerEntry {
     pub prev_hash: String,
 }
 
+/// wallet.json (v2+)
+#[derive(Serialize, Deserialize)]
+struct WalletFile {
+    version: u32,
+    wallet: Wallet,
+    /// tx_id of the last ledger entry the wallet accounts for
+    ledger_head: String,
+    /// HMAC-SHA256 (hex) over the version, the wallet and `ledger_head`
+    mac: String,
+}
+
+/// ledger.json (v2+)
+#[derive(Serialize, Deserialize)]
+struct LedgerFile {
+    version: u32,
+    /// Balance carried over from a v1 wallet, whose grants were never credited
+    #[serde(default)]
+    opening: Balances,
+    entries: Vec<LedgerEntry>,
+    /// HMAC-SHA256 (hex) over the version, `opening` and `entries`
+    mac: String,
+}
+
+/// format.json: the format this node has written, so older files put back
+/// in place of newer ones are refused rather than migrated again
+#[derive(Serialize, Deserialize)]
+struct FormatMarker {
+    version: u32,
+    /// HMAC-SHA256 (hex) over the version
+    mac: String,
+}
+
 /// The Metabolic Controller
 pub struct EconomyController {
     /// Persist Path
@@ -87,8 +349,18 @@ pub struct EconomyController {
     pub wallet: Wallet,
     /// Append-only log
     ledger: Vec<LedgerEntry>,
+    /// Balance the ledger replay starts from
+    opening: Balances,
     /// Current Policy Version
     policy_version: String,
+    /// Time source for ledger timestamps and decay
+    clock: SharedClock,
+    /// Node-derived key for the wallet MAC
+    wallet_key: [u8; 32],
+    /// Node-derived key for the ledger MAC
+    ledger_key: [u8; 32],
+    /// Ledger entries as they are recorded
+    events: broadcast::Sender<EconomyEvent>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,46 +368,124 @@ pub struct Wallet {
     pub node_id: String,
     pub balances: Balances,
     pub reputation: f32,
+    /// Emergency freeze: no spending, granting or converting until unfrozen
     pub locked: bool,
+    /// Set while a mesh-voted freeze awaits operator confirmation; the freeze
+    /// lapses at this time
+    #[serde(default)]
+    pub frozen_until: Option<u64>,
     pub last_updated: u64,
+    /// When decay was last charged (None until the first decay tick)
+    #[serde(default)]
+    pub last_decay: Option<u64>,
 }
 
 impl EconomyController {
-    /// Initialize the Economy for this Node
-    pub fn new(node_id: &str, node_root: &std::path::Path) -> Result<Self> {
+    /// Initialize the Economy for this Node.
+    /// Fails if either file's MAC is invalid, the hash chain is broken or the
+    /// ledger cannot account for the wallet.
+    pub fn new(node_id: &str, node_root: &std::path::Path, secrets: &NodeSecrets) -> Result<Self> {
         let economy_dir = node_root.join("economy");
         std::fs::create_dir_all(&economy_dir)?;
         
         let ledger_path = economy_dir.join("ledger.json");
         let wallet_path = economy_dir.join("wallet.json");
+        let wallet_key = secrets.derive_local_key(b"ippoc-economy-wallet");
+        let ledger_key = secrets.derive_local_key(b"ippoc-economy-ledger");
+
+        let (ledger_version, mut opening, ledger) = if ledger_path.exists() {
+            load_ledger(&std::fs::read_to_string(&ledger_path)?, &ledger_key)?
+        } else {
+            (ECONOMY_FORMAT_VERSION, Balances::default(), Vec::new())
+        };
+
+        // v1 files are unsigned; once v2 has been written they are a downgrade
+        let format_path = economy_dir.join("format.json");
+        if format_path.exists() {
+            let written = load_format(&std::fs::read_to_string(&format_path)?, &ledger_key)?;
+            if ledger_version < written {
+                return Err(anyhow!(
+                    "Ledger is v{} but this node has already written v{}; refusing to downgrade",
+                    ledger_version, written
+                ));
+            }
+        }
 
-        let wallet = if wallet_path.exists() {
+        let (mut wallet, recorded_head) = if wallet_path.exists() {
             let data = std::fs::read_to_string(&wallet_path)?;
-            serde_json::from_str(&data)?
+            load_wallet(&data, ledger_version, &wallet_key)?
         } else {
             // Genesis Wallet (Empty)
-            Wallet {
+            (Wallet {
                 node_id: node_id.to_string(),
                 balances: Balances::default(),
                 reputation: 10.0, // Base rep
                 locked: false,
+                frozen_until: None,
                 last_updated: Utc::now().timestamp() as u64,
-            }
+                last_decay: None,
+            }, None)
         };
 
-        let ledger = if ledger_path.exists() {
-            let data = std::fs::read_to_string(&ledger_path)?;
-            serde_json::from_str(&data)?
-        } else {
-            Vec::new()
-        };
+        // v1 ledgers never credited grants; carry the difference as an opening balance
+        if ledger_version == 1 {
+            opening = balance_difference(&wallet.balances, &ledger_balance(&Balances::default(), &ledger));
+        }
+
+        // Reconcile: the ledger is the source of truth for the balance
+        let expected = ledger_balance(&opening, &ledger);
+        let head = ledger_head(&ledger);
+        let mut reconciled = false;
+        if let Some(recorded) = recorded_head.as_deref().filter(|h| *h != head) {
+            // save() writes the ledger first, so a crash between the two writes
+            // leaves the wallet exactly one entry behind. Anything else means a
+            // file was swapped or rolled back.
+            let behind = if recorded == GENESIS_HASH {
+                Some(ledger.len())
+            } else {
+                ledger.iter().position(|e| e.tx_id == recorded).map(|i| ledger.len() - 1 - i)
+            };
+            if behind != Some(1) {
+                return Err(anyhow!(
+                    "Wallet was saved at ledger head {} which a lost write cannot explain (ledger head {}); refusing to start",
+                    recorded, head
+                ));
+            }
+            warn!(
+                "Wallet balance {:?} (ledger head {}) is one entry behind ledger replay {:?} (head {}); correcting wallet",
+                wallet.balances, recorded, expected, head
+            );
+            wallet.balances = expected;
+            reconciled = true;
+        } else if wallet.balances != expected {
+            // Both files agree on the last entry, so one of them was edited
+            return Err(anyhow!(
+                "Ledger at {} replays to {:?} but the wallet holds {:?}; refusing to start",
+                head, expected, wallet.balances
+            ));
+        }
 
-        Ok(Self {
+        let controller = Self {
             db_path: economy_dir,
             wallet,
             ledger,
+            opening,
             policy_version: "1.0.0".to_string(),
-        })
+            clock: clock::system(),
+            wallet_key,
+            ledger_key,
+            events: broadcast::channel(EVENT_BUFFER).0,
+        };
+        controller.verify()?;
+        if reconciled {
+            controller.save()?;
+        }
+        Ok(controller)
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
     }
 
     /// Check if we can afford an action
@@ -173,103 +523,1009 @@ impl EconomyController {
                 iusd: 0,
                 eth_virtual: 0,
             },
+            ActionType::Conversion { from, amount, .. } => Balances::of(*from, *amount),
+            ActionType::Slash { amount, .. } => Balances::of(CurrencyLayer::Ippc, *amount),
             _ => Balances::default(),
         }
     }
 
     /// Apply Entropy (Decay) to IPPC to prevent hoarding
     /// Humans get tired. Nodes leak energy.
+    ///
+    /// Burns 2% for every full `DECAY_PERIOD_SECS` since the last charge, so
+    /// calling it more often doesn't decay faster.
     pub fn apply_decay(&mut self) -> Result<()> {
-        let current_ippc = self.wallet.balances.ippc;
-        if current_ippc > 100 {
-            let decay_amount = current_ippc / 50; // 2% decay
-            if decay_amount > 0 {
-                let node_id = self.wallet.node_id.clone();
-                self.record_action(
-                    &node_id,
-                    ActionType::DecayBurn { amount: decay_amount },
-                    Outcome::Success
-                )?;
+        let now = self.clock.unix_secs();
+        let Some(last) = self.wallet.last_decay else {
+            // First tick only starts the decay clock
+            self.wallet.last_decay = Some(now);
+            return self.save();
+        };
+
+        let periods = now.saturating_sub(last) / DECAY_PERIOD_SECS;
+        if periods == 0 {
+            return Ok(());
+        }
+        self.wallet.last_decay = Some(last + periods * DECAY_PERIOD_SECS);
+
+        let node_id = self.wallet.node_id.clone();
+        for decay_amount in decay_burns(self.wallet.balances.ippc, periods) {
+            self.record_action(
+                &node_id,
+                ActionType::DecayBurn { amount: decay_amount },
+                Outcome::Success
+            )?;
+        }
+        self.save()
+    }
+
+    /// IPPC the next `apply_decay` would burn
+    pub fn pending_decay(&self) -> u128 {
+        let Some(last) = self.wallet.last_decay else { return 0 };
+        let periods = self.clock.unix_secs().saturating_sub(last) / DECAY_PERIOD_SECS;
+        decay_burns(self.wallet.balances.ippc, periods).iter().sum()
+    }
+
+    /// What `record_action` would do, without touching the ledger or wallet
+    pub fn simulate_action(&self, actor: &str, action: ActionType, outcome: Outcome) -> SimulatedEntry {
+        let before = self.wallet.balances.clone();
+        let (debit, credit, after, affordable) = match self.prepare_entry(actor, action.clone(), outcome.clone(), Balances::default()) {
+            Ok(entry) => {
+                let mut after = before.clone();
+                if outcome == Outcome::Success {
+                    apply_entry(&mut after, &entry);
+                }
+                (entry.debit, entry.credit, after, true)
             }
+            Err(_) => (self.estimate_cost(&action), Balances::default(), before.clone(), false),
+        };
+        SimulatedEntry {
+            action,
+            outcome,
+            debit,
+            credit,
+            balances_before: before,
+            balances_after: after,
+            pending_decay: self.pending_decay(),
+            affordable,
         }
-        Ok(())
     }
 
     /// Execute a transaction (Append to Ledger + Update Wallet)
     pub fn record_action(&mut self, actor: &str, action: ActionType, outcome: Outcome) -> Result<()> {
+        self.append_entry(actor, action, outcome, Balances::default())
+    }
+
+    fn append_entry(&mut self, actor: &str, action: ActionType, outcome: Outcome, credit: Balances) -> Result<()> {
+        let entry = self.prepare_entry(actor, action, outcome, credit)?;
+
+        // Update Wallet State
+        if entry.outcome == Outcome::Success {
+            apply_entry(&mut self.wallet.balances, &entry);
+            self.wallet.last_updated = entry.timestamp;
+        }
+
+        let event = EconomyEvent {
+            seq_no: entry.seq_no,
+            timestamp: entry.timestamp,
+            action: entry.action.kind().to_string(),
+            outcome: entry.outcome.clone(),
+            debit: entry.debit.clone(),
+            credit: entry.credit.clone(),
+            balances: self.wallet.balances.clone(),
+        };
+        self.ledger.push(entry);
+        self.save()?;
+
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+        Ok(())
+    }
+
+    /// Every ledger entry from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<EconomyEvent> {
+        self.events.subscribe()
+    }
+
+    /// Cost, funds check and the next ledger entry; shared by record and simulate
+    fn prepare_entry(&self, actor: &str, action: ActionType, outcome: Outcome, credit: Balances) -> Result<LedgerEntry> {
+        // Physics (decay) and penalties still apply to a frozen wallet
+        if self.is_frozen() && !matches!(action, ActionType::DecayBurn { .. } | ActionType::Slash { .. }) {
+            return Err(anyhow!("Wallet frozen"));
+        }
+
         let cost = self.estimate_cost(&action);
         
         // Check Funds
         if outcome == Outcome::Success && self.wallet.balances.ippc < cost.ippc {
              return Err(anyhow!("Insufficient IPPC Funds"));
         }
+        if outcome == Outcome::Success
+            && (self.wallet.balances.iusd < cost.iusd || self.wallet.balances.eth_virtual < cost.eth_virtual)
+        {
+             return Err(anyhow!("Insufficient iUSD or ETH Funds"));
+        }
 
         // Create Ledger Entry
-        let prev_hash = self.ledger.last()
-            .map(|e| e.tx_id.clone())
-            .unwrap_or_else(|| "0000000000000000000000000000000000000000000000000000000000000000".to_string());
+        let prev_hash = ledger_head(&self.ledger).to_string();
         
         let seq_no = self.ledger.last().map(|e| e.seq_no + 1).unwrap_or(0);
-        let timestamp = Utc::now().timestamp() as u64;
+        let timestamp = self.clock.unix_secs();
         
-        // Calculate hash
-        let mut hasher = Sha256::new();
-        hasher.update(&prev_hash);
-        hasher.update(actor.as_bytes());
-        hasher.update(timestamp.to_be_bytes());
-        hasher.update(seq_no.to_be_bytes()); // Include seq_no in hash
-        let tx_id = hex::encode(hasher.finalize());
-
-        let entry = LedgerEntry {
+        let tx_id = entry_hash(&prev_hash, actor, timestamp, seq_no);
+
+        Ok(LedgerEntry {
             seq_no,
             tx_id,
             timestamp,
             actor: actor.to_string(),
             policy_version: self.policy_version.clone(),
             action,
-            debit: cost.clone(), 
-            credit: Balances::default(),
-            outcome: outcome.clone(),
+            debit: cost, 
+            credit,
+            outcome,
             proof: None, 
             prev_hash,
-        };
+        })
+    }
 
-        // Update Wallet State
-        if outcome == Outcome::Success {
-            self.wallet.balances.ippc -= cost.ippc;
-            self.wallet.balances.iusd -= cost.iusd;
-            self.wallet.last_updated = timestamp;
+    /// Convert `amount` of `from` into `to` at `rate` (typically from a `RateOracle`).
+    /// Recorded as one entry debiting `from` and crediting `to`; value is
+    /// conserved exactly, so amounts that would round are rejected.
+    pub fn convert(&mut self, from: CurrencyLayer, to: CurrencyLayer, amount: u128, rate: Rate) -> Result<u128> {
+        if from == to || amount == 0 {
+            return Err(anyhow!("Conversion needs two different layers and a non-zero amount"));
+        }
+        let converted = rate.apply(amount)
+            .filter(|c| *c > 0)
+            .ok_or_else(|| anyhow!("{} {:?} does not convert exactly at {}/{}", amount, from, rate.numerator, rate.denominator))?;
+        if self.wallet.balances.layer(from) < amount {
+            return Err(anyhow!("Insufficient {:?} balance for conversion", from));
         }
 
-        self.ledger.push(entry);
-        self.save()?;
-        
+        let node_id = self.wallet.node_id.clone();
+        self.append_entry(
+            &node_id,
+            ActionType::Conversion { from, to, amount, rate },
+            Outcome::Success,
+            Balances::of(to, converted),
+        )?;
+        Ok(converted)
+    }
+
+    /// Emergency brake: reject every spend, grant and conversion until
+    /// `unfreeze`. Persisted, so it survives a restart. Also confirms a
+    /// pending mesh-voted freeze, which then no longer lapses.
+    pub fn freeze(&mut self, reason: &str) -> Result<()> {
+        if !self.is_frozen() || self.wallet.frozen_until.is_some() {
+            warn!("Freezing wallet of {}: {}", self.wallet.node_id, reason);
+            self.wallet.locked = true;
+            self.wallet.frozen_until = None;
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Freeze for a passed mesh vote. Lapses after `MESH_FREEZE_SECS` unless
+    /// an operator confirms it with `freeze`; a freeze already in place is
+    /// left as it is.
+    pub fn freeze_by_vote(&mut self, reason: &str) -> Result<()> {
+        if !self.is_frozen() {
+            let until = self.clock.unix_secs() + MESH_FREEZE_SECS;
+            warn!("Freezing wallet of {} until {} unless an operator confirms: {}", self.wallet.node_id, until, reason);
+            self.wallet.locked = true;
+            self.wallet.frozen_until = Some(until);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Lift a freeze. Deliberately not reachable from the HTTP API or the
+    /// mesh: only an operator with the node stopped (`ippoc-econ unfreeze`)
+    /// can call it.
+    pub fn unfreeze(&mut self) -> Result<()> {
+        if self.wallet.locked {
+            warn!("Unfreezing wallet of {}", self.wallet.node_id);
+            self.wallet.locked = false;
+            self.wallet.frozen_until = None;
+            self.save()?;
+        }
         Ok(())
     }
 
+    pub fn is_frozen(&self) -> bool {
+        self.wallet.locked && self.wallet.frozen_until.is_none_or(|until| self.clock.unix_secs() < until)
+    }
+
+    /// Burn `percent` of our IPPC for a passed slash proposal. Recorded once
+    /// per proposal, so a re-gossiped claim burns nothing more. Returns the
+    /// amount burned.
+    pub fn slash(&mut self, proposal_id: &str, percent: u8) -> Result<u128> {
+        let already = self.ledger.iter().any(|e| {
+            matches!(&e.action, ActionType::Slash { proposal_id: p, .. } if p == proposal_id)
+        });
+        if already {
+            return Ok(0);
+        }
+
+        let amount = self.wallet.balances.ippc * percent.min(100) as u128 / 100;
+        let node_id = self.wallet.node_id.clone();
+        self.append_entry(
+            &node_id,
+            ActionType::Slash { proposal_id: proposal_id.to_string(), amount },
+            Outcome::Success,
+            Balances::default(),
+        )?;
+        Ok(amount)
+    }
+
     /// Grant funds (System / Genesis / Reward)
     pub fn grant(&mut self, amount: Balances, _reason: &str) -> Result<()> {
         let node_id = self.wallet.node_id.clone();
-        
-        self.wallet.balances.ippc += amount.ippc;
-        self.wallet.balances.iusd += amount.iusd;
-        self.wallet.balances.eth_virtual += amount.eth_virtual;
-        
-        self.record_action(
-            &node_id, 
-            ActionType::SystemGrant, 
-            Outcome::Success
-        )?;
+        // Recorded as a credit so the ledger alone accounts for the balance
+        self.append_entry(&node_id, ActionType::SystemGrant, Outcome::Success, amount)
+    }
+
+    /// Summarize successful entries with `since <= timestamp < until`
+    pub fn report(&self, since: u64, until: u64) -> EconomyReport {
+        let mut debit = Balances::default();
+        let mut credit = Balances::default();
+        let mut by_action: Vec<ActionTotals> = Vec::new();
+        let mut drivers: Vec<CostDriver> = Vec::new();
+        let mut entries = 0;
+
+        for entry in self.ledger.iter()
+            .filter(|e| e.outcome == Outcome::Success && (since..until).contains(&e.timestamp))
+        {
+            entries += 1;
+            add_balances(&mut debit, &entry.debit);
+            add_balances(&mut credit, &entry.credit);
+
+            let kind = entry.action.kind();
+            let index = by_action.iter().position(|t| t.kind == kind).unwrap_or_else(|| {
+                by_action.push(ActionTotals {
+                    kind: kind.to_string(),
+                    count: 0,
+                    debit: Balances::default(),
+                    credit: Balances::default(),
+                });
+                by_action.len() - 1
+            });
+            let totals = &mut by_action[index];
+            totals.count += 1;
+            add_balances(&mut totals.debit, &entry.debit);
+            add_balances(&mut totals.credit, &entry.credit);
+
+            if entry.debit.ippc > 0 {
+                let driver = entry.action.driver();
+                match drivers.iter_mut().find(|d| d.driver == driver) {
+                    Some(d) => d.ippc += entry.debit.ippc,
+                    None => drivers.push(CostDriver { driver, ippc: entry.debit.ippc }),
+                }
+            }
+        }
+
+        by_action.sort_by(|a, b| b.debit.ippc.cmp(&a.debit.ippc).then_with(|| a.kind.cmp(&b.kind)));
+        drivers.sort_by(|a, b| b.ippc.cmp(&a.ippc).then_with(|| a.driver.cmp(&b.driver)));
+        drivers.truncate(TOP_COST_DRIVERS);
+
+        EconomyReport {
+            since,
+            until,
+            entries,
+            net_ippc: credit.ippc as i128 - debit.ippc as i128,
+            debit,
+            credit,
+            by_action,
+            top_costs: drivers,
+        }
+    }
+
+    /// Entries in order, oldest first
+    pub fn ledger(&self) -> &[LedgerEntry] {
+        &self.ledger
+    }
+
+    /// Check the hash chain end to end and that the wallet matches a replay of it
+    pub fn verify(&self) -> Result<()> {
+        let mut prev = GENESIS_HASH;
+        for (i, entry) in self.ledger.iter().enumerate() {
+            if entry.seq_no != i as u64 {
+                return Err(anyhow!("Ledger entry {} has seq_no {}", i, entry.seq_no));
+            }
+            if entry.prev_hash != prev {
+                return Err(anyhow!("Ledger entry {} does not link to the previous entry", i));
+            }
+            if entry.tx_id != entry_hash(&entry.prev_hash, &entry.actor, entry.timestamp, entry.seq_no) {
+                return Err(anyhow!("Ledger entry {} has a bad tx_id", i));
+            }
+            prev = &entry.tx_id;
+        }
+
+        let replay = ledger_balance(&self.opening, &self.ledger);
+        if replay != self.wallet.balances {
+            return Err(anyhow!("Wallet {:?} does not match ledger replay {:?}", self.wallet.balances, replay));
+        }
         Ok(())
     }
 
     fn save(&self) -> Result<()> {
-        let wallet_json = serde_json::to_string_pretty(&self.wallet)?;
-        let ledger_json = serde_json::to_string_pretty(&self.ledger)?;
+        let head = ledger_head(&self.ledger);
+        let wallet_file = WalletFile {
+            version: ECONOMY_FORMAT_VERSION,
+            wallet: self.wallet.clone(),
+            ledger_head: head.to_string(),
+            mac: hex::encode(wallet_mac(&self.wallet_key, ECONOMY_FORMAT_VERSION, &self.wallet, head)?.finalize().into_bytes()),
+        };
+        let wallet_json = serde_json::to_string_pretty(&wallet_file)?;
+        let ledger_json = serde_json::to_string_pretty(&LedgerFile {
+            version: ECONOMY_FORMAT_VERSION,
+            opening: self.opening.clone(),
+            entries: self.ledger.clone(),
+            mac: hex::encode(ledger_mac(&self.ledger_key, ECONOMY_FORMAT_VERSION, &self.opening, &self.ledger)?.finalize().into_bytes()),
+        })?;
         
-        std::fs::write(self.db_path.join("wallet.json"), wallet_json)?;
-        std::fs::write(self.db_path.join("ledger.json"), ledger_json)?;
+        // Marker before either file, so no v2 file exists without it
+        let format_path = self.db_path.join("format.json");
+        if !format_path.exists() {
+            let marker = FormatMarker {
+                version: ECONOMY_FORMAT_VERSION,
+                mac: hex::encode(format_mac(&self.ledger_key, ECONOMY_FORMAT_VERSION).finalize().into_bytes()),
+            };
+            atomic_write(&format_path, serde_json::to_string_pretty(&marker)?.as_bytes())?;
+        }
+
+        // Ledger first: it is what reconciliation trusts
+        atomic_write(&self.db_path.join("ledger.json"), ledger_json.as_bytes())?;
+        atomic_write(&self.db_path.join("wallet.json"), wallet_json.as_bytes())?;
         
         Ok(())
     }
 }
+
+/// Wallet effect of a successful entry
+fn apply_entry(balances: &mut Balances, entry: &LedgerEntry) {
+    for layer in CurrencyLayer::ALL {
+        let value = balances.layer_mut(layer);
+        *value = value.saturating_sub(entry.debit.layer(layer)) + entry.credit.layer(layer);
+    }
+}
+
+/// 2% per period, stopping once the balance is down to 100 IPPC
+fn decay_burns(mut ippc: u128, periods: u64) -> Vec<u128> {
+    let mut burns = Vec::new();
+    for _ in 0..periods {
+        let decay_amount = ippc / 50;
+        if ippc <= 100 || decay_amount == 0 {
+            break;
+        }
+        ippc -= decay_amount;
+        burns.push(decay_amount);
+    }
+    burns
+}
+
+fn add_balances(total: &mut Balances, amount: &Balances) {
+    total.ippc += amount.ippc;
+    total.iusd += amount.iusd;
+    total.eth_virtual += amount.eth_virtual;
+}
+
+/// SHA256(prev_hash + actor + timestamp + seq_no)
+fn entry_hash(prev_hash: &str, actor: &str, timestamp: u64, seq_no: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash);
+    hasher.update(actor.as_bytes());
+    hasher.update(timestamp.to_be_bytes());
+    hasher.update(seq_no.to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn ledger_head(ledger: &[LedgerEntry]) -> &str {
+    ledger.last().map(|e| e.tx_id.as_str()).unwrap_or(GENESIS_HASH)
+}
+
+/// Balance obtained by replaying the ledger from `opening`
+fn ledger_balance(opening: &Balances, ledger: &[LedgerEntry]) -> Balances {
+    let mut balances = opening.clone();
+    for entry in ledger.iter().filter(|e| e.outcome == Outcome::Success) {
+        apply_entry(&mut balances, entry);
+    }
+    balances
+}
+
+/// `a - b` per currency, floored at zero
+fn balance_difference(a: &Balances, b: &Balances) -> Balances {
+    Balances {
+        ippc: a.ippc.saturating_sub(b.ippc),
+        iusd: a.iusd.saturating_sub(b.iusd),
+        eth_virtual: a.eth_virtual.saturating_sub(b.eth_virtual),
+    }
+}
+
+fn wallet_mac(key: &[u8; 32], version: u32, wallet: &Wallet, ledger_head: &str) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(&version.to_be_bytes());
+    mac.update(&serde_json::to_vec(wallet)?);
+    mac.update(ledger_head.as_bytes());
+    Ok(mac)
+}
+
+fn ledger_mac(key: &[u8; 32], version: u32, opening: &Balances, entries: &[LedgerEntry]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(&version.to_be_bytes());
+    mac.update(&serde_json::to_vec(opening)?);
+    mac.update(&serde_json::to_vec(entries)?);
+    Ok(mac)
+}
+
+fn format_mac(key: &[u8; 32], version: u32) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(b"ippoc-economy-format");
+    mac.update(&version.to_be_bytes());
+    mac
+}
+
+fn unsupported(kind: &str, version: u32) -> anyhow::Error {
+    anyhow!("{} format v{} is not supported (this build reads up to v{})", kind, version, ECONOMY_FORMAT_VERSION)
+}
+
+/// Parse ledger.json, migrating older formats and checking the MAC.
+/// Returns the version found on disk, the opening balance and the entries.
+fn load_ledger(data: &str, key: &[u8; 32]) -> Result<(u32, Balances, Vec<LedgerEntry>)> {
+    let value: serde_json::Value = serde_json::from_str(data)?;
+    if value.is_array() {
+        return Ok((1, Balances::default(), serde_json::from_value(value)?));
+    }
+    let file: LedgerFile = serde_json::from_value(value)?;
+    match file.version {
+        ECONOMY_FORMAT_VERSION => {
+            ledger_mac(key, file.version, &file.opening, &file.entries)?
+                .verify_slice(&hex::decode(&file.mac)?)
+                .map_err(|_| anyhow!("Ledger signature does not match its contents; refusing to load"))?;
+            Ok((file.version, file.opening, file.entries))
+        }
+        v => Err(unsupported("Ledger", v)),
+    }
+}
+
+/// Parse format.json and check the MAC. Returns the version it records.
+fn load_format(data: &str, key: &[u8; 32]) -> Result<u32> {
+    let marker: FormatMarker = serde_json::from_str(data)?;
+    format_mac(key, marker.version)
+        .verify_slice(&hex::decode(&marker.mac)?)
+        .map_err(|_| anyhow!("Economy format marker signature does not match; refusing to load"))?;
+    Ok(marker.version)
+}
+
+/// Parse wallet.json, migrating older formats and checking the MAC.
+/// Returns the wallet and the ledger head it was saved against (None for v1).
+fn load_wallet(data: &str, ledger_version: u32, key: &[u8; 32]) -> Result<(Wallet, Option<String>)> {
+    let value: serde_json::Value = serde_json::from_str(data)?;
+    let version = value.get("version").and_then(|v| v.as_u64()).map_or(1, |v| v as u32);
+    match version {
+        // Unsigned, trusted once; the next save writes v2. Downgrading a
+        // migrated wallet back to v1 would skip the MAC, so require a v1 ledger.
+        1 if ledger_version == 1 => Ok((serde_json::from_value(value)?, None)),
+        1 => Err(anyhow!("Refusing unsigned v1 wallet next to a v{} ledger", ledger_version)),
+        ECONOMY_FORMAT_VERSION => {
+            let file: WalletFile = serde_json::from_value(value)?;
+            wallet_mac(key, file.version, &file.wallet, &file.ledger_head)?
+                .verify_slice(&hex::decode(&file.mac)?)
+                .map_err(|_| anyhow!("Wallet signature does not match its contents; refusing to load"))?;
+            Ok((file.wallet, Some(file.ledger_head)))
+        }
+        v => Err(unsupported("Wallet", v)),
+    }
+}
+
+/// Exclusive hold on a node's economy files, so a CLI cannot race the running
+/// node. The lock file holds the owner's pid; a dead owner's lock is reclaimed.
+pub struct EconomyLock {
+    path: PathBuf,
+}
+
+impl EconomyLock {
+    pub fn acquire(node_root: &Path) -> Result<Self> {
+        let dir = node_root.join("economy");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("economy.lock");
+
+        for _ in 0..2 {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    write!(file, "{}", std::process::id())?;
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let holder = std::fs::read_to_string(&path).ok().and_then(|s| s.trim().parse::<u32>().ok());
+                    if let Some(pid) = holder.filter(|pid| process_alive(*pid)) {
+                        return Err(anyhow!("Economy at {:?} is locked by running process {}", dir, pid));
+                    }
+                    warn!("Removing stale economy lock {:?}", path);
+                    std::fs::remove_file(&path)?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(anyhow!("Could not acquire economy lock {:?}", path))
+    }
+}
+
+impl Drop for EconomyLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    if cfg!(target_os = "linux") {
+        Path::new("/proc").join(pid.to_string()).exists()
+    } else {
+        // No cheap liveness check elsewhere; assume the holder is still running
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("economy_{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_decay_is_charged_per_elapsed_day() -> Result<()> {
+        let node_root = temp_root();
+        let clock = MockClock::starting_now();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?.with_clock(clock.clone());
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+
+        economy.apply_decay()?;
+        clock.advance(chrono::Duration::hours(12));
+        economy.apply_decay()?;
+        assert_eq!(economy.wallet.balances.ippc, 1000);
+
+        clock.advance(chrono::Duration::hours(12));
+        economy.apply_decay()?;
+        assert_eq!(economy.wallet.balances.ippc, 980);
+
+        // Missed ticks catch up
+        clock.advance(chrono::Duration::days(2));
+        economy.apply_decay()?;
+        assert_eq!(economy.wallet.balances.ippc, 942);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wallet_round_trips_and_rejects_tampering() -> Result<()> {
+        let node_root = temp_root();
+        let secrets = NodeSecrets::generate();
+        {
+            let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+            economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+            economy.record_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success)?;
+        }
+        let economy = EconomyController::new("node", &node_root, &secrets)?;
+        assert_eq!(economy.wallet.balances.ippc, 950);
+        assert_eq!(ledger_balance(&economy.opening, &economy.ledger), economy.wallet.balances);
+
+        // Another node's key cannot vouch for this wallet
+        assert!(EconomyController::new("node", &node_root, &NodeSecrets::generate()).is_err());
+
+        // Inflating the balance breaks the MAC
+        let wallet_path = node_root.join("economy").join("wallet.json");
+        let original = std::fs::read_to_string(&wallet_path)?;
+        let mut file: serde_json::Value = serde_json::from_str(&original)?;
+        file["wallet"]["balances"]["ippc"] = serde_json::json!(1_000_000);
+        std::fs::write(&wallet_path, file.to_string())?;
+        let err = EconomyController::new("node", &node_root, &secrets).err().unwrap();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+
+        // Rewriting an amount under the same ledger head cannot be reconciled
+        std::fs::write(&wallet_path, &original)?;
+        let ledger_path = node_root.join("economy").join("ledger.json");
+        let mut ledger: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&ledger_path)?)?;
+        ledger["entries"][1]["debit"]["ippc"] = serde_json::json!(0);
+        std::fs::write(&ledger_path, ledger.to_string())?;
+        let err = EconomyController::new("node", &node_root, &secrets).err().unwrap();
+        assert!(err.to_string().contains("Ledger signature"), "{}", err);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_versions_migrate_or_refuse() -> Result<()> {
+        let node_root = temp_root();
+        let secrets = NodeSecrets::generate();
+        let dir = node_root.join("economy");
+        std::fs::create_dir_all(&dir)?;
+
+        // v1: bare JSON as written before versioning
+        let legacy = Wallet {
+            node_id: "node".into(),
+            balances: Balances { ippc: 42, ..Default::default() },
+            reputation: 10.0,
+            locked: false,
+            frozen_until: None,
+            last_updated: 0,
+            last_decay: None,
+        };
+        std::fs::write(dir.join("wallet.json"), serde_json::to_string(&legacy)?)?;
+        std::fs::write(dir.join("ledger.json"), "[]")?;
+        let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+        assert_eq!(economy.wallet.balances.ippc, 42);
+
+        // Saving upgrades both files, after which a v1 wallet is no longer accepted
+        economy.apply_decay()?;
+        let upgraded: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("wallet.json"))?)?;
+        assert_eq!(upgraded["version"], ECONOMY_FORMAT_VERSION);
+        std::fs::write(dir.join("wallet.json"), serde_json::to_string(&legacy)?)?;
+        assert!(EconomyController::new("node", &node_root, &secrets).is_err());
+
+        // Nor is a v1 wallet and ledger put back together once v2 was written
+        let upgraded_ledger = std::fs::read_to_string(dir.join("ledger.json"))?;
+        std::fs::write(dir.join("ledger.json"), "[]")?;
+        let err = EconomyController::new("node", &node_root, &secrets).err().unwrap();
+        assert!(err.to_string().contains("refusing to downgrade"), "{}", err);
+        std::fs::write(dir.join("ledger.json"), upgraded_ledger)?;
+        std::fs::write(dir.join("wallet.json"), upgraded.to_string())?;
+        EconomyController::new("node", &node_root, &secrets)?;
+
+        // A format from the future is refused rather than misread
+        let mut future = upgraded;
+        future["version"] = serde_json::json!(ECONOMY_FORMAT_VERSION + 1);
+        std::fs::write(dir.join("wallet.json"), future.to_string())?;
+        let err = EconomyController::new("node", &node_root, &secrets).err().unwrap();
+        assert!(err.to_string().contains("not supported"), "{}", err);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile_corrects_wallet_after_lost_write() -> Result<()> {
+        let node_root = temp_root();
+        let secrets = NodeSecrets::generate();
+        let dir = node_root.join("economy");
+        let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        let ledger_before = std::fs::read_to_string(dir.join("ledger.json"))?;
+        let wallet_before = std::fs::read_to_string(dir.join("wallet.json"))?;
+        economy.record_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success)?;
+        drop(economy);
+
+        // Crash after the ledger write: the wallet still predates the last entry
+        std::fs::write(dir.join("wallet.json"), &wallet_before)?;
+        let economy = EconomyController::new("node", &node_root, &secrets)?;
+        assert_eq!(economy.wallet.balances.ippc, 950);
+        drop(economy);
+
+        // The corrected wallet was persisted and now loads cleanly
+        let reloaded = EconomyController::new("node", &node_root, &secrets)?;
+        assert_eq!(reloaded.wallet.balances.ippc, 950);
+        drop(reloaded);
+
+        // The ledger is written first, so a wallet ahead of it means the ledger
+        // was rolled back; so does a wallet more than one entry behind
+        let wallet_after = std::fs::read_to_string(dir.join("wallet.json"))?;
+        let ledger_after = std::fs::read_to_string(dir.join("ledger.json"))?;
+        std::fs::write(dir.join("ledger.json"), &ledger_before)?;
+        let err = EconomyController::new("node", &node_root, &secrets).err().unwrap();
+        assert!(err.to_string().contains("lost write cannot explain"), "{}", err);
+
+        std::fs::write(dir.join("ledger.json"), &ledger_after)?;
+        let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+        economy.record_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success)?;
+        drop(economy);
+        std::fs::write(dir.join("wallet.json"), &wallet_before)?;
+        assert!(EconomyController::new("node", &node_root, &secrets).is_err());
+        std::fs::write(dir.join("wallet.json"), &wallet_after)?;
+        EconomyController::new("node", &node_root, &secrets)?;
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_detects_broken_chain_and_lock_is_exclusive() -> Result<()> {
+        let node_root = temp_root();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?;
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        economy.record_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success)?;
+        economy.verify()?;
+
+        economy.ledger[1].actor = "mallory".into();
+        assert!(economy.verify().is_err());
+
+        let lock = EconomyLock::acquire(&node_root)?;
+        assert!(EconomyLock::acquire(&node_root).is_err());
+        drop(lock);
+        // A lock left by a dead process is reclaimed
+        std::fs::write(node_root.join("economy").join("economy.lock"), "999999999")?;
+        #[cfg(target_os = "linux")]
+        EconomyLock::acquire(&node_root)?;
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_aggregates_window() -> Result<()> {
+        let node_root = temp_root();
+        let clock = MockClock::starting_now();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?.with_clock(clock.clone());
+        let start = clock.unix_secs();
+
+        economy.grant(Balances { ippc: 10_000, ..Default::default() }, "genesis")?;
+        clock.advance(chrono::Duration::seconds(10));
+        let since = clock.unix_secs();
+        for tool in ["search", "search", "shell"] {
+            economy.record_action("node", ActionType::ToolExecution { tool: tool.into() }, Outcome::Success)?;
+        }
+        economy.record_action("node", ActionType::LlmInference { tokens: 1000, model: "pro".into() }, Outcome::Success)?;
+        economy.record_action("node", ActionType::EvolutionSim { pr_id: "7".into() }, Outcome::Fail)?;
+        clock.advance(chrono::Duration::seconds(10));
+        let until = clock.unix_secs();
+        economy.record_action("node", ActionType::ToolExecution { tool: "late".into() }, Outcome::Success)?;
+
+        let report = economy.report(since, until);
+        assert_eq!(report.entries, 4);
+        assert_eq!(report.debit.ippc, 3 * 50 + 20);
+        assert_eq!(report.credit.ippc, 0);
+        assert_eq!(report.net_ippc, -170);
+        assert_eq!(report.by_action[0], ActionTotals {
+            kind: "ToolExecution".into(),
+            count: 3,
+            debit: Balances { ippc: 150, ..Default::default() },
+            credit: Balances::default(),
+        });
+        assert_eq!(report.top_costs[0], CostDriver { driver: "ToolExecution:search".into(), ippc: 100 });
+        assert_eq!(report.top_costs.len(), 3);
+
+        // The grant is income once the window covers it
+        let all = economy.report(start, until + 1);
+        assert_eq!(all.net_ippc, 10_000 - 220);
+        assert!(all.by_action.iter().any(|t| t.kind == "SystemGrant" && t.credit.ippc == 10_000));
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_leaves_ledger_and_wallet_unchanged() -> Result<()> {
+        let node_root = temp_root();
+        let clock = MockClock::starting_now();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?.with_clock(clock.clone());
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        economy.apply_decay()?;
+        clock.advance(chrono::Duration::days(1));
+
+        let ledger_len = economy.ledger().len();
+        let wallet = economy.wallet.balances.clone();
+        let sim = economy.simulate_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success);
+        assert!(sim.affordable);
+        assert_eq!(sim.debit.ippc, 50);
+        assert_eq!(sim.balances_after.ippc, 950);
+        assert_eq!(sim.pending_decay, 20);
+        assert_eq!(economy.ledger().len(), ledger_len);
+        assert_eq!(economy.wallet.balances, wallet);
+
+        // Matches what recording actually does
+        economy.record_action("node", ActionType::ToolExecution { tool: "ls".into() }, Outcome::Success)?;
+        assert_eq!(economy.wallet.balances, sim.balances_after);
+
+        let too_big = economy.simulate_action("node", ActionType::LlmInference { tokens: 1_000_000, model: "pro".into() }, Outcome::Success);
+        assert!(!too_big.affordable);
+        assert_eq!(too_big.balances_after, too_big.balances_before);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_conversion_debits_and_credits_one_entry() -> Result<()> {
+        let node_root = temp_root();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?;
+        economy.grant(Balances { ippc: 1500, ..Default::default() }, "genesis")?;
+        let oracle = FixedRateOracle::default();
+
+        let rate = oracle.rate(CurrencyLayer::Ippc, CurrencyLayer::Iusd)?;
+        assert_eq!(economy.convert(CurrencyLayer::Ippc, CurrencyLayer::Iusd, 1000, rate)?, 10);
+        assert_eq!(economy.wallet.balances, Balances { ippc: 500, iusd: 10, eth_virtual: 0 });
+
+        let entry = economy.ledger().last().unwrap();
+        assert_eq!(entry.debit, Balances::of(CurrencyLayer::Ippc, 1000));
+        assert_eq!(entry.credit, Balances::of(CurrencyLayer::Iusd, 10));
+        economy.verify()?;
+
+        // The reverse rate comes from the same configuration
+        let back = oracle.rate(CurrencyLayer::Iusd, CurrencyLayer::Ippc)?;
+        assert_eq!(economy.convert(CurrencyLayer::Iusd, CurrencyLayer::Ippc, 5, back)?, 500);
+        assert_eq!(economy.wallet.balances, Balances { ippc: 1000, iusd: 5, eth_virtual: 0 });
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_conversion_rejects_overdraw_and_rounding() -> Result<()> {
+        let node_root = temp_root();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?;
+        economy.grant(Balances { ippc: 500, ..Default::default() }, "genesis")?;
+        let rate = FixedRateOracle::default().rate(CurrencyLayer::Ippc, CurrencyLayer::Iusd)?;
+        let entries = economy.ledger().len();
+
+        assert!(economy.convert(CurrencyLayer::Ippc, CurrencyLayer::Iusd, 1000, rate).is_err());
+        // 150 IPPC would be 1.5 iUSD
+        assert!(economy.convert(CurrencyLayer::Ippc, CurrencyLayer::Iusd, 150, rate).is_err());
+        assert!(economy.convert(CurrencyLayer::Ippc, CurrencyLayer::Ippc, 100, Rate::new(1, 1)).is_err());
+        assert!(FixedRateOracle::empty().rate(CurrencyLayer::Ippc, CurrencyLayer::EthVirtual).is_err());
+
+        assert_eq!(economy.ledger().len(), entries);
+        assert_eq!(economy.wallet.balances.ippc, 500);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_frozen_wallet_rejects_spends_until_unfrozen() -> Result<()> {
+        let node_root = temp_root();
+        let secrets = NodeSecrets::generate();
+        let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        economy.freeze("misbehaving")?;
+        let entries = economy.ledger().len();
+
+        let spend = ActionType::ToolExecution { tool: "shell".into() };
+        assert!(economy.record_action("node", spend.clone(), Outcome::Success).unwrap_err().to_string().contains("frozen"));
+        assert!(economy.record_action("node", ActionType::Transfer { target: "peer".into() }, Outcome::Success).is_err());
+        assert!(economy.grant(Balances { ippc: 1, ..Default::default() }, "reward").is_err());
+        assert!(!economy.simulate_action("node", spend.clone(), Outcome::Success).affordable);
+        assert_eq!(economy.ledger().len(), entries);
+
+        // The freeze survives a restart
+        let mut economy = EconomyController::new("node", &node_root, &secrets)?;
+        assert!(economy.is_frozen());
+
+        economy.unfreeze()?;
+        economy.record_action("node", spend, Outcome::Success)?;
+        assert_eq!(economy.wallet.balances.ippc, 950);
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mesh_freeze_lapses_unless_confirmed() -> Result<()> {
+        let node_root = temp_root();
+        let clock = MockClock::starting_now();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?.with_clock(clock.clone());
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        let spend = ActionType::ToolExecution { tool: "shell".into() };
+
+        economy.freeze_by_vote("proposal p1")?;
+        assert!(economy.record_action("node", spend.clone(), Outcome::Success).is_err());
+        clock.advance(chrono::Duration::seconds(MESH_FREEZE_SECS as i64));
+        assert!(!economy.is_frozen());
+        economy.record_action("node", spend.clone(), Outcome::Success)?;
+
+        // Confirmed by an operator: no longer lapses, and a later vote cannot shorten it
+        economy.freeze_by_vote("proposal p2")?;
+        economy.freeze("confirmed")?;
+        economy.freeze_by_vote("proposal p3")?;
+        clock.advance(chrono::Duration::seconds(MESH_FREEZE_SECS as i64 * 2));
+        assert!(economy.is_frozen());
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_subscribers_see_each_ledger_entry() -> Result<()> {
+        let node_root = temp_root();
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?;
+        let mut events = economy.subscribe();
+
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+        economy.record_action("node", ActionType::ToolExecution { tool: "secret-tool".into() }, Outcome::Success)?;
+
+        let grant = events.try_recv()?;
+        assert_eq!((grant.action.as_str(), grant.credit.ippc, grant.balances.ippc), ("SystemGrant", 1000, 1000));
+        let spend = events.try_recv()?;
+        assert_eq!(spend.action, "ToolExecution");
+        assert_eq!((spend.debit.ippc, spend.balances.ippc), (50, 950));
+        // Tool names stay out of the stream
+        assert!(!serde_json::to_string(&spend)?.contains("secret-tool"));
+        assert!(events.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(node_root);
+        Ok(())
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn arb_text() -> impl Strategy<Value = String> {
+            "\\PC{0,24}"
+        }
+
+        fn arb_balances() -> impl Strategy<Value = Balances> {
+            (any::<u128>(), any::<u128>(), any::<u128>())
+                .prop_map(|(ippc, iusd, eth_virtual)| Balances { ippc, iusd, eth_virtual })
+        }
+
+        fn arb_layer() -> impl Strategy<Value = CurrencyLayer> {
+            prop::sample::select(CurrencyLayer::ALL.to_vec())
+        }
+
+        fn arb_action() -> impl Strategy<Value = ActionType> {
+            prop_oneof![
+                (any::<u32>(), arb_text()).prop_map(|(tokens, model)| ActionType::LlmInference { tokens, model }),
+                arb_text().prop_map(|tool| ActionType::ToolExecution { tool }),
+                arb_text().prop_map(|pr_id| ActionType::EvolutionSim { pr_id }),
+                arb_text().prop_map(|target| ActionType::BountyPayout { target }),
+                Just(ActionType::DaoFee),
+                arb_text().prop_map(|target| ActionType::Transfer { target }),
+                Just(ActionType::SystemGrant),
+                any::<u128>().prop_map(|amount| ActionType::DecayBurn { amount }),
+                (arb_text(), any::<bool>()).prop_map(|(proposal_id, vote)| ActionType::Vote { proposal_id, vote }),
+                (arb_layer(), arb_layer(), any::<u128>(), any::<u128>(), any::<u128>()).prop_map(
+                    |(from, to, amount, numerator, denominator)| ActionType::Conversion {
+                        from,
+                        to,
+                        amount,
+                        rate: Rate::new(numerator, denominator),
+                    }
+                ),
+                (arb_text(), any::<u128>()).prop_map(|(proposal_id, amount)| ActionType::Slash { proposal_id, amount }),
+            ]
+        }
+
+        fn arb_entry() -> impl Strategy<Value = LedgerEntry> {
+            let head = (any::<u64>(), arb_text(), any::<u64>(), arb_text(), arb_text(), arb_action());
+            let tail = (
+                arb_balances(),
+                arb_balances(),
+                prop::sample::select(vec![Outcome::Pending, Outcome::Success, Outcome::Fail]),
+                prop::option::of((arb_text(), arb_text()).prop_map(|(signature, signer)| Proof { signature, signer })),
+                arb_text(),
+            );
+            (head, tail).prop_map(
+                |((seq_no, tx_id, timestamp, actor, policy_version, action), (debit, credit, outcome, proof, prev_hash))| LedgerEntry {
+                    seq_no,
+                    tx_id,
+                    timestamp,
+                    actor,
+                    policy_version,
+                    action,
+                    debit,
+                    credit,
+                    outcome,
+                    proof,
+                    prev_hash,
+                },
+            )
+        }
+
+        proptest! {
+            #[test]
+            fn prop_ledger_entry_json_round_trips(entry in arb_entry()) {
+                let json = serde_json::to_string(&entry).unwrap();
+                let decoded: LedgerEntry = serde_json::from_str(&json).unwrap();
+                prop_assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Node Event Bus - how background tasks tell the supervisor they died
+//!
+//! Long-running tasks (transport intake, maintenance, auto-evolution) are
+//! spawned through `EventBus::spawn_supervised` or, if they can be rebuilt,
+//! `EventBus::spawn_restartable`. A task that errors or panics for good is
+//! reported as `NodeEvent::TaskFailed` and flips the lifecycle to `Degraded`,
+//! instead of vanishing into a log line.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::lifecycle::{LifecycleManager, NodeState};
+
+const EVENT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeEvent {
+    TaskStarted { task: String },
+    /// Task returned without error; unexpected for long-running loops
+    TaskStopped { task: String },
+    TaskFailed { task: String, error: String },
+    /// Task died and will be started again after `delay_ms`
+    TaskRestarting { task: String, attempt: u32, error: String, delay_ms: u64 },
+    StateChanged { from: NodeState, to: NodeState },
+}
+
+/// How often a critical task is restarted before the node gives up on it
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    pub max_restarts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// base_delay * 2^(attempt - 1), capped at max_delay
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+    lifecycle: Arc<RwLock<LifecycleManager>>,
+}
+
+impl EventBus {
+    pub fn new(lifecycle: Arc<RwLock<LifecycleManager>>) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { sender, lifecycle }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish an event (dropped if nobody is listening)
+    pub fn report(&self, event: NodeEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Report a fatal task error and mark the node `Degraded`
+    pub async fn task_failed(&self, task: &str, err: impl std::fmt::Display) {
+        error!("Background task '{}' failed: {}", task, err);
+        self.report(NodeEvent::TaskFailed { task: task.to_string(), error: err.to_string() });
+
+        let mut lifecycle = self.lifecycle.write().await;
+        let from = lifecycle.current();
+        if lifecycle.degrade() {
+            warn!("Lifecycle: {:?} -> Degraded after '{}' failed", from, task);
+            self.report(NodeEvent::StateChanged { from, to: NodeState::Degraded });
+        }
+    }
+
+    /// Spawn `task` and report how it ends, including panics
+    pub fn spawn_supervised<F>(&self, name: &str, task: F) -> JoinHandle<()>
+    where
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        let bus = self.clone();
+        let name = name.to_string();
+        self.report(NodeEvent::TaskStarted { task: name.clone() });
+
+        let handle = tokio::spawn(task);
+        tokio::spawn(async move {
+            match handle.await {
+                Ok(Ok(())) => {
+                    info!("Background task '{}' stopped", name);
+                    bus.report(NodeEvent::TaskStopped { task: name });
+                }
+                Ok(Err(e)) => bus.task_failed(&name, e).await,
+                Err(join) if join.is_panic() => bus.task_failed(&name, "task panicked").await,
+                Err(_) => bus.report(NodeEvent::TaskStopped { task: name }),
+            }
+        })
+    }
+
+    /// Run the task built by `make_task`, rebuilding it with backoff whenever
+    /// it exits or panics. Past `policy.max_restarts` the node is degraded;
+    /// a run that stayed up longer than `policy.max_delay` starts the count over.
+    pub fn spawn_restartable<F, Fut>(&self, name: &str, policy: RestartPolicy, mut make_task: F) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let bus = self.clone();
+        let name = name.to_string();
+
+        tokio::spawn(async move {
+            let mut restarts = 0;
+            loop {
+                bus.report(NodeEvent::TaskStarted { task: name.clone() });
+                let started = tokio::time::Instant::now();
+                // Long-running tasks aren't supposed to return at all
+                let reason = match tokio::spawn(make_task()).await {
+                    Ok(Ok(())) => "task exited".to_string(),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(join) if join.is_panic() => "task panicked".to_string(),
+                    Err(_) => return bus.report(NodeEvent::TaskStopped { task: name }),
+                };
+                // Stable for longer than any backoff: an isolated failure, not a crash loop
+                if started.elapsed() > policy.max_delay {
+                    restarts = 0;
+                }
+
+                if restarts >= policy.max_restarts {
+                    let error = format!("{} (gave up after {} restarts)", reason, restarts);
+                    return bus.task_failed(&name, error).await;
+                }
+
+                restarts += 1;
+                let delay = policy.backoff(restarts);
+                warn!("Background task '{}' died ({}), restart {}/{} in {:?}", name, reason, restarts, policy.max_restarts, delay);
+                bus.report(NodeEvent::TaskRestarting {
+                    task: name.clone(),
+                    attempt: restarts,
+                    error: reason,
+                    delay_ms: delay.as_millis() as u64,
+                });
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_task_reports_event_and_degrades() {
+        let lifecycle = Arc::new(RwLock::new(LifecycleManager::new()));
+        lifecycle.write().await.set_state(NodeState::Active);
+        let bus = EventBus::new(lifecycle.clone());
+        let mut events = bus.subscribe();
+
+        bus.spawn_supervised("incoming", async { Err(anyhow::anyhow!("transport channel closed")) })
+            .await
+            .unwrap();
+
+        assert_eq!(events.recv().await.unwrap(), NodeEvent::TaskStarted { task: "incoming".into() });
+        assert_eq!(
+            events.recv().await.unwrap(),
+            NodeEvent::TaskFailed { task: "incoming".into(), error: "transport channel closed".into() }
+        );
+        assert_eq!(
+            events.recv().await.unwrap(),
+            NodeEvent::StateChanged { from: NodeState::Active, to: NodeState::Degraded }
+        );
+        assert_eq!(lifecycle.read().await.current(), NodeState::Degraded);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_reported() {
+        let lifecycle = Arc::new(RwLock::new(LifecycleManager::new()));
+        let bus = EventBus::new(lifecycle.clone());
+        let mut events = bus.subscribe();
+
+        bus.spawn_supervised("maintenance", async { panic!("boom") }).await.unwrap();
+
+        events.recv().await.unwrap(); // TaskStarted
+        assert!(matches!(events.recv().await.unwrap(), NodeEvent::TaskFailed { task, .. } if task == "maintenance"));
+        assert_eq!(lifecycle.read().await.current(), NodeState::Degraded);
+    }
+
+    fn fast_policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy { max_restarts, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5) }
+    }
+
+    #[tokio::test]
+    async fn test_panicked_task_is_restarted() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let lifecycle = Arc::new(RwLock::new(LifecycleManager::new()));
+        let bus = EventBus::new(lifecycle.clone());
+        let mut events = bus.subscribe();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = runs.clone();
+        bus.spawn_restartable("mesh-incoming", fast_policy(3), move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run == 0 {
+                    panic!("receiver blew up");
+                }
+                // Healthy from now on
+                std::future::pending::<()>().await;
+                Ok(())
+            }
+        });
+
+        assert!(matches!(events.recv().await.unwrap(), NodeEvent::TaskStarted { .. }));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            NodeEvent::TaskRestarting { attempt: 1, error, .. } if error == "task panicked"
+        ));
+        assert!(matches!(events.recv().await.unwrap(), NodeEvent::TaskStarted { .. }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_ne!(lifecycle.read().await.current(), NodeState::Degraded);
+    }
+
+    #[tokio::test]
+    async fn test_stable_runs_reset_the_restart_count() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let lifecycle = Arc::new(RwLock::new(LifecycleManager::new()));
+        let bus = EventBus::new(lifecycle.clone());
+        let runs = Arc::new(AtomicU32::new(0));
+
+        // Each run outlives the 5ms backoff ceiling before failing
+        let counter = runs.clone();
+        let handle = bus.spawn_restartable("gossip", fast_policy(2), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Err(anyhow::anyhow!("peer reset"))
+            }
+        });
+
+        while runs.load(Ordering::SeqCst) < 5 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert!(!handle.is_finished());
+        assert_ne!(lifecycle.read().await.current(), NodeState::Degraded);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_restart_cap_degrades_node() {
+        let lifecycle = Arc::new(RwLock::new(LifecycleManager::new()));
+        let bus = EventBus::new(lifecycle.clone());
+        let mut events = bus.subscribe();
+
+        bus.spawn_restartable("decay", fast_policy(2), || async { Err(anyhow::anyhow!("db gone")) })
+            .await
+            .unwrap();
+
+        let mut restarts = 0;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                NodeEvent::TaskRestarting { .. } => restarts += 1,
+                NodeEvent::TaskFailed { error, .. } => assert!(error.contains("gave up after 2 restarts")),
+                _ => {}
+            }
+        }
+        assert_eq!(restarts, 2);
+        assert_eq!(lifecycle.read().await.current(), NodeState::Degraded);
+    }
+}
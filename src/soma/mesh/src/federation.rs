@@ -0,0 +1,137 @@
+//! Federation: signed bundles of a swarm's trusted-peer keys
+//!
+//! A node joining an existing swarm imports one bundle instead of meeting
+//! every peer through discovery. The issuer must already be pinned on the
+//! importing node (see `load_trusted_peers`); anyone else's bundle is refused.
+//!
+//! Capability attestations follow the same rule: a node signs the role and
+//! capabilities it announces, and privileged ones also need an endorsement
+//! from a pinned authority before peers route work to it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{node_id, verify_signature, NodeIdentity, NodeSecrets};
+use crate::trust::TrustLevel;
+
+/// Peers vouched for by one swarm key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustBundle {
+    pub peers: Vec<(NodeIdentity, TrustLevel)>,
+    /// Node id of the signer
+    pub issuer: String,
+    /// Ed25519 over `bundle_message` (hex)
+    pub signature: String,
+}
+
+impl TrustBundle {
+    pub fn sign(secrets: &NodeSecrets, issuer: &str, peers: Vec<(NodeIdentity, TrustLevel)>) -> Self {
+        let signature = secrets.sign(&bundle_message(issuer, &peers));
+        Self { peers, issuer: issuer.to_string(), signature: hex::encode(signature) }
+    }
+
+    pub fn verify(&self, signing_public: &[u8; 32]) -> bool {
+        let Ok(bytes) = hex::decode(&self.signature) else { return false };
+        let Ok(signature) = <[u8; 64]>::try_from(bytes.as_slice()) else { return false };
+        let message = bundle_message(&self.issuer, &self.peers);
+        verify_signature(signing_public, &message, &signature).unwrap_or(false)
+    }
+
+    /// First entry whose id is not the hash of its signing key
+    pub fn inconsistent_entry(&self) -> Option<&str> {
+        self.peers.iter()
+            .map(|(identity, _)| identity)
+            .find(|identity| identity.id != node_id(&identity.signing_public))
+            .map(|identity| identity.id.as_str())
+    }
+}
+
+fn bundle_message(issuer: &str, peers: &[(NodeIdentity, TrustLevel)]) -> Vec<u8> {
+    let mut message = format!("ippoc-trust-bundle:{}:", issuer).into_bytes();
+    message.extend(serde_json::to_vec(peers).unwrap_or_default());
+    message
+}
+
+/// A node's signed claim to a role and capabilities, carried in discovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityAttestation {
+    pub node_id: String,
+    pub role: String,
+    pub capabilities: Vec<String>,
+    /// Ed25519 by the node itself over `attestation_message` (hex)
+    pub signature: String,
+    /// Counter-signature required for privileged capabilities
+    #[serde(default)]
+    pub endorsement: Option<Endorsement>,
+}
+
+/// An authority's signature over the same claim
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Endorsement {
+    /// Node id of the endorsing authority
+    pub authority: String,
+    /// Ed25519 over `attestation_message` (hex)
+    pub signature: String,
+}
+
+impl CapabilityAttestation {
+    pub fn sign(secrets: &NodeSecrets, node_id: &str, role: &str, capabilities: Vec<String>) -> Self {
+        let signature = secrets.sign(&attestation_message(node_id, role, &capabilities));
+        Self {
+            node_id: node_id.to_string(),
+            role: role.to_string(),
+            capabilities,
+            signature: hex::encode(signature),
+            endorsement: None,
+        }
+    }
+
+    /// Counter-sign as `authority`; the node attaches the result to its announcements
+    pub fn endorse(&self, authority_secrets: &NodeSecrets, authority: &str) -> Endorsement {
+        let signature = authority_secrets.sign(&attestation_message(&self.node_id, &self.role, &self.capabilities));
+        Endorsement { authority: authority.to_string(), signature: hex::encode(signature) }
+    }
+
+    /// Signed by the node it describes
+    pub fn verify(&self, signing_public: &[u8; 32]) -> bool {
+        node_id(signing_public) == self.node_id && self.check(&self.signature, signing_public)
+    }
+
+    /// Endorsed by the authority holding `authority_public`
+    pub fn verify_endorsement(&self, authority_public: &[u8; 32]) -> bool {
+        self.endorsement.as_ref().is_some_and(|e| self.check(&e.signature, authority_public))
+    }
+
+    fn check(&self, signature: &str, signing_public: &[u8; 32]) -> bool {
+        let Ok(bytes) = hex::decode(signature) else { return false };
+        let Ok(signature) = <[u8; 64]>::try_from(bytes.as_slice()) else { return false };
+        let message = attestation_message(&self.node_id, &self.role, &self.capabilities);
+        verify_signature(signing_public, &message, &signature).unwrap_or(false)
+    }
+}
+
+fn attestation_message(node_id: &str, role: &str, capabilities: &[String]) -> Vec<u8> {
+    let mut message = format!("ippoc-capabilities:{}:{}:", node_id, role).into_bytes();
+    message.extend(serde_json::to_vec(capabilities).unwrap_or_default());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_covers_levels() {
+        let issuer = NodeSecrets::generate();
+        let issuer_id = issuer.identity("seed", "relay").id;
+        let member = NodeSecrets::generate().identity("member", "tool");
+        let bundle = TrustBundle::sign(&issuer, &issuer_id, vec![(member, TrustLevel::Trusted)]);
+
+        let signing_public = issuer.identity("seed", "relay").signing_public;
+        assert!(bundle.verify(&signing_public));
+        assert!(bundle.inconsistent_entry().is_none());
+
+        let mut promoted = bundle.clone();
+        promoted.peers[0].1 = TrustLevel::System;
+        assert!(!promoted.verify(&signing_public));
+    }
+}
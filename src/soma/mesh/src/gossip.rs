@@ -2,7 +2,7 @@
 //! Probabilistic message forwarding with encryption and deniability
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use rand::Rng;
@@ -12,16 +12,21 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use x25519_dalek::{PublicKey, StaticSecret};
-use ed25519_dalek::{Keypair, Signature, Signer, Verifier};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use bincode::Options;
+use tracing::warn;
+
+use crate::clock::{self, SharedClock};
+use crate::messages::{Ttl, DEFAULT_MAX_HOPS};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipMessage {
     pub id: String,
     pub payload: Vec<u8>,
     pub timestamp: u64,
-    pub ttl: u64,
+    pub ttl: Ttl,
     pub origin: String,
-    pub hops: usize,
+    pub hops: u8,
     pub signature: Vec<u8>,
 }
 
@@ -33,39 +38,160 @@ pub struct EncryptedGossipPacket {
     pub mac: Vec<u8>,
 }
 
+/// Link quality used to bias forwarding toward reliable, nearby peers
+#[derive(Debug, Clone, Copy)]
+pub struct PeerQuality {
+    /// 0.0 - 1.0
+    pub trust: f64,
+    pub rtt_ms: u32,
+}
+
+impl Default for PeerQuality {
+    fn default() -> Self {
+        Self { trust: 0.5, rtt_ms: 0 }
+    }
+}
+
+impl PeerQuality {
+    fn weight(&self) -> f64 {
+        (self.trust.clamp(0.0, 1.0) + 0.1) / (1.0 + self.rtt_ms as f64 / 100.0)
+    }
+}
+
+/// Largest ciphertext accepted from a peer unless configured otherwise
+pub const DEFAULT_MAX_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+/// Trust (0.0 - 1.0) lost each time a peer sends an oversized packet
+const OVERSIZE_PENALTY: f64 = 0.2;
+
+/// Message ids remembered for duplicate detection; past this the ones
+/// closest to expiry are forgotten first
+pub const MAX_SEEN_MESSAGES: usize = 10_000;
+
+/// Peers to forward each message to: ln(n) + 2, so the subset grows slowly with network size
+pub fn adaptive_fanout(peer_count: usize) -> usize {
+    if peer_count <= 1 {
+        return peer_count;
+    }
+    ((peer_count as f64).ln().ceil() as usize + 2).min(peer_count)
+}
+
+/// Forwarding chance after `duplicates` redundant copies of a message arrived
+pub fn forward_probability(base: f64, duplicates: u32) -> f64 {
+    base / (1.0 + duplicates as f64)
+}
+
+/// Weighted random sample of `k` distinct candidates
+pub fn select_forward_targets<T: Clone, R: Rng>(candidates: &[(T, f64)], k: usize, rng: &mut R) -> Vec<T> {
+    let mut pool: Vec<(T, f64)> = candidates.to_vec();
+    let mut chosen = Vec::with_capacity(k.min(pool.len()));
+
+    while chosen.len() < k && !pool.is_empty() {
+        let total: f64 = pool.iter().map(|(_, w)| w.max(f64::EPSILON)).sum();
+        let mut pick = rng.gen::<f64>() * total;
+        let mut index = pool.len() - 1;
+        for (i, (_, w)) in pool.iter().enumerate() {
+            pick -= w.max(f64::EPSILON);
+            if pick <= 0.0 {
+                index = i;
+                break;
+            }
+        }
+        chosen.push(pool.swap_remove(index).0);
+    }
+    chosen
+}
+
 pub struct GossipNode {
     node_id: String,
-    identity_keypair: Keypair,
-    known_peers: Arc<RwLock<HashMap<String, PublicKey>>>,
-    seen_messages: Arc<RwLock<HashSet<String>>>,
+    identity_keypair: SigningKey,
+    known_peers: Arc<RwLock<HashMap<String, VerifyingKey>>>,
+    peer_quality: Arc<RwLock<HashMap<String, PeerQuality>>>,
+    /// Message ids seen, with when their TTL runs out (Unix seconds)
+    seen_messages: Arc<RwLock<HashMap<String, u64>>>,
+    /// Redundant copies received per message id; pruned with `seen_messages`
+    duplicates: Arc<RwLock<HashMap<String, u32>>>,
     message_buffer: Arc<RwLock<Vec<GossipMessage>>>,
+    /// Forwarding chance before any redundancy is observed
     forward_probability: f64,
+    /// Time source for TTL checks
+    clock: SharedClock,
+    /// Packets with a longer ciphertext are dropped before decrypting
+    max_payload_bytes: usize,
 }
 
 impl GossipNode {
     pub fn new(node_id: String) -> Self {
-        let mut rng = rand::thread_rng();
-        let identity_keypair = Keypair::generate(&mut rng);
-        
+        let identity_keypair = SigningKey::generate(&mut rand::rngs::OsRng);
+
         Self {
             node_id,
             identity_keypair,
             known_peers: Arc::new(RwLock::new(HashMap::new())),
-            seen_messages: Arc::new(RwLock::new(HashSet::new())),
+            peer_quality: Arc::new(RwLock::new(HashMap::new())),
+            seen_messages: Arc::new(RwLock::new(HashMap::new())),
+            duplicates: Arc::new(RwLock::new(HashMap::new())),
             message_buffer: Arc::new(RwLock::new(Vec::new())),
-            forward_probability: 0.6,
+            forward_probability: 1.0,
+            clock: clock::system(),
+            max_payload_bytes: DEFAULT_MAX_PAYLOAD_BYTES,
         }
     }
 
-    pub async fn add_peer(&self, peer_id: String, pubkey: PublicKey) {
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_max_payload(mut self, bytes: usize) -> Self {
+        self.max_payload_bytes = bytes;
+        self
+    }
+
+    /// Public half of this node's identity, for peers to `add_peer` with
+    pub fn public_key(&self) -> VerifyingKey {
+        self.identity_keypair.verifying_key()
+    }
+
+    pub async fn add_peer(&self, peer_id: String, pubkey: VerifyingKey) {
         self.known_peers.write().await.insert(peer_id, pubkey);
     }
 
+    /// Update trust (0-100, as in the peer table) and RTT for a peer
+    pub async fn set_peer_quality(&self, peer_id: &str, trust_score: u8, rtt_ms: u32) {
+        self.peer_quality.write().await.insert(
+            peer_id.to_string(),
+            PeerQuality { trust: trust_score as f64 / 100.0, rtt_ms },
+        );
+    }
+
+    /// Known peers with their link quality, and how much traffic has been seen
+    pub async fn topology(&self) -> serde_json::Value {
+        let quality = self.peer_quality.read().await;
+        let mut peers: Vec<serde_json::Value> = self.known_peers.read().await
+            .keys()
+            .map(|id| {
+                let q = quality.get(id).copied().unwrap_or_default();
+                serde_json::json!({ "id": id, "trust": q.trust, "rtt_ms": q.rtt_ms })
+            })
+            .collect();
+        peers.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+        serde_json::json!({
+            "node_id": self.node_id,
+            "peers": peers,
+            "seen_messages": self.seen_messages.read().await.len(),
+            "duplicates": self.duplicates.read().await.values().sum::<u32>(),
+            "buffered": self.message_buffer.read().await.len(),
+        })
+    }
+
     pub fn sign_message(&self, message: &mut GossipMessage) -> Result<(), Box<dyn std::error::Error>> {
         let mut hasher = Sha256::new();
         hasher.update(&message.payload);
         hasher.update(message.timestamp.to_le_bytes());
-        hasher.update(message.ttl.to_le_bytes());
+        hasher.update([message.ttl.max_hops]);
+        hasher.update(message.ttl.expires_at.to_le_bytes());
         let digest = hasher.finalize();
         
         let signature = self.identity_keypair.sign(&digest);
@@ -73,36 +199,41 @@ impl GossipNode {
         Ok(())
     }
 
-    pub fn verify_signature(&self, message: &GossipMessage, pubkey: &PublicKey) -> bool {
+    pub fn verify_signature(&self, message: &GossipMessage, pubkey: &VerifyingKey) -> bool {
         let mut hasher = Sha256::new();
         hasher.update(&message.payload);
         hasher.update(message.timestamp.to_le_bytes());
-        hasher.update(message.ttl.to_le_bytes());
+        hasher.update([message.ttl.max_hops]);
+        hasher.update(message.ttl.expires_at.to_le_bytes());
         let digest = hasher.finalize();
         
-        if let Ok(signature) = ed25519_dalek::Signature::try_from(message.signature.as_slice()) {
-            pubkey.verify(&digest, &signature).is_ok()
+        if let Ok(signature) = Signature::try_from(message.signature.as_slice()) {
+            pubkey.verify_strict(&digest, &signature).is_ok()
         } else {
             false
         }
     }
 
-    pub fn encrypt_for_peer(&self, payload: &[u8], peer_pubkey: &PublicKey) -> Result<EncryptedGossipPacket, Box<dyn std::error::Error>> {
+    pub fn encrypt_for_peer(&self, payload: &[u8], peer_pubkey: &VerifyingKey) -> Result<EncryptedGossipPacket, Box<dyn std::error::Error>> {
         let mut rng = rand::thread_rng();
         
         // Generate ephemeral keypair
         let ephemeral_secret = StaticSecret::random_from_rng(&mut rng);
         let ephemeral_public = PublicKey::from(&ephemeral_secret);
         
-        // Derive shared secret
-        let shared_secret = ephemeral_secret.diffie_hellman(peer_pubkey);
+        // Derive shared secret with the X25519 form of the peer's identity key
+        let peer_exchange = PublicKey::from(peer_pubkey.to_montgomery().to_bytes());
+        let shared_secret = ephemeral_secret.diffie_hellman(&peer_exchange);
         let key = derive_aes_key(&shared_secret.to_bytes());
         
         // Encrypt payload
         let cipher = Aes256Gcm::new(&key.into());
-        let nonce = Nonce::from_slice(&rng.gen::<[u8; 12]>());
+        let nonce_bytes = rng.gen::<[u8; 12]>();
+        let nonce = Nonce::from_slice(&nonce_bytes);
         
-        let ciphertext = cipher.encrypt(nonce, payload)?;
+        let ciphertext = cipher
+            .encrypt(nonce, payload)
+            .map_err(|_| "Gossip encryption failed")?;
         
         Ok(EncryptedGossipPacket {
             ephemeral_pubkey: ephemeral_public.as_bytes().to_vec(),
@@ -112,19 +243,46 @@ impl GossipNode {
         })
     }
 
-    pub async fn receive_message(&self, packet: EncryptedGossipPacket) -> Result<Option<GossipMessage>, Box<dyn std::error::Error>> {
-        // Decrypt using our identity key
-        let shared_secret = self.identity_keypair.secret.expand_edwards()?.to_montgomery()?.to_bytes();
-        let key = derive_aes_key(&shared_secret);
+    pub async fn receive_message(&self, sender: &str, packet: EncryptedGossipPacket) -> Result<Option<GossipMessage>, Box<dyn std::error::Error>> {
+        // Size check first: decryption and deserialization both allocate
+        if packet.ciphertext.len() > self.max_payload_bytes {
+            warn!(
+                "Rejecting {}-byte gossip packet from {} (limit {})",
+                packet.ciphertext.len(), sender, self.max_payload_bytes
+            );
+            let mut quality = self.peer_quality.write().await;
+            let entry = quality.entry(sender.to_string()).or_default();
+            entry.trust = (entry.trust - OVERSIZE_PENALTY).max(0.0);
+            return Err("Gossip packet exceeds size limit".into());
+        }
+
+        // Decrypt using the X25519 form of our identity key
+        let ephemeral: [u8; 32] = packet.ephemeral_pubkey.as_slice()
+            .try_into()
+            .map_err(|_| "Malformed ephemeral key")?;
+        if packet.nonce.len() != 12 {
+            return Err("Malformed gossip nonce".into());
+        }
+        let secret = StaticSecret::from(self.identity_keypair.to_scalar_bytes());
+        let shared_secret = secret.diffie_hellman(&PublicKey::from(ephemeral));
+        let key = derive_aes_key(&shared_secret.to_bytes());
         let cipher = Aes256Gcm::new(&key.into());
         let nonce = Nonce::from_slice(&packet.nonce);
-        
-        let plaintext = cipher.decrypt(nonce, packet.ciphertext.as_ref())?;
-        let message: GossipMessage = bincode::deserialize(&plaintext)?;
+
+        let plaintext = cipher
+            .decrypt(nonce, packet.ciphertext.as_ref())
+            .map_err(|_| "Gossip decryption failed")?;
+        // Length prefixes inside the plaintext cannot claim more than the limit either
+        let message: GossipMessage = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(self.max_payload_bytes as u64)
+            .deserialize(&plaintext)?;
         
         // Check if we've seen this message
         let message_id = message.id.clone();
-        if self.seen_messages.read().await.contains(&message_id) {
+        if self.seen_messages.read().await.contains_key(&message_id) {
+            *self.duplicates.write().await.entry(message_id).or_default() += 1;
             return Ok(None);
         }
         
@@ -136,10 +294,14 @@ impl GossipNode {
         }
         
         // Mark as seen
-        self.seen_messages.write().await.insert(message_id);
+        let now = self.clock.unix_secs();
+        self.remember(message_id, message.ttl.expires_at, now).await;
         
         // Buffer for potential forwarding
-        if message.hops < 5 && message.ttl > std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs() {
+        if message.ttl.is_expired(message.hops, now) {
+            return Ok(None);
+        }
+        if message.ttl.can_forward(message.hops, now) {
             self.message_buffer.write().await.push(message.clone());
         }
         
@@ -147,25 +309,34 @@ impl GossipNode {
     }
 
     pub async fn forward_messages(&self) -> Vec<(String, EncryptedGossipPacket)> {
+        self.prune_seen(self.clock.unix_secs()).await;
         let mut forwards = Vec::new();
         let mut buffer = self.message_buffer.write().await;
         let peers = self.known_peers.read().await;
-        
+        let quality = self.peer_quality.read().await;
+        let duplicates = self.duplicates.read().await;
+
+        let candidates: Vec<(&String, f64)> = peers.keys()
+            .map(|id| (id, quality.get(id).copied().unwrap_or_default().weight()))
+            .collect();
+        let fanout = adaptive_fanout(candidates.len());
+
         let mut rng = rand::thread_rng();
-        
-        buffer.retain(|msg| {
-            if msg.hops >= 5 || msg.ttl <= std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() {
+        let now = self.clock.unix_secs();
+
+        buffer.retain_mut(|msg| {
+            if !msg.ttl.can_forward(msg.hops, now) {
                 return false;
             }
             
-            // Probabilistic forwarding
-            if rng.gen::<f64>() < self.forward_probability {
+            // Probabilistic forwarding, backing off as copies keep arriving
+            let redundancy = duplicates.get(&msg.id).copied().unwrap_or(0);
+            if rng.gen::<f64>() < forward_probability(self.forward_probability, redundancy) {
                 msg.hops += 1;
                 
-                // Send to random subset of peers
-                let peer_subset: Vec<_> = peers.iter().take(3).collect();
-                for (peer_id, pubkey) in peer_subset {
-                    if let Ok(packet) = self.encrypt_for_peer(&bincode::serialize(msg).unwrap(), pubkey) {
+                // Send to a trust/RTT-weighted random subset of peers
+                for peer_id in select_forward_targets(&candidates, fanout, &mut rng) {
+                    if let Ok(packet) = self.encrypt_for_peer(&bincode::serialize(msg).unwrap(), &peers[peer_id]) {
                         forwards.push((peer_id.clone(), packet));
                     }
                 }
@@ -177,33 +348,57 @@ impl GossipNode {
     }
 
     pub async fn broadcast(&self, payload: Vec<u8>, ttl_seconds: u64) -> Result<GossipMessage, Box<dyn std::error::Error>> {
+        let now = self.clock.unix_secs();
         let message_id = format!("{}-{}", self.node_id, std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_nanos());
         
         let mut message = GossipMessage {
             id: message_id,
             payload,
-            timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs(),
-            ttl: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs() + ttl_seconds,
+            timestamp: now,
+            ttl: Ttl::starting_at(now, DEFAULT_MAX_HOPS, ttl_seconds),
             origin: self.node_id.clone(),
             hops: 0,
             signature: vec![],
         };
         
         self.sign_message(&mut message)?;
-        self.seen_messages.write().await.insert(message.id.clone());
+        self.remember(message.id.clone(), message.ttl.expires_at, now).await;
         self.message_buffer.write().await.push(message.clone());
         
         Ok(message)
     }
+
+    async fn remember(&self, message_id: String, expires_at: u64, now: u64) {
+        if self.seen_messages.read().await.len() >= MAX_SEEN_MESSAGES {
+            self.prune_seen(now).await;
+        }
+        self.seen_messages.write().await.insert(message_id, expires_at);
+    }
+
+    /// Forget ids whose TTL has run out (an expired copy is dropped anyway),
+    /// then the closest to expiry until there is room; duplicate counts go
+    /// with them
+    async fn prune_seen(&self, now: u64) {
+        let mut seen = self.seen_messages.write().await;
+        seen.retain(|_, expires_at| *expires_at > now);
+        if seen.len() >= MAX_SEEN_MESSAGES {
+            let mut by_expiry: Vec<(u64, String)> = seen.iter().map(|(id, expires_at)| (*expires_at, id.clone())).collect();
+            by_expiry.sort_unstable();
+            let excess = seen.len() + 1 - MAX_SEEN_MESSAGES;
+            for (_, id) in by_expiry.into_iter().take(excess) {
+                seen.remove(&id);
+            }
+        }
+        self.duplicates.write().await.retain(|id, _| seen.contains_key(id));
+    }
 }
 
 fn derive_aes_key(shared_secret: &[u8; 32]) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.update(shared_secret);
-    hasher.update(b"EGP_KEY_DERIVATION");
-    let result = hasher.finalize();
+    // Gossip keys live apart from direct-message keys derived from the same secret
+    let hk = hkdf::Hkdf::<Sha256>::new(None, shared_secret);
     let mut key = [0u8; 32];
-    key.copy_from_slice(&result[..32]);
+    hk.expand(crate::crypto::GOSSIP_CONTEXT.as_bytes(), &mut key)
+        .expect("HKDF expand failed");
     key
 }
 
@@ -217,18 +412,176 @@ mod tests {
         let node2 = GossipNode::new("node2".to_string());
         
         // Exchange public keys
-        node1.add_peer("node2".to_string(), node2.identity_keypair.public).await;
-        node2.add_peer("node1".to_string(), node1.identity_keypair.public).await;
+        node1.add_peer("node2".to_string(), node2.public_key()).await;
+        node2.add_peer("node1".to_string(), node1.public_key()).await;
         
         // Broadcast message
         let message = node1.broadcast(b"test message".to_vec(), 300).await.unwrap();
         
         // Create packet for node2
-        let packet = node1.encrypt_for_peer(&bincode::serialize(&message).unwrap(), &node2.identity_keypair.public).unwrap();
+        let packet = node1.encrypt_for_peer(&bincode::serialize(&message).unwrap(), &node2.public_key()).unwrap();
         
         // Receive and verify
-        let received = node2.receive_message(packet).await.unwrap().unwrap();
+        let received = node2.receive_message("node1", packet).await.unwrap().unwrap();
         assert_eq!(received.payload, b"test message");
         assert_eq!(received.origin, "node1");
+
+        node2.set_peer_quality("node1", 80, 12).await;
+        let topology = node2.topology().await;
+        assert_eq!(topology["peers"], serde_json::json!([{ "id": "node1", "trust": 0.8, "rtt_ms": 12 }]));
+        assert_eq!(topology["seen_messages"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_packet_is_rejected_before_decrypting() {
+        let node = GossipNode::new("node1".to_string()).with_max_payload(1024);
+        node.set_peer_quality("mallory", 50, 0).await;
+
+        // Garbage ciphertext: reaching decryption would fail with a different error
+        let packet = EncryptedGossipPacket {
+            ephemeral_pubkey: vec![0; 32],
+            nonce: vec![0; 12],
+            ciphertext: vec![0; 1025],
+            mac: vec![],
+        };
+        let err = node.receive_message("mallory", packet).await.unwrap_err();
+        assert!(err.to_string().contains("size limit"), "{}", err);
+
+        let trust = node.peer_quality.read().await["mallory"].trust;
+        assert!((trust - (0.5 - OVERSIZE_PENALTY)).abs() < 1e-9);
+        assert_eq!(node.topology().await["seen_messages"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_length_prefix_cannot_claim_past_the_packet() {
+        let sender = GossipNode::new("node1".to_string());
+        let node = GossipNode::new("node2".to_string()).with_max_payload(1024);
+
+        // A small packet whose first string claims an enormous length
+        let packet = sender.encrypt_for_peer(&u64::MAX.to_le_bytes(), &node.public_key()).unwrap();
+        assert!(packet.ciphertext.len() <= 1024);
+        assert!(node.receive_message("node1", packet).await.is_err());
+        assert_eq!(node.topology().await["seen_messages"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_seen_cache_and_duplicates_are_pruned_on_expiry() {
+        use crate::clock::{Clock, MockClock};
+
+        let clock = MockClock::starting_now();
+        let node1 = GossipNode::new("node1".to_string()).with_clock(clock.clone());
+        let node2 = GossipNode::new("node2".to_string()).with_clock(clock.clone());
+        node2.add_peer("node1".to_string(), node1.public_key()).await;
+
+        let message = node1.broadcast(b"once".to_vec(), 60).await.unwrap();
+        let bytes = bincode::serialize(&message).unwrap();
+        for _ in 0..3 {
+            let packet = node1.encrypt_for_peer(&bytes, &node2.public_key()).unwrap();
+            node2.receive_message("node1", packet).await.unwrap();
+        }
+        assert_eq!(node2.topology().await["seen_messages"], 1);
+        assert_eq!(node2.topology().await["duplicates"], 2);
+
+        clock.advance(chrono::Duration::seconds(61));
+        node2.forward_messages().await;
+        assert_eq!(node2.topology().await["seen_messages"], 0);
+        assert!(node2.duplicates.read().await.is_empty());
+
+        // Past the cap, the ids closest to expiry make room
+        for i in 0..MAX_SEEN_MESSAGES as u64 + 5 {
+            node2.remember(format!("m{}", i), clock.unix_secs() + 100 + i, clock.unix_secs()).await;
+        }
+        let seen = node2.seen_messages.read().await;
+        assert!(seen.len() <= MAX_SEEN_MESSAGES);
+        assert!(!seen.contains_key("m0"));
+        assert!(seen.contains_key(&format!("m{}", MAX_SEEN_MESSAGES as u64 + 4)));
+    }
+
+    #[test]
+    fn test_fanout_scales_logarithmically() {
+        assert_eq!(adaptive_fanout(0), 0);
+        assert_eq!(adaptive_fanout(1), 1);
+        assert_eq!(adaptive_fanout(3), 3);
+        assert_eq!(adaptive_fanout(50), 6);
+        assert_eq!(adaptive_fanout(10_000), 12);
+    }
+
+    /// Delivery ratio and message count for one message from node 0
+    fn simulate(neighbors: &[Vec<usize>], trust: &[f64], adaptive: bool, rng: &mut impl Rng) -> (f64, usize) {
+        let n = neighbors.len();
+        let mut seen = vec![false; n];
+        let mut duplicates = vec![0u32; n];
+        let mut sent = 0;
+        seen[0] = true;
+        let mut buffered = vec![0];
+
+        while !buffered.is_empty() {
+            let mut next = Vec::new();
+            for node in buffered {
+                let targets = if adaptive {
+                    if node != 0 && rng.gen::<f64>() >= forward_probability(1.0, duplicates[node]) {
+                        continue;
+                    }
+                    let candidates: Vec<(usize, f64)> = neighbors[node].iter()
+                        .map(|&peer| (peer, PeerQuality { trust: trust[peer], rtt_ms: 0 }.weight()))
+                        .collect();
+                    select_forward_targets(&candidates, adaptive_fanout(candidates.len()), rng)
+                } else {
+                    neighbors[node].clone()
+                };
+                for target in targets {
+                    sent += 1;
+                    if seen[target] {
+                        duplicates[target] += 1;
+                    } else {
+                        seen[target] = true;
+                        next.push(target);
+                    }
+                }
+            }
+            buffered = next;
+        }
+
+        (seen.iter().filter(|s| **s).count() as f64 / n as f64, sent)
+    }
+
+    #[test]
+    fn test_adaptive_fanout_simulation_50_nodes() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let trials = 50;
+        let (mut adaptive_delivery, mut adaptive_sent, mut flood_sent) = (0.0, 0, 0);
+
+        for seed in 0..trials {
+            let mut rng = StdRng::seed_from_u64(seed);
+            // Ring (keeps the graph connected) plus random chords
+            let n = 50;
+            let mut neighbors = vec![std::collections::HashSet::new(); n];
+            for i in 0..n {
+                let next = (i + 1) % n;
+                neighbors[i].insert(next);
+                neighbors[next].insert(i);
+                for _ in 0..3 {
+                    let j = rng.gen_range(0..n);
+                    if j != i {
+                        neighbors[i].insert(j);
+                        neighbors[j].insert(i);
+                    }
+                }
+            }
+            let mut neighbors: Vec<Vec<usize>> = neighbors.into_iter().map(|s| s.into_iter().collect()).collect();
+            neighbors.iter_mut().for_each(|list| list.sort_unstable());
+            let trust: Vec<f64> = (0..n).map(|_| rng.gen()).collect();
+
+            let (delivery, sent) = simulate(&neighbors, &trust, true, &mut rng);
+            adaptive_delivery += delivery;
+            adaptive_sent += sent;
+            flood_sent += simulate(&neighbors, &trust, false, &mut rng).1;
+        }
+
+        let delivery_ratio = adaptive_delivery / trials as f64;
+        let overhead_ratio = adaptive_sent as f64 / flood_sent as f64;
+        assert!(delivery_ratio >= 0.9, "delivery ratio too low: {:.3}", delivery_ratio);
+        assert!(overhead_ratio <= 0.6, "too much overhead: {:.3}", overhead_ratio);
     }
 }
\ No newline at end of file
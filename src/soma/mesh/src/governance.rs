@@ -0,0 +1,235 @@
+//! DAO Governance: proposals, signed ballots and tallies (Layer 4)
+//!
+//! A node only controls its own wallet, so a passed proposal is not applied
+//! by whoever proposed it. Instead the proposal travels together with the
+//! signed ballots that passed it; every node re-tallies that claim against
+//! its own view of the electorate and enforces the outcome locally.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+use crate::crypto::{verify_signature, NodeSecrets};
+use crate::economy::ProposalType;
+
+/// Share of the eligible electorate that must approve: 2/3
+pub const PASS_NUMERATOR: usize = 2;
+pub const PASS_DENOMINATOR: usize = 3;
+
+/// Fewer eligible voters than this and nothing passes, so a node that only
+/// trusts one or two peers cannot be steered by them alone
+pub const MIN_ELECTORATE: usize = 3;
+
+/// How hard a passed `SlashNode` hits its target
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SlashSeverity {
+    #[default]
+    Minor,
+    Major,
+    /// Also blacklists the target
+    Severe,
+}
+
+impl SlashSeverity {
+    /// Subtracted from the target's trust score on every node
+    pub fn trust_penalty(self) -> i8 {
+        match self {
+            SlashSeverity::Minor => 20,
+            SlashSeverity::Major => 50,
+            SlashSeverity::Severe => 100,
+        }
+    }
+
+    /// Percent of its IPPC the target burns
+    pub fn burn_percent(self) -> u8 {
+        match self {
+            SlashSeverity::Minor => 10,
+            SlashSeverity::Major => 25,
+            SlashSeverity::Severe => 50,
+        }
+    }
+
+    pub fn blacklists(self) -> bool {
+        self == SlashSeverity::Severe
+    }
+}
+
+/// A governance proposal; its id is the hash of its contents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Proposal {
+    pub id: String,
+    pub kind: ProposalType,
+    pub proposer: String,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+impl Proposal {
+    pub fn new(kind: ProposalType, proposer: &str, created_at: u64) -> Self {
+        let id = proposal_id(&kind, proposer, created_at);
+        Self { id, kind, proposer: proposer.to_string(), created_at }
+    }
+
+    /// The id matches the contents
+    pub fn is_consistent(&self) -> bool {
+        self.id == proposal_id(&self.kind, &self.proposer, self.created_at)
+    }
+
+    /// Node that may not vote on this proposal (the target of a slash)
+    pub fn subject(&self) -> Option<&str> {
+        match &self.kind {
+            ProposalType::SlashNode { node_id, .. } => Some(node_id),
+            _ => None,
+        }
+    }
+}
+
+fn proposal_id(kind: &ProposalType, proposer: &str, created_at: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(kind).unwrap_or_default());
+    hasher.update(proposer.as_bytes());
+    hasher.update(created_at.to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// One node's signed vote on a proposal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ballot {
+    pub proposal_id: String,
+    pub voter: String,
+    pub approve: bool,
+    /// Ed25519 over `ballot_message` (hex)
+    pub signature: String,
+}
+
+impl Ballot {
+    pub fn sign(secrets: &NodeSecrets, voter: &str, proposal: &Proposal, approve: bool) -> Self {
+        let signature = secrets.sign(&ballot_message(&proposal.id, voter, approve));
+        Self {
+            proposal_id: proposal.id.clone(),
+            voter: voter.to_string(),
+            approve,
+            signature: hex::encode(signature),
+        }
+    }
+
+    pub fn verify(&self, signing_public: &[u8; 32]) -> bool {
+        let Ok(bytes) = hex::decode(&self.signature) else { return false };
+        let Ok(signature) = <[u8; 64]>::try_from(bytes.as_slice()) else { return false };
+        let message = ballot_message(&self.proposal_id, &self.voter, self.approve);
+        verify_signature(signing_public, &message, &signature).unwrap_or(false)
+    }
+}
+
+fn ballot_message(proposal_id: &str, voter: &str, approve: bool) -> Vec<u8> {
+    format!("ippoc-ballot:{}:{}:{}", proposal_id, voter, approve).into_bytes()
+}
+
+/// Outcome of counting ballots against an electorate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tally {
+    pub approvals: usize,
+    pub rejections: usize,
+    /// Electorate members allowed to vote on this proposal
+    pub eligible: usize,
+    pub passed: bool,
+}
+
+/// Count valid ballots. `electorate` maps node id to signing key; ballots
+/// from outsiders, the proposal's subject, bad signatures or repeat voters
+/// are ignored.
+pub fn tally(proposal: &Proposal, ballots: &[Ballot], electorate: &HashMap<String, [u8; 32]>) -> Tally {
+    let subject = proposal.subject();
+    let eligible = electorate.keys().filter(|id| Some(id.as_str()) != subject).count();
+
+    let mut seen = HashSet::new();
+    let (mut approvals, mut rejections) = (0, 0);
+    for ballot in ballots {
+        if ballot.proposal_id != proposal.id || Some(ballot.voter.as_str()) == subject {
+            continue;
+        }
+        let Some(key) = electorate.get(&ballot.voter) else { continue };
+        if !ballot.verify(key) || !seen.insert(ballot.voter.as_str()) {
+            continue;
+        }
+        if ballot.approve {
+            approvals += 1;
+        } else {
+            rejections += 1;
+        }
+    }
+
+    let passed = eligible >= MIN_ELECTORATE && approvals * PASS_DENOMINATOR >= eligible * PASS_NUMERATOR;
+    Tally { approvals, rejections, eligible, passed }
+}
+
+/// A passed proposal with the ballots that passed it, as gossiped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassedProposal {
+    pub proposal: Proposal,
+    pub ballots: Vec<Ballot>,
+}
+
+impl PassedProposal {
+    /// Re-tally under our own electorate; errors unless it passes
+    pub fn verify(&self, electorate: &HashMap<String, [u8; 32]>) -> Result<Tally> {
+        if !self.proposal.is_consistent() {
+            return Err(anyhow!("Proposal id does not match its contents"));
+        }
+        let result = tally(&self.proposal, &self.ballots, electorate);
+        if !result.passed {
+            return Err(anyhow!(
+                "Proposal {} did not pass: {}/{} approvals",
+                self.proposal.id, result.approvals, result.eligible
+            ));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_ignores_subject_outsiders_and_forgeries() {
+        let voters: Vec<(String, NodeSecrets)> = (0..4)
+            .map(|i| {
+                let secrets = NodeSecrets::generate();
+                (secrets.identity(&format!("n{}", i), "tool").id, secrets)
+            })
+            .collect();
+        let electorate: HashMap<String, [u8; 32]> = voters.iter()
+            .map(|(id, s)| (id.clone(), s.identity("", "").signing_public))
+            .collect();
+        let target = voters[3].0.clone();
+        let proposal = Proposal::new(
+            ProposalType::SlashNode { node_id: target, reason: "spam".into(), severity: SlashSeverity::Major },
+            &voters[0].0,
+            1_700_000_000,
+        );
+
+        let mut ballots = vec![
+            Ballot::sign(&voters[0].1, &voters[0].0, &proposal, true),
+            // The target votes against itself: not counted
+            Ballot::sign(&voters[3].1, &voters[3].0, &proposal, false),
+            // Duplicate
+            Ballot::sign(&voters[0].1, &voters[0].0, &proposal, true),
+        ];
+        // Forged: voter 1's name, signed with voter 0's key
+        ballots.push(Ballot::sign(&voters[0].1, &voters[1].0, &proposal, true));
+
+        let result = tally(&proposal, &ballots, &electorate);
+        assert_eq!((result.approvals, result.rejections, result.eligible), (1, 0, 3));
+        assert!(!result.passed);
+
+        ballots.push(Ballot::sign(&voters[1].1, &voters[1].0, &proposal, true));
+        assert!(tally(&proposal, &ballots, &electorate).passed);
+
+        let mut claim = PassedProposal { proposal, ballots };
+        assert!(claim.verify(&electorate).is_ok());
+        claim.proposal.created_at += 1;
+        assert!(claim.verify(&electorate).is_err());
+    }
+}
@@ -3,13 +3,48 @@
 
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs::File;
-use std::io::{Read, Write};
-use anyhow::{Result, anyhow};
+use std::io::Read;
+use anyhow::Result;
 use crate::crypto::{NodeSecrets, NodeIdentity};
-#[cfg(target_family = "unix")]
-use std::os::unix::fs::PermissionsExt;
+
+/// Why a stored identity could not be booted. Returned inside `anyhow::Error`;
+/// supervisors can `downcast_ref` to tell an operator problem from a disk one.
+#[derive(Debug)]
+pub enum IdentityError {
+    /// Sealed to other hardware
+    HardwareMismatch { sealed: String, current: String },
+    /// The key file or node directory could not be read or written
+    Storage { path: PathBuf, source: std::io::Error },
+    /// The key file was read but does not hold a usable identity
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl std::fmt::Display for IdentityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityError::HardwareMismatch { sealed, current } => {
+                write!(f, "Hardware mismatch! Identity is bound to {:?}, but running on {:?}", sealed, current)
+            }
+            IdentityError::Storage { path, source } => write!(f, "Identity storage error at {:?}: {}", path, source),
+            IdentityError::Corrupt { path, reason } => write!(f, "Corrupt identity at {:?}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for IdentityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IdentityError::Storage { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn storage(path: &Path) -> impl FnOnce(std::io::Error) -> IdentityError + '_ {
+    move |source| IdentityError::Storage { path: path.to_path_buf(), source }
+}
 
 /// Binds identity to hardware traits to prevent cloning
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -86,23 +121,34 @@ impl PersistentIdentity {
         })
     }
 
-    /// Load from disk and verify hardware binding
-    pub fn load_and_verify(path: &Path) -> Result<Self> {
-        let mut file = File::open(path)?;
+    /// Load from disk without checking the hardware binding (inspection, backups)
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut file = File::open(path).map_err(storage(path))?;
         let mut json = String::new();
-        file.read_to_string(&mut json)?;
+        file.read_to_string(&mut json).map_err(storage(path))?;
         
-        let persisted: Self = serde_json::from_str(&json)?;
+        let persisted: Self = serde_json::from_str(&json)
+            .map_err(|e| IdentityError::Corrupt { path: path.to_path_buf(), reason: e.to_string() })?;
+        persisted.secrets()
+            .map_err(|e| IdentityError::Corrupt { path: path.to_path_buf(), reason: e.to_string() })?;
+        Ok(persisted)
+    }
+
+    /// Whether the seal matches the hardware we are running on
+    pub fn matches_hardware(&self) -> Result<bool> {
+        Ok(self.hardware_seal == HardwareFingerprint::current()?.hash())
+    }
+
+    /// Load from disk and verify hardware binding
+    pub fn load_and_verify(path: &Path) -> Result<Self> {
+        let persisted = Self::load(path)?;
         
         // Verify hardware binding
         let current_fp = HardwareFingerprint::current()?;
         let current_hash = current_fp.hash();
         
         if persisted.hardware_seal != current_hash {
-            return Err(anyhow!(
-                "Hardware mismatch! Identity is bound to {:?}, but running on {:?}",
-                persisted.hardware_seal, current_hash
-            ));
+            return Err(IdentityError::HardwareMismatch { sealed: persisted.hardware_seal, current: current_hash }.into());
         }
 
         Ok(persisted)
@@ -111,23 +157,8 @@ impl PersistentIdentity {
     /// Save to disk with strict permissions
     pub fn save(&self, path: &Path) -> Result<()> {
         let json = serde_json::to_string_pretty(&self)?;
-        
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let mut file = File::create(path)?;
-        
-        // Set permissions to 0600 (Read/Write for owner only) on Unix
-        #[cfg(target_family = "unix")]
-        {
-            let mut perms = file.metadata()?.permissions();
-            perms.set_mode(0o600);
-            file.set_permissions(perms)?;
-        }
-
-        file.write_all(json.as_bytes())?;
-        Ok(())
+        // 0600 (Read/Write for owner only) on Unix
+        crate::persist::atomic_write_private(path, json.as_bytes())
     }
 
     /// Reconstruct NodeSecrets
@@ -154,29 +185,15 @@ mod hex_bytes {
 /// Main Entry Point: Sovereignty Bootloader
 /// 
 /// 1. Scans `data/nodes/` for any existing identity.
-/// 2. If found, verifies hardware seal. Pannics if mismatch.
+/// 2. If found, verifies hardware seal. Errors with `IdentityError` if it can't be used.
 /// 3. If none, generates new Identity, calculates NodeID, creates `data/nodes/<NodeID>/data`, and saves.
 /// 4. Returns the verified `PersistentIdentity` and its root path.
-pub fn load_or_create_identity(base_dir: &Path, role: &str, name: &str) -> Result<(PersistentIdentity, std::path::PathBuf)> {
-    let nodes_dir = base_dir.join("nodes");
-    
+pub fn load_or_create_identity(base_dir: &Path, role: &str, name: &str) -> Result<(PersistentIdentity, PathBuf)> {
     // 1. Scan for existing nodes
-    if nodes_dir.exists() {
-        for entry in std::fs::read_dir(&nodes_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                // Check for identity.key inside
-                let key_path = path.join("data").join("identity.key");
-                if key_path.exists() {
-                    println!("Found existing node identity at {key_path:?}");
-                    let identity = PersistentIdentity::load_and_verify(&key_path)?;
-                    println!("Hardware Binding Verified. Identity: {}", identity.identity.id);
-                    return Ok((identity, path));
-                }
-            }
-        }
+    if let Some(found) = find_existing_identity(base_dir)? {
+        return Ok(found);
     }
+    let nodes_dir = base_dir.join("nodes");
 
     // 2. No identity found. Genesis sequence.
     println!("No existing identity found. Initiating Genesis Sequence...");
@@ -190,10 +207,36 @@ pub fn load_or_create_identity(base_dir: &Path, role: &str, name: &str) -> Resul
     let key_path = data_dir.join("identity.key");
     
     // 3. Persist with strict permissions (0600)
-    new_identity.save(&key_path)?;
+    new_identity.save(&key_path).map_err(|e| match e.downcast::<std::io::Error>() {
+        Ok(io) => IdentityError::Storage { path: key_path.clone(), source: io }.into(),
+        Err(e) => e,
+    })?;
     
     println!("Genesis Complete. Sovereign Node Created: {node_id}");
     println!("Root: {node_root:?}");
-    
+
     Ok((new_identity, node_root))
 }
+
+/// The first hardware-verified identity under `base_dir/nodes`, without creating one
+pub fn find_existing_identity(base_dir: &Path) -> Result<Option<(PersistentIdentity, PathBuf)>> {
+    let nodes_dir = base_dir.join("nodes");
+    if !nodes_dir.exists() {
+        return Ok(None);
+    }
+    for entry in std::fs::read_dir(&nodes_dir).map_err(storage(&nodes_dir))? {
+        let entry = entry.map_err(storage(&nodes_dir))?;
+        let path = entry.path();
+        if path.is_dir() {
+            // Check for identity.key inside
+            let key_path = path.join("data").join("identity.key");
+            if key_path.exists() {
+                println!("Found existing node identity at {key_path:?}");
+                let identity = PersistentIdentity::load_and_verify(&key_path)?;
+                println!("Hardware Binding Verified. Identity: {}", identity.identity.id);
+                return Ok(Some((identity, path)));
+            }
+        }
+    }
+    Ok(None)
+}
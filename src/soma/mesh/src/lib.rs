@@ -9,15 +9,26 @@ mod peer;
 mod mesh;
 pub mod identity;
 pub mod transport;
+pub mod trust;
 
-pub use crypto::{NodeIdentity, SharedSecret, encrypt_message, decrypt_message};
-pub use messages::{AiMessage, MessageType, Thought, Broadcast};
-pub use peer::{Peer, PeerStatus};
-pub use mesh::{AiMesh, MeshConfig};
+pub use crypto::{CipherSuite, EphemeralExchange, NodeIdentity, NodeSecrets, SharedSecret, DIRECT_CONTEXT, GOSSIP_CONTEXT, direct_aad, encrypt_message, decrypt_message, node_id, verify_signature};
+pub use messages::{AiMessage, Broadcast, ContentId, EmbeddingPolicy, MessageNonce, MessageType, Thought, Ttl, WireError};
+pub use peer::{Peer, PeerStatus, RekeyPolicy, TrustedPeer, load_trusted_peers};
+pub use trust::TrustLevel;
+pub use transport::{KeepaliveConfig, LinkEvent, ReceiveError, ReceiveLimits, ReceiveSnapshot};
+pub use mesh::{AiMesh, MeshConfig, PeerUnreachable};
+pub use clock::{Clock, MockClock, SharedClock, SystemClock};
 
 /// Re-export common types
 pub mod prelude {
     pub use crate::{AiMesh, MeshConfig, AiMessage, MessageType, Peer, NodeIdentity};
 }
+pub mod audit;
+pub mod clock;
 pub mod economy;
+pub mod events;
+pub mod federation;
+pub mod gossip;
+pub mod governance;
 pub mod lifecycle;
+pub mod persist;
@@ -17,6 +17,8 @@ pub enum NodeState {
     Trusted,
     /// Inactive due to lack of funds or tasks.
     Dormant,
+    /// A critical background task died; running with reduced capabilities.
+    Degraded,
     /// System detects failure/unresponsiveness.
     Dying,
     /// Gracefully retired. Code and Memory preserved.
@@ -61,7 +63,7 @@ impl LifecycleManager {
 
     /// Check if node can perform a specific high-level capability
     pub fn can_reason(&self) -> bool {
-        matches!(self.state.current_state, NodeState::Active | NodeState::Trusted | NodeState::Probation)
+        matches!(self.state.current_state, NodeState::Active | NodeState::Trusted | NodeState::Probation | NodeState::Degraded)
     }
 
     pub fn can_vote(&self) -> bool {
@@ -92,6 +94,17 @@ impl LifecycleManager {
         }
     }
 
+    /// Mark the node Degraded; returns false if already degraded or terminal
+    pub fn degrade(&mut self) -> bool {
+        match self.state.current_state {
+            NodeState::Degraded | NodeState::Dying | NodeState::Archived | NodeState::Slashed => false,
+            _ => {
+                self.state.current_state = NodeState::Degraded;
+                true
+            }
+        }
+    }
+
     /// Force state transition (System/DAO override)
     pub fn set_state(&mut self, new_state: NodeState) {
         self.state.current_state = new_state;
@@ -3,17 +3,46 @@
 use anyhow::Result;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock, broadcast};
-use tracing::{info, warn, debug};
+use tokio::sync::{mpsc, oneshot, RwLock, broadcast};
+use tracing::{info, warn, debug, error};
 use uuid::Uuid;
-
-use sha2::Digest;
-use std::collections::{HashSet, VecDeque};
-use crate::crypto::{NodeSecrets, NodeIdentity, encrypt_message, decrypt_message, verify_signature};
-use crate::messages::{AiMessage, MessageType, Thought, Broadcast};
-use crate::peer::{Peer, PeerTable, ReputationManager};
+use chrono::{DateTime, Utc};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::audit::{AuditEvent, AuditLog};
+use crate::clock::{self, SharedClock};
+use crate::events::RestartPolicy;
+use crate::economy::ProposalType;
+use crate::federation::{CapabilityAttestation, Endorsement, TrustBundle};
+use crate::governance::{Ballot, PassedProposal, Proposal, Tally};
+use crate::crypto::{CipherSuite, EphemeralExchange, NodeSecrets, NodeIdentity, DIRECT_CONTEXT, direct_aad, encrypt_message, verify_signature};
+use crate::messages::{AiMessage, ContentId, EmbeddingPolicy, MessageNonce, MessageType, Thought, Broadcast, CAP_ZSTD, LOCAL_CAPABILITIES};
+use crate::transport::{KeepaliveConfig, LinkEvent, ReceiveBuffers, ReceiveLimits};
+use crate::peer::{Peer, PeerStatus, PeerTable, RekeyPolicy, ReputationManager, TrustLevel, load_trusted_peers};
 use std::path::PathBuf;
 use std::fs;
+use std::time::Duration;
+
+/// How long a persisted peer address gets before we fall back to discovery
+pub const RECONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `connect_to` waits for a dialed node to complete the handshake
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A direct send was refused because the peer's connection is known dead and
+/// a quick redial failed. The message was not queued; retry or pick another peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerUnreachable {
+    pub peer_id: String,
+    pub status: PeerStatus,
+}
+
+impl std::fmt::Display for PeerUnreachable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "peer {} is unreachable ({:?})", self.peer_id, self.status)
+    }
+}
+
+impl std::error::Error for PeerUnreachable {}
 
 /// Configuration for the AI mesh
 #[derive(Debug, Clone)]
@@ -32,6 +61,39 @@ pub struct MeshConfig {
     pub heartbeat_secs: u64,
     /// Enable encryption
     pub encrypted: bool,
+    /// AEAD for messages we seal; peers decrypt either suite
+    pub cipher_suite: CipherSuite,
+    /// Give up on a dialed node that hasn't authenticated within this
+    pub handshake_timeout: Duration,
+    /// Sign and require signatures over the whole envelope. Off only while
+    /// peers from before envelope signing remain: we then sign the payload
+    /// alone, as they expect, and accept either form.
+    pub strict_signing: bool,
+    /// Bind transports and announce on the network; off for offline tests
+    pub networking: bool,
+    /// Pinned System-trusted peers (defaults to `<node_root>/trusted_peers.toml`)
+    pub trusted_peers: Option<PathBuf>,
+    /// Bytes per day after which non-critical broadcasts pause (None = unmetered)
+    pub daily_byte_budget: Option<u64>,
+    /// Time source for the economy and replay window
+    pub clock: SharedClock,
+    /// Payloads at least this large are zstd-compressed for peers that negotiated it
+    pub compression_threshold: usize,
+    /// When per-peer session keys are rotated
+    pub rekey: RekeyPolicy,
+    /// QUIC keepalive and idle timeout
+    pub keepalive: KeepaliveConfig,
+    /// Caps on messages still being received
+    pub receive: ReceiveLimits,
+    /// Messages older than this are dropped as possible replays (seconds)
+    pub replay_window_secs: i64,
+    /// Largest peer clock skew we correct for once a handshake has measured
+    /// it, and the extra age allowed for handshakes themselves (seconds)
+    pub max_clock_skew_secs: i64,
+    /// Capabilities only routed to peers endorsed by a pinned authority
+    pub privileged_capabilities: Vec<String>,
+    /// An authority's endorsement of our role, attached to announcements
+    pub endorsement: Option<Endorsement>,
 }
 
 impl Default for MeshConfig {
@@ -44,14 +106,38 @@ impl Default for MeshConfig {
             max_peers: 100,
             heartbeat_secs: 30,
             encrypted: true,
+            cipher_suite: CipherSuite::default(),
+            handshake_timeout: HANDSHAKE_TIMEOUT,
+            strict_signing: true,
+            networking: true,
+            trusted_peers: None,
+            daily_byte_budget: None,
+            clock: clock::system(),
+            compression_threshold: crate::messages::COMPRESSION_THRESHOLD,
+            rekey: RekeyPolicy::default(),
+            keepalive: KeepaliveConfig::default(),
+            receive: ReceiveLimits::default(),
+            replay_window_secs: REPLAY_WINDOW_SECS,
+            max_clock_skew_secs: MAX_CLOCK_SKEW_SECS,
+            privileged_capabilities: Vec::new(),
+            endorsement: None,
         }
     }
 }
 
-/// The AI Mesh - manages P2P communication between AI nodes
+/// A `connect_to` waiting for the node it dialed to answer our SYN
+struct PendingDial {
+    addr: SocketAddr,
+    /// Gets the node's ID once it has authenticated
+    authenticated: oneshot::Sender<String>,
+}
+
+/// The AI Mesh - manages P2P communication between AI nodes. Clones are
+/// handles onto the same node.
+#[derive(Clone)]
 pub struct AiMesh {
     /// Our secrets
-    secrets: NodeSecrets,
+    secrets: Arc<NodeSecrets>,
     /// Our public identity
     identity: NodeIdentity,
     /// Configuration
@@ -62,6 +148,18 @@ pub struct AiMesh {
     reputation_manager: Arc<ReputationManager>,
     /// Replay cache to prevent replay attacks
     replay_cache: Arc<RwLock<ReplayCache>>,
+    /// Handshake nonces, kept for the longer age handshakes are allowed
+    handshake_replay: Arc<RwLock<ReplayCache>>,
+    /// Content already delivered, so retransmissions (fresh nonce, same content) are dropped
+    content_cache: Arc<RwLock<ReplayCache<ContentId>>>,
+    /// SYN nonces already answered, so a duplicated SYN can't restart a session
+    handshake_nonces: Arc<RwLock<ReplayCache<[u8; 16]>>>,
+    /// Our outstanding dials, by the nonce of the SYN we sent
+    pending_dials: Arc<RwLock<HashMap<[u8; 16], PendingDial>>>,
+    /// Caps the work unauthenticated sources can make us do
+    amplification: Arc<RwLock<AmplificationGuard>>,
+    /// Proposals already enforced, so re-gossiped claims apply once
+    enforced_proposals: Arc<RwLock<HashSet<String>>>,
     /// Outgoing message channel
     outbox: mpsc::Sender<AiMessage>,
     /// Incoming message broadcast
@@ -74,6 +172,12 @@ pub struct AiMesh {
    
     /// Networking transport (QUIC)
     transport: Arc<RwLock<Option<Arc<crate::transport::QuicTransport>>>>,
+
+    /// Bytes moved by the transport, per peer and in total
+    traffic: Arc<crate::transport::TrafficStats>,
+
+    /// Bytes held for messages still arriving, bounded node-wide
+    receive: Arc<ReceiveBuffers>,
     
     /// Root directory for this specific node (e.g. data/nodes/<ID>/)
     pub node_root: PathBuf,
@@ -83,20 +187,26 @@ pub struct AiMesh {
 
     /// Biological Lifecycle (Age, State)
     pub lifecycle: Arc<RwLock<crate::lifecycle::LifecycleManager>>,
+
+    /// Background task failures and lifecycle events
+    pub events: crate::events::EventBus,
+
+    /// Security decisions, appended to `<node_root>/audit.log`
+    audit: Arc<AuditLog>,
 }
 
 impl AiMesh {
-    /// Create a new AI mesh
-    pub fn new(config: MeshConfig) -> (Self, mpsc::Receiver<AiMessage>, broadcast::Receiver<AiMessage>) {
+    /// Create a new AI mesh. Fails if the stored identity can't be booted
+    /// (`identity::IdentityError` tells hardware, storage and corruption apart).
+    pub fn new(config: MeshConfig) -> Result<(Self, mpsc::Receiver<AiMessage>, broadcast::Receiver<AiMessage>)> {
         let (outbox_tx, outbox_rx) = mpsc::channel(100);
         let (inbox_tx, inbox_rx) = broadcast::channel(100); 
 
         // --- SOVEREIGN BOOT SEQUENCE (Phase 1.1) ---
         // 1. Load or Generate Identity (Hardware Bound)
-        let (persisted_identity, node_root) = crate::identity::load_or_create_identity(&config.data_dir, &config.role, &config.name)
-            .expect("CRITICAL: Sovereign Boot Failed! Hardware Mismatch or FS Error. HALTING.");
+        let (persisted_identity, node_root) = crate::identity::load_or_create_identity(&config.data_dir, &config.role, &config.name)?;
             
-        let secrets = persisted_identity.secrets().expect("Failed to derive secrets");
+        let secrets = persisted_identity.secrets()?;
         let identity = persisted_identity.identity;
 
         info!("Identity Authenticated: {} ({})", identity.name, identity.id);
@@ -110,51 +220,88 @@ impl AiMesh {
 
         // Economy (Phase 2)
         info!("Initializing Metabolism...");
-        let economy_controller = crate::economy::EconomyController::new(&identity.id, &node_root)
-            .expect("Failed to initialize Economy Controller");
+        let economy_controller = crate::economy::EconomyController::new(&identity.id, &node_root, &secrets)?
+            .with_clock(config.clock.clone());
         let economy = Arc::new(RwLock::new(economy_controller));
 
         // Lifecycle (Phase 6)
         info!("Initializing Biological Clock...");
         let lifecycle_manager = crate::lifecycle::LifecycleManager::new();
         let lifecycle = Arc::new(RwLock::new(lifecycle_manager));
+        let events = crate::events::EventBus::new(lifecycle.clone());
 
         let mut peer_table = PeerTable::new();
         // Load persisted reputation
         if let Ok(entries) = reputation_manager.load() {
             info!("Loaded {} peers from reputation DB", entries.len());
             for entry in entries {
-                let mut peer = Peer::new(NodeIdentity {
-                    id: entry.id.clone(),
-                    exchange_public: [0u8; 32], 
-                    signing_public: [0u8; 32],
-                    role: "unknown".into(),
-                    name: "unknown".into(),
-                });
-                peer.set_trust_level(entry.trust_level);
-                peer.update_trust(entry.trust_score as i8 - peer.trust_score as i8);
+                let mut peer = entry.into_peer();
+                if peer.needs_discovery() {
+                    warn!("Peer {} has no stored keys; waiting for re-discovery", peer.identity.id);
+                } else {
+                    let shared = secrets.derive_shared_for(&peer.identity.exchange_public, DIRECT_CONTEXT);
+                    peer.set_shared_secret(shared);
+                }
                 peer_table.upsert(peer);
             }
         }
 
+        // Pinned peers override whatever reputation remembers about them
+        let trusted_path = config.trusted_peers.clone()
+            .unwrap_or_else(|| node_root.join("trusted_peers.toml"));
+        match load_trusted_peers(&trusted_path) {
+            Ok(pinned) => {
+                if !pinned.is_empty() {
+                    info!("Pinned {} System-trusted peers from {:?}", pinned.len(), trusted_path);
+                }
+                for mut peer in pinned {
+                    let shared = secrets.derive_shared_for(&peer.identity.exchange_public, DIRECT_CONTEXT);
+                    peer.set_shared_secret(shared);
+                    peer_table.upsert(peer);
+                }
+            }
+            Err(e) => error!("Ignoring trusted peers file {:?}: {}", trusted_path, e),
+        }
+
+        let traffic = Arc::new(crate::transport::TrafficStats::new(config.daily_byte_budget));
+        let receive = Arc::new(ReceiveBuffers::new(config.receive));
+        let audit = Arc::new(AuditLog::new(&node_root, config.clock.clone()));
+        let replay_cache = ReplayCache::new(REPLAY_CACHE_CAPACITY, config.replay_window_secs, config.clock.clone());
+        let content_cache = ReplayCache::new(REPLAY_CACHE_CAPACITY, config.replay_window_secs, config.clock.clone());
+        // Handshakes may be up to the skew allowance older than other traffic
+        let handshake_window = config.replay_window_secs + config.max_clock_skew_secs;
+        let handshake_replay = ReplayCache::new(REPLAY_CACHE_CAPACITY, handshake_window, config.clock.clone());
+        let handshake_nonces = ReplayCache::new(REPLAY_CACHE_CAPACITY, handshake_window, config.clock.clone());
+        let amplification = AmplificationGuard::new(AMPLIFICATION_WINDOW_SECS, config.clock.clone());
+
         let mesh = Self {
-            secrets,
+            secrets: Arc::new(secrets),
             identity,
             config,
             peers: Arc::new(RwLock::new(peer_table)),
             reputation_manager,
-            replay_cache: Arc::new(RwLock::new(ReplayCache::new(1000))),
+            replay_cache: Arc::new(RwLock::new(replay_cache)),
+            handshake_replay: Arc::new(RwLock::new(handshake_replay)),
+            content_cache: Arc::new(RwLock::new(content_cache)),
+            handshake_nonces: Arc::new(RwLock::new(handshake_nonces)),
+            pending_dials: Arc::new(RwLock::new(HashMap::new())),
+            amplification: Arc::new(RwLock::new(amplification)),
+            enforced_proposals: Arc::new(RwLock::new(HashSet::new())),
             outbox: outbox_tx,
             inbox: inbox_tx,
             sequence: Arc::new(RwLock::new(0)),
             running: Arc::new(RwLock::new(false)),
             transport: Arc::new(RwLock::new(None)),
+            traffic,
+            receive,
             node_root,
             economy,
             lifecycle,
+            events,
+            audit,
         };
         
-        (mesh, outbox_rx, inbox_rx)
+        Ok((mesh, outbox_rx, inbox_rx))
     }
 
     /// Check if the node is allowed to perform an action based on its Lifecycle State
@@ -165,7 +312,7 @@ impl AiMesh {
         use crate::economy::ActionType;
         use crate::lifecycle::NodeState;
 
-        match state {
+        let decision = match state {
             NodeState::Newborn => {
                 match action {
                     ActionType::LlmInference { .. } => Ok(()), // Newborns can learn
@@ -175,7 +322,8 @@ impl AiMesh {
                 }
             },
             NodeState::Active | NodeState::Trusted => Ok(()), // Adults can do mostly everything (Trusted adds Voting/Spawning)
-            NodeState::Probation => {
+            // Degraded nodes keep working but don't mutate themselves
+            NodeState::Probation | NodeState::Degraded => {
                  // Probation: Restricted from sensitive ops
                  match action {
                     ActionType::EvolutionSim { .. } => Err(anyhow::anyhow!("Permission Denied: Probation nodes cannot evolve.")),
@@ -193,11 +341,25 @@ impl AiMesh {
             NodeState::Dying | NodeState::Slashed | NodeState::Archived => {
                 Err(anyhow::anyhow!("Node is DEAD/ARCHIVED. No actions allowed."))
             }
+        };
+        if let Err(e) = &decision {
+            self.audit.record(AuditEvent::PermissionDenied, None, format!("{:?}: {}", action, e));
         }
+        decision
+    }
+
+    /// The security audit trail
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
     }
 
     /// Start the networking layer (bind port)
     pub async fn start_networking(&self) -> Result<()> {
+        if !self.config.networking {
+            info!("AI Mesh networking disabled; running offline");
+            return Ok(());
+        }
+
         let mut running = self.running.write().await;
         if *running {
             return Ok(());
@@ -205,28 +367,63 @@ impl AiMesh {
         
         info!("Starting AI Mesh networking on port {}", self.config.port);
         
-        let inbox_tx = self.inbox.clone();
-        let (tx, mut rx) = mpsc::channel(100);
+        let (tx, rx) = mpsc::channel(100);
+        let (link_tx, mut link_rx) = mpsc::channel(16);
         
         // Bind QUIC transport
-        let transport = Arc::new(crate::transport::QuicTransport::bind(self.config.port, tx).await?);
+        let transport = Arc::new(
+            crate::transport::QuicTransport::bind(
+                self.config.port,
+                &self.config.keepalive,
+                tx,
+                link_tx,
+                self.traffic.clone(),
+                self.receive.clone(),
+            ).await?,
+        );
         
-        // Attempt WAN mapping (Fire and forget? No, let's log it)
-        let _ = crate::transport::QuicTransport::map_port_upnp(self.config.port).await;
+        // Attempt WAN mapping; an ephemeral port isn't worth mapping
+        if self.config.port != 0 {
+            let _ = crate::transport::QuicTransport::map_port_upnp(self.config.port).await;
+        }
 
         {
             let mut t = self.transport.write().await;
             *t = Some(transport.clone());
         }
         
-        // Spawn incoming message handler
+        // Spawn incoming message handler; the channel only closes if the transport dies.
+        // The receiver is shared so a restarted handler picks up where the last one died.
+        // Messages reach the inbox only after `handle_message_from` has checked them.
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        let mesh = self.clone();
+        self.events.spawn_restartable("mesh-incoming", RestartPolicy::default(), move || {
+            let rx = rx.clone();
+            let mesh = mesh.clone();
+            async move {
+                let mut rx = rx.lock().await;
+                while let Some((source, msg)) = rx.recv().await {
+                    let (id, sender) = (msg.id, msg.sender.clone());
+                    if let Err(e) = mesh.handle_message_from(Some(source), msg).await {
+                        warn!("Dropping message {} from {} ({}): {}", id, sender, source, e);
+                    }
+                }
+                Err(anyhow::anyhow!("QUIC transport channel closed"))
+            }
+        });
+        
+        // Dead connections reported by the transport's idle timeout
+        let peers = self.peers.clone();
         tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                 let _ = inbox_tx.send(msg);
+            while let Some(event) = link_rx.recv().await {
+                mark_link_lost(&peers, &event).await;
             }
         });
         
         *running = true;
+        drop(running);
+
+        self.reconnect_known_peers().await;
         Ok(())
     }
 
@@ -254,50 +451,215 @@ impl AiMesh {
         peers.upsert(peer);
     }
 
-    /// Connect to a peer by address
-    pub async fn connect_to(&self, addr: SocketAddr) -> Result<()> {
+    /// Where the transport listens, once networking has started
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        let transport = self.transport.read().await.clone()?;
+        transport.local_addr().ok()
+    }
+
+    /// Dial a node by address and authenticate it: announce ourselves, send
+    /// a SYN and wait for its SYN-ACK, all within `handshake_timeout`. The
+    /// node joins the peer table only once it has proven it holds its keys.
+    /// Returns its node ID.
+    pub async fn connect_to(&self, addr: SocketAddr) -> Result<String> {
+        let transport = self.transport.read().await.clone()
+            .ok_or_else(|| anyhow::anyhow!("Cannot dial {addr}: networking is not running"))?;
         info!("Connecting to peer at {}", addr);
-        // In production, this would:
-        // 1. Open QUIC connection
-        // 2. Exchange identities
-        // 3. Perform key exchange
-        // 4. Add to peer table
-        Ok(())
+
+        let mut nonce = [0u8; 16];
+        getrandom::getrandom(&mut nonce)?;
+        let (authenticated, answer) = oneshot::channel();
+        self.pending_dials.write().await.insert(nonce, PendingDial { addr, authenticated });
+
+        let dial = async {
+            transport.connect(addr).await?;
+            transport.send(addr, AiMessage::discovery(&self.identity.id, self.discovery_info())).await?;
+
+            let hs = crate::messages::HandshakeMessage {
+                kind: crate::messages::HandshakeKind::Syn,
+                exchange_public: self.identity.exchange_public,
+                signing_public: self.identity.signing_public,
+                nonce,
+                challenge: None,
+                capabilities: LOCAL_CAPABILITIES,
+            };
+            let mut syn = AiMessage::handshake(&self.identity.id, None, &hs);
+            self.sign_message(&mut syn);
+            transport.send(addr, syn).await?;
+
+            answer.await.map_err(|_| anyhow::anyhow!("Handshake with {addr} abandoned"))
+        };
+        let result = tokio::time::timeout(self.config.handshake_timeout, dial).await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Handshake with {addr} timed out after {:?}", self.config.handshake_timeout)));
+
+        self.pending_dials.write().await.remove(&nonce);
+        result
+    }
+
+    /// Dial trusted peers at their persisted addresses and re-authenticate
+    /// them. A peer that does not answer within `handshake_timeout` loses its
+    /// stale address and is left to mDNS discovery. Returns the peers reached.
+    pub async fn reconnect_known_peers(&self) -> usize {
+        let targets: Vec<(String, SocketAddr)> = self.peers.read().await
+            .peers
+            .values()
+            .filter(|p| p.trust_level >= TrustLevel::Trusted && !p.needs_discovery())
+            .filter_map(|p| p.address.map(|addr| (p.identity.id.clone(), addr)))
+            .collect();
+
+        let mut reached = 0;
+        for (id, addr) in targets {
+            match self.connect_to(addr).await {
+                Ok(found) if found == id => reached += 1,
+                Ok(found) => self.forget_address(&id, addr, &format!("answered as {found}")).await,
+                Err(e) => self.forget_address(&id, addr, &e.to_string()).await,
+            }
+        }
+        reached
     }
 
-    /// Send a thought to the mesh
-    pub async fn send_thought(&self, thought: Thought) -> Result<()> {
+    /// React to a connection-level event from the transport
+    pub async fn handle_link_event(&self, event: LinkEvent) {
+        mark_link_lost(&self.peers, &event).await;
+    }
+
+    async fn forget_address(&self, peer_id: &str, addr: SocketAddr, reason: &str) {
+        warn!("Reconnect to {} at {} failed ({}); waiting for discovery", peer_id, addr, reason);
+        if let Some(peer) = self.peers.write().await.peers.get_mut(peer_id) {
+            peer.address = None;
+            peer.status = PeerStatus::Disconnected;
+        }
+    }
+
+    /// Whether `signature` on `msg` is by `signing_public`: over the whole
+    /// envelope, or (unless strict) the payload alone
+    fn signature_valid(&self, msg: &AiMessage, signing_public: &[u8; 32], signature: &[u8; 64]) -> Result<bool> {
+        Ok(verify_signature(signing_public, &msg.signing_bytes(), signature)?
+            || (!self.config.strict_signing && verify_signature(signing_public, &msg.payload, signature)?))
+    }
+
+    /// Sign as this node: the whole envelope, or just the payload for legacy
+    /// peers. Run last, after compression and encryption.
+    fn sign_message(&self, msg: &mut AiMessage) {
+        let signed = if self.config.strict_signing { msg.signing_bytes() } else { msg.payload.clone() };
+        msg.signature = hex::encode(self.secrets.sign(&signed));
+    }
+
+    /// Send a thought to the mesh. Use `EmbeddingPolicy::Strip` unless every
+    /// receiver needs the vector; direct memory sync carries it regardless.
+    pub async fn send_thought(&self, thought: Thought, embeddings: EmbeddingPolicy) -> Result<()> {
+        if self.paused_for_budget("thought") {
+            return Ok(());
+        }
         let seq = self.next_sequence().await;
-        let mut msg = AiMessage::thought(&self.identity.id, &thought, seq);
+        let mut msg = AiMessage::thought(&self.identity.id, &thought.for_transport(embeddings), seq);
+        self.maybe_compress(&mut msg).await?;
         
-        // Sign the message
-        msg.signature = hex::encode(self.secrets.sign(&msg.payload));
+        // Sign the message (compressed bytes, as they travel)
+        self.sign_message(&mut msg);
         
         self.outbox.send(msg).await?;
         Ok(())
     }
 
     /// Broadcast a message to all peers
-    pub async fn broadcast(&self, broadcast: Broadcast) -> Result<()> {
+    pub async fn broadcast(&self, broadcast: Broadcast, embeddings: EmbeddingPolicy) -> Result<()> {
+        if self.paused_for_budget("broadcast") {
+            return Ok(());
+        }
         let seq = self.next_sequence().await;
-        let mut msg = AiMessage::broadcast(&self.identity.id, &broadcast, seq);
+        let mut msg = AiMessage::broadcast(&self.identity.id, &broadcast.for_transport(embeddings), seq);
+        self.maybe_compress(&mut msg).await?;
         
-        msg.signature = hex::encode(self.secrets.sign(&msg.payload));
+        self.sign_message(&mut msg);
         
         self.outbox.send(msg).await?;
         Ok(())
     }
 
-    /// Send a direct message to a specific peer
+    /// Compress a fan-out message when every connected peer negotiated zstd.
+    /// Direct payloads are encrypted first and would not shrink.
+    async fn maybe_compress(&self, msg: &mut AiMessage) -> Result<()> {
+        let all_support = {
+            let peers = self.peers.read().await;
+            let mut connected = peers.connected().peekable();
+            connected.peek().is_some() && connected.all(|p| p.supports(CAP_ZSTD))
+        };
+        if all_support {
+            msg.compress(self.config.compression_threshold)?;
+        }
+        Ok(())
+    }
+
+    /// Non-critical broadcasts stop once the daily byte budget is spent
+    fn paused_for_budget(&self, kind: &str) -> bool {
+        let over = self.traffic.over_budget();
+        if over {
+            debug!("Daily byte budget exhausted; dropping outgoing {}", kind);
+        }
+        over
+    }
+
+    /// Bytes moved per peer and in total
+    pub fn traffic_stats(&self) -> crate::transport::TrafficSnapshot {
+        self.traffic.snapshot()
+    }
+
+    /// Incomplete inbound messages: bytes held, evictions and abandoning senders
+    pub fn receive_stats(&self) -> crate::transport::ReceiveSnapshot {
+        self.receive.snapshot()
+    }
+
+    /// Fail fast on a peer whose link is known dead (keepalive idle timeout,
+    /// blocked, or awaiting discovery), after one redial within `RECONNECT_TIMEOUT`
+    async fn ensure_reachable(&self, peer_id: &str) -> Result<()> {
+        let (status, address) = {
+            let peers = self.peers.read().await;
+            let peer = peers.get(peer_id)
+                .ok_or_else(|| anyhow::anyhow!("Peer not found: {peer_id}"))?;
+            (peer.status.clone(), peer.address)
+        };
+        match status {
+            PeerStatus::Disconnected => {}
+            PeerStatus::Blocked | PeerStatus::NeedsDiscovery => {
+                return Err(PeerUnreachable { peer_id: peer_id.to_string(), status }.into());
+            }
+            _ => return Ok(()),
+        }
+
+        let transport = self.transport.read().await.clone();
+        if let (Some(transport), Some(addr)) = (transport, address) {
+            match tokio::time::timeout(RECONNECT_TIMEOUT, transport.connect(addr)).await {
+                Ok(Ok(())) => {
+                    if let Some(peer) = self.peers.write().await.peers.get_mut(peer_id) {
+                        peer.status = PeerStatus::Connected;
+                    }
+                    return Ok(());
+                }
+                Ok(Err(e)) => debug!("Redial of {} at {} failed: {}", peer_id, addr, e),
+                Err(_) => debug!("Redial of {} at {} timed out", peer_id, addr),
+            }
+        }
+        Err(PeerUnreachable { peer_id: peer_id.to_string(), status }.into())
+    }
+
+    /// Send a direct message to a specific peer. Errors with `PeerUnreachable`
+    /// instead of queueing into a dead connection.
     pub async fn send_direct(&self, recipient: &str, content: serde_json::Value) -> Result<()> {
-        let peers = self.peers.read().await;
-        let peer = peers.get(recipient)
+        self.ensure_reachable(recipient).await?;
+
+        let mut peers = self.peers.write().await;
+        let peer = peers.get_mut(recipient)
             .ok_or_else(|| anyhow::anyhow!("Peer not found: {recipient}"))?;
         
         // Encrypt if we have a shared secret
+        let mut rekey = false;
         let payload = if let Some(secret) = peer.shared_secret() {
             let plaintext = serde_json::to_vec(&content)?;
-            encrypt_message(secret, &plaintext)?
+            let sealed = encrypt_message(secret, self.config.cipher_suite, &plaintext, &direct_aad(&self.identity.id, recipient))?;
+            rekey = peer.record_sent(self.config.clock.unix_secs(), &self.config.rekey)
+                && peer.trust_level >= TrustLevel::Authenticated;
+            sealed
         } else {
             serde_json::to_vec(&content)?
         };
@@ -307,52 +669,173 @@ impl AiMesh {
         let seq = self.next_sequence().await;
         let mut msg = AiMessage::direct(&self.identity.id, recipient, content, seq);
         msg.payload = payload;
-        msg.signature = hex::encode(self.secrets.sign(&msg.payload));
+        self.sign_message(&mut msg);
         
         self.outbox.send(msg).await?;
+
+        // The key has carried enough: start rotating it
+        if rekey {
+            self.rekey_peer(recipient).await?;
+        }
+        Ok(())
+    }
+
+    /// Offer the peer a fresh ephemeral DH exchange. We switch to the new key
+    /// on the answer, the peer once we use it; both keep the old one until
+    /// the other side has switched, then for the overlap. An offer left
+    /// unanswered is made again by the next send after `retry_secs`.
+    pub async fn rekey_peer(&self, peer_id: &str) -> Result<()> {
+        let exchange = EphemeralExchange::generate();
+        let mut nonce = [0u8; 16];
+        getrandom::getrandom(&mut nonce)?;
+        let hs = crate::messages::HandshakeMessage {
+            kind: crate::messages::HandshakeKind::Rekey,
+            exchange_public: exchange.public,
+            signing_public: self.identity.signing_public,
+            nonce,
+            challenge: None,
+            capabilities: LOCAL_CAPABILITIES,
+        };
+
+        {
+            let mut peers = self.peers.write().await;
+            let peer = peers.get_mut(peer_id)
+                .filter(|p| p.shared_secret().is_some())
+                .ok_or_else(|| anyhow::anyhow!("No session with {peer_id} to re-key"))?;
+            peer.start_rekey(exchange, nonce, self.config.clock.unix_secs() + self.config.rekey.retry_secs);
+        }
+
+        let mut msg = AiMessage::handshake(&self.identity.id, Some(peer_id), &hs);
+        self.sign_message(&mut msg);
+        self.outbox.send(msg).await?;
+        debug!("Re-keying session with {}", peer_id);
         Ok(())
     }
 
     /// Handle an incoming message
     pub async fn handle_message(&self, msg: AiMessage) -> Result<()> {
+        self.handle_message_from(None, msg).await
+    }
+
+    /// Handle a message whose transport-level source address is known.
+    /// Unauthenticated traffic is budgeted per address (per claimed sender
+    /// without one) until the handshake completes.
+    pub async fn handle_message_from(&self, source: Option<SocketAddr>, msg: AiMessage) -> Result<()> {
         debug!("Received message from {} (type: {:?})", msg.sender, msg.msg_type);
         
         // 1. Reputation Filter (PRD 09)
+        let authenticated;
         {
             let peers = self.peers.read().await;
-            if let Some(peer) = peers.get(&msg.sender) {
-                if peer.trust_level == crate::peer::TrustLevel::Blacklisted || peer.trust_score < 10 {
+            let known = peers.get(&msg.sender);
+            authenticated = known
+                .filter(|p| p.trust_level >= TrustLevel::Authenticated)
+                .map(|p| (p.identity.signing_public, p.address));
+            if let Some(peer) = known {
+                let pinned = peer.trust_level == TrustLevel::System;
+                if !pinned && (peer.trust_level == TrustLevel::Blacklisted || peer.trust_score < 10) {
                     warn!("Rejecting message from low-reputation peer: {}", msg.sender);
                     return Ok(());
                 }
+                // Placeholder keys can't verify anything; only a fresh announcement helps
+                if peer.needs_discovery() && msg.msg_type != MessageType::Discovery {
+                    warn!("Dropping message from {}: peer needs re-discovery", msg.sender);
+                    return Ok(());
+                }
             }
         }
 
-        // Verify signature
-        {
-            let peers = self.peers.read().await;
-            if let Some(peer) = peers.get(&msg.sender) {
-                if let Ok(sig_bytes) = hex::decode(&msg.signature) {
-                    if sig_bytes.len() == 64 {
-                        let mut sig_arr = [0u8; 64];
-                        sig_arr.copy_from_slice(&sig_bytes);
-                        if !verify_signature(&peer.identity.signing_public, &msg.payload, &sig_arr)? {
-                            warn!("Invalid signature from peer {}", msg.sender);
-                            return Ok(());
-                        }
-                    }
+        // 2. Anti-amplification: strangers get a small budget per window.
+        // Claiming an authenticated peer's id earns no exemption by itself:
+        // the message must come from the address that peer handshaked from
+        // or carry its signature (checked now rather than after the budget).
+        let mut verified = false;
+        let exempt = match authenticated {
+            Some((_, address)) if source.is_some() && source == address => true,
+            Some((signing_public, _)) if msg.msg_type != MessageType::Discovery => {
+                if !self.sender_signed(&msg, &signing_public)? {
+                    return Ok(());
                 }
+                verified = true;
+                true
+            }
+            _ => false,
+        };
+        if !exempt {
+            let key = source.map(|a| a.to_string()).unwrap_or_else(|| msg.sender.clone());
+            if !self.amplification.write().await.admit(&key, msg.payload.len()) {
+                return Ok(());
             }
         }
 
+        // Verify signature. Everything but a discovery announcement must be
+        // signed; strangers may only open a handshake, signed by the key
+        // their id derives from.
+        if msg.msg_type != MessageType::Discovery && !verified {
+            let known = self.peers.read().await.get(&msg.sender).map(|p| p.identity.signing_public);
+            let Some(signing_public) = known.or_else(|| handshake_signer(&msg)) else {
+                debug!("Dropping {:?} {} from unknown sender {}", msg.msg_type, msg.id, msg.sender);
+                return Ok(());
+            };
+            if !self.sender_signed(&msg, &signing_public)? {
+                return Ok(());
+            }
+        }
+
+        // Signature covered the wire bytes; inflate only now
+        let mut msg = msg;
+        if let Err(e) = msg.decompress() {
+            warn!("Dropping message {} from {}: bad compressed payload: {}", msg.id, msg.sender, e);
+            return Ok(());
+        }
+
+        // Judge age by the sender's clock as measured in its handshake.
+        // Handshakes prove freshness by challenge, so they get the full skew
+        // allowance: their cache's window is widened by it rather than the
+        // timestamp shifted, so their nonces are remembered for all of it.
+        let max_skew = self.config.max_clock_skew_secs;
+        let handshake = msg.msg_type == MessageType::Handshake;
+        let skew = if handshake {
+            0
+        } else {
+            self.peers.read().await.get(&msg.sender)
+                .and_then(|p| p.clock_offset_secs)
+                .map_or(0, |offset| offset.clamp(-max_skew, max_skew))
+        };
+        let sent_at = msg.timestamp - chrono::Duration::seconds(skew);
+
         // Verify nonce (Replay Protection)
         {
-            let mut cache = self.replay_cache.write().await;
+            let mut cache = if handshake { &self.handshake_replay } else { &self.replay_cache }.write().await;
+            // Nonces older than the window are forgotten, so such messages can't be checked
+            if cache.is_stale(sent_at) {
+                let behind = (self.config.clock.now() - msg.timestamp).num_seconds();
+                if !cache.is_stale(msg.timestamp + chrono::Duration::seconds(max_skew)) {
+                    warn!(
+                        "Dropping message {} from {}: its clock looks {}s behind ours (window {}s). \
+                         A completed handshake measures the skew and corrects for it.",
+                        msg.id, msg.sender, behind, self.config.replay_window_secs
+                    );
+                } else {
+                    warn!("Dropping stale message {} from {} sent at {}", msg.id, msg.sender, msg.timestamp);
+                }
+                return Ok(());
+            }
             if !cache.check_and_add(msg.nonce) {
                 warn!("Replay attack detected or duplicate nonce: {} from {}", msg.nonce, msg.sender);
+                self.audit.record(AuditEvent::ReplayDetected, Some(msg.sender.as_str()), format!("nonce {} reused by {:?} {}", msg.nonce, msg.msg_type, msg.id));
                 return Ok(());
             }
         }
+
+        // Retransmitted content. Control traffic (discovery, handshakes) is
+        // legitimately repeated, so only the content pipeline is deduplicated.
+        if matches!(msg.msg_type, MessageType::Thought | MessageType::Broadcast | MessageType::Direct)
+            && !self.content_cache.write().await.check_and_add(msg.content_id())
+        {
+            debug!("Dropping retransmitted {:?} {} from {}", msg.msg_type, msg.content_id(), msg.sender);
+            return Ok(());
+        }
         
         // Match message type
         let result = match msg.msg_type {
@@ -360,6 +843,11 @@ impl AiMesh {
                 self.handle_discovery(&msg).await
             }
             MessageType::Thought | MessageType::Broadcast => {
+                // Relayed too far or for too long: drop rather than keep it circulating
+                if msg.is_expired(self.config.clock.unix_secs()) {
+                    debug!("Dropping expired {:?} {} after {} hops", msg.msg_type, msg.id, msg.hops);
+                    return Ok(());
+                }
                 // Forward to inbox
                 let _ = self.inbox.send(msg);
                 Ok(())
@@ -375,7 +863,15 @@ impl AiMesh {
             }
             MessageType::Handshake => {
                 // Secure handshake bridge
-                self.handle_handshake(&msg).await
+                self.handle_handshake(&msg, source).await
+            }
+            MessageType::Unknown => {
+                debug!("Dropping message {} from {}: type unknown to this build", msg.id, msg.sender);
+                return Ok(());
+            }
+            MessageType::ProposalPassed => {
+                let passed: PassedProposal = serde_json::from_slice(&msg.payload)?;
+                self.enforce_proposal(&passed).await.map(|_| ())
             }
             _ => {
                 // Forward to inbox for application handling
@@ -384,14 +880,28 @@ impl AiMesh {
             }
         };
 
-        // Persist reputation if it changed (optimization: only save some times)
-        // For now, let's save on every message to ensure persistence
+        // Persist reputation; an unchanged table is not rewritten
         let peers = self.peers.read().await;
         let _ = self.reputation_manager.save(&peers.peers);
 
         result
     }
 
+    /// Whether `msg` carries a valid signature by `signing_public`; failures are audited
+    fn sender_signed(&self, msg: &AiMessage, signing_public: &[u8; 32]) -> Result<bool> {
+        let Some(sig_arr) = parse_signature(&msg.signature) else {
+            warn!("Missing or malformed signature from peer {}", msg.sender);
+            self.audit.record(AuditEvent::SignatureRejected, Some(msg.sender.as_str()), format!("malformed signature on {:?} {}", msg.msg_type, msg.id));
+            return Ok(false);
+        };
+        if !self.signature_valid(msg, signing_public, &sig_arr)? {
+            warn!("Invalid signature from peer {}", msg.sender);
+            self.audit.record(AuditEvent::SignatureRejected, Some(msg.sender.as_str()), format!("invalid signature on {:?} {}", msg.msg_type, msg.id));
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
     async fn handle_discovery(&self, msg: &AiMessage) -> Result<()> {
         let info: serde_json::Value = serde_json::from_slice(&msg.payload)?;
         info!("Discovery from peer: {:?}", info.get("name"));
@@ -402,26 +912,15 @@ impl AiMesh {
             info.get("name").and_then(|v| v.as_str()),
             info.get("role").and_then(|v| v.as_str()),
         ) {
-            let exchange_public = info.get("exchange_public")
-                .and_then(|v| v.as_str())
-                .and_then(|s| hex::decode(s).ok())
-                .map(|b| {
-                    let mut arr = [0u8; 32];
-                    arr.copy_from_slice(&b);
-                    arr
-                })
-                .unwrap_or([0u8; 32]);
-            
-            let signing_public = info.get("signing_public")
-                .and_then(|v| v.as_str())
-                .and_then(|s| hex::decode(s).ok())
-                .map(|b| {
-                    let mut arr = [0u8; 32];
-                    arr.copy_from_slice(&b);
-                    arr
-                })
-                .unwrap_or([0u8; 32]);
-            
+            // A peer with defaulted keys would derive a bogus shared secret
+            let exchange_public = parse_public_key(&info, "exchange_public")?;
+            let signing_public = parse_public_key(&info, "signing_public")?;
+            // Ids are derived from the signing key; a mismatch is someone
+            // claiming another node's id (and the reputation that goes with it)
+            if crate::crypto::node_id(&signing_public) != id {
+                return Err(anyhow::anyhow!("Discovery rejected: id {} does not match its signing_public", id));
+            }
+
             let identity = NodeIdentity {
                 id: id.to_string(),
                 exchange_public,
@@ -430,10 +929,50 @@ impl AiMesh {
                 name: name.to_string(),
             };
             
+            let capabilities = {
+                let peers = self.peers.read().await;
+                self.attested_capabilities(&identity, info.get("attestation"), &peers)
+            };
+
+            // Discovery is unsigned, so for a peer we already hold keys for it
+            // may only refresh what the peer says about itself. Standing,
+            // session keys, clock offset and address were earned or observed
+            // elsewhere and stay as they are.
+            {
+                let mut peers = self.peers.write().await;
+                if let Some(known) = peers.get_mut(id).filter(|p| !p.needs_discovery()) {
+                    if known.identity.signing_public != identity.signing_public
+                        || known.identity.exchange_public != identity.exchange_public
+                    {
+                        return Err(anyhow::anyhow!("Discovery rejected: keys for known peer {} do not match", id));
+                    }
+                    if known.trust_level != TrustLevel::System {
+                        known.identity.name = identity.name;
+                        known.identity.role = identity.role;
+                        known.capabilities = capabilities;
+                    }
+                    return Ok(());
+                }
+            }
+
+            // Addresses are only recorded from where a handshake actually
+            // came from; an announced one could point anywhere
             let mut peer = Peer::new(identity);
+            peer.capabilities = capabilities;
+
+            // Keep the reputation earned before a restart that lost this peer's
+            // keys; the id check above ties it to the same signing key
+            if let Some(known) = self.peers.read().await.get(id).filter(|p| p.needs_discovery()) {
+                peer.set_trust_level(known.trust_level);
+                peer.trust_score = known.trust_score;
+                peer.address = known.address;
+                if known.trust_level == TrustLevel::Blacklisted {
+                    peer.blacklist();
+                }
+            }
             
             // Derive shared secret
-            let shared = self.secrets.derive_shared(&peer.identity.exchange_public);
+            let shared = self.secrets.derive_shared_for(&peer.identity.exchange_public, DIRECT_CONTEXT);
             peer.set_shared_secret(shared);
             
             self.add_peer(peer).await;
@@ -442,12 +981,40 @@ impl AiMesh {
         Ok(())
     }
 
+    /// Capabilities from a discovery attestation that we will route work
+    /// for. Unsigned or mis-signed claims count for nothing, since discovery
+    /// itself is unsigned and anyone can announce another node's keys.
+    /// Privileged capabilities also need a pinned authority's endorsement.
+    fn attested_capabilities(&self, identity: &NodeIdentity, attestation: Option<&serde_json::Value>, peers: &PeerTable) -> Vec<String> {
+        let Some(attestation) = attestation.and_then(|a| serde_json::from_value::<CapabilityAttestation>(a.clone()).ok()) else {
+            warn!("Discovery from {} carries no capability attestation; not routing to it", identity.id);
+            return vec![];
+        };
+        if attestation.node_id != identity.id || attestation.role != identity.role || !attestation.verify(&identity.signing_public) {
+            warn!("Discovery from {}: capability attestation does not verify; not routing to it", identity.id);
+            return vec![];
+        }
+
+        let endorsed = attestation.endorsement.as_ref()
+            .and_then(|e| peers.get(&e.authority))
+            .filter(|authority| authority.trust_level == TrustLevel::System)
+            .is_some_and(|authority| attestation.verify_endorsement(&authority.identity.signing_public));
+        let (kept, dropped): (Vec<String>, Vec<String>) = attestation.capabilities.into_iter()
+            .partition(|c| endorsed || !self.config.privileged_capabilities.contains(c));
+        if !dropped.is_empty() {
+            warn!("Peer {} claims privileged {:?} without a pinned authority's endorsement", identity.id, dropped);
+        }
+        kept
+    }
+
     async fn handle_direct(&self, msg: &AiMessage) -> Result<()> {
-        let peers = self.peers.read().await;
+        let mut peers = self.peers.write().await;
         
-        if let Some(peer) = peers.get(&msg.sender) {
-            if let Some(secret) = peer.shared_secret() {
-                let plaintext = decrypt_message(secret, &msg.payload)?;
+        if let Some(peer) = peers.get_mut(&msg.sender) {
+            if peer.shared_secret().is_some() {
+                // Sealed for us by the sender: anything else is a replay from another conversation
+                let aad = direct_aad(&msg.sender, &self.identity.id);
+                let plaintext = peer.decrypt(&msg.payload, &aad, self.config.clock.unix_secs(), self.config.rekey.overlap_secs)?;
                 let content: serde_json::Value = serde_json::from_slice(&plaintext)?;
                 info!("Direct message from {}: {:?}", peer.identity.name, content);
             }
@@ -471,6 +1038,172 @@ impl AiMesh {
             .collect()
     }
 
+    /// Summaries of all known peers (no secrets)
+    pub async fn list_peers(&self) -> Vec<serde_json::Value> {
+        self.peers.read().await
+            .peers
+            .values()
+            .map(|p| serde_json::json!({
+                "id": p.identity.id,
+                "name": p.identity.name,
+                "role": p.identity.role,
+                "status": p.status,
+                "trust_level": p.trust_level,
+                "trust_score": p.trust_score,
+                "last_seen": p.last_seen,
+            }))
+            .collect()
+    }
+
+    /// This node's view of the network graph, for debugging partitions and
+    /// asymmetric links. Each peer is an edge from `node`; no key material.
+    pub async fn topology(&self) -> serde_json::Value {
+        let peers = self.peers.read().await;
+        let mut edges: Vec<serde_json::Value> = peers.peers
+            .values()
+            .map(|p| serde_json::json!({
+                "id": p.identity.id,
+                "name": p.identity.name,
+                "status": p.status,
+                "trust_level": p.trust_level,
+                "trust_score": p.trust_score,
+                "rtt_ms": p.rtt_ms,
+                "address": p.address.map(|a| a.to_string()),
+                "shared_secret": p.shared_secret().is_some(),
+                "last_seen": p.last_seen,
+            }))
+            .collect();
+        edges.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+        serde_json::json!({
+            "node": {
+                "id": self.identity.id,
+                "name": self.identity.name,
+                "role": self.identity.role,
+            },
+            "peers": edges,
+        })
+    }
+
+    /// Our signed vote on a proposal
+    pub fn sign_ballot(&self, proposal: &Proposal, approve: bool) -> Ballot {
+        Ballot::sign(&self.secrets, &self.identity.id, proposal, approve)
+    }
+
+    /// Who may vote, as far as we know: ourselves and every Trusted or pinned
+    /// peer, keyed by node id. A completed handshake proves only that a key
+    /// exists, which anyone can mint, so it earns no vote.
+    pub async fn electorate(&self) -> HashMap<String, [u8; 32]> {
+        let mut electorate: HashMap<String, [u8; 32]> = self.peers.read().await
+            .peers
+            .values()
+            .filter(|p| p.trust_level >= TrustLevel::Trusted && !p.needs_discovery())
+            .map(|p| (p.identity.id.clone(), p.identity.signing_public))
+            .collect();
+        electorate.insert(self.identity.id.clone(), self.identity.signing_public);
+        electorate
+    }
+
+    /// Enforce a passed proposal locally, then gossip it so peers do the same
+    pub async fn publish_proposal(&self, passed: &PassedProposal) -> Result<Tally> {
+        let tally = self.enforce_proposal(passed).await?;
+        let seq = self.next_sequence().await;
+        let mut msg = AiMessage::proposal_passed(&self.identity.id, passed, seq);
+        self.sign_message(&mut msg);
+        self.outbox.send(msg).await?;
+        Ok(tally)
+    }
+
+    /// Install the peers of a swarm's trust bundle. The issuer must be a
+    /// peer we pinned ourselves; entries keep the level the issuer gave them.
+    /// Pinned peers are never overwritten. Returns the peers installed.
+    pub async fn import_trust_bundle(&self, bundle: &TrustBundle) -> Result<usize> {
+        let mut peers = self.peers.write().await;
+        let issuer = peers.get(&bundle.issuer)
+            .filter(|p| p.trust_level == TrustLevel::System)
+            .ok_or_else(|| anyhow::anyhow!("Trust bundle rejected: unknown issuer {}", bundle.issuer))?;
+        if !bundle.verify(&issuer.identity.signing_public) {
+            return Err(anyhow::anyhow!("Trust bundle rejected: bad signature from {}", bundle.issuer));
+        }
+        if let Some(id) = bundle.inconsistent_entry() {
+            return Err(anyhow::anyhow!("Trust bundle rejected: id {} does not match its signing key", id));
+        }
+
+        let mut installed = 0;
+        for (identity, level) in &bundle.peers {
+            if identity.id == self.identity.id {
+                continue;
+            }
+            let known = peers.get(&identity.id);
+            if known.is_some_and(|p| p.trust_level == TrustLevel::System) {
+                continue;
+            }
+            let address = known.and_then(|p| p.address);
+
+            let mut peer = Peer::new(identity.clone());
+            peer.set_trust_level(*level);
+            peer.address = address;
+            peer.set_shared_secret(self.secrets.derive_shared_for(&identity.exchange_public, DIRECT_CONTEXT));
+            peers.upsert(peer);
+            installed += 1;
+        }
+        info!("Imported {} peers from trust bundle by {}", installed, bundle.issuer);
+        Ok(installed)
+    }
+
+    /// Re-tally a passed proposal against our own electorate and apply it.
+    /// Nobody can touch another node's wallet, so each node enforces the
+    /// outcome on its own view: a slashed peer loses standing here, and a
+    /// slashed self burns IPPC through the ledger.
+    pub async fn enforce_proposal(&self, passed: &PassedProposal) -> Result<Tally> {
+        let tally = passed.verify(&self.electorate().await)?;
+        if !self.enforced_proposals.write().await.insert(passed.proposal.id.clone()) {
+            return Ok(tally);
+        }
+
+        match &passed.proposal.kind {
+            ProposalType::SlashNode { node_id, reason, severity } => {
+                info!("Slash {} passed against {} ({:?}): {}", passed.proposal.id, node_id, severity, reason);
+                if *node_id == self.identity.id {
+                    let burned = self.economy.write().await.slash(&passed.proposal.id, severity.burn_percent())?;
+                    warn!("We were slashed: burned {} IPPC", burned);
+                } else {
+                    let mut peers = self.peers.write().await;
+                    match peers.get_mut(node_id) {
+                        // Operator pins outrank mesh votes
+                        Some(peer) if peer.trust_level == TrustLevel::System => {
+                            warn!("Not slashing pinned peer {}", node_id);
+                        }
+                        Some(peer) => {
+                            let before = peer.trust_level;
+                            peer.update_trust(-severity.trust_penalty());
+                            if peer.trust_level == TrustLevel::Trusted && peer.trust_score < 80 {
+                                peer.set_trust_level(TrustLevel::Authenticated);
+                            }
+                            if severity.blacklists() {
+                                peer.blacklist();
+                            }
+                            let detail = format!("slash {} ({:?}): {}, {:?} -> {:?}", passed.proposal.id, severity, reason, before, peer.trust_level);
+                            match peer.trust_level {
+                                TrustLevel::Blacklisted => self.audit.record(AuditEvent::Blacklisted, Some(node_id.as_str()), detail),
+                                level if level < before => self.audit.record(AuditEvent::TrustDemoted, Some(node_id.as_str()), detail),
+                                _ => {}
+                            }
+                        }
+                        None => debug!("Slashed node {} is not a peer of ours", node_id),
+                    }
+                    let _ = self.reputation_manager.save(&peers.peers);
+                }
+            }
+            ProposalType::EmergencyFreeze => {
+                // Lapses unless an operator confirms it, so a vote alone cannot halt us for good
+                self.economy.write().await.freeze_by_vote(&format!("emergency freeze proposal {}", passed.proposal.id))?;
+            }
+            other => debug!("Proposal {} passed; no local enforcement for {:?}", passed.proposal.id, other),
+        }
+        Ok(tally)
+    }
+
     /// Initiate a handshake with a peer
     pub async fn initiate_handshake(&self, peer_id: &str) -> Result<()> {
         let peers = self.peers.read().await;
@@ -486,10 +1219,11 @@ impl AiMesh {
             signing_public: self.identity.signing_public,
             nonce,
             challenge: None,
+            capabilities: LOCAL_CAPABILITIES,
         };
 
         let mut msg = AiMessage::handshake(&self.identity.id, Some(peer_id), &hs);
-        msg.signature = hex::encode(self.secrets.sign(&msg.payload));
+        self.sign_message(&mut msg);
         
         drop(peers);
         self.outbox.send(msg).await?;
@@ -498,8 +1232,28 @@ impl AiMesh {
         Ok(())
     }
 
-    /// Handle handshake sequence
-    async fn handle_handshake(&self, msg: &AiMessage) -> Result<()> {
+    /// Send a handshake reply back where the request came from when it came
+    /// over the network, otherwise through the outbox
+    async fn reply(&self, source: Option<SocketAddr>, msg: AiMessage) -> Result<()> {
+        let transport = self.transport.read().await.clone();
+        match (transport, source) {
+            (Some(transport), Some(addr)) => transport.send(addr, msg).await,
+            _ => Ok(self.outbox.send(msg).await?),
+        }
+    }
+
+    /// A SYN-ACK answering one of our `connect_to` dials: the challenge opens
+    /// to that dial's SYN nonce under the key the responder's handshake implies
+    async fn take_answered_dial(&self, msg: &AiMessage, hs: &crate::messages::HandshakeMessage) -> Option<PendingDial> {
+        let challenge = hs.challenge.as_ref()?;
+        let shared = self.secrets.derive_shared_for(&hs.exchange_public, DIRECT_CONTEXT);
+        let opened = crate::crypto::decrypt_message(&shared, challenge, &direct_aad(&msg.sender, &self.identity.id)).ok()?;
+        let nonce: [u8; 16] = opened.as_slice().try_into().ok()?;
+        self.pending_dials.write().await.remove(&nonce)
+    }
+
+    /// Handle handshake sequence. `source` is where it came from, when known.
+    async fn handle_handshake(&self, msg: &AiMessage, source: Option<SocketAddr>) -> Result<()> {
         let hs: crate::messages::HandshakeMessage = serde_json::from_slice(&msg.payload)?;
         info!("Handshake {:?} from {}", hs.kind, msg.sender);
 
@@ -509,32 +1263,53 @@ impl AiMesh {
             crate::messages::HandshakeKind::Syn => {
                 // Node A -> Node B (SYN)
                 // 1. Verify NodeID matches signing public key
-                let derived_id = hex::encode(sha2::Sha256::digest(hs.signing_public));
+                let derived_id = crate::crypto::node_id(&hs.signing_public);
                 if derived_id != msg.sender {
                     warn!("Handshake NodeID mismatch for {}", msg.sender);
                     return Ok(());
                 }
 
-                // 2. Pin public key / Create peer
-                let mut peer = Peer::new(NodeIdentity {
+                let pinned = peers.get(&msg.sender).filter(|p| p.trust_level == TrustLevel::System);
+                if pinned.is_some_and(|p| p.identity.exchange_public != hs.exchange_public) {
+                    warn!("Handshake exchange key mismatch for pinned peer {}", msg.sender);
+                    return Ok(());
+                }
+                let pinned = pinned.is_some();
+
+                if !self.handshake_nonces.write().await.check_and_add(hs.nonce) {
+                    warn!("Ignoring duplicate SYN from {}", msg.sender);
+                    return Ok(());
+                }
+
+                // 2. Pin public key / Create peer. A peer that already completed a
+                // handshake with these keys (e.g. it restarted) re-keys but keeps its standing;
+                // one only discovered with these keys keeps what it announced.
+                let known = peers.get(&msg.sender)
+                    .filter(|p| p.identity.exchange_public == hs.exchange_public && p.identity.signing_public == hs.signing_public)
+                    .cloned();
+                let keeps_trust = known.as_ref().is_some_and(|p| p.trust_level >= TrustLevel::Authenticated);
+                let mut peer = known.unwrap_or_else(|| Peer::new(NodeIdentity {
                     id: msg.sender.clone(),
                     exchange_public: hs.exchange_public,
                     signing_public: hs.signing_public,
                     role: "unknown".to_string(), // To be updated via discovery
                     name: "unknown".to_string(),
-                });
+                }));
                 
                 // 3. Derive shared secret
-                let shared = self.secrets.derive_shared(&hs.exchange_public);
+                let shared = self.secrets.derive_shared_for(&hs.exchange_public, DIRECT_CONTEXT);
                 peer.set_shared_secret(shared.clone());
-                peer.set_trust_level(crate::peer::TrustLevel::Discovered);
+                if !keeps_trust {
+                    peer.set_trust_level(if pinned { TrustLevel::System } else { TrustLevel::Discovered });
+                }
+                peer.wire_capabilities = hs.capabilities & LOCAL_CAPABILITIES;
                 
                 // 4. Respond with SYN-ACK
                 let mut resp_nonce = [0u8; 16];
                 getrandom::getrandom(&mut resp_nonce)?;
                 
                 // Challenge: Encrypt A's nonce with our shared secret
-                let challenge = crate::crypto::encrypt_message(&shared, &hs.nonce)?;
+                let challenge = crate::crypto::encrypt_message(&shared, self.config.cipher_suite, &hs.nonce, &direct_aad(&self.identity.id, &msg.sender))?;
 
                 let resp_hs = crate::messages::HandshakeMessage {
                     kind: crate::messages::HandshakeKind::SynAck,
@@ -542,21 +1317,75 @@ impl AiMesh {
                     signing_public: self.identity.signing_public,
                     nonce: resp_nonce,
                     challenge: Some(challenge),
+                    capabilities: LOCAL_CAPABILITIES,
                 };
 
+                peer.address = source.or(peer.address);
                 peers.upsert(peer);
                 
                 let mut resp_msg = AiMessage::handshake(&self.identity.id, Some(&msg.sender), &resp_hs);
-                resp_msg.signature = hex::encode(self.secrets.sign(&resp_msg.payload));
+                self.sign_message(&mut resp_msg);
                 
-                self.outbox.send(resp_msg).await?;
+                self.reply(source, resp_msg).await?;
             }
             crate::messages::HandshakeKind::SynAck => {
-                // Node B -> Node A (SYN-ACK)
+                // Node B -> Node A (SYN-ACK) answering `connect_to`: B may be new to us
+                if let Some(dial) = self.take_answered_dial(msg, &hs).await {
+                    // Unknown senders skipped the signature check, so check it here
+                    let signed = parse_signature(&msg.signature)
+                        .map(|sig| self.signature_valid(msg, &hs.signing_public, &sig))
+                        .transpose()?
+                        .unwrap_or(false);
+                    if crate::crypto::node_id(&hs.signing_public) != msg.sender || !signed {
+                        warn!("Rejecting SYN-ACK from {}: identity does not match its keys", msg.sender);
+                        self.audit.record(AuditEvent::SignatureRejected, Some(msg.sender.as_str()), format!("unverifiable SYN-ACK {}", msg.id));
+                        return Ok(());
+                    }
+                    if peers.get(&msg.sender).is_some_and(|p| p.trust_level == TrustLevel::System && p.identity.exchange_public != hs.exchange_public) {
+                        warn!("Handshake exchange key mismatch for pinned peer {}", msg.sender);
+                        return Ok(());
+                    }
+
+                    let mut peer = peers.get(&msg.sender)
+                        .filter(|p| p.identity.signing_public == hs.signing_public)
+                        .cloned()
+                        .unwrap_or_else(|| Peer::new(NodeIdentity {
+                            id: msg.sender.clone(),
+                            exchange_public: hs.exchange_public,
+                            signing_public: hs.signing_public,
+                            role: "unknown".to_string(), // To be updated via discovery
+                            name: "unknown".to_string(),
+                        }));
+                    peer.identity.exchange_public = hs.exchange_public;
+                    peer.set_shared_secret(self.secrets.derive_shared_for(&hs.exchange_public, DIRECT_CONTEXT));
+                    peer.wire_capabilities = hs.capabilities & LOCAL_CAPABILITIES;
+                    peer.authenticate();
+                    peer.status = PeerStatus::Connected;
+                    peer.address = Some(dial.addr);
+                    peer.observe_clock_offset((msg.timestamp - self.config.clock.now()).num_seconds());
+                    peers.upsert(peer);
+                    info!("Handshake completed with {} at {}", msg.sender, dial.addr);
+
+                    let resp_hs = crate::messages::HandshakeMessage {
+                        kind: crate::messages::HandshakeKind::Ack,
+                        exchange_public: self.identity.exchange_public,
+                        signing_public: self.identity.signing_public,
+                        nonce: hs.nonce, // Echo B's nonce
+                        challenge: None,
+                        capabilities: LOCAL_CAPABILITIES,
+                    };
+                    let mut resp_msg = AiMessage::handshake(&self.identity.id, Some(&msg.sender), &resp_hs);
+                    self.sign_message(&mut resp_msg);
+                    drop(peers);
+                    self.reply(Some(dial.addr), resp_msg).await?;
+                    let _ = dial.authenticated.send(msg.sender.clone());
+                    return Ok(());
+                }
+
                 if let Some(peer) = peers.get_mut(&msg.sender) {
                     if let (Some(shared), Some(challenge)) = (peer.shared_secret(), hs.challenge) {
                         // 1. Verify challenge (B decrypted our nonce)
-                        let decrypted_nonce = crate::crypto::decrypt_message(shared, &challenge)?;
+                        let decrypted_nonce = crate::crypto::decrypt_message(shared, &challenge, &direct_aad(&msg.sender, &self.identity.id))?;
                         if decrypted_nonce.len() != 16 {
                             warn!("Invalid handshake challenge from {}", msg.sender);
                             return Ok(());
@@ -569,43 +1398,103 @@ impl AiMesh {
                             signing_public: self.identity.signing_public,
                             nonce: hs.nonce, // Echo B's nonce
                             challenge: None,
+                            capabilities: LOCAL_CAPABILITIES,
                         };
 
+                        peer.wire_capabilities = hs.capabilities & LOCAL_CAPABILITIES;
                         peer.authenticate();
+                        // Answers our challenge, so the timestamp is fresh: a clock sample
+                        peer.observe_clock_offset((msg.timestamp - self.config.clock.now()).num_seconds());
                         info!("Handshake completed with {}", msg.sender);
 
                         let mut resp_msg = AiMessage::handshake(&self.identity.id, Some(&msg.sender), &resp_hs);
-                        resp_msg.signature = hex::encode(self.secrets.sign(&resp_msg.payload));
+                        self.sign_message(&mut resp_msg);
                         
-                        self.outbox.send(resp_msg).await?;
+                        self.reply(source, resp_msg).await?;
                     }
                 }
             }
             crate::messages::HandshakeKind::Ack => {
                 // Node A -> Node B (ACK)
                 if let Some(peer) = peers.get_mut(&msg.sender) {
+                    peer.wire_capabilities = hs.capabilities & LOCAL_CAPABILITIES;
                     peer.authenticate();
+                    peer.observe_clock_offset((msg.timestamp - self.config.clock.now()).num_seconds());
                     info!("Handshake finalized with {}", msg.sender);
                 }
             }
+            crate::messages::HandshakeKind::Rekey => {
+                // The message signature was checked against the peer's pinned
+                // key before we got here; the claimed key must match it too
+                let now = self.config.clock.unix_secs();
+                let Some(peer) = peers.get_mut(&msg.sender)
+                    .filter(|p| p.trust_level >= TrustLevel::Authenticated && p.identity.signing_public == hs.signing_public)
+                else {
+                    warn!("Ignoring re-key from unauthenticated peer {}", msg.sender);
+                    return Ok(());
+                };
+                let bases = peer.rekey_bases();
+                if bases.is_empty() {
+                    return Ok(());
+                }
+                // Both sides offered at once: the lower node id's offer wins
+                if peer.rekey_pending(now) && self.identity.id < msg.sender {
+                    return Ok(());
+                }
+
+                let exchange = EphemeralExchange::generate();
+                let resp_hs = crate::messages::HandshakeMessage {
+                    kind: crate::messages::HandshakeKind::RekeyAck,
+                    exchange_public: exchange.public,
+                    signing_public: self.identity.signing_public,
+                    nonce: hs.nonce,
+                    challenge: None,
+                    capabilities: LOCAL_CAPABILITIES,
+                };
+                // Keep sending under the current key until the peer proves it got this answer
+                peer.stage_secrets(bases.iter().map(|base| exchange.rekey(base, &hs.exchange_public)).collect());
+                info!("Re-key with {} agreed; switching once it uses the new key", msg.sender);
+
+                let mut resp_msg = AiMessage::handshake(&self.identity.id, Some(&msg.sender), &resp_hs);
+                self.sign_message(&mut resp_msg);
+                self.reply(source, resp_msg).await?;
+            }
+            crate::messages::HandshakeKind::RekeyAck => {
+                let now = self.config.clock.unix_secs();
+                if let Some(peer) = peers.get_mut(&msg.sender) {
+                    // Only the answer to the offer outstanding; a late one for a replaced offer would derive the wrong key
+                    if let (Some(current), Some(exchange)) = (peer.shared_secret().cloned(), peer.take_pending_rekey(&hs.nonce)) {
+                        // The peer keeps sending under the old key until it sees the new one in use
+                        peer.rotate_secret(exchange.rekey(&current, &hs.exchange_public), now, None);
+                        info!("Session with {} re-keyed", msg.sender);
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    /// Broadcast discovery message
-    pub async fn announce(&self) -> Result<()> {
-        let discovery_info = serde_json::json!({
+    /// Our discovery payload, with a signed capability attestation
+    pub fn discovery_info(&self) -> serde_json::Value {
+        let capabilities = vec![self.identity.role.clone()];
+        let mut attestation = CapabilityAttestation::sign(&self.secrets, &self.identity.id, &self.identity.role, capabilities.clone());
+        attestation.endorsement = self.config.endorsement.clone();
+        serde_json::json!({
             "id": self.identity.id,
             "name": self.identity.name,
             "role": self.identity.role,
             "exchange_public": hex::encode(self.identity.exchange_public),
             "signing_public": hex::encode(self.identity.signing_public),
-            "capabilities": vec![&self.identity.role],
+            "capabilities": capabilities,
+            "attestation": attestation,
             "port": self.config.port,
-        });
-        
-        let msg = AiMessage::discovery(&self.identity.id, discovery_info);
+        })
+    }
+
+    /// Broadcast discovery message
+    pub async fn announce(&self) -> Result<()> {
+        let msg = AiMessage::discovery(&self.identity.id, self.discovery_info());
         self.outbox.send(msg).await?;
         
         info!("Announced to mesh");
@@ -616,14 +1505,17 @@ impl AiMesh {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::decrypt_message;
+    use crate::economy::{ActionType, Balances};
+    use crate::governance::SlashSeverity;
     
     #[tokio::test]
     async fn test_handshake_sequence() -> Result<()> {
         let config_a = MeshConfig { name: "node-a".into(), ..Default::default() };
         let config_b = MeshConfig { name: "node-b".into(), ..Default::default() };
         
-        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config_a);
-        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config_b);
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config_a)?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config_b)?;
 
         let id_a = mesh_a.identity().id.clone();
         let id_b = mesh_b.identity().id.clone();
@@ -655,14 +1547,996 @@ mod tests {
         
         assert_eq!(peers_a.get(&id_b).unwrap().trust_level, crate::peer::TrustLevel::Authenticated);
         assert_eq!(peers_b.get(&id_a).unwrap().trust_level, crate::peer::TrustLevel::Authenticated);
+        // Both sides negotiated compression
+        assert!(peers_a.get(&id_b).unwrap().supports(CAP_ZSTD));
+        assert!(peers_b.get(&id_a).unwrap().supports(CAP_ZSTD));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_replay_protection() -> Result<()> {
-        let config = MeshConfig { name: "test-node".into(), ..Default::default() };
-        let (mesh, _out, _in) = AiMesh::new(config);
+    async fn test_topology_reflects_handshaken_peers() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_topology_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+
+        assert_eq!(mesh_a.topology().await["peers"], serde_json::json!([]));
+
+        let info = Peer::new(mesh_b.identity().clone()).to_discovery_info();
+        mesh_a.handle_message(AiMessage::discovery(&id_b, info)).await?;
+        mesh_a.initiate_handshake(&id_b).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+
+        for (mesh, own, other) in [(&mesh_a, &id_a, &id_b), (&mesh_b, &id_b, &id_a)] {
+            let topology = mesh.topology().await;
+            assert_eq!(topology["node"]["id"], serde_json::json!(own));
+            let peers = topology["peers"].as_array().unwrap();
+            assert_eq!(peers.len(), 1);
+            assert_eq!(peers[0]["id"], serde_json::json!(other));
+            assert_eq!(peers[0]["trust_level"], serde_json::json!(TrustLevel::Authenticated));
+            assert_eq!(peers[0]["shared_secret"], serde_json::json!(true));
+            assert!(peers[0].get("rtt_ms").is_some());
+        }
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    /// Discovery plus a full SYN / SYN-ACK / ACK exchange from `a` to `b`
+    async fn handshake(
+        a: &AiMesh, out_a: &mut mpsc::Receiver<AiMessage>,
+        b: &AiMesh, out_b: &mut mpsc::Receiver<AiMessage>,
+    ) -> Result<()> {
+        let id_b = b.identity().id.clone();
+        a.handle_message(AiMessage::discovery(&id_b, Peer::new(b.identity().clone()).to_discovery_info())).await?;
+        a.initiate_handshake(&id_b).await?;
+        b.handle_message(out_a.recv().await.unwrap()).await?;
+        a.handle_message(out_b.recv().await.unwrap()).await?;
+        b.handle_message(out_a.recv().await.unwrap()).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_syn_does_not_downgrade_peer() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_dup_syn_{}", Uuid::new_v4()));
+        // Legacy payload-only signing, under which a rewritten nonce still verifies
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, strict_signing: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_a.initiate_handshake(&id_b).await?;
+        let syn = out_a.recv().await.unwrap();
+        mesh_b.handle_message(syn.clone()).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        assert_eq!(mesh_b.peers.read().await.get(&id_a).unwrap().trust_level, TrustLevel::Authenticated);
+
+        // The legacy signature covers only the payload, so a fresh message
+        // nonce gets a replayed SYN past the message replay cache
+        let mut replayed = syn;
+        replayed.nonce = MessageNonce::random();
+        mesh_b.handle_message(replayed).await?;
+
+        assert_eq!(mesh_b.peers.read().await.get(&id_a).unwrap().trust_level, TrustLevel::Authenticated);
+        assert!(out_b.try_recv().is_err(), "no SYN-ACK for a duplicate SYN");
+
+        // A genuinely new SYN (the peer restarted) re-keys without losing standing
+        mesh_a.initiate_handshake(&id_b).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        assert_eq!(out_b.recv().await.unwrap().msg_type, MessageType::Handshake);
+        assert_eq!(mesh_b.peers.read().await.get(&id_a).unwrap().trust_level, TrustLevel::Authenticated);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handshake_measures_clock_skew() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_skew_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        let id_b = mesh_b.identity().id.clone();
+
+        // node-a's clock runs 15 minutes slow: older than node-b's replay window
+        let skewed = |mut msg: AiMessage| {
+            msg.timestamp -= chrono::Duration::seconds(900);
+            mesh_a.sign_message(&mut msg);
+            msg
+        };
+        let thought = |n: u64| {
+            let t = Thought { content: serde_json::json!({"n": n}), embedding: None, confidence: 1.0, context: None, tags: vec![] };
+            AiMessage::thought(&mesh_a.identity().id, &t, n)
+        };
+
+        mesh_b.handle_message(skewed(thought(1))).await?;
+        assert!(in_b.try_recv().is_err(), "unmeasured skew must not widen the window");
+
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_a.initiate_handshake(&id_b).await?;
+        mesh_b.handle_message(skewed(out_a.recv().await.unwrap())).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        mesh_b.handle_message(skewed(out_a.recv().await.unwrap())).await?;
+
+        let offset = mesh_b.peers.read().await.get(&mesh_a.identity().id).unwrap().clock_offset_secs;
+        assert!(offset.is_some_and(|o| (-905..=-895).contains(&o)), "offset {:?}", offset);
+
+        // Same skew, otherwise valid: now judged by the sender's clock
+        mesh_b.handle_message(skewed(thought(2))).await?;
+        assert_eq!(in_b.try_recv()?.sequence, 2);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_old_syn_is_not_replayable_within_skew_allowance() -> Result<()> {
+        use crate::clock::MockClock;
+
+        let base = std::env::temp_dir().join(format!("test_syn_replay_{}", Uuid::new_v4()));
+        let clock = MockClock::starting_now();
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(MeshConfig { clock: clock.clone(), ..config("node-b") })?;
+        let id_b = mesh_b.identity().id.clone();
+
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_a.initiate_handshake(&id_b).await?;
+        let syn = out_a.recv().await.unwrap();
+        mesh_b.handle_message(syn.clone()).await?;
+        assert!(out_b.try_recv().is_ok());
+
+        // Past the ordinary window but inside the handshake allowance
+        clock.advance(chrono::Duration::seconds(REPLAY_WINDOW_SECS + 60));
+        mesh_b.handle_message(syn).await?;
+        assert!(out_b.try_recv().is_err(), "replayed SYN was answered");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_forged_capabilities_are_not_routed() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_caps_{}", Uuid::new_v4()));
+        let config = |name: &str, role: &str| MeshConfig {
+            name: name.into(),
+            role: role.into(),
+            data_dir: base.join(name),
+            networking: false,
+            privileged_capabilities: vec!["reasoning".into()],
+            ..Default::default()
+        };
+        let (router, _out, _in) = AiMesh::new(config("router", "relay"))?;
+        let (worker, _out_w, _in_w) = AiMesh::new(config("worker", "tool"))?;
+        let (claimant, _out_c, _in_c) = AiMesh::new(config("claimant", "reasoning"))?;
+
+        // Unsigned claim (the old discovery format) is not routed
+        let unsigned = Peer::new(worker.identity().clone()).to_discovery_info();
+        router.handle_message(AiMessage::discovery(&worker.identity().id, unsigned)).await?;
+        assert!(router.peers_with_capability("tool").await.is_empty());
+
+        // Signed by the node itself: routed
+        router.handle_message(AiMessage::discovery(&worker.identity().id, worker.discovery_info())).await?;
+        assert_eq!(router.peers_with_capability("tool").await, vec![worker.identity().id.clone()]);
+
+        // Someone else's keys with a re-labelled role: the attestation no longer matches
+        let mut relabelled = worker.discovery_info();
+        relabelled["role"] = serde_json::json!("reasoning");
+        relabelled["attestation"]["role"] = serde_json::json!("reasoning");
+        relabelled["attestation"]["capabilities"] = serde_json::json!(["reasoning"]);
+        router.handle_message(AiMessage::discovery(&worker.identity().id, relabelled)).await?;
+        assert!(router.peers_with_capability("reasoning").await.is_empty());
+
+        // Privileged role: self-signed is not enough...
+        router.handle_message(AiMessage::discovery(&claimant.identity().id, claimant.discovery_info())).await?;
+        assert!(router.peers_with_capability("reasoning").await.is_empty());
+
+        // ...until a pinned authority endorses it
+        let authority = NodeSecrets::generate();
+        let authority_identity = authority.identity("authority", "relay");
+        let mut pinned = Peer::new(authority_identity.clone());
+        pinned.set_trust_level(TrustLevel::System);
+        router.add_peer(pinned).await;
+        let mut endorsed = claimant.discovery_info();
+        let attestation: CapabilityAttestation = serde_json::from_value(endorsed["attestation"].clone())?;
+        endorsed["attestation"]["endorsement"] = serde_json::to_value(attestation.endorse(&authority, &authority_identity.id))?;
+        router.handle_message(AiMessage::discovery(&claimant.identity().id, endorsed)).await?;
+        assert_eq!(router.peers_with_capability("reasoning").await, vec![claimant.identity().id.clone()]);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    /// Raise `members` to Trusted in `mesh`'s view, as earned standing would
+    async fn trust_all(mesh: &AiMesh, members: &[&AiMesh]) {
+        let mut peers = mesh.peers.write().await;
+        for member in members {
+            match peers.get_mut(&member.identity().id) {
+                Some(peer) => peer.set_trust_level(TrustLevel::Trusted),
+                None => {
+                    let mut peer = Peer::new(member.identity().clone());
+                    peer.set_trust_level(TrustLevel::Trusted);
+                    peers.upsert(peer);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshaked_identities_cannot_pass_proposals() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_sybil_vote_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let mut sybils = Vec::new();
+        for i in 0..3 {
+            let (sybil, mut out_s, in_s) = AiMesh::new(config(&format!("sybil-{}", i)))?;
+            handshake(&mesh_a, &mut out_a, &sybil, &mut out_s).await?;
+            sybils.push((sybil, out_s, in_s));
+        }
+
+        // Every fresh identity approves: unanimous, but none of them has a vote
+        let proposal = Proposal::new(ProposalType::EmergencyFreeze, &sybils[0].0.identity().id, 1_700_000_000);
+        let mut passed = PassedProposal { proposal: proposal.clone(), ballots: Vec::new() };
+        for (sybil, _, _) in &sybils {
+            passed.ballots.push(sybil.sign_ballot(&proposal, true));
+        }
+        assert_eq!(mesh_a.electorate().await.len(), 1);
+        assert!(mesh_a.enforce_proposal(&passed).await.is_err());
+        assert!(!mesh_a.economy.read().await.is_frozen());
+
+        // Even our own approval is below the minimum electorate
+        passed.ballots.push(mesh_a.sign_ballot(&proposal, true));
+        assert!(mesh_a.enforce_proposal(&passed).await.is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_passed_slash_lowers_target_standing() -> Result<()> {
+        // Separate data dirs: each node needs its own identity and wallet
+        let base = std::env::temp_dir().join(format!("test_slash_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let (mesh_c, mut out_c, _in_c) = AiMesh::new(config("node-c"))?;
+        let (mesh_d, mut out_d, _in_d) = AiMesh::new(config("node-d"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        handshake(&mesh_a, &mut out_a, &mesh_c, &mut out_c).await?;
+        handshake(&mesh_a, &mut out_a, &mesh_d, &mut out_d).await?;
+        trust_all(&mesh_a, &[&mesh_b, &mesh_c, &mesh_d]).await;
+        trust_all(&mesh_c, &[&mesh_a, &mesh_b, &mesh_d]).await;
+        let id_c = mesh_c.identity().id.clone();
+        mesh_c.economy.write().await.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+
+        let score_before = mesh_a.peers.read().await.get(&id_c).unwrap().trust_score;
+        let proposal = Proposal::new(
+            ProposalType::SlashNode { node_id: id_c.clone(), reason: "spam".into(), severity: SlashSeverity::Major },
+            &mesh_a.identity().id,
+            1_700_000_000,
+        );
+
+        // A alone is 1 of the 3 nodes eligible at A: not enough
+        let mut passed = PassedProposal { proposal: proposal.clone(), ballots: vec![mesh_a.sign_ballot(&proposal, true)] };
+        assert!(mesh_a.enforce_proposal(&passed).await.is_err());
+
+        // C's vote does not count on its own slash
+        passed.ballots.push(mesh_c.sign_ballot(&proposal, false));
+        passed.ballots.push(mesh_b.sign_ballot(&proposal, true));
+        let tally = mesh_a.publish_proposal(&passed).await?;
+        assert_eq!((tally.approvals, tally.rejections, tally.eligible), (2, 0, 3));
+
+        let score_after = mesh_a.peers.read().await.get(&id_c).unwrap().trust_score;
+        assert_eq!(score_after, score_before.saturating_sub(SlashSeverity::Major.trust_penalty() as u8));
+
+        // The target honors the gossiped claim and burns from its own wallet, once
+        let gossip = out_a.recv().await.unwrap();
+        assert_eq!(gossip.msg_type, MessageType::ProposalPassed);
+        mesh_c.handle_message(gossip).await?;
+        mesh_c.enforce_proposal(&passed).await?;
+        let economy = mesh_c.economy.read().await;
+        assert_eq!(economy.wallet.balances.ippc, 750);
+        assert!(matches!(economy.ledger().last().unwrap().action, ActionType::Slash { amount: 250, .. }));
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rediscovery_keeps_an_authenticated_session() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_rediscovery_session_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        let id_b = mesh_b.identity().id.clone();
+
+        // Standing and a rotated session key, neither of which discovery can vouch for
+        let first = session_key(&mesh_a, &id_b).await;
+        mesh_a.rekey_peer(&id_b).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        {
+            let mut peers = mesh_a.peers.write().await;
+            let peer = peers.get_mut(&id_b).unwrap();
+            peer.set_trust_level(TrustLevel::Trusted);
+            peer.trust_score = 90;
+            peer.clock_offset_secs = Some(7);
+        }
+        let key = session_key(&mesh_a, &id_b).await;
+        assert_ne!(key.as_bytes(), first.as_bytes());
+
+        mesh_a.handle_message(AiMessage::discovery("someone-else", mesh_b.discovery_info())).await?;
+
+        let peers = mesh_a.peers.read().await;
+        let peer = peers.get(&id_b).unwrap();
+        assert_eq!(peer.trust_level, TrustLevel::Trusted);
+        assert_eq!(peer.trust_score, 90);
+        assert_eq!(peer.status, PeerStatus::Connected);
+        assert_eq!(peer.clock_offset_secs, Some(7));
+        assert_eq!(peer.shared_secret().unwrap().as_bytes(), key.as_bytes());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rediscovery_cannot_lift_a_slash() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_slash_rediscovery_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let (mesh_c, mut out_c, _in_c) = AiMesh::new(config("node-c"))?;
+        let (mesh_d, mut out_d, _in_d) = AiMesh::new(config("node-d"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        handshake(&mesh_a, &mut out_a, &mesh_c, &mut out_c).await?;
+        handshake(&mesh_a, &mut out_a, &mesh_d, &mut out_d).await?;
+        trust_all(&mesh_a, &[&mesh_b, &mesh_c]).await;
+        let id_d = mesh_d.identity().id.clone();
+
+        let proposal = Proposal::new(
+            ProposalType::SlashNode { node_id: id_d.clone(), reason: "forgery".into(), severity: SlashSeverity::Severe },
+            &mesh_a.identity().id,
+            1_700_000_000,
+        );
+        let ballots = [&mesh_a, &mesh_b, &mesh_c].iter().map(|m| m.sign_ballot(&proposal, true)).collect();
+        mesh_a.enforce_proposal(&PassedProposal { proposal, ballots }).await?;
+        let slashed = mesh_a.peers.read().await.get(&id_d).unwrap().clone();
+        assert_eq!(slashed.trust_level, TrustLevel::Blacklisted);
+
+        // Relayed under another sender, so the reputation filter doesn't see it
+        mesh_a.handle_message(AiMessage::discovery(&mesh_b.identity().id, mesh_d.discovery_info())).await?;
+
+        let peers = mesh_a.peers.read().await;
+        let peer = peers.get(&id_d).unwrap();
+        assert_eq!(peer.trust_level, TrustLevel::Blacklisted);
+        assert_eq!(peer.status, PeerStatus::Blocked);
+        assert_eq!(peer.trust_score, slashed.trust_score);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    async fn session_key(mesh: &AiMesh, peer: &str) -> crate::crypto::SharedSecret {
+        mesh.peers.read().await.get(peer).unwrap().shared_secret().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_session_rekeys_after_message_threshold() -> Result<()> {
+        use crate::clock::MockClock;
+
+        let base = std::env::temp_dir().join(format!("test_rekey_{}", Uuid::new_v4()));
+        let clock = MockClock::starting_now();
+        let rekey = RekeyPolicy { after_messages: 3, after_secs: 3600, overlap_secs: 60, retry_secs: 30 };
+        let config = |name: &str| MeshConfig {
+            name: name.into(),
+            data_dir: base.join(name),
+            clock: clock.clone(),
+            rekey,
+            ..Default::default()
+        };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+        let original = session_key(&mesh_a, &id_b).await;
+
+        for i in 0..3 {
+            mesh_a.send_direct(&id_b, serde_json::json!({ "n": i })).await?;
+            mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        }
+        // The third message crossed the threshold and queued a re-key offer
+        let offer = out_a.recv().await.unwrap();
+        assert_eq!(offer.msg_type, MessageType::Handshake);
+
+        // Sent before the switch, delivered after it: opens under the overlap
+        mesh_a.send_direct(&id_b, serde_json::json!({ "n": 3 })).await?;
+        let in_flight = out_a.recv().await.unwrap();
+        mesh_b.handle_message(offer).await?;
+        mesh_b.handle_message(in_flight).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+
+        // A switched on the answer; B keeps the old key until A uses the new one
+        let rotated = session_key(&mesh_a, &id_b).await;
+        assert_ne!(rotated.as_bytes(), original.as_bytes());
+        assert_eq!(session_key(&mesh_b, &id_a).await.as_bytes(), original.as_bytes());
+
+        mesh_a.send_direct(&id_b, serde_json::json!({ "n": 4 })).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        assert_eq!(rotated.as_bytes(), session_key(&mesh_b, &id_a).await.as_bytes());
+
+        // Once the overlap ends the old key no longer opens anything
+        clock.advance(chrono::Duration::seconds(61));
+        let mut msg = AiMessage::direct(&id_a, &id_b, serde_json::json!({ "n": 5 }), 99);
+        msg.payload = encrypt_message(&original, CipherSuite::default(), b"{}", &direct_aad(&id_a, &id_b))?;
+        mesh_a.sign_message(&mut msg);
+        assert!(mesh_b.handle_message(msg).await.is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lost_rekey_answer_is_retried() -> Result<()> {
+        use crate::clock::MockClock;
+
+        let base = std::env::temp_dir().join(format!("test_rekey_retry_{}", Uuid::new_v4()));
+        let clock = MockClock::starting_now();
+        let rekey = RekeyPolicy { after_messages: 2, after_secs: 3600, overlap_secs: 60, retry_secs: 30 };
+        let config = |name: &str| MeshConfig {
+            name: name.into(),
+            data_dir: base.join(name),
+            networking: false,
+            clock: clock.clone(),
+            rekey,
+            ..Default::default()
+        };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+        let original = session_key(&mesh_a, &id_b).await;
+
+        for i in 0..2 {
+            mesh_a.send_direct(&id_b, serde_json::json!({ "n": i })).await?;
+            mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        }
+        let offer = out_a.recv().await.unwrap();
+
+        // An unsigned offer is dropped before it reaches the re-key handler
+        let mut unsigned = offer.clone();
+        unsigned.signature = String::new();
+        mesh_b.handle_message(unsigned).await?;
+        assert!(out_b.try_recv().is_err());
+
+        // The answer is lost: A keeps the old key, and B still reads it long after the overlap
+        mesh_b.handle_message(offer).await?;
+        let _lost = out_b.recv().await.unwrap();
+        clock.advance(chrono::Duration::seconds(61));
+        mesh_a.send_direct(&id_b, serde_json::json!({ "n": 2 })).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        assert_eq!(session_key(&mesh_a, &id_b).await.as_bytes(), original.as_bytes());
+        assert_eq!(session_key(&mesh_b, &id_a).await.as_bytes(), original.as_bytes());
+
+        // Past its deadline the offer is made again, and this time completes
+        let retry = out_a.recv().await.unwrap();
+        assert_eq!(retry.msg_type, MessageType::Handshake);
+        mesh_b.handle_message(retry).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        mesh_a.send_direct(&id_b, serde_json::json!({ "n": 3 })).await?;
+        mesh_b.handle_message(out_a.recv().await.unwrap()).await?;
+        let rotated = session_key(&mesh_a, &id_b).await;
+        assert_ne!(rotated.as_bytes(), original.as_bytes());
+        assert_eq!(rotated.as_bytes(), session_key(&mesh_b, &id_a).await.as_bytes());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discovery_with_malformed_key_is_rejected() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_discovery_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh, _out, _in) = AiMesh::new(config("node-a"))?;
+        let (other, _out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let id_b = other.identity().id.clone();
+        let valid = Peer::new(other.identity().clone()).to_discovery_info();
+
+        let cases = [
+            ("exchange_public", Some("abcd".to_string())), // too short
+            ("signing_public", Some("ab".repeat(33))),     // too long
+            ("exchange_public", Some("zz".repeat(32))),    // not hex
+            ("signing_public", None),                      // missing
+        ];
+        for (field, value) in cases {
+            let mut info = valid.clone();
+            match value {
+                Some(v) => info[field] = serde_json::json!(v),
+                None => { info.as_object_mut().unwrap().remove(field); }
+            }
+            let err = mesh.handle_message(AiMessage::discovery(&id_b, info)).await.unwrap_err();
+            assert!(err.to_string().contains(field), "unexpected error: {}", err);
+        }
+
+        // No zombie peer with all-zero keys was created
+        assert!(mesh.peers.read().await.get(&id_b).is_none());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcasts_pause_over_byte_budget() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_byte_budget_{}", Uuid::new_v4()));
+        let config = MeshConfig { name: "metered".into(), data_dir: base.clone(), daily_byte_budget: Some(1_000), ..Default::default() };
+        let (mesh, mut out, _in) = AiMesh::new(config)?;
+        let thought = || Thought {
+            content: serde_json::json!({"test": "data"}),
+            embedding: None,
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+
+        mesh.send_thought(thought(), EmbeddingPolicy::Strip).await?;
+        assert!(out.try_recv().is_ok());
+
+        mesh.traffic.record_received("peer-a", 1_000);
+        mesh.send_thought(thought(), EmbeddingPolicy::Strip).await?;
+        assert!(out.try_recv().is_err());
+        assert!(mesh.traffic_stats().over_budget);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_future_message_type_is_dropped() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_future_type_{}", Uuid::new_v4()));
+        let (mesh, _out, _in) = AiMesh::new(MeshConfig { name: "test-node".into(), data_dir: base.clone(), ..Default::default() })?;
+        let mut inbox = mesh.inbox();
+
+        // A newer peer's message, as JSON with a type this build lacks
+        let mut json = serde_json::to_value(AiMessage::discovery("newer-node", serde_json::json!({})))?;
+        json["msg_type"] = serde_json::json!("Telepathy");
+        let msg: AiMessage = serde_json::from_value(json)?;
+        assert_eq!(msg.msg_type, MessageType::Unknown);
+
+        mesh.handle_message(msg).await?;
+        assert!(inbox.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expired_broadcast_is_not_delivered() -> Result<()> {
+        use crate::clock::{Clock, MockClock};
+
+        let base = std::env::temp_dir().join(format!("test_expired_broadcast_{}", Uuid::new_v4()));
+        let clock = MockClock::starting_now();
+        let config = MeshConfig { name: "test-node".into(), data_dir: base.clone(), clock: clock.clone(), ..Default::default() };
+        let (mesh, _out, _in) = AiMesh::new(config)?;
+        let mut inbox = mesh.inbox();
+        let now = clock.unix_secs();
+        let broadcast = |ttl| Broadcast {
+            channel: "alerts".into(),
+            content: serde_json::json!({"level": "info"}),
+            priority: 1,
+            ttl,
+        };
+
+        let relay = discovered_sender(&mesh, "relay").await?;
+        let signed = |mut msg: AiMessage| {
+            relay.sign_message(&mut msg);
+            msg
+        };
+
+        let stale = AiMessage::broadcast(&relay.identity().id, &broadcast(crate::messages::Ttl { max_hops: 5, expires_at: now - 1 }), 0);
+        mesh.handle_message(signed(stale)).await?;
+        let mut looping = AiMessage::broadcast(&relay.identity().id, &broadcast(crate::messages::Ttl { max_hops: 2, expires_at: now + 60 }), 1);
+        looping.hops = 3;
+        mesh.handle_message(signed(looping)).await?;
+        assert!(inbox.try_recv().is_err());
+
+        let fresh = AiMessage::broadcast(&relay.identity().id, &broadcast(crate::messages::Ttl::default()), 2);
+        mesh.handle_message(signed(fresh)).await?;
+        assert!(inbox.try_recv().is_ok());
+
+        // Expiry is judged by the mesh's clock
+        let short = AiMessage::broadcast(&relay.identity().id, &broadcast(crate::messages::Ttl { max_hops: 5, expires_at: now + 60 }), 3);
+        clock.advance(chrono::Duration::seconds(61));
+        mesh.handle_message(signed(short)).await?;
+        assert!(inbox.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_thought_verifies_and_inflates() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_compress_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+
+        // Each side knows the other and has negotiated zstd
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_b.handle_message(AiMessage::discovery(&id_a, Peer::new(mesh_a.identity().clone()).to_discovery_info())).await?;
+        mesh_a.peers.write().await.get_mut(&id_b).unwrap().wire_capabilities = CAP_ZSTD;
+
+        let thought = Thought {
+            content: serde_json::json!({"summary": "shared embedding"}),
+            embedding: Some(vec![0.125; 1536]),
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+        let plain_len = serde_json::to_vec(&thought)?.len();
+        mesh_a.send_thought(thought, EmbeddingPolicy::Keep).await?;
+
+        let sent = out_a.recv().await.expect("thought in outbox");
+        assert!(sent.compressed);
+        assert!(sent.payload.len() < plain_len);
+
+        mesh_b.handle_message(sent).await?;
+        let received = in_b.recv().await?;
+        assert!(!received.compressed);
+        let decoded: Thought = serde_json::from_slice(&received.payload)?;
+        assert_eq!(decoded.embedding.map(|e| e.len()), Some(1536));
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trust_bundle_from_pinned_issuer_pins_peers() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_trust_bundle_{}", Uuid::new_v4()));
+        let (mesh, _out, _in) = AiMesh::new(MeshConfig { name: "joiner".into(), data_dir: base.clone(), networking: false, ..Default::default() })?;
+        let seed = NodeSecrets::generate();
+        let seed_identity = seed.identity("seed", "relay");
+        let members: Vec<NodeIdentity> = (0..2)
+            .map(|i| NodeSecrets::generate().identity(&format!("member-{}", i), "tool"))
+            .collect();
+        let bundle = TrustBundle::sign(
+            &seed,
+            &seed_identity.id,
+            members.iter().map(|m| (m.clone(), TrustLevel::System)).collect(),
+        );
+
+        // Nobody pinned the seed yet
+        let err = mesh.import_trust_bundle(&bundle).await.unwrap_err();
+        assert!(err.to_string().contains("unknown issuer"), "{}", err);
+
+        let mut pinned = Peer::new(seed_identity);
+        pinned.set_trust_level(TrustLevel::System);
+        mesh.add_peer(pinned).await;
+
+        let mut forged = bundle.clone();
+        forged.peers.truncate(1);
+        assert!(mesh.import_trust_bundle(&forged).await.is_err());
+
+        assert_eq!(mesh.import_trust_bundle(&bundle).await?, 2);
+        let peers = mesh.peers.read().await;
+        for member in &members {
+            let peer = peers.get(&member.id).expect("member installed");
+            assert_eq!(peer.trust_level, TrustLevel::System);
+            assert!(peer.shared_secret().is_some());
+        }
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_marks_peer_disconnected() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_idle_timeout_{}", Uuid::new_v4()));
+        let (mesh, _out, _in) = AiMesh::new(MeshConfig { name: "keepalive".into(), data_dir: base.clone(), networking: false, ..Default::default() })?;
+        let addr: SocketAddr = "10.0.0.7:8080".parse()?;
+        let mut gone = Peer::new(NodeSecrets::generate().identity("gone", "tool"));
+        gone.address = Some(addr);
+        gone.status = PeerStatus::Connected;
+        let mut alive = Peer::new(NodeSecrets::generate().identity("alive", "tool"));
+        alive.address = Some("10.0.0.8:8080".parse()?);
+        alive.status = PeerStatus::Connected;
+        let (gone_id, alive_id) = (gone.identity.id.clone(), alive.identity.id.clone());
+        mesh.add_peer(gone).await;
+        mesh.add_peer(alive).await;
+
+        // What the transport reports when keepalives go unanswered past the idle timeout
+        mesh.handle_link_event(LinkEvent::Lost { addr, reason: "timed out".into() }).await;
+
+        let peers = mesh.peers.read().await;
+        assert_eq!(peers.get(&gone_id).unwrap().status, PeerStatus::Disconnected);
+        assert_eq!(peers.get(&gone_id).unwrap().address, Some(addr));
+        assert_eq!(peers.get(&alive_id).unwrap().status, PeerStatus::Connected);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_to_disconnected_peer_errors() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_send_disconnected_{}", Uuid::new_v4()));
+        let (mesh, mut out, _in) = AiMesh::new(MeshConfig { name: "sender".into(), data_dir: base.clone(), networking: false, ..Default::default() })?;
+        let addr: SocketAddr = "10.0.0.9:8080".parse()?;
+        let mut peer = Peer::new(NodeSecrets::generate().identity("dead", "tool"));
+        peer.address = Some(addr);
+        peer.status = PeerStatus::Connected;
+        let peer_id = peer.identity.id.clone();
+        mesh.add_peer(peer).await;
+
+        mesh.send_direct(&peer_id, serde_json::json!({ "n": 1 })).await?;
+        assert!(out.try_recv().is_ok());
+
+        mesh.handle_link_event(LinkEvent::Lost { addr, reason: "timed out".into() }).await;
+        let err = mesh.send_direct(&peer_id, serde_json::json!({ "n": 2 })).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PeerUnreachable>(),
+            Some(&PeerUnreachable { peer_id: peer_id.clone(), status: PeerStatus::Disconnected })
+        );
+        // Nothing was queued for the dead link
+        assert!(out.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wrong_length_signature_is_dropped() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_signature_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        mesh_b.handle_message(AiMessage::discovery(&id_a, Peer::new(mesh_a.identity().clone()).to_discovery_info())).await?;
+
+        let thought = Thought {
+            content: serde_json::json!({"summary": "signed"}),
+            embedding: None,
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+        mesh_a.send_thought(thought, EmbeddingPolicy::Strip).await?;
+        let sent = out_a.recv().await.expect("thought in outbox");
+
+        // Under- and over-length signatures are dropped before the replay cache sees the nonce
+        for signature in [sent.signature[..126].to_string(), format!("{}00", sent.signature)] {
+            let mut forged = sent.clone();
+            forged.signature = signature;
+            mesh_b.handle_message(forged).await?;
+        }
+
+        mesh_b.handle_message(sent.clone()).await?;
+        assert_eq!(in_b.recv().await?.signature, sent.signature);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    /// A node `receiver` has discovered, with its own data dir, so the
+    /// messages it signs verify
+    async fn discovered_sender(receiver: &AiMesh, name: &str) -> Result<AiMesh> {
+        let data_dir = std::env::temp_dir().join(format!("test_data_{}", Uuid::new_v4()));
+        let (sender, _out, _in) = AiMesh::new(MeshConfig { name: name.into(), data_dir, networking: false, ..Default::default() })?;
+        receiver.add_peer(Peer::new(sender.identity().clone())).await;
+        Ok(sender)
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_messages_are_dropped() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_unsigned_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        let thought = |n: u64| {
+            let t = Thought { content: serde_json::json!({"n": n}), embedding: None, confidence: 1.0, context: None, tags: vec![] };
+            AiMessage::thought(&mesh_a.identity().id, &t, n)
+        };
+
+        // Authenticated sender, signature stripped
+        mesh_b.handle_message(thought(1)).await?;
+        assert!(in_b.try_recv().is_err());
+
+        // A stranger can't get past the handshake, signed or not
+        let (stranger, _out_x, _in_x) = AiMesh::new(config("node-x"))?;
+        let t = Thought { content: serde_json::json!({"n": 2}), embedding: None, confidence: 1.0, context: None, tags: vec![] };
+        let mut msg = AiMessage::thought(&stranger.identity().id, &t, 2);
+        stranger.sign_message(&mut msg);
+        mesh_b.handle_message(msg).await?;
+        assert!(in_b.try_recv().is_err());
+
+        let mut signed = thought(3);
+        mesh_a.sign_message(&mut signed);
+        mesh_b.handle_message(signed).await?;
+        assert_eq!(in_b.try_recv()?.sequence, 3);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_authenticates_over_loopback() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_dial_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), port: 0, ..Default::default() };
+        let (mesh_a, _out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        mesh_a.start_networking().await?;
+        mesh_b.start_networking().await?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+        let addr_b = SocketAddr::from(([127, 0, 0, 1], mesh_b.local_addr().await.unwrap().port()));
+
+        assert!(mesh_a.peers.read().await.get(&id_b).is_none());
+        assert_eq!(mesh_a.connect_to(addr_b).await?, id_b);
+        {
+            let peers = mesh_a.peers.read().await;
+            let b = peers.get(&id_b).expect("B joins A's table once authenticated");
+            assert_eq!(b.trust_level, TrustLevel::Authenticated);
+            assert_eq!(b.address, Some(addr_b));
+        }
+
+        // B authenticates A when the ACK lands
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while mesh_b.peers.read().await.get(&id_a).map(|p| p.trust_level) != Some(TrustLevel::Authenticated) {
+            assert!(tokio::time::Instant::now() < deadline, "B never authenticated A");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(mesh_b.peers.read().await.get(&id_a).unwrap().identity.name, "node-a");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_times_out_on_a_silent_address() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_dial_timeout_{}", Uuid::new_v4()));
+        let (mesh, _out, _in) = AiMesh::new(MeshConfig {
+            name: "dialer".into(),
+            data_dir: base.clone(),
+            port: 0,
+            handshake_timeout: Duration::from_millis(300),
+            ..Default::default()
+        })?;
+        mesh.start_networking().await?;
+
+        // Bound but never answers QUIC
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0")?;
+        let err = mesh.connect_to(silent.local_addr()?).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(mesh.pending_dials.read().await.is_empty());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rewritten_header_fails_verification() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_envelope_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_b.handle_message(AiMessage::discovery(&id_a, Peer::new(mesh_a.identity().clone()).to_discovery_info())).await?;
+
+        mesh_a.send_direct(&id_b, serde_json::json!({ "pay": 10 })).await?;
+        let sent = out_a.recv().await.expect("direct in outbox");
+        let signature = parse_signature(&sent.signature).unwrap();
+        assert!(verify_signature(&mesh_a.identity().signing_public, &sent.signing_bytes(), &signature)?);
+
+        // Readdressed: the payload is untouched but the envelope no longer verifies
+        let mut readdressed = sent.clone();
+        readdressed.recipient = Some("node-c".into());
+        assert!(!verify_signature(&mesh_a.identity().signing_public, &readdressed.signing_bytes(), &signature)?);
+        mesh_b.handle_message(readdressed).await?;
+        assert!(in_b.try_recv().is_err());
+
+        let entries = mesh_b.audit().since(None)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event, AuditEvent::SignatureRejected);
+
+        // Relays bumping the hop count don't invalidate it
+        let mut relayed = sent;
+        relayed.hops += 1;
+        mesh_b.handle_message(relayed).await?;
+        assert!(in_b.recv().await.is_ok());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_signature_rejection_is_audited() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_audit_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        mesh_b.handle_message(AiMessage::discovery(&id_a, Peer::new(mesh_a.identity().clone()).to_discovery_info())).await?;
+
+        let thought = Thought {
+            content: serde_json::json!({"summary": "signed"}),
+            embedding: None,
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+        mesh_a.send_thought(thought, EmbeddingPolicy::Strip).await?;
+        let mut forged = out_a.recv().await.expect("thought in outbox");
+        forged.payload = b"tampered".to_vec();
+        mesh_b.handle_message(forged).await?;
+
+        let entries = mesh_b.audit().since(None)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event, AuditEvent::SignatureRejected);
+        assert_eq!(entries[0].peer.as_deref(), Some(id_a.as_str()));
+        assert!(mesh_b.audit().path().starts_with(&mesh_b.node_root));
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gossiped_thought_drops_embedding_direct_keeps_it() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_embeddings_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, _out_b, mut in_b) = AiMesh::new(config("node-b"))?;
+        let id_a = mesh_a.identity().id.clone();
+        let id_b = mesh_b.identity().id.clone();
+        mesh_a.handle_message(AiMessage::discovery(&id_b, Peer::new(mesh_b.identity().clone()).to_discovery_info())).await?;
+        mesh_b.handle_message(AiMessage::discovery(&id_a, Peer::new(mesh_a.identity().clone()).to_discovery_info())).await?;
+
+        let thought = Thought {
+            content: serde_json::json!({"summary": "memory"}),
+            embedding: Some(vec![0.5; 1536]),
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+
+        // Gossip: the vector stays home
+        mesh_a.send_thought(thought.clone(), EmbeddingPolicy::Strip).await?;
+        mesh_b.handle_message(out_a.recv().await.expect("thought")).await?;
+        let gossiped: Thought = serde_json::from_slice(&in_b.recv().await?.payload)?;
+        assert!(gossiped.embedding.is_none());
+        assert_eq!(gossiped.content, thought.content);
+
+        // Direct memory sync: the vector travels (encrypted)
+        mesh_a.send_direct(&id_b, serde_json::to_value(&thought)?).await?;
+        mesh_b.handle_message(out_a.recv().await.expect("direct")).await?;
+        let sealed = in_b.recv().await?;
+        let plaintext = {
+            let peers = mesh_b.peers.read().await;
+            decrypt_message(peers.get(&id_a).unwrap().shared_secret().unwrap(), &sealed.payload, &direct_aad(&id_a, &id_b))?
+        };
+        let synced: Thought = serde_json::from_slice(&plaintext)?;
+        assert_eq!(synced.embedding.map(|e| e.len()), Some(1536));
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replay_protection() -> Result<()> {
+        let config = MeshConfig { name: "test-node".into(), ..Default::default() };
+        let (mesh, _out, _in) = AiMesh::new(config)?;
 
         let mut msg = AiMessage::thought("sender", &Thought {
             content: serde_json::json!({"test": "data"}),
@@ -680,12 +2554,180 @@ mod tests {
         assert!(!cache.check_and_add(msg.nonce));
         
         // Different nonce is okay
-        msg.nonce += 1;
+        msg.nonce = MessageNonce::random();
         assert!(cache.check_and_add(msg.nonce));
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_retransmitted_content_is_delivered_once() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_dedup_{}", Uuid::new_v4()));
+        let (mesh, _out, _in) = AiMesh::new(MeshConfig { name: "dedup".into(), data_dir: base.clone(), networking: false, ..Default::default() })?;
+        let mut inbox = mesh.inbox.subscribe();
+        let thought = Thought {
+            content: serde_json::json!({"retry": true}),
+            embedding: None,
+            confidence: 1.0,
+            context: None,
+            tags: vec![],
+        };
+
+        let sender = discovered_sender(&mesh, "sender").await?;
+        let signed = |sequence| {
+            let mut msg = AiMessage::thought(&sender.identity().id, &thought, sequence);
+            sender.sign_message(&mut msg);
+            msg
+        };
+
+        // Rebuilt for a retry: new id and nonce, same content and sequence
+        mesh.handle_message(signed(3)).await?;
+        mesh.handle_message(signed(3)).await?;
+        mesh.handle_message(signed(4)).await?;
+
+        assert_eq!(inbox.try_recv()?.sequence, 3);
+        assert_eq!(inbox.try_recv()?.sequence, 4);
+        assert!(inbox.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_flood_is_throttled() -> Result<()> {
+        use crate::clock::MockClock;
+
+        let base = std::env::temp_dir().join(format!("test_flood_{}", Uuid::new_v4()));
+        let clock = MockClock::starting_now();
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh, mut out, _in) = AiMesh::new(MeshConfig { clock: clock.clone(), ..config("node-a") })?;
+        let (attacker, mut out_x, _in_x) = AiMesh::new(config("node-x"))?;
+        attacker.add_peer(Peer::new(mesh.identity().clone())).await;
+        let source: SocketAddr = "198.51.100.9:4433".parse()?;
+
+        // Every SYN would elicit a SYN-ACK; the flood never completes a handshake
+        for round in 0..2 {
+            for _ in 0..50 {
+                attacker.initiate_handshake(&mesh.identity().id).await?;
+                mesh.handle_message_from(Some(source), out_x.recv().await.unwrap()).await?;
+            }
+            let mut replies = 0;
+            while out.try_recv().is_ok() {
+                replies += 1;
+            }
+            assert_eq!(replies, UNAUTH_MESSAGE_BUDGET, "round {}", round);
+
+            // A fresh window restores the budget
+            clock.advance(chrono::Duration::seconds(AMPLIFICATION_WINDOW_SECS + 1));
+        }
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claimed_peer_id_does_not_skip_the_budget() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_claimed_flood_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh_a, mut out_a, _in_a) = AiMesh::new(config("node-a"))?;
+        let (mesh_b, mut out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        handshake(&mesh_a, &mut out_a, &mesh_b, &mut out_b).await?;
+        let id_b = mesh_b.identity().id.clone();
+        let addr_b: SocketAddr = "10.0.0.8:4433".parse()?;
+        {
+            let mut peers = mesh_a.peers.write().await;
+            peers.get_mut(&id_b).unwrap().address = Some(addr_b);
+            mesh_a.reputation_manager.save(&peers.peers)?;
+        }
+        let reputation = mesh_a.node_root.join("data").join("reputation.json");
+        let saved_at = std::fs::metadata(&reputation)?.modified()?;
+
+        // Unsigned announcements under B's id, from somewhere else
+        let spoofer: SocketAddr = "198.51.100.9:4433".parse()?;
+        for _ in 0..UNAUTH_MESSAGE_BUDGET + 4 {
+            mesh_a.handle_message_from(Some(spoofer), AiMessage::discovery(&id_b, mesh_b.discovery_info())).await?;
+        }
+        assert_eq!(mesh_a.amplification.read().await.sources[&spoofer.to_string()].1, UNAUTH_MESSAGE_BUDGET + 4);
+
+        // From B's handshake address, or signed by B, they cost nothing
+        let spent = |guard: &AmplificationGuard| guard.sources.get(&id_b).map(|entry| entry.1);
+        let during_handshake = spent(&*mesh_a.amplification.read().await);
+        mesh_a.handle_message_from(Some(addr_b), AiMessage::discovery(&id_b, mesh_b.discovery_info())).await?;
+        let hello = Broadcast { channel: "alerts".into(), content: serde_json::json!({}), priority: 1, ttl: Default::default() };
+        mesh_b.broadcast(hello, EmbeddingPolicy::default()).await?;
+        mesh_a.handle_message(out_b.recv().await.unwrap()).await?;
+        let guard = mesh_a.amplification.read().await;
+        assert!(!guard.sources.contains_key(&addr_b.to_string()));
+        assert_eq!(spent(&guard), during_handshake);
+
+        // Nothing changed, so nothing was rewritten
+        assert_eq!(std::fs::metadata(&reputation)?.modified()?, saved_at);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[test]
+    fn test_amplification_sources_are_capped() {
+        use crate::clock::MockClock;
+
+        let clock = MockClock::starting_now();
+        let mut guard = AmplificationGuard::new(AMPLIFICATION_WINDOW_SECS, clock.clone());
+        for n in 0..AMPLIFICATION_SOURCES {
+            assert!(guard.admit(&format!("10.0.{}.{}:4433", n / 256, n % 256), 1));
+        }
+
+        // Full of live sources: newcomers wait, known ones keep their budget
+        assert!(!guard.admit("203.0.113.1:4433", 1));
+        assert!(guard.admit("10.0.0.0:4433", 1));
+        assert_eq!(guard.sources.len(), AMPLIFICATION_SOURCES);
+
+        clock.advance(chrono::Duration::seconds(AMPLIFICATION_WINDOW_SECS + 1));
+        assert!(guard.admit("203.0.113.1:4433", 1));
+        assert_eq!(guard.sources.len(), 1);
+    }
+
+    #[test]
+    fn test_replay_window_evicts_old_nonces() {
+        use crate::clock::{Clock, MockClock};
+
+        let clock = MockClock::starting_now();
+        let mut cache = ReplayCache::new(1000, 60, clock.clone());
+
+        let nonce = MessageNonce::random();
+        assert!(cache.check_and_add(nonce));
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(!cache.check_and_add(nonce));
+
+        // Forgotten once the window has passed; by then the message itself is stale
+        let sent_at = clock.now();
+        clock.advance(chrono::Duration::seconds(61));
+        assert!(cache.is_stale(sent_at));
+        assert!(cache.check_and_add(nonce));
+        assert_eq!(cache.order.len(), 1);
+    }
+
+    #[test]
+    fn test_full_replay_cache_fails_closed() {
+        use crate::clock::MockClock;
+
+        let clock = MockClock::starting_now();
+        let mut cache = ReplayCache::new(10, 60, clock.clone());
+        let first = MessageNonce::random();
+        assert!(cache.check_and_add(first));
+        for _ in 0..20 {
+            cache.check_and_add(MessageNonce::random());
+        }
+
+        // More than `capacity` messages later, still inside the window
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(!cache.check_and_add(first));
+        assert!(!cache.check_and_add(MessageNonce::random()));
+
+        clock.advance(chrono::Duration::seconds(31));
+        assert!(cache.check_and_add(MessageNonce::random()));
+    }
+
     #[tokio::test]
     async fn test_reputation_persistence() -> Result<()> {
         let temp_path = std::env::temp_dir().join(format!("reputation_{}.json", Uuid::new_v4()));
@@ -699,7 +2741,7 @@ mod tests {
         };
         
         {
-            let (mesh, _out, _in) = AiMesh::new(config.clone());
+            let (mesh, _out, _in) = AiMesh::new(config.clone())?;
             let mut peer = Peer::new(NodeIdentity {
                 id: "peer-1".into(),
                 exchange_public: [0u8; 32],
@@ -718,7 +2760,7 @@ mod tests {
         }
 
         // Reload in new mesh instance
-        let (mesh2, _out2, _in2) = AiMesh::new(config);
+        let (mesh2, _out2, _in2) = AiMesh::new(config)?;
         let peers2 = mesh2.peers.read().await;
         let peer2 = peers2.get("peer-1").expect("Peer should be reloaded");
         
@@ -729,6 +2771,200 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_reloaded_peer_keeps_keys() -> Result<()> {
+        let data_dir = std::env::temp_dir().join(format!("test_data_{}", Uuid::new_v4()));
+        let config = MeshConfig { name: "test-node".into(), data_dir: data_dir.clone(), ..Default::default() };
+        let (other, _out_b, _in_b) = AiMesh::new(MeshConfig { name: "node-b".into(), data_dir: data_dir.join("node-b"), ..Default::default() })?;
+
+        {
+            let (mesh, _out, _in) = AiMesh::new(config.clone())?;
+            mesh.add_peer(Peer::new(other.identity().clone())).await;
+            let peers = mesh.peers.read().await;
+            mesh.reputation_manager.save(&peers.peers)?;
+        }
+
+        let (mesh2, _out2, _in2) = AiMesh::new(config)?;
+        let peers = mesh2.peers.read().await;
+        let peer = peers.get(&other.identity().id).expect("Peer should be reloaded");
+        assert!(!peer.needs_discovery());
+        assert_eq!(peer.identity.signing_public, other.identity().signing_public);
+        assert_eq!(peer.identity.exchange_public, other.identity().exchange_public);
+        assert!(peer.shared_secret().is_some());
+
+        std::fs::remove_dir_all(data_dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_legacy_reputation_needs_rediscovery() -> Result<()> {
+        let data_dir = std::env::temp_dir().join(format!("test_data_{}", Uuid::new_v4()));
+        let config = MeshConfig { name: "test-node".into(), data_dir: data_dir.clone(), ..Default::default() };
+        let other_config = |name: &str| MeshConfig { name: name.into(), data_dir: data_dir.join(name), ..Default::default() };
+        let (other, _out_b, _in_b) = AiMesh::new(other_config("node-b"))?;
+        let (impostor, _out_x, _in_x) = AiMesh::new(other_config("node-x"))?;
+        let id_b = other.identity().id.clone();
+
+        // Reputation file from before keys were persisted
+        let reputation_path = {
+            let (mesh, _out, _in) = AiMesh::new(config.clone())?;
+            mesh.node_root.join("data").join("reputation.json")
+        };
+        std::fs::create_dir_all(reputation_path.parent().unwrap())?;
+        std::fs::write(&reputation_path, serde_json::to_string(&serde_json::json!([{
+            "id": id_b,
+            "trust_level": "Trusted",
+            "trust_score": 85,
+            "last_seen": chrono::Utc::now(),
+        }]))?)?;
+
+        let (mesh, _out, _in) = AiMesh::new(config)?;
+        {
+            let peers = mesh.peers.read().await;
+            let peer = peers.get(&id_b).expect("Peer should be reloaded");
+            assert!(peer.needs_discovery());
+            assert!(peer.shared_secret().is_none());
+        }
+
+        // Someone else's keys under node-b's id don't inherit its reputation
+        let mut forged = Peer::new(impostor.identity().clone()).to_discovery_info();
+        forged["id"] = serde_json::json!(id_b);
+        let err = mesh.handle_message(AiMessage::discovery(&id_b, forged)).await.unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+        assert!(mesh.peers.read().await.get(&id_b).unwrap().needs_discovery());
+
+        // Re-discovery restores the keys and keeps the earned reputation
+        let info = Peer::new(other.identity().clone()).to_discovery_info();
+        mesh.handle_message(AiMessage::discovery(&id_b, info)).await?;
+        let peers = mesh.peers.read().await;
+        let peer = peers.get(&id_b).unwrap();
+        assert!(!peer.needs_discovery());
+        assert_eq!(peer.identity.signing_public, other.identity().signing_public);
+        assert_eq!(peer.trust_level, crate::peer::TrustLevel::Trusted);
+        assert_eq!(peer.trust_score, 85);
+
+        std::fs::remove_dir_all(data_dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discovery_does_not_set_peer_addresses() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_discovery_address_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), networking: false, ..Default::default() };
+        let (mesh, _out, _in) = AiMesh::new(config("test-node"))?;
+        let (known, _out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        let (stranger, _out_c, _in_c) = AiMesh::new(config("node-c"))?;
+        let addr: SocketAddr = "10.0.0.7:8080".parse()?;
+        mesh.add_peer(Peer::new(known.identity().clone()).with_address(addr)).await;
+
+        let elsewhere = "203.0.113.9:9000";
+        for node in [&known, &stranger] {
+            let mut info = node.discovery_info();
+            info["address"] = elsewhere.into();
+            mesh.handle_message(AiMessage::discovery("relay", info)).await?;
+        }
+
+        let peers = mesh.peers.read().await;
+        assert_eq!(peers.get(&known.identity().id).unwrap().address, Some(addr));
+        assert_eq!(peers.get(&stranger.identity().id).unwrap().address, None);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_persisted_address_triggers_reconnect() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("test_reconnect_{}", Uuid::new_v4()));
+        let config = |name: &str| MeshConfig { name: name.into(), data_dir: base.join(name), port: 0, ..Default::default() };
+        let (other, _out_b, _in_b) = AiMesh::new(config("node-b"))?;
+        other.start_networking().await?;
+        let id_b = other.identity().id.clone();
+        let addr = SocketAddr::from(([127, 0, 0, 1], other.local_addr().await.unwrap().port()));
+
+        // Previous run learned node-b's address and trusted it
+        {
+            let (mesh, _out, _in) = AiMesh::new(config("test-node"))?;
+            let mut peer = Peer::new(other.identity().clone()).with_address(addr);
+            peer.set_trust_level(TrustLevel::Trusted);
+            mesh.add_peer(peer).await;
+            let peers = mesh.peers.read().await;
+            mesh.reputation_manager.save(&peers.peers)?;
+        }
+
+        let (mesh, _out, _in) = AiMesh::new(config("test-node"))?;
+        assert_eq!(mesh.peers.read().await.get(&id_b).unwrap().address, Some(addr));
+
+        // Starting the network dials it and re-runs the handshake
+        mesh.start_networking().await?;
+        {
+            let peers = mesh.peers.read().await;
+            let b = peers.get(&id_b).unwrap();
+            assert_eq!(b.status, PeerStatus::Connected);
+            assert_eq!(b.trust_level, TrustLevel::Trusted);
+        }
+        assert_eq!(mesh.reconnect_known_peers().await, 1);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trusted_peers_are_pinned_as_system() -> Result<()> {
+        let data_dir = std::env::temp_dir().join(format!("test_data_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&data_dir)?;
+        let (seed, _out_s, _in_s) = AiMesh::new(MeshConfig { name: "seed".into(), data_dir: data_dir.join("seed"), ..Default::default() })?;
+        let seed_id = seed.identity();
+
+        let pinned_file = data_dir.join("trusted_peers.toml");
+        std::fs::write(&pinned_file, format!(
+            "[[peer]]\nid = \"{}\"\nsigning_public = \"{}\"\nexchange_public = \"{}\"\nname = \"seed-1\"\n",
+            seed_id.id, hex::encode(seed_id.signing_public), hex::encode(seed_id.exchange_public),
+        ))?;
+        let config = MeshConfig {
+            name: "test-node".into(),
+            data_dir: data_dir.clone(),
+            trusted_peers: Some(pinned_file.clone()),
+            ..Default::default()
+        };
+
+        {
+            let (mesh, _out, _in) = AiMesh::new(config.clone())?;
+            let mut peers = mesh.peers.write().await;
+            let peer = peers.get_mut(&seed_id.id).expect("Pinned peer should be loaded");
+            assert_eq!(peer.trust_level, crate::peer::TrustLevel::System);
+            assert_eq!(peer.identity.name, "seed-1");
+            assert!(peer.shared_secret().is_some());
+
+            // Reputation can't silence a pinned peer
+            peer.trust_score = 0;
+            drop(peers);
+            let mut msg = AiMessage::thought(&seed_id.id, &Thought {
+                content: serde_json::json!({"hello": "mesh"}),
+                embedding: None,
+                confidence: 1.0,
+                context: None,
+                tags: vec![],
+            }, 0);
+            seed.sign_message(&mut msg);
+            let mut inbox = mesh.inbox.subscribe();
+            mesh.handle_message(msg).await?;
+            assert!(inbox.try_recv().is_ok());
+        }
+
+        // An id that isn't SHA256(signing_public) rejects the file
+        std::fs::write(&pinned_file, format!(
+            "[[peer]]\nid = \"{}\"\nsigning_public = \"{}\"\nexchange_public = \"{}\"\n",
+            "f".repeat(64), hex::encode(seed_id.signing_public), hex::encode(seed_id.exchange_public),
+        ))?;
+        assert!(load_trusted_peers(&pinned_file).is_err_and(|e| e.to_string().contains("does not match")));
+        let (mesh, _out, _in) = AiMesh::new(config)?;
+        let trust = mesh.peers.read().await.get(&seed_id.id).map(|p| p.trust_level);
+        assert_ne!(trust, Some(crate::peer::TrustLevel::System));
+
+        std::fs::remove_dir_all(data_dir)?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_identity_persistence() -> Result<()> {
         let temp_dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
@@ -741,11 +2977,11 @@ mod tests {
             ..Default::default() 
         };
         
-        let (mesh1, _, _) = AiMesh::new(config.clone());
+        let (mesh1, _, _) = AiMesh::new(config.clone())?;
         let id1 = mesh1.identity().id.clone();
         
         // 2. Second Boot: Load
-        let (mesh2, _, _) = AiMesh::new(config);
+        let (mesh2, _, _) = AiMesh::new(config)?;
         let id2 = mesh2.identity().id.clone();
         
         // IDs must match
@@ -754,41 +2990,184 @@ mod tests {
         std::fs::remove_dir_all(temp_dir)?;
         Ok(())
     }
+    #[tokio::test]
+    async fn test_corrupt_identity_is_an_error() -> Result<()> {
+        use crate::identity::IdentityError;
+
+        let temp_dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let config = MeshConfig { name: "test-node".into(), data_dir: temp_dir.clone(), networking: false, ..Default::default() };
+        let (mesh, _, _) = AiMesh::new(config.clone())?;
+        let key = mesh.node_root.join("data").join("identity.key");
+        drop(mesh);
+
+        std::fs::write(&key, b"{ \"identity\": truncated")?;
+        match AiMesh::new(config) {
+            Ok(_) => panic!("booted from a corrupt identity"),
+            Err(e) => assert!(matches!(e.downcast_ref::<IdentityError>(), Some(IdentityError::Corrupt { .. })), "{:#}", e),
+        }
+
+        std::fs::remove_dir_all(temp_dir)?;
+        Ok(())
+    }
+}
+
+/// Mark the peer behind a timed-out connection as disconnected. The address
+/// is kept so the next send or reconnect pass can try it again.
+async fn mark_link_lost(peers: &RwLock<PeerTable>, event: &LinkEvent) {
+    let LinkEvent::Lost { addr, reason } = event;
+    let mut peers = peers.write().await;
+    for peer in peers.peers.values_mut().filter(|p| p.address == Some(*addr)) {
+        if peer.status != PeerStatus::Blocked {
+            warn!("Lost connection to {} at {}: {}", peer.identity.id, addr, reason);
+            peer.status = PeerStatus::Disconnected;
+        }
+    }
+}
+
+/// Decode a hex-encoded 32-byte public key from discovery info
+fn parse_public_key(info: &serde_json::Value, field: &str) -> Result<[u8; 32]> {
+    let encoded = info.get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Discovery rejected: missing {}", field))?;
+    let bytes = hex::decode(encoded)
+        .map_err(|e| anyhow::anyhow!("Discovery rejected: {} is not valid hex: {}", field, e))?;
+    bytes.try_into()
+        .map_err(|b: Vec<u8>| anyhow::anyhow!("Discovery rejected: {} must be 32 bytes, got {}", field, b.len()))
+}
+
+/// The key a handshake from a sender we don't know yet must be signed with:
+/// the one it carries, provided the sender's id derives from it. Only a SYN
+/// (or a SYN-ACK answering our own dial) may come from a stranger.
+fn handshake_signer(msg: &AiMessage) -> Option<[u8; 32]> {
+    if msg.msg_type != MessageType::Handshake {
+        return None;
+    }
+    let hs: crate::messages::HandshakeMessage = serde_json::from_slice(&msg.payload).ok()?;
+    let opening = matches!(hs.kind, crate::messages::HandshakeKind::Syn | crate::messages::HandshakeKind::SynAck);
+    (opening && crate::crypto::node_id(&hs.signing_public) == msg.sender).then_some(hs.signing_public)
+}
+
+/// Decode a hex-encoded Ed25519 signature, rejecting any other length
+fn parse_signature(encoded: &str) -> Option<[u8; 64]> {
+    hex::decode(encoded).ok()?.try_into().ok()
+}
+
+/// How long a nonce is remembered (and how old a message may be)
+const REPLAY_WINDOW_SECS: i64 = 600;
+/// Nonces a replay cache holds at once. A full cache refuses new messages
+/// until its oldest nonces leave the window, so this caps the sustained rate
+/// (about 80 messages a second over the default 600s window).
+const REPLAY_CACHE_CAPACITY: usize = 50_000;
+/// Default cap on the clock skew corrected per peer
+const MAX_CLOCK_SKEW_SECS: i64 = 3600;
+
+const AMPLIFICATION_WINDOW_SECS: i64 = 60;
+/// Messages an unauthenticated source may have handled per window
+const UNAUTH_MESSAGE_BUDGET: u32 = 16;
+/// Payload bytes an unauthenticated source may send per window
+const UNAUTH_BYTE_BUDGET: usize = 64 * 1024;
+/// Sources tracked at once. Idle ones are swept when full; if none are idle,
+/// new sources are refused until the window turns over.
+const AMPLIFICATION_SOURCES: usize = 4096;
+
+/// Per-source budget for traffic from peers that have not completed a handshake
+struct AmplificationGuard {
+    /// Window start, messages and bytes seen per source
+    sources: HashMap<String, (DateTime<Utc>, u32, usize)>,
+    window: chrono::Duration,
+    clock: SharedClock,
+}
+
+impl AmplificationGuard {
+    fn new(window_secs: i64, clock: SharedClock) -> Self {
+        Self {
+            sources: HashMap::new(),
+            window: chrono::Duration::seconds(window_secs),
+            clock,
+        }
+    }
+
+    /// Count a message against `source`. Returns false once over budget.
+    fn admit(&mut self, source: &str, bytes: usize) -> bool {
+        let now = self.clock.now();
+        if self.sources.len() >= AMPLIFICATION_SOURCES && !self.sources.contains_key(source) {
+            let window = self.window;
+            self.sources.retain(|_, (start, _, _)| *start >= now - window);
+            if self.sources.len() >= AMPLIFICATION_SOURCES {
+                debug!("Refusing unauthenticated traffic from {}: tracking {} sources already", source, self.sources.len());
+                return false;
+            }
+        }
+
+        let entry = self.sources.entry(source.to_string()).or_insert((now, 0, 0));
+        if entry.0 < now - self.window {
+            *entry = (now, 0, 0);
+        }
+        entry.1 += 1;
+        entry.2 += bytes;
+
+        let within = entry.1 <= UNAUTH_MESSAGE_BUDGET && entry.2 <= UNAUTH_BYTE_BUDGET;
+        // Warn once per window, not once per dropped message
+        if !within && entry.1 == UNAUTH_MESSAGE_BUDGET + 1 {
+            warn!("Throttling unauthenticated traffic from {} until it completes a handshake", source);
+        }
+        within
+    }
 }
 
 /// Cache to prevent message replay attacks
-struct ReplayCache {
+struct ReplayCache<K = MessageNonce> {
     /// Seen nonces
-    seen: HashSet<u64>,
-    /// Order of arrival for eviction
-    order: VecDeque<u64>,
+    seen: HashSet<K>,
+    /// Order of arrival (with arrival time) for eviction
+    order: VecDeque<(K, DateTime<Utc>)>,
     /// Maximum capacity
     capacity: usize,
+    /// Nonces older than this are evicted
+    window: chrono::Duration,
+    clock: SharedClock,
 }
 
-impl ReplayCache {
-    fn new(capacity: usize) -> Self {
+impl<K: Copy + Eq + std::hash::Hash> ReplayCache<K> {
+    fn new(capacity: usize, window_secs: i64, clock: SharedClock) -> Self {
         Self {
-            seen: HashSet::with_capacity(capacity),
-            order: VecDeque::with_capacity(capacity),
+            seen: HashSet::new(),
+            order: VecDeque::new(),
             capacity,
+            window: chrono::Duration::seconds(window_secs),
+            clock,
         }
     }
 
+    /// Sent before the replay window, i.e. its nonce may already be forgotten
+    fn is_stale(&self, sent_at: DateTime<Utc>) -> bool {
+        sent_at < self.clock.now() - self.window
+    }
+
     /// Check if nonce is new and add it if so. Returns true if unique.
-    fn check_and_add(&mut self, nonce: u64) -> bool {
+    /// A cache full of nonces still inside the window refuses new ones
+    /// rather than forget one that could then be replayed.
+    fn check_and_add(&mut self, nonce: K) -> bool {
+        let now = self.clock.now();
+        while let Some(&(old, seen_at)) = self.order.front() {
+            if seen_at >= now - self.window {
+                break;
+            }
+            self.order.pop_front();
+            self.seen.remove(&old);
+        }
+
         if self.seen.contains(&nonce) {
             return false;
         }
 
         if self.order.len() >= self.capacity {
-            if let Some(old) = self.order.pop_front() {
-                self.seen.remove(&old);
-            }
+            warn!("Replay cache full ({} nonces in {}s); refusing new nonces until the oldest expire", self.capacity, self.window.num_seconds());
+            return false;
         }
 
         self.seen.insert(nonce);
-        self.order.push_back(nonce);
+        self.order.push_back((nonce, now));
         true
     }
 }
@@ -1,6 +1,7 @@
 //! AI-to-AI Message Types
 //! Defines the protocol for node communication
 
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -28,6 +29,12 @@ pub enum MessageType {
     EvolutionProposal,
     /// Handshake protocol
     Handshake,
+    /// A passed governance proposal with its ballots
+    ProposalPassed,
+    /// A type added by a newer build. Received messages of this type are
+    /// dropped; new types must be unit variants for this to catch them.
+    #[serde(other)]
+    Unknown,
 }
 
 /// LangChain-compatible Message Types (Strict Alignment)
@@ -69,6 +76,10 @@ pub enum HandshakeKind {
     SynAck,
     /// ACK (Finalization)
     Ack,
+    /// Session re-key offer; `exchange_public` is a fresh ephemeral key
+    Rekey,
+    /// Answer to `Rekey` with the responder's ephemeral key
+    RekeyAck,
 }
 
 /// Handshake message for key exchange and authentication
@@ -84,8 +95,54 @@ pub struct HandshakeMessage {
     pub nonce: [u8; 16],
     /// Optional challenge (encrypted for SYN-ACK)
     pub challenge: Option<Vec<u8>>,
+    /// Wire features the sender understands (`CAP_*` bits; 0 for older peers)
+    #[serde(default)]
+    pub capabilities: u32,
 }
 
+/// Peer accepts zstd-compressed payloads
+pub const CAP_ZSTD: u32 = 1 << 0;
+/// Wire features this build understands
+pub const LOCAL_CAPABILITIES: u32 = CAP_ZSTD;
+
+/// Wire envelope version written by this build (first byte of `to_bytes`)
+pub const WIRE_VERSION: u8 = 1;
+/// Oldest envelope version this build still parses
+pub const MIN_WIRE_VERSION: u8 = 1;
+/// Newest envelope version this build parses
+pub const MAX_WIRE_VERSION: u8 = WIRE_VERSION;
+
+/// Why a received envelope could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// Nothing to read, not even the version byte
+    Empty,
+    /// Sent by a build whose envelope we don't understand
+    UnsupportedVersion { version: u8, min: u8, max: u8 },
+    /// Right version, but the body did not decode
+    Malformed(String),
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireError::Empty => write!(f, "empty message envelope"),
+            WireError::UnsupportedVersion { version, min, max } => {
+                write!(f, "unsupported wire version {} (supported {}..={})", version, min, max)
+            }
+            WireError::Malformed(e) => write!(f, "malformed message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+/// Payloads smaller than this are sent as-is: zstd framing would eat the gain
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+/// Refuse to inflate a payload beyond this (decompression bombs)
+pub const MAX_DECOMPRESSED_BYTES: usize = 16 * 1024 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+
 /// An AI thought to be shared across the mesh
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Thought {
@@ -101,6 +158,138 @@ pub struct Thought {
     pub tags: Vec<String>,
 }
 
+/// Whether embeddings travel with a fanned-out thought or broadcast.
+/// Relays rarely need the ~6KB vector; receivers that do re-embed locally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmbeddingPolicy {
+    /// Drop the embedding before sending
+    #[default]
+    Strip,
+    /// Send it (e.g. memory sync)
+    Keep,
+}
+
+impl Thought {
+    /// Apply an embedding policy before the thought leaves the node
+    pub fn for_transport(mut self, policy: EmbeddingPolicy) -> Self {
+        if policy == EmbeddingPolicy::Strip {
+            self.embedding = None;
+        }
+        self
+    }
+}
+
+/// Default hop budget for forwarded messages
+pub const DEFAULT_MAX_HOPS: u8 = 5;
+/// Default lifetime for forwarded messages
+pub const DEFAULT_LIFETIME_SECS: u64 = 300;
+
+/// Time-to-live of a forwarded message: it dies when it has travelled more
+/// than `max_hops` or the wall clock passes `expires_at`, whichever is first.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Ttl {
+    pub max_hops: u8,
+    /// Unix timestamp (seconds)
+    pub expires_at: u64,
+}
+
+impl Ttl {
+    pub fn new(max_hops: u8, lifetime_secs: u64) -> Self {
+        Self::starting_at(unix_now(), max_hops, lifetime_secs)
+    }
+
+    /// TTL whose lifetime counts from `now` (Unix seconds)
+    pub fn starting_at(now: u64, max_hops: u8, lifetime_secs: u64) -> Self {
+        Self {
+            max_hops,
+            expires_at: now.saturating_add(lifetime_secs),
+        }
+    }
+
+    pub fn hops_exhausted(&self, hops: u8) -> bool {
+        hops > self.max_hops
+    }
+
+    pub fn time_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Too far or too old to deliver
+    pub fn is_expired(&self, hops: u8, now: u64) -> bool {
+        self.hops_exhausted(hops) || self.time_expired(now)
+    }
+
+    /// Another hop is still within both bounds
+    pub fn can_forward(&self, hops: u8, now: u64) -> bool {
+        !self.is_expired(hops.saturating_add(1), now)
+    }
+}
+
+impl Default for Ttl {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_HOPS, DEFAULT_LIFETIME_SECS)
+    }
+}
+
+/// Replay-protection nonce: 128 independently drawn random bits.
+///
+/// Nonces carry no ordering (that's `sequence`), so two messages can only
+/// share one by a birthday collision. For `n` messages the chance of any
+/// collision is at most n² / 2^129: about 1.5e-15 after a trillion messages,
+/// where a 64-bit nonce would already collide with near certainty.
+/// Travels as a 32-digit hex string so JSON clients without 128-bit integers
+/// can carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageNonce(u128);
+
+impl MessageNonce {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// Upper bound on the probability that any two of `messages` nonces collide
+    pub fn collision_bound(messages: f64) -> f64 {
+        (messages * messages / 2f64.powi(129)).min(1.0)
+    }
+}
+
+impl std::fmt::Display for MessageNonce {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+impl Serialize for MessageNonce {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageNonce {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        u128::from_str_radix(&hex, 16).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Identity of a message's content: SHA-256 over sender, payload and sequence.
+///
+/// Unlike `AiMessage::id` and the nonce, both drawn fresh per construction, a
+/// retransmission of the same logical message hashes to the same value, so
+/// dedup works end to end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentId([u8; 32]);
+
+impl std::fmt::Display for ContentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
+
 /// A broadcast message to all nodes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Broadcast {
@@ -110,8 +299,20 @@ pub struct Broadcast {
     pub content: serde_json::Value,
     /// Priority (higher = more important)
     pub priority: u8,
-    /// TTL in hops
-    pub ttl: u8,
+    /// Hop and wall-clock bounds
+    pub ttl: Ttl,
+}
+
+impl Broadcast {
+    /// Apply an embedding policy to the content (a top-level `embedding` field)
+    pub fn for_transport(mut self, policy: EmbeddingPolicy) -> Self {
+        if policy == EmbeddingPolicy::Strip {
+            if let Some(content) = self.content.as_object_mut() {
+                content.remove("embedding");
+            }
+        }
+        self
+    }
 }
 
 /// Complete AI message envelope
@@ -129,17 +330,99 @@ pub struct AiMessage {
     pub timestamp: DateTime<Utc>,
     /// Payload (encrypted content)
     pub payload: Vec<u8>,
-    /// Signature of payload (as hex string for serialization)
+    /// Signature of `signing_bytes` (as hex string for serialization)
     pub signature: String,
     /// Message sequence number (for ordering)
     pub sequence: u64,
     /// Nonce for replay protection
-    pub nonce: u64,
+    pub nonce: MessageNonce,
     /// Optional reply-to message ID
     pub reply_to: Option<Uuid>,
+    /// Bounds for forwarded messages (None for point-to-point traffic)
+    #[serde(default)]
+    pub ttl: Option<Ttl>,
+    /// Hops travelled so far
+    #[serde(default)]
+    pub hops: u8,
+    /// Payload is zstd-compressed; the signature covers the compressed bytes
+    #[serde(default)]
+    pub compressed: bool,
 }
 
 impl AiMessage {
+    /// Compress the payload if it is at least `threshold` bytes and zstd
+    /// actually shrinks it. Must run before signing.
+    pub fn compress(&mut self, threshold: usize) -> Result<()> {
+        if self.compressed || self.payload.len() < threshold {
+            return Ok(());
+        }
+        let packed = zstd::bulk::compress(&self.payload, ZSTD_LEVEL)?;
+        if packed.len() < self.payload.len() {
+            self.payload = packed;
+            self.compressed = true;
+        }
+        Ok(())
+    }
+
+    /// Content-derived id for dedup caches; `id` stays for transient tracking.
+    /// Covers the payload as it is now, so compare before compressing or after
+    /// decompressing.
+    pub fn content_id(&self) -> ContentId {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(self.sender.as_bytes());
+        hasher.update([0]);
+        hasher.update(&self.payload);
+        hasher.update(self.sequence.to_be_bytes());
+        ContentId(hasher.finalize().into())
+    }
+
+    /// What the sender signs: every header field the sender sets, then the
+    /// payload as it travels. Leaves out `id` (transient), `hops` (bumped by
+    /// relays) and the signature itself. Fields are length-prefixed so no two
+    /// envelopes serialize alike.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        fn field(bytes: &mut Vec<u8>, value: &[u8]) {
+            bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(value);
+        }
+
+        let mut bytes = b"ippoc-envelope-v1".to_vec();
+        field(&mut bytes, &serde_json::to_vec(&self.msg_type).unwrap_or_default());
+        field(&mut bytes, self.sender.as_bytes());
+        match &self.recipient {
+            Some(recipient) => field(&mut bytes, &[&[1u8][..], recipient.as_bytes()].concat()),
+            None => field(&mut bytes, &[0]),
+        }
+        bytes.extend_from_slice(&self.timestamp.timestamp().to_be_bytes());
+        bytes.extend_from_slice(&self.timestamp.timestamp_subsec_nanos().to_be_bytes());
+        bytes.extend_from_slice(&self.sequence.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce.0.to_be_bytes());
+        match self.reply_to {
+            Some(id) => field(&mut bytes, &[&[1u8][..], &id.as_bytes()[..]].concat()),
+            None => field(&mut bytes, &[0]),
+        }
+        match self.ttl {
+            Some(ttl) => {
+                bytes.extend_from_slice(&[1, ttl.max_hops]);
+                bytes.extend_from_slice(&ttl.expires_at.to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(self.compressed as u8);
+        field(&mut bytes, &self.payload);
+        bytes
+    }
+
+    /// Restore the original payload. Run after the signature has been checked.
+    pub fn decompress(&mut self) -> Result<()> {
+        if self.compressed {
+            self.payload = zstd::bulk::decompress(&self.payload, MAX_DECOMPRESSED_BYTES)?;
+            self.compressed = false;
+        }
+        Ok(())
+    }
+
     /// Create a new thought message
     pub fn thought(sender: &str, thought: &Thought, sequence: u64) -> Self {
         let payload = serde_json::to_vec(thought).unwrap_or_default();
@@ -152,8 +435,11 @@ impl AiMessage {
             payload,
             signature: String::new(),
             sequence,
-            nonce: rand::random(),
+            nonce: MessageNonce::random(),
             reply_to: None,
+            ttl: Some(Ttl::default()),
+            hops: 0,
+            compressed: false,
         }
     }
 
@@ -169,8 +455,11 @@ impl AiMessage {
             payload,
             signature: String::new(),
             sequence,
-            nonce: rand::random(),
+            nonce: MessageNonce::random(),
             reply_to: None,
+            ttl: Some(broadcast.ttl),
+            hops: 0,
+            compressed: false,
         }
     }
 
@@ -186,8 +475,31 @@ impl AiMessage {
             payload,
             signature: String::new(),
             sequence,
-            nonce: rand::random(),
+            nonce: MessageNonce::random(),
+            reply_to: None,
+            ttl: None,
+            hops: 0,
+            compressed: false,
+        }
+    }
+
+    /// Create a message announcing a passed governance proposal
+    pub fn proposal_passed(sender: &str, passed: &crate::governance::PassedProposal, sequence: u64) -> Self {
+        let payload = serde_json::to_vec(passed).unwrap_or_default();
+        Self {
+            id: Uuid::new_v4(),
+            msg_type: MessageType::ProposalPassed,
+            sender: sender.to_string(),
+            recipient: None,
+            timestamp: Utc::now(),
+            payload,
+            signature: String::new(),
+            sequence,
+            nonce: MessageNonce::random(),
             reply_to: None,
+            ttl: Some(Ttl::default()),
+            hops: 0,
+            compressed: false,
         }
     }
 
@@ -203,8 +515,11 @@ impl AiMessage {
             payload,
             signature: String::new(),
             sequence: 0,
-            nonce: rand::random(),
+            nonce: MessageNonce::random(),
             reply_to: None,
+            ttl: None,
+            hops: 0,
+            compressed: false,
         }
     }
 
@@ -220,19 +535,48 @@ impl AiMessage {
             payload,
             signature: String::new(),
             sequence: 0,
-            nonce: rand::random(),
+            nonce: MessageNonce::random(),
             reply_to: None,
+            ttl: None,
+            hops: 0,
+            compressed: false,
         }
     }
 
-    /// Serialize for transmission
+    /// Whether a forwarded message has outlived either TTL bound
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.ttl.is_some_and(|ttl| ttl.is_expired(self.hops, now))
+    }
+
+    /// Copy for relaying one hop further, or None once a bound is reached
+    pub fn forwarded(&self, now: u64) -> Option<Self> {
+        let ttl = self.ttl?;
+        if !ttl.can_forward(self.hops, now) {
+            return None;
+        }
+        let mut next = self.clone();
+        next.hops += 1;
+        Some(next)
+    }
+
+    /// Serialize for transmission: `WIRE_VERSION` followed by the bincode body
     pub fn to_bytes(&self) -> Vec<u8> {
-        bincode::serialize(self).unwrap_or_default()
+        let mut bytes = vec![WIRE_VERSION];
+        bytes.extend(bincode::serialize(self).unwrap_or_default());
+        bytes
     }
 
-    /// Deserialize from bytes
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        bincode::deserialize(bytes).ok()
+    /// Deserialize an envelope written by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let (&version, body) = bytes.split_first().ok_or(WireError::Empty)?;
+        if !(MIN_WIRE_VERSION..=MAX_WIRE_VERSION).contains(&version) {
+            return Err(WireError::UnsupportedVersion {
+                version,
+                min: MIN_WIRE_VERSION,
+                max: MAX_WIRE_VERSION,
+            });
+        }
+        bincode::deserialize(body).map_err(|e| WireError::Malformed(e.to_string()))
     }
 }
 
@@ -278,3 +622,331 @@ pub struct EvolutionProposal {
     /// Current votes
     pub votes: Vec<Uuid>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay(sender: &str, ttl: Ttl) -> AiMessage {
+        let broadcast = Broadcast {
+            channel: "test".into(),
+            content: serde_json::json!({}),
+            priority: 1,
+            ttl,
+        };
+        AiMessage::broadcast(sender, &broadcast, 0)
+    }
+
+    #[test]
+    fn test_hop_exhaustion_drops_message() {
+        let now = unix_now();
+        // Plenty of time, two hops
+        let mut msg = relay("node-a", Ttl { max_hops: 2, expires_at: now + 3600 });
+
+        for _ in 0..2 {
+            msg = msg.forwarded(now).expect("within hop budget");
+            assert!(!msg.is_expired(now));
+        }
+        assert_eq!(msg.hops, 2);
+        assert!(msg.forwarded(now).is_none());
+
+        // A peer that relays anyway produces a message receivers drop
+        msg.hops += 1;
+        assert!(msg.is_expired(now));
+    }
+
+    #[test]
+    fn test_time_expiry_drops_message() {
+        let now = unix_now();
+        // Plenty of hops, short lifetime
+        let msg = relay("node-a", Ttl { max_hops: 50, expires_at: now + 10 });
+
+        assert!(!msg.is_expired(now));
+        assert!(msg.forwarded(now + 5).is_some());
+        assert!(msg.is_expired(now + 10));
+        assert!(msg.forwarded(now + 10).is_none());
+    }
+
+    #[test]
+    fn test_point_to_point_messages_never_expire() {
+        let msg = AiMessage::direct("node-a", "node-b", serde_json::json!({}), 0);
+        assert!(!msg.is_expired(u64::MAX));
+        assert!(msg.forwarded(0).is_none());
+    }
+
+    #[test]
+    fn test_nonce_collision_probability_within_bound() {
+        // A trillion messages stay below one-in-a-hundred-trillion odds
+        assert!(MessageNonce::collision_bound(1e12) < 1e-14);
+        // The old 64-bit nonce: n² / 2^65 is already ~3% at a billion messages
+        assert!(1e9f64 * 1e9 / 2f64.powi(65) > 0.02);
+
+        // Empirically: no duplicates in a sample whose bound is ~1e-29
+        let n = 100_000;
+        let nonces: std::collections::HashSet<_> = (0..n).map(|_| MessageNonce::random()).collect();
+        assert_eq!(nonces.len(), n);
+        assert!(MessageNonce::collision_bound(n as f64) < 1e-28);
+    }
+
+    #[test]
+    fn test_nonce_serializes_as_hex() {
+        let nonce = MessageNonce(u128::MAX - 1);
+        let json = serde_json::to_string(&nonce).unwrap();
+        assert_eq!(json, format!("\"{:032x}\"", u128::MAX - 1));
+        assert_eq!(serde_json::from_str::<MessageNonce>(&json).unwrap(), nonce);
+        assert!(serde_json::from_str::<MessageNonce>("\"not-hex\"").is_err());
+    }
+
+    #[test]
+    fn test_resent_content_keeps_content_id() {
+        let thought = embedding_thought();
+        let first = AiMessage::thought("node-a", &thought, 7);
+        let resent = AiMessage::thought("node-a", &thought, 7);
+        assert_ne!(first.id, resent.id);
+        assert_ne!(first.nonce, resent.nonce);
+        assert_eq!(first.content_id(), resent.content_id());
+
+        assert_ne!(AiMessage::thought("node-a", &thought, 8).content_id(), first.content_id());
+        assert_ne!(AiMessage::thought("node-b", &thought, 7).content_id(), first.content_id());
+    }
+
+    fn embedding_thought() -> Thought {
+        Thought {
+            content: serde_json::json!({"summary": "routing table converged after partition heal"}),
+            // Shaped like a real 1536-dim embedding: small values, limited precision
+            embedding: Some((0..1536).map(|i| ((i as f32 * 0.37).sin() * 0.05 * 1e4).round() / 1e4).collect()),
+            confidence: 0.9,
+            context: None,
+            tags: vec!["mesh".into()],
+        }
+    }
+
+    #[test]
+    fn test_embedding_thought_compresses() {
+        let mut msg = AiMessage::thought("node-a", &embedding_thought(), 0);
+        let original = msg.payload.clone();
+
+        msg.compress(COMPRESSION_THRESHOLD).unwrap();
+        assert!(msg.compressed);
+        let ratio = msg.payload.len() as f64 / original.len() as f64;
+        println!("embedding thought: {} -> {} bytes ({:.0}%)", original.len(), msg.payload.len(), ratio * 100.0);
+        assert!(ratio < 0.5, "expected at least 2x reduction, got {:.2}", ratio);
+
+        msg.decompress().unwrap();
+        assert!(!msg.compressed);
+        assert_eq!(msg.payload, original);
+    }
+
+    #[test]
+    fn test_small_payload_stays_uncompressed() {
+        let mut msg = AiMessage::direct("node-a", "node-b", serde_json::json!({"ping": 1}), 0);
+        let original = msg.payload.clone();
+        msg.compress(COMPRESSION_THRESHOLD).unwrap();
+        assert!(!msg.compressed);
+        assert_eq!(msg.payload, original);
+    }
+
+    #[test]
+    fn test_envelope_round_trips_and_rejects_newer_versions() {
+        let msg = relay("node-a", Ttl::default());
+        let bytes = msg.to_bytes();
+        assert_eq!(bytes[0], WIRE_VERSION);
+        let decoded = AiMessage::from_bytes(&bytes).expect("own envelope");
+        assert_eq!((decoded.id, decoded.msg_type), (msg.id, MessageType::Broadcast));
+
+        let mut newer = bytes.clone();
+        newer[0] = MAX_WIRE_VERSION + 1;
+        assert_eq!(
+            AiMessage::from_bytes(&newer).unwrap_err(),
+            WireError::UnsupportedVersion { version: MAX_WIRE_VERSION + 1, min: MIN_WIRE_VERSION, max: MAX_WIRE_VERSION }
+        );
+        assert_eq!(AiMessage::from_bytes(&[]).unwrap_err(), WireError::Empty);
+        assert!(matches!(AiMessage::from_bytes(&bytes[..10]), Err(WireError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_unknown_message_type_decodes_as_unknown() {
+        // bincode carries the variant index, JSON the name
+        let future_index = bincode::serialize(&999u32).unwrap();
+        assert_eq!(bincode::deserialize::<MessageType>(&future_index).unwrap(), MessageType::Unknown);
+        assert_eq!(serde_json::from_str::<MessageType>("\"Telepathy\"").unwrap(), MessageType::Unknown);
+        assert_eq!(serde_json::from_str::<MessageType>("\"Direct\"").unwrap(), MessageType::Direct);
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        // Dyadic fractions print and parse back exactly, so JSON equality is
+        // a fair check; arbitrary floats would test serde_json's float parser.
+        fn arb_f32() -> impl Strategy<Value = f32> {
+            (-4096i32..4096).prop_map(|n| n as f32 / 64.0)
+        }
+
+        fn arb_text() -> impl Strategy<Value = String> {
+            "\\PC{0,24}"
+        }
+
+        fn arb_json() -> impl Strategy<Value = serde_json::Value> {
+            let leaf = prop_oneof![
+                Just(serde_json::Value::Null),
+                any::<bool>().prop_map(serde_json::Value::from),
+                any::<i64>().prop_map(serde_json::Value::from),
+                any::<u64>().prop_map(serde_json::Value::from),
+                arb_text().prop_map(serde_json::Value::from),
+            ];
+            leaf.prop_recursive(3, 24, 4, |inner| {
+                prop_oneof![
+                    prop::collection::vec(inner.clone(), 0..4).prop_map(serde_json::Value::Array),
+                    prop::collection::btree_map(arb_text(), inner, 0..4)
+                        .prop_map(|m| serde_json::Value::Object(m.into_iter().collect())),
+                ]
+            })
+        }
+
+        fn arb_ttl() -> impl Strategy<Value = Ttl> {
+            (any::<u8>(), any::<u64>()).prop_map(|(max_hops, expires_at)| Ttl { max_hops, expires_at })
+        }
+
+        fn arb_msg_type() -> impl Strategy<Value = MessageType> {
+            prop::sample::select(vec![
+                MessageType::Thought,
+                MessageType::Broadcast,
+                MessageType::Direct,
+                MessageType::Discovery,
+                MessageType::CollaborationRequest,
+                MessageType::Response,
+                MessageType::MemorySync,
+                MessageType::ToolRequest,
+                MessageType::EvolutionProposal,
+                MessageType::Handshake,
+                MessageType::ProposalPassed,
+                MessageType::Unknown,
+            ])
+        }
+
+        fn arb_thought() -> impl Strategy<Value = Thought> {
+            (
+                arb_json(),
+                prop::option::of(prop::collection::vec(arb_f32(), 0..2048)),
+                arb_f32(),
+                prop::option::of(arb_text()),
+                prop::collection::vec(arb_text(), 0..4),
+            )
+                .prop_map(|(content, embedding, confidence, context, tags)| Thought {
+                    content,
+                    embedding,
+                    confidence,
+                    context,
+                    tags,
+                })
+        }
+
+        fn arb_broadcast() -> impl Strategy<Value = Broadcast> {
+            (arb_text(), arb_json(), any::<u8>(), arb_ttl())
+                .prop_map(|(channel, content, priority, ttl)| Broadcast { channel, content, priority, ttl })
+        }
+
+        fn arb_handshake() -> impl Strategy<Value = HandshakeMessage> {
+            (
+                prop::sample::select(vec![
+                    HandshakeKind::Syn,
+                    HandshakeKind::SynAck,
+                    HandshakeKind::Ack,
+                    HandshakeKind::Rekey,
+                    HandshakeKind::RekeyAck,
+                ]),
+                any::<[u8; 32]>(),
+                any::<[u8; 32]>(),
+                any::<[u8; 16]>(),
+                prop::option::of(prop::collection::vec(any::<u8>(), 0..64)),
+                any::<u32>(),
+            )
+                .prop_map(|(kind, exchange_public, signing_public, nonce, challenge, capabilities)| HandshakeMessage {
+                    kind,
+                    exchange_public,
+                    signing_public,
+                    nonce,
+                    challenge,
+                    capabilities,
+                })
+        }
+
+        fn arb_message() -> impl Strategy<Value = AiMessage> {
+            let header = (
+                any::<u128>(),
+                arb_msg_type(),
+                arb_text(),
+                prop::option::of(arb_text()),
+                // 1970 through 9999 (four-digit RFC 3339 years), to the nanosecond
+                (0i64..253_402_300_800, 0u32..1_000_000_000),
+            );
+            let body = (
+                prop::collection::vec(any::<u8>(), 0..4096),
+                arb_text(),
+                any::<u64>(),
+                any::<u128>(),
+                prop::option::of(any::<u128>()),
+                prop::option::of(arb_ttl()),
+                any::<u8>(),
+                any::<bool>(),
+            );
+            (header, body).prop_map(
+                |((id, msg_type, sender, recipient, (secs, nanos)), (payload, signature, sequence, nonce, reply_to, ttl, hops, compressed))| AiMessage {
+                    id: Uuid::from_u128(id),
+                    msg_type,
+                    sender,
+                    recipient,
+                    timestamp: DateTime::from_timestamp(secs, nanos).unwrap(),
+                    payload,
+                    signature,
+                    sequence,
+                    nonce: MessageNonce(nonce),
+                    reply_to: reply_to.map(Uuid::from_u128),
+                    ttl,
+                    hops,
+                    compressed,
+                },
+            )
+        }
+
+        /// Serialized JSON is the field-by-field comparison (the types don't implement PartialEq)
+        fn json_of<T: Serialize>(value: &T) -> String {
+            serde_json::to_string(value).expect("serializable")
+        }
+
+        fn json_round_trip<T: Serialize + serde::de::DeserializeOwned>(value: &T) -> String {
+            let decoded: T = serde_json::from_str(&json_of(value)).expect("decodes");
+            json_of(&decoded)
+        }
+
+        proptest! {
+            #[test]
+            fn prop_message_envelope_round_trips(msg in arb_message()) {
+                let decoded = AiMessage::from_bytes(&msg.to_bytes());
+                prop_assert!(decoded.is_ok(), "{:?}", decoded.err());
+                prop_assert_eq!(json_of(&decoded.unwrap()), json_of(&msg));
+            }
+
+            #[test]
+            fn prop_message_json_round_trips(msg in arb_message()) {
+                prop_assert_eq!(json_round_trip(&msg), json_of(&msg));
+            }
+
+            #[test]
+            fn prop_thought_json_round_trips(thought in arb_thought()) {
+                prop_assert_eq!(json_round_trip(&thought), json_of(&thought));
+            }
+
+            #[test]
+            fn prop_broadcast_json_round_trips(broadcast in arb_broadcast()) {
+                prop_assert_eq!(json_round_trip(&broadcast), json_of(&broadcast));
+            }
+
+            #[test]
+            fn prop_handshake_json_round_trips(hs in arb_handshake()) {
+                prop_assert_eq!(json_round_trip(&hs), json_of(&hs));
+            }
+        }
+    }
+}
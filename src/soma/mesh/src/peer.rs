@@ -5,8 +5,8 @@ use chrono::{DateTime, Utc};
 use std::net::SocketAddr;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use anyhow::Result;
-use crate::crypto::{NodeIdentity, SharedSecret};
+use anyhow::{anyhow, Result};
+use crate::crypto::{decrypt_message, EphemeralExchange, NodeIdentity, SharedSecret};
 
 /// Unique identifier for a peer (NodeID)
 #[allow(dead_code)]
@@ -25,23 +25,43 @@ pub enum PeerStatus {
     Disconnected,
     /// Blacklisted
     Blocked,
+    /// Restored without public keys; unusable until it announces itself again
+    NeedsDiscovery,
 }
 
-/// Admissions levels for trust
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub enum TrustLevel {
-    /// -1: Violations detected
-    Blacklisted = -1,
-    /// 0: Initial state
-    Unknown = 0,
-    /// 1: Detected via discovery
-    Discovered = 1,
-    /// 2: Cryptographically verified
-    Authenticated = 2,
-    /// 3: High reputation
-    Trusted = 3,
-    /// 4: User-bootstrapped
-    System = 4,
+pub use crate::trust::TrustLevel;
+
+/// When a peer's session key is rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    /// Direct messages sent under one key
+    pub after_messages: u64,
+    /// Age of a key in seconds
+    pub after_secs: u64,
+    /// How long the replaced key still decrypts once the peer uses the new one
+    pub overlap_secs: u64,
+    /// How long an unanswered offer is waited on before it is made again
+    pub retry_secs: u64,
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self {
+            after_messages: 10_000,
+            after_secs: 3600,
+            overlap_secs: 60,
+            retry_secs: 30,
+        }
+    }
+}
+
+/// Our half of a re-key offer, the nonce its answer must echo, and when it
+/// is given up on
+#[derive(Clone)]
+struct PendingRekey {
+    exchange: EphemeralExchange,
+    nonce: [u8; 16],
+    deadline: u64,
 }
 
 /// A peer in the AI mesh
@@ -57,6 +77,19 @@ pub struct Peer {
     pub trust_level: TrustLevel,
     /// Shared secret (once established)
     shared_secret: Option<SharedSecret>,
+    /// Key replaced by the last re-key. Accepted until the peer is seen
+    /// using the new key, then until the Unix time given.
+    previous_secret: Option<(SharedSecret, Option<u64>)>,
+    /// Keys agreed in answer to the peer's latest re-key offer. We keep
+    /// sending under the current key until the peer uses one, which proves
+    /// our answer arrived.
+    staged_secrets: Vec<SharedSecret>,
+    /// Our re-key offer the peer hasn't answered yet
+    pending_rekey: Option<PendingRekey>,
+    /// Direct messages sent under the current key
+    pub messages_since_rekey: u64,
+    /// When the current key came into use (Unix seconds)
+    pub keyed_at: Option<u64>,
     /// Last seen timestamp
     pub last_seen: DateTime<Utc>,
     /// Last message sequence
@@ -67,6 +100,11 @@ pub struct Peer {
     pub trust_score: u8,
     /// Capabilities/roles
     pub capabilities: Vec<String>,
+    /// Wire features negotiated in the handshake (`messages::CAP_*` bits)
+    pub wire_capabilities: u32,
+    /// How far the peer's clock runs ahead of ours in seconds (negative =
+    /// behind), measured from handshake replies
+    pub clock_offset_secs: Option<i64>,
 }
 
 impl Peer {
@@ -79,10 +117,17 @@ impl Peer {
             status: PeerStatus::Discovered,
             trust_level: TrustLevel::Discovered,
             shared_secret: None,
+            previous_secret: None,
+            staged_secrets: Vec::new(),
+            pending_rekey: None,
+            messages_since_rekey: 0,
+            keyed_at: None,
             last_seen: Utc::now(),
             last_sequence: 0,
             rtt_ms: 0,
             trust_score: 50, // Neutral trust
+            wire_capabilities: 0,
+            clock_offset_secs: None,
         }
     }
 
@@ -95,6 +140,11 @@ impl Peer {
     /// Set shared secret after key exchange
     pub fn set_shared_secret(&mut self, secret: SharedSecret) {
         self.shared_secret = Some(secret);
+        self.previous_secret = None;
+        self.staged_secrets.clear();
+        self.pending_rekey = None;
+        self.messages_since_rekey = 0;
+        self.keyed_at = None;
         self.status = PeerStatus::Connected;
     }
 
@@ -103,6 +153,98 @@ impl Peer {
         self.shared_secret.as_ref()
     }
 
+    /// Install a re-keyed secret. The old one stays valid for decryption
+    /// until `overlap_until`, or while that is None until the peer is seen
+    /// using the new key, so messages it sends meanwhile still open.
+    pub fn rotate_secret(&mut self, next: SharedSecret, now: u64, overlap_until: Option<u64>) {
+        self.previous_secret = self.shared_secret.replace(next).map(|old| (old, overlap_until));
+        self.staged_secrets.clear();
+        self.pending_rekey = None;
+        self.messages_since_rekey = 0;
+        self.keyed_at = Some(now);
+    }
+
+    /// Keys a re-key offer from the peer may have been built on: the
+    /// current one, and a still unconfirmed staged one if the peer got our
+    /// answer to its last offer (we cannot tell whether it did)
+    pub fn rekey_bases(&self) -> Vec<SharedSecret> {
+        self.shared_secret.iter().chain(self.staged_secrets.first()).cloned().collect()
+    }
+
+    /// Hold the keys agreed in answer to the peer's offer until it uses one
+    pub fn stage_secrets(&mut self, candidates: Vec<SharedSecret>) {
+        self.staged_secrets = candidates;
+        self.pending_rekey = None;
+    }
+
+    /// Decrypt with the current key, then a staged one (switching to it on
+    /// success), then the previous one while it is still accepted
+    pub fn decrypt(&mut self, encrypted: &[u8], aad: &[u8], now: u64, overlap_secs: u64) -> Result<Vec<u8>> {
+        let current = self.shared_secret.as_ref()
+            .ok_or_else(|| anyhow!("No shared secret with {}", self.identity.id))?;
+        let error = match decrypt_message(current, encrypted, aad) {
+            Ok(plaintext) => {
+                // The peer has switched: the old key only has to cover what is in flight
+                if let Some((_, until @ None)) = &mut self.previous_secret {
+                    *until = Some(now + overlap_secs);
+                }
+                return Ok(plaintext);
+            }
+            Err(e) => e,
+        };
+        let staged = self.staged_secrets.iter()
+            .find_map(|candidate| decrypt_message(candidate, encrypted, aad).ok().map(|plaintext| (candidate.clone(), plaintext)));
+        if let Some((next, plaintext)) = staged {
+            self.rotate_secret(next, now, Some(now + overlap_secs));
+            return Ok(plaintext);
+        }
+        match &self.previous_secret {
+            Some((previous, until)) if until.is_none_or(|until| now < until) => decrypt_message(previous, encrypted, aad),
+            _ => Err(error),
+        }
+    }
+
+    /// Count a direct message sent under the current key. True when the
+    /// policy says it is time to re-key, no offer of ours is awaiting an
+    /// answer and no answer of ours is awaiting the peer's switch.
+    pub fn record_sent(&mut self, now: u64, policy: &RekeyPolicy) -> bool {
+        self.messages_since_rekey += 1;
+        let keyed_at = *self.keyed_at.get_or_insert(now);
+        self.staged_secrets.is_empty()
+            && !self.rekey_pending(now)
+            && (self.messages_since_rekey >= policy.after_messages
+                || now.saturating_sub(keyed_at) >= policy.after_secs)
+    }
+
+    /// Remember our offer; unanswered by `deadline`, it is made again
+    pub fn start_rekey(&mut self, exchange: EphemeralExchange, nonce: [u8; 16], deadline: u64) {
+        self.pending_rekey = Some(PendingRekey { exchange, nonce, deadline });
+    }
+
+    pub fn rekey_pending(&self, now: u64) -> bool {
+        self.pending_rekey.as_ref().is_some_and(|p| now < p.deadline)
+    }
+
+    /// Our half of the offer answered by `nonce`, if it is the one outstanding
+    pub fn take_pending_rekey(&mut self, nonce: &[u8; 16]) -> Option<EphemeralExchange> {
+        match self.pending_rekey.take() {
+            Some(pending) if pending.nonce == *nonce => Some(pending.exchange),
+            other => {
+                self.pending_rekey = other;
+                None
+            }
+        }
+    }
+
+    /// Fold in one sample of the peer's timestamp minus our clock
+    pub fn observe_clock_offset(&mut self, sample_secs: i64) {
+        self.clock_offset_secs = Some(match self.clock_offset_secs {
+            // Smooth out network delay between samples
+            Some(current) => (3 * current + sample_secs) / 4,
+            None => sample_secs,
+        });
+    }
+
     /// Update last seen
     pub fn touch(&mut self) {
         self.last_seen = Utc::now();
@@ -113,6 +255,11 @@ impl Peer {
         self.trust_level = level;
     }
 
+    /// Whether the peer advertised a wire feature (`messages::CAP_*`)
+    pub fn supports(&self, capability: u32) -> bool {
+        self.wire_capabilities & capability != 0
+    }
+
     /// Mark as authenticated (successfully handshaked)
     pub fn authenticate(&mut self) {
         if self.trust_level < TrustLevel::Authenticated {
@@ -138,6 +285,11 @@ impl Peer {
         }
     }
 
+    /// Whether this peer's keys are unknown and must be re-learned via discovery
+    pub fn needs_discovery(&self) -> bool {
+        self.status == PeerStatus::NeedsDiscovery
+    }
+
     /// Check if peer is available for communication
     pub fn is_available(&self) -> bool {
         matches!(self.status, PeerStatus::Connected)
@@ -232,37 +384,176 @@ pub struct ReputationEntry {
     pub trust_level: TrustLevel,
     pub trust_score: u8,
     pub last_seen: chrono::DateTime<chrono::Utc>,
+    /// Public keys (hex) so a known peer is usable straight after restart.
+    /// Missing in files written before keys were persisted.
+    #[serde(default)]
+    pub exchange_public: Option<String>,
+    #[serde(default)]
+    pub signing_public: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Last address the peer was reachable at, for reconnecting after restart
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+}
+
+impl ReputationEntry {
+    /// Rebuild the peer; without valid keys it is marked `NeedsDiscovery`
+    pub fn into_peer(self) -> Peer {
+        let keys = decode_key(self.exchange_public.as_deref())
+            .zip(decode_key(self.signing_public.as_deref()));
+
+        let (exchange_public, signing_public) = keys.unwrap_or_default();
+        let mut peer = Peer::new(NodeIdentity {
+            id: self.id,
+            exchange_public,
+            signing_public,
+            role: self.role.unwrap_or_else(|| "unknown".into()),
+            name: self.name.unwrap_or_else(|| "unknown".into()),
+        });
+        if keys.is_none() {
+            peer.status = PeerStatus::NeedsDiscovery;
+        }
+        peer.address = self.address;
+        // System trust only ever comes from trusted_peers.toml, so unpinning sticks
+        peer.set_trust_level(self.trust_level.min(TrustLevel::Trusted));
+        peer.update_trust(self.trust_score as i8 - peer.trust_score as i8);
+        peer
+    }
+}
+
+fn decode_key(encoded: Option<&str>) -> Option<[u8; 32]> {
+    hex::decode(encoded?).ok()?.try_into().ok()
+}
+
+/// One `[[peer]]` entry of `trusted_peers.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustedPeer {
+    pub id: String,
+    /// Ed25519 public key (hex)
+    pub signing_public: String,
+    /// X25519 public key (hex)
+    pub exchange_public: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrustedPeersFile {
+    #[serde(default, rename = "peer")]
+    peers: Vec<TrustedPeer>,
+}
+
+/// Load operator-pinned peers as `TrustLevel::System`.
+///
+/// ```toml
+/// [[peer]]
+/// id = "<sha256(signing_public)>"
+/// signing_public = "<64 hex chars>"
+/// exchange_public = "<64 hex chars>"
+/// name = "seed-1"
+/// ```
+///
+/// The whole file is rejected if any entry is malformed or its id does not
+/// match its signing key.
+pub fn load_trusted_peers(path: &std::path::Path) -> Result<Vec<Peer>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let file: TrustedPeersFile = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+    file.peers.into_iter().map(|entry| {
+        let signing_public = decode_key(Some(&entry.signing_public))
+            .ok_or_else(|| anyhow::anyhow!("Trusted peer {}: signing_public must be 32 hex-encoded bytes", entry.id))?;
+        let exchange_public = decode_key(Some(&entry.exchange_public))
+            .ok_or_else(|| anyhow::anyhow!("Trusted peer {}: exchange_public must be 32 hex-encoded bytes", entry.id))?;
+
+        let expected_id = crate::crypto::node_id(&signing_public);
+        if entry.id != expected_id {
+            return Err(anyhow::anyhow!(
+                "Trusted peer {}: id does not match SHA256(signing_public) ({})",
+                entry.id, expected_id
+            ));
+        }
+
+        let mut peer = Peer::new(NodeIdentity {
+            id: entry.id,
+            exchange_public,
+            signing_public,
+            role: entry.role.unwrap_or_else(|| "unknown".into()),
+            name: entry.name.unwrap_or_else(|| "pinned".into()),
+        });
+        peer.set_trust_level(TrustLevel::System);
+        Ok(peer)
+    }).collect()
 }
 
 /// Manages persistence of peer reputation
 pub struct ReputationManager {
     path: PathBuf,
+    /// Fingerprint of the table last written, so unchanged tables aren't rewritten
+    written: std::sync::Mutex<Option<u64>>,
 }
 
 impl ReputationManager {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self { path, written: std::sync::Mutex::new(None) }
     }
 
-    /// Save peer table to disk
+    /// Save peer table to disk, unless it matches what was last saved
     pub fn save(&self, peers: &HashMap<String, Peer>) -> Result<()> {
+        let fingerprint = Self::fingerprint(peers);
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if *written == Some(fingerprint) {
+            return Ok(());
+        }
+
         let entries: Vec<ReputationEntry> = peers.values()
             .map(|p| ReputationEntry {
                 id: p.identity.id.clone(),
                 trust_level: p.trust_level,
                 trust_score: p.trust_score,
                 last_seen: chrono::Utc::now(),
+                // Never persist placeholder keys as if they were real
+                exchange_public: (!p.needs_discovery()).then(|| hex::encode(p.identity.exchange_public)),
+                signing_public: (!p.needs_discovery()).then(|| hex::encode(p.identity.signing_public)),
+                name: Some(p.identity.name.clone()),
+                role: Some(p.identity.role.clone()),
+                address: p.address,
             })
             .collect();
 
         let json = serde_json::to_string_pretty(&entries)?;
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, json)?;
+        crate::persist::atomic_write(&self.path, json.as_bytes())?;
+        *written = Some(fingerprint);
         Ok(())
     }
 
+    /// Hash of everything `save` persists
+    fn fingerprint(peers: &HashMap<String, Peer>) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut sorted: Vec<&Peer> = peers.values().collect();
+        sorted.sort_by(|a, b| a.identity.id.cmp(&b.identity.id));
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for p in sorted {
+            p.identity.id.hash(&mut hasher);
+            p.trust_level.hash(&mut hasher);
+            p.trust_score.hash(&mut hasher);
+            p.needs_discovery().hash(&mut hasher);
+            p.identity.exchange_public.hash(&mut hasher);
+            p.identity.signing_public.hash(&mut hasher);
+            p.identity.name.hash(&mut hasher);
+            p.identity.role.hash(&mut hasher);
+            p.address.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
     /// Load peer data from disk
     pub fn load(&self) -> Result<Vec<ReputationEntry>> {
         if !self.path.exists() {
@@ -0,0 +1,94 @@
+//! Crash-safe file persistence
+//!
+//! Every state file (wallet, ledger, reputation, identity) is replaced
+//! atomically: the bytes go to a temp file in the same directory, are
+//! fsynced, and the temp file is renamed over the target. A crash leaves
+//! either the old file or the new one, never a truncated mix.
+
+use anyhow::{anyhow, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Atomically replace `path` with `bytes`
+pub fn atomic_write(path: &Path, bytes: &[u8]) -> Result<()> {
+    write_with(path, None, |file| file.write_all(bytes))
+}
+
+/// Like `atomic_write`, but the file is readable by the owner only (keys)
+pub fn atomic_write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    write_with(path, Some(0o600), |file| file.write_all(bytes))
+}
+
+fn write_with(path: &Path, mode: Option<u32>, fill: impl FnOnce(&mut File) -> io::Result<()>) -> Result<()> {
+    let name = path.file_name()
+        .ok_or_else(|| anyhow!("Not a file path: {:?}", path))?
+        .to_string_lossy();
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+
+    // Same directory, so the rename cannot cross filesystems
+    let tmp = dir.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+
+    let result = (|| -> Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(target_family = "unix")]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+        #[cfg(not(target_family = "unix"))]
+        let _ = mode;
+
+        let mut file = options.open(&tmp)?;
+        fill(&mut file)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp, path)?;
+        // Make the rename itself durable
+        #[cfg(target_family = "unix")]
+        File::open(dir)?.sync_all()?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupted_write_keeps_previous_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("persist_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("wallet.json");
+        atomic_write(&path, b"{\"ippc\": 1000}")?;
+
+        // The writer dies halfway through the new contents
+        let result = write_with(&path, None, |file| {
+            file.write_all(b"{\"ippc\": 9")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "killed"))
+        });
+        assert!(result.is_err());
+
+        assert_eq!(fs::read(&path)?, b"{\"ippc\": 1000}");
+        // No temp file is left behind
+        assert_eq!(fs::read_dir(&dir)?.count(), 1);
+
+        atomic_write_private(&path, b"{\"ippc\": 950}")?;
+        assert_eq!(fs::read(&path)?, b"{\"ippc\": 950}");
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
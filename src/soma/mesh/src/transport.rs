@@ -1,22 +1,464 @@
 use anyhow::{Result, anyhow};
 use quinn::{Endpoint, ServerConfig, ClientConfig};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 use rustls::{Certificate, PrivateKey};
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::sync::mpsc;
-use crate::messages::AiMessage;
+use crate::messages::{AiMessage, WireError};
+
+const DAY_SECS: u64 = 86_400;
+/// How long one outbound QUIC dial may take before it is given up on
+pub const DIAL_TIMEOUT: Duration = Duration::from_secs(10);
+/// Hosts counted individually; the rest share `OTHER_HOSTS`, so a flood of
+/// addresses cannot grow the table or the metrics without bound
+pub const MAX_TRAFFIC_HOSTS: usize = 256;
+/// Traffic key for hosts past `MAX_TRAFFIC_HOSTS`
+pub const OTHER_HOSTS: &str = "other";
+/// Hosts whose abandoned messages are remembered at once
+pub const MAX_ABANDON_HOSTS: usize = 4096;
+
+/// Connection liveness. Home routers drop idle UDP mappings after ~30s, so the
+/// default keepalive stays well under that and a silent peer is given up on
+/// after a few missed intervals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    /// How often an idle connection sends a keepalive frame
+    pub interval: Duration,
+    /// How long a connection may go without hearing from the peer
+    pub idle_timeout: Duration,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            idle_timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+impl KeepaliveConfig {
+    /// QUIC transport parameters for both the server and client side
+    pub fn transport_config(&self) -> Result<quinn::TransportConfig> {
+        if self.interval.is_zero() || self.interval >= self.idle_timeout {
+            return Err(anyhow!(
+                "Keepalive interval ({:?}) must be non-zero and shorter than the idle timeout ({:?})",
+                self.interval, self.idle_timeout
+            ));
+        }
+        let idle = quinn::IdleTimeout::try_from(self.idle_timeout)
+            .map_err(|e| anyhow!("Idle timeout out of range: {}", e))?;
+        let mut config = quinn::TransportConfig::default();
+        config.keep_alive_interval(Some(self.interval)).max_idle_timeout(Some(idle));
+        Ok(config)
+    }
+}
+
+/// Connection-level news the mesh acts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkEvent {
+    /// The peer at this address stopped answering keepalives
+    Lost { addr: SocketAddr, reason: String },
+}
+
+/// Bounds on inbound messages still being received. Each QUIC stream carries
+/// one message; until it finishes, its bytes count against a node-wide cap so
+/// senders that start messages and never finish them cannot pin memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiveLimits {
+    /// Largest single message
+    pub max_message_bytes: usize,
+    /// Total bytes held across all incomplete messages
+    pub max_buffered_bytes: usize,
+    /// Bytes of incomplete messages one host may hold, so a single host
+    /// cannot starve the others of `max_buffered_bytes`
+    pub max_host_buffered_bytes: usize,
+    /// An incomplete message is dropped after this long
+    pub message_timeout: Duration,
+    /// Timed-out messages from one host within `abandon_window` before its
+    /// connections are closed
+    pub max_abandoned: u32,
+    /// How long a host's abandoned messages count against it
+    pub abandon_window: Duration,
+}
+
+impl Default for ReceiveLimits {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 10 * 1024 * 1024,
+            max_buffered_bytes: 64 * 1024 * 1024,
+            max_host_buffered_bytes: 16 * 1024 * 1024,
+            message_timeout: Duration::from_secs(30),
+            max_abandoned: 5,
+            abandon_window: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Why an inbound message was dropped before it was complete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiveError {
+    /// Larger than `max_message_bytes`
+    TooLarge { limit: usize },
+    /// Would push incomplete messages past `max_buffered_bytes`
+    OverBudget { limit: usize },
+    /// Would push the sending host's incomplete messages past `max_host_buffered_bytes`
+    HostOverBudget { limit: usize },
+    /// Not finished within `message_timeout`; counts against the sender
+    TimedOut,
+    Io(String),
+}
+
+impl std::fmt::Display for ReceiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReceiveError::TooLarge { limit } => write!(f, "message exceeds {} bytes", limit),
+            ReceiveError::OverBudget { limit } => write!(f, "incomplete messages already hold {} bytes", limit),
+            ReceiveError::HostOverBudget { limit } => write!(f, "incomplete messages from this host already hold {} bytes", limit),
+            ReceiveError::TimedOut => write!(f, "message abandoned before it finished"),
+            ReceiveError::Io(e) => write!(f, "read failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReceiveError {}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct ReceiveSnapshot {
+    /// Bytes currently held for incomplete messages
+    pub buffered_bytes: u64,
+    pub in_flight: u64,
+    pub completed: u64,
+    /// Dropped for exceeding the per-message or node-wide cap
+    pub refused: u64,
+    /// Dropped after `message_timeout`
+    pub evicted: u64,
+    /// Timed-out messages per sending host within the abandon window
+    pub abandoned: HashMap<String, u32>,
+}
+
+/// Node-wide accounting for inbound messages that have not finished arriving
+#[derive(Default)]
+pub struct ReceiveBuffers {
+    limits: ReceiveLimits,
+    buffered: AtomicU64,
+    in_flight: AtomicU64,
+    completed: AtomicU64,
+    refused: AtomicU64,
+    evicted: AtomicU64,
+    /// Bytes held for incomplete messages per sending host
+    host_buffered: Mutex<HashMap<String, u64>>,
+    /// Per host: timed-out messages and when their window opened
+    abandoned: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl ReceiveBuffers {
+    pub fn new(limits: ReceiveLimits) -> Self {
+        Self { limits, ..Default::default() }
+    }
+
+    /// Read one message to the end, charging its bytes to the node-wide cap
+    /// while incomplete. `sender` is the host the penalty is kept against.
+    pub async fn read_message<R: AsyncRead + Unpin>(&self, reader: &mut R, sender: &str) -> Result<Vec<u8>, ReceiveError> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let mut buf = Vec::new();
+        let result = tokio::time::timeout(self.limits.message_timeout, self.fill(reader, &mut buf, sender)).await;
+        // Everything read so far was reserved; the message is done with either way
+        self.buffered.fetch_sub(buf.len() as u64, Ordering::Relaxed);
+        self.release_host(sender, buf.len() as u64);
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+
+        match result {
+            Ok(Ok(())) => {
+                self.completed.fetch_add(1, Ordering::Relaxed);
+                Ok(buf)
+            }
+            Ok(Err(e)) => {
+                if matches!(e, ReceiveError::TooLarge { .. } | ReceiveError::OverBudget { .. } | ReceiveError::HostOverBudget { .. }) {
+                    self.refused.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e)
+            }
+            Err(_) => {
+                self.evicted.fetch_add(1, Ordering::Relaxed);
+                self.record_abandoned(sender);
+                Err(ReceiveError::TimedOut)
+            }
+        }
+    }
+
+    async fn fill<R: AsyncRead + Unpin>(&self, reader: &mut R, buf: &mut Vec<u8>, sender: &str) -> Result<(), ReceiveError> {
+        let mut chunk = [0u8; 8192];
+        loop {
+            let n = reader.read(&mut chunk).await.map_err(|e| ReceiveError::Io(e.to_string()))?;
+            if n == 0 {
+                return Ok(());
+            }
+            if buf.len() + n > self.limits.max_message_bytes {
+                return Err(ReceiveError::TooLarge { limit: self.limits.max_message_bytes });
+            }
+            let max = self.limits.max_buffered_bytes as u64;
+            let reserved = self.buffered.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |held| {
+                (held + n as u64 <= max).then_some(held + n as u64)
+            });
+            if reserved.is_err() {
+                return Err(ReceiveError::OverBudget { limit: self.limits.max_buffered_bytes });
+            }
+            {
+                let mut hosts = self.host_buffered.lock().unwrap();
+                let held = hosts.entry(sender.to_string()).or_default();
+                if *held + n as u64 > self.limits.max_host_buffered_bytes as u64 {
+                    drop(hosts);
+                    self.buffered.fetch_sub(n as u64, Ordering::Relaxed);
+                    return Err(ReceiveError::HostOverBudget { limit: self.limits.max_host_buffered_bytes });
+                }
+                *held += n as u64;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    fn release_host(&self, sender: &str, bytes: u64) {
+        let mut hosts = self.host_buffered.lock().unwrap();
+        if let Some(held) = hosts.get_mut(sender) {
+            *held = held.saturating_sub(bytes);
+            // Only hosts with messages in flight are tracked
+            if *held == 0 {
+                hosts.remove(sender);
+            }
+        }
+    }
+
+    fn record_abandoned(&self, sender: &str) {
+        let now = Instant::now();
+        let window = self.limits.abandon_window;
+        let mut abandoned = self.abandoned.lock().unwrap();
+        if !abandoned.contains_key(sender) && abandoned.len() >= MAX_ABANDON_HOSTS {
+            abandoned.retain(|_, (_, since)| now.duration_since(*since) < window);
+            // Still full: forget the oldest record rather than grow
+            if abandoned.len() >= MAX_ABANDON_HOSTS {
+                let oldest = abandoned.iter().min_by_key(|(_, (_, since))| *since).map(|(host, _)| host.clone());
+                if let Some(oldest) = oldest {
+                    abandoned.remove(&oldest);
+                }
+            }
+        }
+        let (count, since) = abandoned.entry(sender.to_string()).or_insert((0, now));
+        if now.duration_since(*since) >= window {
+            (*count, *since) = (0, now);
+        }
+        *count += 1;
+    }
+
+    /// Whether `sender` has abandoned enough messages within the window to be cut off
+    pub fn abandoned_too_often(&self, sender: &str) -> bool {
+        self.abandoned.lock().unwrap().get(sender)
+            .is_some_and(|(count, since)| since.elapsed() < self.limits.abandon_window && *count >= self.limits.max_abandoned)
+    }
+
+    pub fn snapshot(&self) -> ReceiveSnapshot {
+        ReceiveSnapshot {
+            buffered_bytes: self.buffered.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            refused: self.refused.load(Ordering::Relaxed),
+            evicted: self.evicted.load(Ordering::Relaxed),
+            abandoned: self.abandoned.lock().unwrap().iter()
+                .filter(|(_, (_, since))| since.elapsed() < self.limits.abandon_window)
+                .map(|(host, (count, _))| (host.clone(), *count))
+                .collect(),
+        }
+    }
+}
+
+/// Bytes moved with one remote host (keyed by IP address: the sender a
+/// message claims is not authenticated at this layer)
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct PeerTraffic {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrafficSnapshot {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Bytes moved in the current 24h budget window
+    pub window_bytes: u64,
+    pub daily_budget: Option<u64>,
+    pub over_budget: bool,
+    pub peers: HashMap<String, PeerTraffic>,
+}
+
+impl TrafficSnapshot {
+    /// Prometheus text exposition of the counters
+    pub fn prometheus(&self) -> String {
+        let mut hosts: Vec<_> = self.peers.iter().collect();
+        hosts.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut body = String::new();
+        body.push_str("# TYPE ippoc_mesh_bytes_sent_total counter\n");
+        body.push_str(&format!("ippoc_mesh_bytes_sent_total {}\n", self.bytes_sent));
+        body.push_str("# TYPE ippoc_mesh_bytes_received_total counter\n");
+        body.push_str(&format!("ippoc_mesh_bytes_received_total {}\n", self.bytes_received));
+        body.push_str("# TYPE ippoc_mesh_peer_bytes_sent_total counter\n");
+        for (host, t) in &hosts {
+            body.push_str(&format!("ippoc_mesh_peer_bytes_sent_total{{peer=\"{}\"}} {}\n", escape_label(host), t.bytes_sent));
+        }
+        body.push_str("# TYPE ippoc_mesh_peer_bytes_received_total counter\n");
+        for (host, t) in &hosts {
+            body.push_str(&format!("ippoc_mesh_peer_bytes_received_total{{peer=\"{}\"}} {}\n", escape_label(host), t.bytes_received));
+        }
+        body.push_str("# TYPE ippoc_mesh_over_budget gauge\n");
+        body.push_str(&format!("ippoc_mesh_over_budget {}\n", self.over_budget as u8));
+        body
+    }
+}
+
+/// Backslash, double quote and newline escaped, as label values require
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Byte counters for operators on metered links, with an optional daily budget
+#[derive(Default)]
+pub struct TrafficStats {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    peers: Mutex<HashMap<String, PeerTraffic>>,
+    daily_budget: Option<u64>,
+    /// (window start in unix secs, bytes in window)
+    window: Mutex<(u64, u64)>,
+}
+
+impl TrafficStats {
+    pub fn new(daily_budget: Option<u64>) -> Self {
+        Self {
+            daily_budget,
+            window: Mutex::new((unix_now(), 0)),
+            ..Default::default()
+        }
+    }
+
+    pub fn record_sent(&self, host: &str, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.count(host, |t| t.bytes_sent += bytes as u64);
+        self.add_to_window(bytes as u64);
+    }
+
+    pub fn record_received(&self, host: &str, bytes: usize) {
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.count(host, |t| t.bytes_received += bytes as u64);
+        self.add_to_window(bytes as u64);
+    }
+
+    fn count(&self, host: &str, update: impl FnOnce(&mut PeerTraffic)) {
+        let mut peers = self.peers.lock().unwrap();
+        let key = if peers.contains_key(host) || peers.len() < MAX_TRAFFIC_HOSTS { host } else { OTHER_HOSTS };
+        update(peers.entry(key.to_string()).or_default());
+    }
+
+    /// Whether today's traffic has used up the configured budget
+    pub fn over_budget(&self) -> bool {
+        match self.daily_budget {
+            Some(budget) => self.window_bytes() >= budget,
+            None => false,
+        }
+    }
+
+    pub fn snapshot(&self) -> TrafficSnapshot {
+        TrafficSnapshot {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            window_bytes: self.window_bytes(),
+            daily_budget: self.daily_budget,
+            over_budget: self.over_budget(),
+            peers: self.peers.lock().unwrap().clone(),
+        }
+    }
+
+    fn add_to_window(&self, bytes: u64) {
+        let mut window = self.window.lock().unwrap();
+        Self::roll(&mut window);
+        window.1 += bytes;
+    }
+
+    fn window_bytes(&self) -> u64 {
+        let mut window = self.window.lock().unwrap();
+        Self::roll(&mut window);
+        window.1
+    }
+
+    fn roll(window: &mut (u64, u64)) {
+        let now = unix_now();
+        if now.saturating_sub(window.0) >= DAY_SECS {
+            *window = (now, 0);
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Accepts any server certificate. Nodes present throwaway self-signed
+/// certificates; who is on the other end is proven by the signed mesh
+/// handshake, not by TLS.
+struct AcceptAnyCertificate;
+
+impl rustls::client::ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
 
 pub struct QuicTransport {
     endpoint: Endpoint,
-    _msg_tx: mpsc::Sender<AiMessage>,
+    _msg_tx: mpsc::Sender<(SocketAddr, AiMessage)>,
+    traffic: Arc<TrafficStats>,
+    /// Connections we dialed, reused for later sends. Each address has its
+    /// own slot, so a dial in flight only holds up senders to that address.
+    connections: Mutex<HashMap<SocketAddr, Arc<tokio::sync::Mutex<Option<quinn::Connection>>>>>,
+    dial_timeout: Duration,
 }
 
 impl QuicTransport {
-    pub async fn bind(port: u16, msg_tx: mpsc::Sender<AiMessage>) -> Result<Self> {
+    pub async fn bind(
+        port: u16,
+        keepalive: &KeepaliveConfig,
+        msg_tx: mpsc::Sender<(SocketAddr, AiMessage)>,
+        link_tx: mpsc::Sender<LinkEvent>,
+        traffic: Arc<TrafficStats>,
+        receive: Arc<ReceiveBuffers>,
+    ) -> Result<Self> {
+        let transport_config = Arc::new(keepalive.transport_config()?);
         let (cert, key) = Self::generate_self_signed_cert()?;
-        let server_config = ServerConfig::with_single_cert(vec![cert], key)?;
+        let mut server_config = ServerConfig::with_single_cert(vec![cert], key)?;
+        server_config.transport_config(transport_config.clone());
         
-        let client_config = ClientConfig::with_native_roots();
+        let crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+            .with_no_client_auth();
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        client_config.transport_config(transport_config);
 
         // Bind to all interfaces (IPv4 and IPv6)
         // Note: binding to 0.0.0.0 allows LAN access
@@ -30,40 +472,86 @@ impl QuicTransport {
         // Spawn listener loop
         let endpoint_clone = endpoint.clone();
         let tx_clone = msg_tx.clone();
+        let traffic_clone = traffic.clone();
         tokio::spawn(async move {
-            Self::listen_loop(endpoint_clone, tx_clone).await;
+            Self::listen_loop(endpoint_clone, tx_clone, link_tx, traffic_clone, receive).await;
         });
 
-        Ok(Self { endpoint, _msg_tx: msg_tx })
+        Ok(Self { endpoint, _msg_tx: msg_tx, traffic, connections: Default::default(), dial_timeout: DIAL_TIMEOUT })
+    }
+
+    pub fn with_dial_timeout(mut self, timeout: Duration) -> Self {
+        self.dial_timeout = timeout;
+        self
     }
 
-    async fn listen_loop(endpoint: Endpoint, tx: mpsc::Sender<AiMessage>) {
+    /// Where we listen (and dial from)
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.endpoint.local_addr()?)
+    }
+
+    async fn listen_loop(
+        endpoint: Endpoint,
+        tx: mpsc::Sender<(SocketAddr, AiMessage)>,
+        link_tx: mpsc::Sender<LinkEvent>,
+        traffic: Arc<TrafficStats>,
+        receive: Arc<ReceiveBuffers>,
+    ) {
         while let Some(conn) = endpoint.accept().await {
             info!("New connection incoming...");
+            let addr = conn.remote_address();
             let tx = tx.clone();
+            let link_tx = link_tx.clone();
+            let traffic = traffic.clone();
+            let receive = receive.clone();
             tokio::spawn(async move {
-                if let Err(e) = Self::handle_connection(conn, tx).await {
+                if let Err(e) = Self::handle_connection(conn, tx, traffic, receive).await {
                     warn!("Connection error: {}", e);
+                    // Keepalives went unanswered: the peer (or its NAT mapping) is gone
+                    if matches!(e.downcast_ref::<quinn::ConnectionError>(), Some(quinn::ConnectionError::TimedOut)) {
+                        let _ = link_tx.send(LinkEvent::Lost { addr, reason: e.to_string() }).await;
+                    }
                 }
             });
         }
     }
 
-    async fn handle_connection(conn: quinn::Connecting, tx: mpsc::Sender<AiMessage>) -> Result<()> {
+    async fn handle_connection(
+        conn: quinn::Connecting,
+        tx: mpsc::Sender<(SocketAddr, AiMessage)>,
+        traffic: Arc<TrafficStats>,
+        receive: Arc<ReceiveBuffers>,
+    ) -> Result<()> {
         let connection = conn.await?;
         info!("Handshake complete with {}", connection.remote_address());
+        // Penalties follow the host, not the ephemeral port of one connection
+        let host = connection.remote_address().ip().to_string();
 
         loop {
              // Accept bidirectional streams (one per message usually)
              match connection.accept_bi().await {
                  Ok((_send, mut recv)) => {
-                     // Read message (max 10MB)
-                     let buf = recv.read_to_end(10 * 1024 * 1024).await?;
+                     let buf = match receive.read_message(&mut recv, &host).await {
+                         Ok(buf) => buf,
+                         Err(e) => {
+                             warn!("Dropping incomplete message from {}: {}", connection.remote_address(), e);
+                             let _ = recv.stop(quinn::VarInt::from_u32(0));
+                             if receive.abandoned_too_often(&host) {
+                                 connection.close(quinn::VarInt::from_u32(1), b"too many abandoned messages");
+                                 return Err(anyhow!("{} abandoned too many messages", host));
+                             }
+                             continue;
+                         }
+                     };
+                     traffic.record_received(&host, buf.len());
                      
-                     // Deserialize
-                     match serde_json::from_slice::<AiMessage>(&buf) {
+                     // Versioned envelope (see `messages::WIRE_VERSION`)
+                     match AiMessage::from_bytes(&buf) {
                          Ok(msg) => {
-                             tx.send(msg).await?;
+                             tx.send((connection.remote_address(), msg)).await?;
+                         }
+                         Err(e @ WireError::UnsupportedVersion { .. }) => {
+                             warn!("Dropping message from {}: {}; one of us needs upgrading", connection.remote_address(), e);
                          }
                          Err(e) => {
                              warn!("Failed to deserialize message: {}", e);
@@ -82,20 +570,57 @@ impl QuicTransport {
         Ok(())
     }
 
+    /// Open a connection (or check the open one) to see the address still answers
+    pub async fn connect(&self, addr: SocketAddr) -> Result<()> {
+        let connection = self.connection(addr).await?;
+        info!("Reached {}", connection.remote_address());
+        Ok(())
+    }
+
+    /// The open connection to `addr`, dialing a new one if there is none
+    /// or it has closed. Concurrent callers for the same address share one dial.
+    async fn connection(&self, addr: SocketAddr) -> Result<quinn::Connection> {
+        let slot = self.connections.lock().unwrap().entry(addr).or_default().clone();
+        let mut slot = slot.lock().await;
+        if let Some(open) = slot.as_ref().filter(|c| c.close_reason().is_none()) {
+            return Ok(open.clone());
+        }
+
+        let dial = async { Ok::<_, anyhow::Error>(self.endpoint.connect(addr, "ipoc-node")?.await?) };
+        match tokio::time::timeout(self.dial_timeout, dial).await {
+            Ok(Ok(connection)) => {
+                *slot = Some(connection.clone());
+                Ok(connection)
+            }
+            failed => {
+                // Don't keep a slot for every address that never answered
+                self.connections.lock().unwrap().remove(&addr);
+                match failed {
+                    Ok(Err(e)) => Err(e),
+                    _ => Err(anyhow!("Dial to {} timed out after {:?}", addr, self.dial_timeout)),
+                }
+            }
+        }
+    }
+
     pub async fn send(&self, addr: SocketAddr, msg: AiMessage) -> Result<()> {
         info!("Sending QUIC packet to {}", addr);
-        // Opens a new connection if needed, or reuses existing?
-        // Quinn manages connection pooling somewhat, but for P2P ephemeral might be okay.
-        let connection = self.endpoint.connect(addr, "ipoc-node")?.await?;
+        let connection = self.connection(addr).await?;
         
         let (mut send, mut _recv) = connection.open_bi().await?;
-        let bytes = serde_json::to_vec(&msg)?;
+        let bytes = msg.to_bytes();
         send.write_all(&bytes).await?;
         send.finish().await?;
+
+        self.traffic.record_sent(&addr.ip().to_string(), bytes.len());
         
         Ok(())
     }
 
+    pub fn traffic(&self) -> &Arc<TrafficStats> {
+        &self.traffic
+    }
+
     fn generate_self_signed_cert() -> Result<(Certificate, PrivateKey)> {
         let cert = rcgen::generate_simple_self_signed(vec!["localhost".into(), "ipoc-node".into()])?;
         let key = PrivateKey(cert.serialize_private_key_der());
@@ -143,3 +668,234 @@ impl QuicTransport {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_track_peers_and_totals() {
+        let traffic = TrafficStats::new(None);
+        traffic.record_sent("peer-a", 100);
+        traffic.record_received("peer-a", 40);
+        traffic.record_sent("127.0.0.1:9000", 10);
+
+        let snap = traffic.snapshot();
+        assert_eq!(snap.bytes_sent, 110);
+        assert_eq!(snap.bytes_received, 40);
+        assert_eq!(snap.window_bytes, 150);
+        assert_eq!(snap.peers["peer-a"], PeerTraffic { bytes_sent: 100, bytes_received: 40 });
+        assert_eq!(snap.peers["127.0.0.1:9000"].bytes_sent, 10);
+        assert!(!snap.over_budget);
+    }
+
+    #[test]
+    fn test_hosts_are_capped_and_labels_escaped() {
+        let traffic = TrafficStats::new(None);
+        for i in 0..MAX_TRAFFIC_HOSTS + 10 {
+            traffic.record_received(&format!("10.0.{}.{}", i / 256, i % 256), 1);
+        }
+        traffic.record_received("a\"b\\c\nd", 5);
+        let snap = traffic.snapshot();
+        assert_eq!(snap.peers.len(), MAX_TRAFFIC_HOSTS + 1);
+        assert_eq!(snap.peers[OTHER_HOSTS].bytes_received, 15);
+
+        let mut snap = TrafficStats::new(None).snapshot();
+        snap.peers.insert("a\"b\\c\nd".into(), PeerTraffic { bytes_sent: 1, bytes_received: 2 });
+        let body = snap.prometheus();
+        assert!(body.contains("ippoc_mesh_peer_bytes_received_total{peer=\"a\\\"b\\\\c\\nd\"} 2\n"), "{body}");
+        assert!(body.lines().all(|l| l.starts_with('#') || l.starts_with("ippoc_mesh_")), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_received_bytes_are_keyed_by_host_not_claimed_sender() {
+        let (msg_tx, mut msg_rx) = mpsc::channel(8);
+        let (link_tx, _link_rx) = mpsc::channel(8);
+        let keepalive = KeepaliveConfig::default();
+        let bind = |traffic| QuicTransport::bind(
+            0, &keepalive, msg_tx.clone(), link_tx.clone(),
+            traffic, Arc::new(ReceiveBuffers::new(ReceiveLimits::default())),
+        );
+        let received = Arc::new(TrafficStats::new(None));
+        let receiver = bind(received.clone()).await.unwrap();
+        let sender = bind(Arc::new(TrafficStats::new(None))).await.unwrap();
+        let receiver_addr = SocketAddr::from(([127, 0, 0, 1], receiver.local_addr().unwrap().port()));
+
+        let msg = AiMessage::direct("someone-else\"}} 1\nfake_metric", "target", serde_json::json!({}), 1);
+        sender.send(receiver_addr, msg).await.unwrap();
+        let (_, delivered) = tokio::time::timeout(Duration::from_secs(5), msg_rx.recv()).await.unwrap().unwrap();
+        assert!(delivered.sender.starts_with("someone-else"));
+
+        let snap = received.snapshot();
+        assert_eq!(snap.peers.keys().collect::<Vec<_>>(), vec!["127.0.0.1"]);
+        assert!(snap.peers["127.0.0.1"].bytes_received > 0);
+        assert_eq!(sender.traffic().snapshot().peers.keys().collect::<Vec<_>>(), vec!["127.0.0.1"]);
+    }
+
+    #[test]
+    fn test_keepalive_must_beat_idle_timeout() {
+        let defaults = KeepaliveConfig::default();
+        assert!(defaults.interval < Duration::from_secs(30), "keepalive must refresh typical NAT mappings");
+        assert!(defaults.transport_config().is_ok());
+
+        let slow = KeepaliveConfig { interval: Duration::from_secs(60), idle_timeout: Duration::from_secs(30) };
+        assert!(slow.transport_config().is_err());
+        let off = KeepaliveConfig { interval: Duration::ZERO, ..defaults };
+        assert!(off.transport_config().is_err());
+    }
+
+    #[test]
+    fn test_daily_budget_is_enforced() {
+        let traffic = TrafficStats::new(Some(1_000));
+        traffic.record_received("peer-a", 600);
+        assert!(!traffic.over_budget());
+        traffic.record_sent("peer-a", 400);
+        assert!(traffic.over_budget());
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_messages_are_bounded_and_evicted() {
+        use tokio::io::AsyncWriteExt;
+
+        let limits = ReceiveLimits {
+            max_message_bytes: 4096,
+            max_buffered_bytes: 10_000,
+            max_host_buffered_bytes: 10_000,
+            message_timeout: Duration::from_millis(200),
+            max_abandoned: 3,
+            abandon_window: Duration::from_secs(60),
+        };
+        let buffers = Arc::new(ReceiveBuffers::new(limits));
+
+        // Ten messages started and never finished: the writers stay open
+        let mut writers = Vec::new();
+        let mut readers = Vec::new();
+        for _ in 0..10 {
+            let (mut client, mut server) = tokio::io::duplex(8192);
+            client.write_all(&[7u8; 3000]).await.unwrap();
+            writers.push(client);
+            let buffers = buffers.clone();
+            readers.push(tokio::spawn(async move { buffers.read_message(&mut server, "10.0.0.66").await }));
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let held = buffers.snapshot();
+        assert!(held.buffered_bytes <= 10_000, "held {} bytes", held.buffered_bytes);
+        assert!(held.refused > 0);
+
+        let mut timed_out = 0;
+        for reader in readers {
+            if reader.await.unwrap() == Err(ReceiveError::TimedOut) {
+                timed_out += 1;
+            }
+        }
+        let after = buffers.snapshot();
+        assert_eq!(after.buffered_bytes, 0);
+        assert_eq!(after.in_flight, 0);
+        assert_eq!(after.evicted, timed_out);
+        assert_eq!(after.evicted + after.refused, 10);
+        assert!(buffers.abandoned_too_often("10.0.0.66"));
+        assert!(!buffers.abandoned_too_often("10.0.0.67"));
+
+        // A message that finishes is delivered and releases its bytes
+        let (mut client, mut server) = tokio::io::duplex(8192);
+        client.write_all(b"complete").await.unwrap();
+        drop(client);
+        assert_eq!(buffers.read_message(&mut server, "10.0.0.67").await.unwrap(), b"complete");
+        assert_eq!(buffers.snapshot().buffered_bytes, 0);
+        drop(writers);
+    }
+
+    #[tokio::test]
+    async fn test_one_host_cannot_take_the_whole_buffer() {
+        use tokio::io::AsyncWriteExt;
+
+        let limits = ReceiveLimits {
+            max_message_bytes: 4096,
+            max_buffered_bytes: 10_000,
+            max_host_buffered_bytes: 4_000,
+            message_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let buffers = Arc::new(ReceiveBuffers::new(limits));
+
+        // The greedy host gets one message's worth; its second is refused
+        let (mut first, mut first_server) = tokio::io::duplex(8192);
+        first.write_all(&[1u8; 3000]).await.unwrap();
+        let (mut second, mut second_server) = tokio::io::duplex(8192);
+        second.write_all(&[2u8; 3000]).await.unwrap();
+        let held = tokio::spawn({
+            let buffers = buffers.clone();
+            async move { buffers.read_message(&mut first_server, "10.0.0.66").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            buffers.read_message(&mut second_server, "10.0.0.66").await,
+            Err(ReceiveError::HostOverBudget { limit: 4_000 })
+        );
+
+        // Another host still has room
+        let (mut other, mut other_server) = tokio::io::duplex(8192);
+        other.write_all(&[3u8; 3000]).await.unwrap();
+        drop(other);
+        assert_eq!(buffers.read_message(&mut other_server, "10.0.0.67").await.unwrap().len(), 3000);
+
+        drop(first);
+        assert_eq!(held.await.unwrap().unwrap().len(), 3000);
+        assert_eq!(buffers.snapshot().buffered_bytes, 0);
+        assert!(buffers.host_buffered.lock().unwrap().is_empty());
+        drop(second);
+    }
+
+    #[test]
+    fn test_abandon_counts_expire_and_stay_bounded() {
+        let limits = ReceiveLimits { max_abandoned: 2, abandon_window: Duration::from_millis(100), ..Default::default() };
+        let buffers = ReceiveBuffers::new(limits);
+        buffers.record_abandoned("10.0.0.66");
+        buffers.record_abandoned("10.0.0.66");
+        assert!(buffers.abandoned_too_often("10.0.0.66"));
+
+        // Outside the window the host starts over
+        std::thread::sleep(Duration::from_millis(120));
+        assert!(!buffers.abandoned_too_often("10.0.0.66"));
+        assert!(buffers.snapshot().abandoned.is_empty());
+        buffers.record_abandoned("10.0.0.66");
+        assert!(!buffers.abandoned_too_often("10.0.0.66"));
+
+        for i in 0..MAX_ABANDON_HOSTS + 10 {
+            buffers.record_abandoned(&format!("host-{}", i));
+        }
+        assert_eq!(buffers.abandoned.lock().unwrap().len(), MAX_ABANDON_HOSTS);
+    }
+
+    #[tokio::test]
+    async fn test_slow_dial_does_not_block_other_addresses() {
+        let (msg_tx, _msg_rx) = mpsc::channel(8);
+        let (link_tx, _link_rx) = mpsc::channel(8);
+        let keepalive = KeepaliveConfig::default();
+        let bind = || QuicTransport::bind(
+            0, &keepalive, msg_tx.clone(), link_tx.clone(),
+            Arc::new(TrafficStats::new(None)), Arc::new(ReceiveBuffers::new(ReceiveLimits::default())),
+        );
+        let dialer = Arc::new(bind().await.unwrap().with_dial_timeout(Duration::from_millis(500)));
+        let live = bind().await.unwrap();
+        let live_addr = SocketAddr::from(([127, 0, 0, 1], live.local_addr().unwrap().port()));
+
+        // Bound but never answers QUIC
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let silent_addr = silent.local_addr().unwrap();
+        let stuck = tokio::spawn({
+            let dialer = dialer.clone();
+            async move { dialer.connect(silent_addr).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let started = std::time::Instant::now();
+        dialer.connect(live_addr).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(400), "live dial waited on the silent one");
+
+        let err = stuck.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(!dialer.connections.lock().unwrap().contains_key(&silent_addr));
+    }
+}
@@ -0,0 +1,97 @@
+//! Canonical trust levels shared by the mesh, admission and identity layers
+//!
+//! Levels are totally ordered from least to most trusted, so checks can be
+//! written as `level >= TrustLevel::Authenticated`. The numeric value is
+//! stable and used when a level has to travel as a byte.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TrustLevel {
+    /// -1: Violations detected (terminal)
+    #[serde(alias = "Rejected")]
+    Blacklisted = -1,
+    /// 0: Initial state
+    #[default]
+    Unknown = 0,
+    /// 1: Detected via discovery
+    Discovered = 1,
+    /// 2: Cryptographically verified (handshake complete, no track record yet)
+    #[serde(alias = "New")]
+    Authenticated = 2,
+    /// 3: Enough verified traffic to widen access
+    Probation = 3,
+    /// 4: High reputation
+    Trusted = 4,
+    /// 5: User-bootstrapped or the local node
+    System = 5,
+}
+
+impl TrustLevel {
+    /// Can never be promoted again
+    pub fn is_terminal(self) -> bool {
+        self == TrustLevel::Blacklisted
+    }
+}
+
+impl From<TrustLevel> for i8 {
+    fn from(level: TrustLevel) -> Self {
+        level as i8
+    }
+}
+
+impl TryFrom<i8> for TrustLevel {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i8) -> Result<Self> {
+        Ok(match value {
+            -1 => TrustLevel::Blacklisted,
+            0 => TrustLevel::Unknown,
+            1 => TrustLevel::Discovered,
+            2 => TrustLevel::Authenticated,
+            3 => TrustLevel::Probation,
+            4 => TrustLevel::Trusted,
+            5 => TrustLevel::System,
+            other => return Err(anyhow!("Unknown trust level {}", other)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [TrustLevel; 7] = [
+        TrustLevel::Blacklisted,
+        TrustLevel::Unknown,
+        TrustLevel::Discovered,
+        TrustLevel::Authenticated,
+        TrustLevel::Probation,
+        TrustLevel::Trusted,
+        TrustLevel::System,
+    ];
+
+    #[test]
+    fn test_numeric_round_trip_keeps_order() {
+        for pair in ALL.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(i8::from(pair[0]) < i8::from(pair[1]));
+        }
+        for level in ALL {
+            assert_eq!(TrustLevel::try_from(i8::from(level)).unwrap(), level);
+        }
+        assert!(TrustLevel::try_from(6).is_err());
+    }
+
+    #[test]
+    fn test_legacy_admission_names_deserialize() {
+        // Names used by the old admission/identity enums
+        let new: TrustLevel = serde_json::from_str("\"New\"").unwrap();
+        let rejected: TrustLevel = serde_json::from_str("\"Rejected\"").unwrap();
+        assert_eq!(new, TrustLevel::Authenticated);
+        assert_eq!(rejected, TrustLevel::Blacklisted);
+        assert!(rejected.is_terminal());
+        assert_eq!(serde_json::to_string(&TrustLevel::Probation).unwrap(), "\"Probation\"");
+    }
+}
@@ -0,0 +1,213 @@
+// Node HTTP API Types
+// Shared between the axum handlers in main.rs and the typed NodeClient
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use cerebellum::{ThoughtRequest, ThoughtResponse};
+pub use cerebellum::llm::TokenUsage;
+#[cfg(feature = "memory")]
+pub use hidb::MemoryRecord;
+pub use nervous_system::economy::{ActionType, Balances, EconomyReport, SimulatedEntry};
+/// GET /v1/economy/events: SSE stream of `ledger_entry` events
+pub use nervous_system::economy::EconomyEvent;
+
+// Response status markers used by every JSON endpoint
+pub const STATUS_SUCCESS: &str = "success";
+pub const STATUS_ERROR: &str = "error";
+pub const STATUS_RECORDED: &str = "recorded";
+
+/// POST /v1/think
+pub type ThinkRequest = ThoughtRequest;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThinkResponse {
+    pub status: String,
+    pub thought: ThoughtResponse,
+}
+
+/// Ledger action billing what a thought's model actually spent; None when no
+/// model answered (memory-only or failed thoughts)
+pub fn inference_action(thought: &ThoughtResponse) -> Option<ActionType> {
+    thought.usage.as_ref().map(|usage| ActionType::LlmInference { tokens: usage.total(), model: usage.model.clone() })
+}
+
+/// POST /v1/memory/search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemorySearchRequest {
+    #[serde(default)]
+    pub vector: Vec<f32>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+#[cfg(feature = "memory")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemorySearchResponse {
+    pub status: String,
+    pub results: Vec<MemoryRecord>,
+}
+
+/// POST /v1/memory/store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryStoreRequest {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryStoreResponse {
+    pub status: String,
+    pub id: Uuid,
+}
+
+/// POST /v1/memory/forget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryForgetRequest {
+    #[serde(default)]
+    pub vector: Vec<f32>,
+    /// Cosine similarity at or above which a memory is forgotten
+    pub threshold: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryForgetResponse {
+    pub status: String,
+    pub forgotten: u64,
+}
+
+/// GET /v1/economy/report?since=&until= (unix seconds; default: the last day)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EconomyReportQuery {
+    #[serde(default)]
+    pub since: Option<u64>,
+    #[serde(default)]
+    pub until: Option<u64>,
+}
+
+/// GET /v1/audit?since= (unix seconds; default: everything)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub since: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EconomyReportResponse {
+    pub status: String,
+    pub report: EconomyReport,
+}
+
+/// POST /v1/economy/record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordActionRequest {
+    /// "Success" or anything else for a failure
+    #[serde(default)]
+    pub outcome: Option<String>,
+    /// Tool name; omitted means generic inference
+    #[serde(default)]
+    pub tool: Option<String>,
+    /// Tokens used by the inference being recorded (default 100)
+    #[serde(default)]
+    pub tokens: Option<u32>,
+    /// Model that did the inference (default "unknown")
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Report the balance impact without recording anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordActionResponse {
+    pub status: String,
+    /// Present for dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<SimulatedEntry>,
+}
+
+/// GET /v1/economy/balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceResponse {
+    pub node_id: String,
+    pub balances: Balances,
+    pub reputation: f32,
+    /// Emergency freeze in effect; spends are rejected
+    #[serde(default)]
+    pub frozen: bool,
+}
+
+/// POST /v1/economy/freeze (there is no HTTP unfreeze; see `ippoc-econ unfreeze`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FreezeRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FreezeResponse {
+    pub status: String,
+    pub frozen: bool,
+}
+
+/// Error body returned with `"status": "error"` (or `permission_denied`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub status: String,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use cerebellum::llm::{Completion, CompletionOptions, LlmBackend, Prompt};
+    use cerebellum::mock::{CannedMemory, CannedSearch};
+    use cerebellum::Cerebrum;
+    use nervous_system::NodeSecrets;
+    use nervous_system::economy::{EconomyController, Outcome};
+
+    /// Always reports 1200 prompt + 300 completion tokens
+    struct Metered;
+
+    #[async_trait]
+    impl LlmBackend for Metered {
+        fn name(&self) -> &str {
+            "metered"
+        }
+
+        async fn complete(&self, _prompt: &Prompt, _opts: &CompletionOptions) -> anyhow::Result<Completion> {
+            let usage = TokenUsage { model: "gemma:2b".into(), prompt_tokens: 1200, completion_tokens: 300 };
+            Ok(Completion { text: "Nodes talk over QUIC.".into(), usage })
+        }
+
+        async fn embed(&self, _text: &str) -> anyhow::Result<Vec<f32>> {
+            Ok(vec![0.0])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_thought_is_billed_by_its_token_usage() -> anyhow::Result<()> {
+        let brain = Cerebrum::new_with(CannedSearch::new(vec![]), CannedMemory::new(vec![]))
+            .with_llm_backend(Box::new(Metered));
+        let thought = brain
+            .think(ThoughtRequest { query: "how do nodes talk?".into(), context_history: vec![] })
+            .await?;
+        assert_eq!(thought.usage.as_ref().map(TokenUsage::total), Some(1500));
+
+        let node_root = std::env::temp_dir().join(format!("think_billing_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&node_root)?;
+        let mut economy = EconomyController::new("node", &node_root, &NodeSecrets::generate())?;
+        economy.grant(Balances { ippc: 1000, ..Default::default() }, "genesis")?;
+
+        let action = inference_action(&thought).expect("the model answered");
+        economy.record_action("node", action, Outcome::Success)?;
+        // 10 base + 1 per 100 tokens
+        assert_eq!(economy.wallet.balances.ippc, 1000 - 25);
+
+        std::fs::remove_dir_all(node_root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+// Bearer-token gates for operator endpoints
+// Each gate reads its token from an env var; an unset token keeps the endpoint off.
+
+use axum::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+use axum::Json;
+use sha2::{Digest, Sha256};
+
+use crate::api;
+
+pub const AUDIT_TOKEN_ENV: &str = "IPPOC_AUDIT_TOKEN";
+pub const OPERATOR_TOKEN_ENV: &str = "IPPOC_OPERATOR_TOKEN";
+
+pub type Rejection = (StatusCode, Json<serde_json::Value>);
+
+// Token configured in `var`, ignoring blank values
+pub fn token_from_env(var: &str) -> Option<String> {
+    std::env::var(var).ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+// Constant-time comparison; hashing first keeps the expected length out of the timing too
+pub fn tokens_match(given: &str, expected: &str) -> bool {
+    let (given, expected) = (Sha256::digest(given.as_bytes()), Sha256::digest(expected.as_bytes()));
+    given.iter().zip(expected.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+// Admit requests carrying `Authorization: Bearer <token>`; `what` names the API in errors
+pub fn require_bearer(headers: &HeaderMap, token: Option<&str>, what: &str, env: &str) -> Result<(), Rejection> {
+    let Some(expected) = token else {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "status": api::STATUS_ERROR,
+            "error": format!("{} API disabled; set {}", what, env),
+        }))));
+    };
+    let presented = headers.get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match presented {
+        Some(given) if tokens_match(given, expected) => Ok(()),
+        _ => Err((StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "status": api::STATUS_ERROR,
+            "error": format!("invalid {} token", what),
+        })))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("s3cret", "s3cret"));
+        assert!(!tokens_match("s3cre", "s3cret"));
+        assert!(!tokens_match("", "s3cret"));
+    }
+
+    #[test]
+    fn test_require_bearer() {
+        assert_eq!(require_bearer(&bearer("s3cret"), None, "audit", AUDIT_TOKEN_ENV).unwrap_err().0, StatusCode::FORBIDDEN);
+        assert_eq!(require_bearer(&HeaderMap::new(), Some("s3cret"), "audit", AUDIT_TOKEN_ENV).unwrap_err().0, StatusCode::UNAUTHORIZED);
+        assert_eq!(require_bearer(&bearer("wrong"), Some("s3cret"), "audit", AUDIT_TOKEN_ENV).unwrap_err().0, StatusCode::UNAUTHORIZED);
+        assert!(require_bearer(&bearer("s3cret"), Some("s3cret"), "audit", AUDIT_TOKEN_ENV).is_ok());
+    }
+}
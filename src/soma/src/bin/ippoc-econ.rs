@@ -0,0 +1,7 @@
+use clap::Parser;
+use ippoc_node::econ_cli::{run, EconCli};
+
+fn main() -> anyhow::Result<()> {
+    let cli = EconCli::parse();
+    run(&cli, &mut std::io::stdout().lock())
+}
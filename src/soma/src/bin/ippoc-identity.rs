@@ -0,0 +1,7 @@
+use clap::Parser;
+use ippoc_node::identity_cli::{run, IdentityCli};
+
+fn main() -> anyhow::Result<()> {
+    let cli = IdentityCli::parse();
+    run(&cli, &mut std::io::stdout().lock())
+}
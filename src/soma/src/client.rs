@@ -0,0 +1,133 @@
+// Typed HTTP client for the IPPOC Node API
+// Mirrors the routes in main.rs using the shared types from `api`
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::{
+    BalanceResponse, EconomyReport, EconomyReportResponse, ErrorResponse, FreezeRequest, FreezeResponse, SimulatedEntry, MemoryForgetRequest, MemoryForgetResponse, MemoryStoreRequest,
+    MemoryStoreResponse,
+    RecordActionRequest, RecordActionResponse, ThinkRequest, ThinkResponse, ThoughtResponse,
+    STATUS_RECORDED, STATUS_SUCCESS,
+};
+#[cfg(feature = "memory")]
+use crate::api::{MemoryRecord, MemorySearchRequest, MemorySearchResponse};
+
+#[derive(Debug, Clone)]
+pub struct NodeClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl NodeClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    // Reuse a preconfigured reqwest client (timeouts, proxies, TLS)
+    pub fn with_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn think(&self, req: &ThinkRequest) -> Result<ThoughtResponse> {
+        let resp: ThinkResponse = self.post("/v1/think", req).await?;
+        Ok(resp.thought)
+    }
+
+    #[cfg(feature = "memory")]
+    pub async fn memory_search(&self, vector: Vec<f32>, limit: i64) -> Result<Vec<MemoryRecord>> {
+        let req = MemorySearchRequest { vector, limit: Some(limit) };
+        let resp: MemorySearchResponse = self.post("/v1/memory/search", &req).await?;
+        Ok(resp.results)
+    }
+
+    pub async fn memory_store(&self, content: impl Into<String>, vector: Vec<f32>) -> Result<uuid::Uuid> {
+        let req = MemoryStoreRequest { content: content.into(), vector };
+        let resp: MemoryStoreResponse = self.post("/v1/memory/store", &req).await?;
+        Ok(resp.id)
+    }
+
+    pub async fn memory_forget(&self, vector: Vec<f32>, threshold: f32) -> Result<u64> {
+        let req = MemoryForgetRequest { vector, threshold };
+        let resp: MemoryForgetResponse = self.post("/v1/memory/forget", &req).await?;
+        Ok(resp.forgotten)
+    }
+
+    pub async fn record_action(&self, req: &RecordActionRequest) -> Result<()> {
+        let _: RecordActionResponse = self.post("/v1/economy/record", req).await?;
+        Ok(())
+    }
+
+    pub async fn simulate_action(&self, req: &RecordActionRequest) -> Result<SimulatedEntry> {
+        let req = RecordActionRequest { dry_run: true, ..req.clone() };
+        let resp: RecordActionResponse = self.post("/v1/economy/record", &req).await?;
+        resp.simulation.ok_or_else(|| anyhow!("Node ignored dry_run"))
+    }
+
+    pub async fn balance(&self) -> Result<BalanceResponse> {
+        self.get("/v1/economy/balance").await
+    }
+
+    /// Pull the emergency brake on the node's wallet; needs the node's IPPOC_OPERATOR_TOKEN
+    pub async fn freeze_economy(&self, operator_token: &str, reason: impl Into<String>) -> Result<()> {
+        let req = FreezeRequest { reason: Some(reason.into()) };
+        let resp = self.http.post(self.url("/v1/economy/freeze"))
+            .bearer_auth(operator_token)
+            .json(&req)
+            .send()
+            .await?;
+        let _: FreezeResponse = Self::decode(resp).await?;
+        Ok(())
+    }
+
+    pub async fn economy_report(&self, since: u64, until: u64) -> Result<EconomyReport> {
+        let resp: EconomyReportResponse = self
+            .get(&format!("/v1/economy/report?since={}&until={}", since, until))
+            .await?;
+        Ok(resp.report)
+    }
+
+    // Internal helpers
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<R> {
+        let resp = self.http.get(self.url(path)).send().await?;
+        Self::decode(resp).await
+    }
+
+    async fn post<B: Serialize + ?Sized, R: DeserializeOwned>(&self, path: &str, body: &B) -> Result<R> {
+        let resp = self.http.post(self.url(path)).json(body).send().await?;
+        Self::decode(resp).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    // The node reports failures in-band with a non-success status field
+    async fn decode<R: DeserializeOwned>(resp: reqwest::Response) -> Result<R> {
+        let code = resp.status();
+        let body: serde_json::Value = resp.json().await?;
+
+        if !code.is_success() {
+            return Err(anyhow!("Node returned HTTP {}: {}", code, body));
+        }
+
+        if let Some(status) = body.get("status").and_then(|s| s.as_str()) {
+            if status != STATUS_SUCCESS && status != STATUS_RECORDED {
+                let err: ErrorResponse = serde_json::from_value(body.clone())
+                    .unwrap_or(ErrorResponse { status: status.to_string(), error: body.to_string() });
+                return Err(anyhow!("Node error ({}): {}", err.status, err.error));
+            }
+        }
+
+        Ok(serde_json::from_value(body)?)
+    }
+}
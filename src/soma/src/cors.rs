@@ -0,0 +1,70 @@
+// Cross-Origin Resource Sharing for the Node API
+// Origins are opt-in via IPPOC_CORS_ORIGINS; nothing is allowed by default.
+
+use axum::http::{HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{info, warn};
+
+pub const CORS_ORIGINS_ENV: &str = "IPPOC_CORS_ORIGINS";
+
+// Parse a comma-separated origin allowlist, skipping blanks and malformed entries
+pub fn parse_origins(raw: &str) -> Vec<HeaderValue> {
+    raw.split(',')
+        .map(|s| s.trim().trim_end_matches('/'))
+        .filter(|s| !s.is_empty())
+        .filter_map(|origin| {
+            if origin == "*" {
+                warn!("CORS: Wildcard origin is not supported, list origins explicitly");
+                return None;
+            }
+            match HeaderValue::from_str(origin) {
+                Ok(v) => Some(v),
+                Err(_) => {
+                    warn!("CORS: Ignoring invalid origin '{}'", origin);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+// Layer for read-only routes (dashboards may poll these from the browser)
+pub fn read_only_layer() -> CorsLayer {
+    let raw = std::env::var(CORS_ORIGINS_ENV).unwrap_or_default();
+    read_only_layer_for(parse_origins(&raw))
+}
+
+pub fn read_only_layer_for(origins: Vec<HeaderValue>) -> CorsLayer {
+    if origins.is_empty() {
+        // No allow-origin header is emitted, so browsers block cross-origin reads
+        return CorsLayer::new();
+    }
+
+    info!("CORS: Allowing {} origin(s) on read-only routes", origins.len());
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::HEAD, Method::OPTIONS])
+}
+
+// Mutating routes never get CORS headers; preflights from browsers fail closed
+pub fn mutating_layer() -> CorsLayer {
+    CorsLayer::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_origins() {
+        let origins = parse_origins(" http://localhost:3000/, ,https://dash.ippoc.local,*");
+        assert_eq!(origins.len(), 2);
+        assert_eq!(origins[0], "http://localhost:3000");
+        assert_eq!(origins[1], "https://dash.ippoc.local");
+    }
+
+    #[test]
+    fn test_default_denies_all() {
+        assert!(parse_origins("").is_empty());
+    }
+}
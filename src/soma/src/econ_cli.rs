@@ -0,0 +1,183 @@
+//! `ippoc-econ`: inspect and manage a node's economy from the shell
+//!
+//! Works directly on the economy files under the node root. Commands that
+//! write take the economy lock, so they refuse to run next to a live node
+//! instead of racing its in-memory wallet.
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use nervous_system::economy::{Balances, EconomyController, EconomyLock};
+use nervous_system::identity::{find_existing_identity, PersistentIdentity};
+
+#[derive(Parser, Debug)]
+#[command(name = "ippoc-econ", about = "Inspect and manage the node economy")]
+pub struct EconCli {
+    /// Storage base holding `nodes/<id>` (the node's IPPOC_DATA_DIR)
+    #[arg(long, default_value = "./data")]
+    pub data_dir: PathBuf,
+
+    /// Use this node root instead of the first node under the data dir
+    #[arg(long)]
+    pub node_root: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: EconCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EconCommand {
+    /// Show wallet balances
+    Balance,
+    /// Grant IPPC to this node (development only)
+    Grant { amount: u128 },
+    /// Show ledger entries
+    Ledger {
+        /// Only the last N entries
+        #[arg(long)]
+        tail: Option<usize>,
+    },
+    /// Check the hash chain and the wallet against a ledger replay
+    Verify,
+    /// Emergency-freeze the wallet; also confirms a pending mesh-voted freeze
+    Freeze {
+        #[arg(long, default_value = "operator request")]
+        reason: String,
+    },
+    /// Lift an emergency freeze (the node must be stopped)
+    Unfreeze,
+}
+
+fn open_identity(cli: &EconCli) -> Result<(PersistentIdentity, PathBuf)> {
+    match &cli.node_root {
+        Some(root) => {
+            let identity = PersistentIdentity::load_and_verify(&root.join("data").join("identity.key"))?;
+            Ok((identity, root.clone()))
+        }
+        None => find_existing_identity(&cli.data_dir)?
+            .ok_or_else(|| anyhow!("No node identity under {:?}", cli.data_dir.join("nodes"))),
+    }
+}
+
+fn open_economy(identity: &PersistentIdentity, node_root: &Path) -> Result<EconomyController> {
+    EconomyController::new(&identity.identity.id, node_root, &identity.secrets()?)
+}
+
+fn write_balances(out: &mut impl Write, balances: &Balances) -> Result<()> {
+    writeln!(out, "ippc        {}", balances.ippc)?;
+    writeln!(out, "iusd        {}", balances.iusd)?;
+    writeln!(out, "eth_virtual {}", balances.eth_virtual)?;
+    Ok(())
+}
+
+fn write_wallet(out: &mut impl Write, economy: &EconomyController) -> Result<()> {
+    write_balances(out, &economy.wallet.balances)?;
+    if economy.is_frozen() {
+        match economy.wallet.frozen_until {
+            Some(until) => writeln!(out, "FROZEN by mesh vote until {} (unconfirmed)", until)?,
+            None => writeln!(out, "FROZEN")?,
+        }
+    }
+    Ok(())
+}
+
+pub fn run(cli: &EconCli, out: &mut impl Write) -> Result<()> {
+    let (identity, node_root) = open_identity(cli)?;
+
+    match &cli.command {
+        EconCommand::Balance => {
+            let economy = open_economy(&identity, &node_root)?;
+            write_wallet(out, &economy)?;
+        }
+        EconCommand::Grant { amount } => {
+            let _lock = EconomyLock::acquire(&node_root)?;
+            let mut economy = open_economy(&identity, &node_root)?;
+            economy.grant(Balances { ippc: *amount, ..Default::default() }, "cli")?;
+            write_wallet(out, &economy)?;
+        }
+        EconCommand::Ledger { tail } => {
+            let economy = open_economy(&identity, &node_root)?;
+            let entries = economy.ledger();
+            let skip = tail.map_or(0, |n| entries.len().saturating_sub(n));
+            for entry in &entries[skip..] {
+                writeln!(
+                    out,
+                    "{:>6} {} {:?} -{} +{} {:?} {}",
+                    entry.seq_no,
+                    entry.timestamp,
+                    entry.outcome,
+                    entry.debit.ippc,
+                    entry.credit.ippc,
+                    entry.action,
+                    entry.tx_id,
+                )?;
+            }
+        }
+        EconCommand::Verify => {
+            let economy = open_economy(&identity, &node_root)?;
+            economy.verify()?;
+            writeln!(out, "ok: {} entries", economy.ledger().len())?;
+        }
+        EconCommand::Freeze { reason } => {
+            let _lock = EconomyLock::acquire(&node_root)?;
+            let mut economy = open_economy(&identity, &node_root)?;
+            economy.freeze(reason)?;
+            write_wallet(out, &economy)?;
+        }
+        // Unfreezing needs the lock, i.e. local access with the node stopped
+        EconCommand::Unfreeze => {
+            let _lock = EconomyLock::acquire(&node_root)?;
+            let mut economy = open_economy(&identity, &node_root)?;
+            economy.unfreeze()?;
+            write_wallet(out, &economy)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_args(args: &[&str]) -> Result<String> {
+        let cli = EconCli::try_parse_from(std::iter::once("ippoc-econ").chain(args.iter().copied()))?;
+        let mut out = Vec::new();
+        run(&cli, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_subcommands_against_temp_node_root() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("econ_cli_{}", uuid::Uuid::new_v4()));
+        let (_, node_root) = nervous_system::identity::load_or_create_identity(&base, "tool", "cli-test")?;
+        let data_dir = base.to_str().unwrap();
+
+        assert!(run_args(&["--data-dir", data_dir, "balance"])?.contains("ippc        0"));
+        assert!(run_args(&["--data-dir", data_dir, "grant", "500"])?.contains("ippc        500"));
+        run_args(&["--data-dir", data_dir, "grant", "250"])?;
+
+        let ledger = run_args(&["--data-dir", data_dir, "ledger", "--tail", "1"])?;
+        assert_eq!(ledger.lines().count(), 1);
+        assert!(ledger.contains("+250"), "{}", ledger);
+
+        let root = node_root.to_str().unwrap();
+        assert_eq!(run_args(&["--node-root", root, "verify"])?, "ok: 2 entries\n");
+
+        // A running node holds the lock; writes refuse, reads still work
+        let daemon = EconomyLock::acquire(&node_root)?;
+        assert!(run_args(&["--data-dir", data_dir, "grant", "1"]).is_err());
+        assert!(run_args(&["--data-dir", data_dir, "balance"])?.contains("ippc        750"));
+        assert!(run_args(&["--data-dir", data_dir, "unfreeze"]).is_err());
+        drop(daemon);
+
+        assert!(run_args(&["--data-dir", data_dir, "freeze"])?.contains("FROZEN"));
+        assert!(run_args(&["--data-dir", data_dir, "grant", "1"]).is_err());
+        assert!(!run_args(&["--data-dir", data_dir, "unfreeze"])?.contains("FROZEN"));
+        run_args(&["--data-dir", data_dir, "grant", "1"])?;
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+// Auto-Evolution Loop
+// Periodically syncs component repositories through GitEvolution and keeps
+// an inspectable record of what each cycle did.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn, error};
+
+use brain_evolution::EvolutionEngine;
+use git_evolution::{GitEvolution, ProgressSender, DEFAULT_MAX_TOTAL_LOC, RepoBusy, RepoGuard, UpdateOutcome, UpdatePlan};
+use nervous_system::AiMesh;
+use nervous_system::economy::{ActionType, Outcome};
+
+pub const DEFAULT_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvolutionComponent {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvolutionConfig {
+    pub components: Vec<EvolutionComponent>,
+    pub interval_secs: u64,
+    pub remote: String,
+    pub branch: String,
+    /// Aggregate Rule 6 ceiling per evolution (lines added + removed)
+    pub max_total_loc: usize,
+}
+
+impl EvolutionConfig {
+    // IPPOC_EVOLUTION_COMPONENTS: comma-separated `name=path` (or bare paths)
+    // IPPOC_EVOLUTION_INTERVAL_SECS, IPPOC_EVOLUTION_REMOTE, IPPOC_EVOLUTION_BRANCH,
+    // IPPOC_EVOLUTION_MAX_LOC
+    pub fn from_env() -> Self {
+        let components = std::env::var("IPPOC_EVOLUTION_COMPONENTS")
+            .map(|raw| parse_components(&raw))
+            .unwrap_or_default();
+        let interval_secs = std::env::var("IPPOC_EVOLUTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+        let max_total_loc = std::env::var("IPPOC_EVOLUTION_MAX_LOC")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_TOTAL_LOC);
+
+        Self {
+            components,
+            interval_secs,
+            remote: std::env::var("IPPOC_EVOLUTION_REMOTE").unwrap_or_else(|_| "origin".to_string()),
+            branch: std::env::var("IPPOC_EVOLUTION_BRANCH").unwrap_or_else(|_| "main".to_string()),
+            max_total_loc,
+        }
+    }
+
+    pub fn component(&self, name: &str) -> Option<&EvolutionComponent> {
+        self.components.iter().find(|c| c.name == name)
+    }
+}
+
+pub fn parse_components(raw: &str) -> Vec<EvolutionComponent> {
+    raw.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((name, path)) => EvolutionComponent {
+                name: name.trim().to_string(),
+                path: PathBuf::from(path.trim()),
+            },
+            None => {
+                let path = PathBuf::from(entry);
+                let name = path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| entry.to_string());
+                EvolutionComponent { name, path }
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "result")]
+pub enum ComponentResult {
+    UpToDate,
+    FastForwarded,
+    Merged,
+    ConflictResolved,
+    Failed { error: String },
+}
+
+impl From<UpdateOutcome> for ComponentResult {
+    fn from(outcome: UpdateOutcome) -> Self {
+        match outcome {
+            UpdateOutcome::UpToDate | UpdateOutcome::NoAction => ComponentResult::UpToDate,
+            UpdateOutcome::FastForwarded => ComponentResult::FastForwarded,
+            UpdateOutcome::Merged => ComponentResult::Merged,
+            UpdateOutcome::ConflictResolved => ComponentResult::ConflictResolved,
+            UpdateOutcome::ConflictRejected => ComponentResult::Failed {
+                error: "Conflict resolution rejected; merge aborted".to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    #[serde(flatten)]
+    pub result: ComponentResult,
+    pub last_commit: Option<String>,
+    pub checked_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EvolutionStatus {
+    pub last_run: Option<u64>,
+    pub interval_secs: u64,
+    pub components: HashMap<String, ComponentStatus>,
+}
+
+/// Why an operator-triggered run was turned away before it started
+#[derive(Debug)]
+pub enum Rejection {
+    /// Another run holds the component's repository
+    Busy,
+    /// Lifecycle or economy refused the EvolutionSim charge
+    PermissionDenied(anyhow::Error),
+    /// The repository could not be opened or locked
+    Failed(anyhow::Error),
+}
+
+impl Rejection {
+    /// Response body for the operator routes
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Rejection::Busy => serde_json::json!({ "status": "busy", "error": "Evolution already running for this component" }),
+            Rejection::PermissionDenied(e) => serde_json::json!({ "status": "permission_denied", "error": e.to_string() }),
+            Rejection::Failed(e) => serde_json::json!({ "status": "error", "error": e.to_string() }),
+        }
+    }
+}
+
+/// Admit an operator-triggered run: take the component's repository lock,
+/// then check permission and charge for it. Locking first means a request
+/// that loses the race is told "busy" without being charged.
+pub async fn admit(mesh: &AiMesh, component: &EvolutionComponent, label: &str) -> Result<RepoGuard, Rejection> {
+    let guard = GitEvolution::open(&component.path)
+        .and_then(|git| git.reserve())
+        .map_err(|e| if e.is::<RepoBusy>() { Rejection::Busy } else { Rejection::Failed(e) })?;
+    authorize(mesh, label).await.map_err(Rejection::PermissionDenied)?;
+    Ok(guard)
+}
+
+/// Lifecycle permission + EvolutionSim charge for operator-triggered runs
+async fn authorize(mesh: &AiMesh, label: &str) -> anyhow::Result<()> {
+    let action = ActionType::EvolutionSim { pr_id: label.to_string() };
+    mesh.check_permission(&action).await?;
+
+    let mut eco = mesh.economy.write().await;
+    eco.record_action(&mesh.identity().id, action, Outcome::Success)
+}
+
+/// Preview what an update of `component` would do, under its reservation
+pub fn plan_component(component: &EvolutionComponent, config: &EvolutionConfig, guard: &RepoGuard) -> anyhow::Result<UpdatePlan> {
+    GitEvolution::open(&component.path)?
+        .with_max_total_loc(config.max_total_loc)
+        .plan_update_reserved(guard, &config.remote, &config.branch)
+}
+
+/// `plan_component` on the blocking pool; git2 fetches block the calling thread
+pub async fn plan_component_blocking(component: EvolutionComponent, config: EvolutionConfig, guard: RepoGuard) -> anyhow::Result<UpdatePlan> {
+    tokio::task::spawn_blocking(move || plan_component(&component, &config, &guard)).await?
+}
+
+/// `evolve_component` on the blocking pool: git2 fetch, merge and checkout
+/// run synchronously between the Brain's awaits and would stall the runtime
+pub async fn evolve_component_blocking(
+    component: EvolutionComponent,
+    config: EvolutionConfig,
+    engine: Arc<EvolutionEngine>,
+    reserved: Option<RepoGuard>,
+    progress: Option<ProgressSender>,
+) -> ComponentStatus {
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        runtime.block_on(evolve_component(&component, &config, &engine, reserved, progress))
+    })
+    .await
+    .unwrap_or_else(|e| ComponentStatus {
+        result: ComponentResult::Failed { error: format!("Evolution task failed: {e}") },
+        last_commit: None,
+        checked_at: now(),
+    })
+}
+
+/// Run one update cycle for a single component, under `reserved` when the
+/// caller already holds the repository (see `reserve`), otherwise waiting
+/// out any other evolution. Phases are reported to `progress` when given.
+pub async fn evolve_component(
+    component: &EvolutionComponent,
+    config: &EvolutionConfig,
+    engine: &EvolutionEngine,
+    reserved: Option<RepoGuard>,
+    progress: Option<ProgressSender>,
+) -> ComponentStatus {
+    let outcome = match GitEvolution::open(&component.path) {
+        Ok(git) => {
+            let git = git.with_max_total_loc(config.max_total_loc);
+            let git = match progress {
+                Some(sink) => git.with_progress(sink),
+                None => git,
+            };
+            let result = match reserved {
+                Some(guard) => git.auto_update_reserved(guard, &config.remote, &config.branch, engine).await,
+                None => git.auto_update(&config.remote, &config.branch, engine).await,
+            };
+            result.map(|outcome| (outcome, git.head_commit().ok()))
+        }
+        Err(e) => Err(e),
+    };
+
+    let (result, last_commit) = match outcome {
+        Ok((outcome, head)) => (ComponentResult::from(outcome), head),
+        Err(e) => (ComponentResult::Failed { error: e.to_string() }, None),
+    };
+
+    ComponentStatus {
+        result,
+        last_commit,
+        checked_at: now(),
+    }
+}
+
+/// Background loop: evolve every configured component on each tick
+pub async fn run_loop(
+    config: EvolutionConfig,
+    engine: Arc<EvolutionEngine>,
+    status: Arc<RwLock<EvolutionStatus>>,
+) {
+    status.write().await.interval_secs = config.interval_secs;
+
+    if config.components.is_empty() {
+        info!("Evolution: No components configured, auto-evolution idle");
+        return;
+    }
+
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(config.interval_secs)).await;
+        info!("Evolution: Starting cycle over {} component(s)", config.components.len());
+
+        for component in &config.components {
+            // Waits out any operator-triggered run on the same repository
+            let result = evolve_component_blocking(component.clone(), config.clone(), engine.clone(), None, None).await;
+            match &result.result {
+                ComponentResult::Failed { error: e } => error!("Evolution: {} failed: {}", component.name, e),
+                ComponentResult::UpToDate => info!("Evolution: {} up to date", component.name),
+                other => warn!("Evolution: {} mutated ({:?})", component.name, other),
+            }
+            status.write().await.components.insert(component.name.clone(), result);
+        }
+
+        status.write().await.last_run = Some(now());
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -4,6 +4,7 @@ use std::fs;
 use std::path::Path;
 use anyhow::Result;
 use tracing::info;
+use nervous_system::persist::{atomic_write, atomic_write_private};
 use ed25519_dalek::{SigningKey, VerifyingKey};
 use rand::rngs::OsRng;
 
@@ -30,13 +31,7 @@ impl NodeIdentity {
             info!("NodeIdentity: No existing master identity found. Generating fresh...");
             let key = SigningKey::generate(&mut OsRng);
             // We write a master sentinel to storage_base to ensure future boots find this ID
-            fs::write(&global_key_path, key.to_bytes())?;
-            
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                fs::set_permissions(&global_key_path, fs::Permissions::from_mode(0o600))?;
-            }
+            atomic_write_private(&global_key_path, &key.to_bytes())?;
             key
         };
 
@@ -54,13 +49,7 @@ impl NodeIdentity {
         let fingerprint_path = identity_dir.join("hardware_fingerprint.json");
 
         if !key_path.exists() {
-            fs::write(&key_path, self.signing_key.to_bytes())?;
-            
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600))?;
-            }
+            atomic_write_private(&key_path, &self.signing_key.to_bytes())?;
 
             let mut sys = System::new_all();
             sys.refresh_all();
@@ -68,7 +57,7 @@ impl NodeIdentity {
             let cpu_info = sys.cpus().first().map(|c| c.brand()).unwrap_or("unknown").to_string();
             let mem_total = sys.total_memory();
             let fingerprint = format!("{hostname}-{cpu_info}-{mem_total}");
-            fs::write(&fingerprint_path, fingerprint)?;
+            atomic_write(&fingerprint_path, fingerprint.as_bytes())?;
             
             info!("NodeIdentity: Identity persisted to isolation root and bound to hardware.");
         }
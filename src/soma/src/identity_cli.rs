@@ -0,0 +1,160 @@
+//! `ippoc-identity`: create and inspect node identities offline
+//!
+//! Lays files out exactly as the node's genesis does (`<data-dir>/nodes/<id>/
+//! data/identity.key`, mode 0600), so a provisioned directory can be shipped
+//! as the target's IPPOC_DATA_DIR. The key is sealed to the hardware it was
+//! generated on; boot on the target with IPPOC_ALLOW_HARDWARE_CHANGE set.
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use nervous_system::identity::PersistentIdentity;
+use nervous_system::node_id;
+
+#[derive(Parser, Debug)]
+#[command(name = "ippoc-identity", about = "Generate and inspect node identities")]
+pub struct IdentityCli {
+    #[command(subcommand)]
+    pub command: IdentityCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IdentityCommand {
+    /// Create a new identity under a storage base
+    Generate {
+        /// Storage base to create `nodes/<id>` in (the target's IPPOC_DATA_DIR)
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long, default_value = "tool")]
+        role: String,
+        /// Defaults to node-<first 8 hex of the id>
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Print the id and public keys of an existing identity
+    Show {
+        /// A storage base, a node root, or an identity.key file
+        path: PathBuf,
+    },
+}
+
+fn key_path(node_root: &Path) -> PathBuf {
+    node_root.join("data").join("identity.key")
+}
+
+/// Resolve a storage base, node root or key file to the identity file
+fn find_key(path: &Path) -> Result<PathBuf> {
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    if key_path(path).is_file() {
+        return Ok(key_path(path));
+    }
+    let nodes = path.join("nodes");
+    if nodes.is_dir() {
+        for entry in std::fs::read_dir(&nodes)? {
+            let key = key_path(&entry?.path());
+            if key.is_file() {
+                return Ok(key);
+            }
+        }
+    }
+    Err(anyhow!("No identity.key under {:?}", path))
+}
+
+fn write_identity(out: &mut impl Write, identity: &PersistentIdentity, key: &Path) -> Result<()> {
+    let public = &identity.identity;
+    writeln!(out, "node_id         {}", public.id)?;
+    writeln!(out, "name            {}", public.name)?;
+    writeln!(out, "role            {}", public.role)?;
+    writeln!(out, "signing_public  {}", hex::encode(public.signing_public))?;
+    writeln!(out, "exchange_public {}", hex::encode(public.exchange_public))?;
+    writeln!(out, "created_at      {}", identity.created_at.to_rfc3339())?;
+    writeln!(out, "key_file        {}", key.display())?;
+    Ok(())
+}
+
+pub fn run(cli: &IdentityCli, out: &mut impl Write) -> Result<()> {
+    match &cli.command {
+        IdentityCommand::Generate { out: base, role, name } => {
+            // The node boots the first identity it finds; a second one would be ignored
+            if let Ok(existing) = find_key(base) {
+                return Err(anyhow!("Identity already exists at {:?}", existing));
+            }
+            let mut identity = PersistentIdentity::new(role, name.as_deref().unwrap_or(""))?;
+            if name.is_none() {
+                identity.identity.name = format!("node-{}", &identity.identity.id[..8]);
+            }
+            let key = key_path(&base.join("nodes").join(&identity.identity.id));
+            identity.save(&key)?;
+            write_identity(out, &identity, &key)?;
+        }
+        IdentityCommand::Show { path } => {
+            let key = find_key(path)?;
+            let identity = PersistentIdentity::load(&key)?;
+            if identity.identity.id != node_id(&identity.identity.signing_public) {
+                return Err(anyhow!("Corrupt identity {:?}: node id does not match the signing key", key));
+            }
+            write_identity(out, &identity, &key)?;
+            if !identity.matches_hardware()? {
+                writeln!(out, "sealed to other hardware")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_args(args: &[&str]) -> Result<String> {
+        let cli = IdentityCli::try_parse_from(std::iter::once("ippoc-identity").chain(args.iter().copied()))?;
+        let mut out = Vec::new();
+        run(&cli, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    fn field<'a>(output: &'a str, name: &str) -> &'a str {
+        output.lines()
+            .find_map(|l| l.strip_prefix(name))
+            .map(str::trim)
+            .unwrap_or_else(|| panic!("no {} in {}", name, output))
+    }
+
+    #[test]
+    fn test_generate_then_show() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("identity_cli_{}", uuid::Uuid::new_v4()));
+        let dir = base.to_str().unwrap();
+
+        let generated = run_args(&["generate", "--out", dir, "--role", "relay"])?;
+        let id = field(&generated, "node_id");
+        assert_eq!(field(&generated, "name"), format!("node-{}", &id[..8]));
+
+        // Same identity whether addressed by storage base or key file
+        let shown = run_args(&["show", dir])?;
+        assert_eq!(field(&shown, "node_id"), id);
+        assert_eq!(field(&shown, "signing_public"), field(&generated, "signing_public"));
+        assert_eq!(field(&shown, "exchange_public"), field(&generated, "exchange_public"));
+        assert!(!shown.contains("sealed to other hardware"));
+        assert_eq!(run_args(&["show", field(&generated, "key_file")])?, shown);
+
+        // The node would load it at boot
+        let (booted, _) = nervous_system::identity::load_or_create_identity(&base, "tool", "ignored")?;
+        assert_eq!(booted.identity.id, id);
+
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(field(&generated, "key_file"))?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(run_args(&["generate", "--out", dir]).is_err());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+}
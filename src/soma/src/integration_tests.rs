@@ -8,10 +8,10 @@ mod tests {
         let manager = UnifiedTrustManager::new();
         let temp_dir = tempfile::tempdir().unwrap();
         
-        let identity = manager.create_identity(temp_dir.path()).unwrap();
+        let identity = manager.create_identity(temp_dir.path()).await.unwrap();
         
         assert!(!identity.node_id.is_empty());
-        assert_eq!(identity.trust_level, TrustLevel::New);
+        assert_eq!(manager.get_trust_level(&identity.node_id).await, TrustLevel::Authenticated);
     }
 
     #[tokio::test]
@@ -37,7 +37,7 @@ mod tests {
         let evaluation = manager.evaluate_trust("test_peer", "SYN").await;
         
         assert!(evaluation.allowed);
-        assert_eq!(evaluation.trust_level, TrustLevel::New);
+        assert_eq!(evaluation.trust_level, TrustLevel::Authenticated);
     }
 
     #[tokio::test]
@@ -50,6 +50,7 @@ mod tests {
             amount: 1.0,
             priority: Priority::Medium,
             duration: Some(60),
+            preemptible: false,
         };
         
         let result = manager.allocate_resource(request).await;
@@ -71,6 +72,7 @@ mod tests {
             amount: 10.0, // Exceeds policy limit of 4.0
             priority: Priority::Medium,
             duration: Some(60),
+            preemptible: false,
         };
         
         let result = manager.allocate_resource(request).await;
@@ -101,7 +103,7 @@ mod tests {
         
         // Check initial trust level
         let initial_level = manager.get_trust_level("promotable_peer").await;
-        assert_eq!(initial_level, TrustLevel::New);
+        assert_eq!(initial_level, TrustLevel::Authenticated);
         
         // Promote trust
         manager.promote_trust("promotable_peer").await.unwrap();
@@ -0,0 +1,14 @@
+//! IPPOC Node - shared HTTP API types and a typed client
+//!
+//! The node binary (main.rs) serves these types; integrators (TUI, OpenClaw
+//! bridge) use `client::NodeClient` instead of hand-rolled JSON. Embedders
+//! assemble a whole node in-process with `system::IPPOC`.
+
+pub mod api;
+pub mod client;
+pub mod econ_cli;
+pub mod identity_cli;
+pub mod system;
+
+pub use client::NodeClient;
+pub use system::{IPPOC, IPPOCBuilder};
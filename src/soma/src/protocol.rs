@@ -4,8 +4,12 @@ use tracing::{info, warn};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 use ed25519_dalek::{SigningKey, Signature, Signer, Verifier, VerifyingKey};
 
+/// Default +/- window for packet timestamps
+pub const DEFAULT_SKEW_TOLERANCE_SECS: u64 = 300;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SignedPacket {
     pub header: PacketHeader,
@@ -21,12 +25,26 @@ pub struct PacketHeader {
 }
 
 impl SignedPacket {
+    /// Wire format: bincode, same as the mesh messages that carry it
+    #[allow(dead_code)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
     #[allow(dead_code)]
     pub fn sign(node_id: &str, signing_key: &SigningKey, payload: Vec<u8>) -> Result<Self> {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)?
             .as_secs();
-        
+        Ok(Self::sign_at(node_id, signing_key, payload, timestamp))
+    }
+
+    #[allow(dead_code)]
+    fn sign_at(node_id: &str, signing_key: &SigningKey, payload: Vec<u8>, timestamp: u64) -> Self {
         let nonce = uuid::Uuid::new_v4().to_string();
         
         // Canonical material for signature: Payload || Timestamp || Nonce
@@ -37,7 +55,7 @@ impl SignedPacket {
         
         let signature = signing_key.sign(&material).to_bytes().to_vec();
 
-        Ok(Self {
+        Self {
             header: PacketHeader {
                 node_id: node_id.to_string(),
                 signature,
@@ -45,22 +63,26 @@ impl SignedPacket {
                 nonce,
             },
             payload,
-        })
+        }
     }
 
     #[allow(dead_code)]
     pub fn verify(&self, public_key_bytes: &[u8]) -> Result<bool> {
-        // 1. Verify Timestamp (Anti-Replay Window: 5 minutes)
+        self.verify_with_tolerance(public_key_bytes, DEFAULT_SKEW_TOLERANCE_SECS)
+    }
+
+    /// Verify with a custom anti-replay window of +/- `tolerance_secs`
+    #[allow(dead_code)]
+    pub fn verify_with_tolerance(&self, public_key_bytes: &[u8], tolerance_secs: u64) -> Result<bool> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)?
             .as_secs();
-            
-        if self.header.timestamp < now - 300 || self.header.timestamp > now + 300 {
-            warn!("Protocol: Signature REJECTED. Packet timestamp out of valid window.");
-            return Ok(false);
-        }
+        self.verify_at(public_key_bytes, tolerance_secs, now)
+    }
 
-        // 2. Ed25519 Verification
+    #[allow(dead_code)]
+    fn verify_at(&self, public_key_bytes: &[u8], tolerance_secs: u64, now: u64) -> Result<bool> {
+        // 1. Ed25519 Verification (first, so a skew rejection is reported as such)
         let verifying_key = VerifyingKey::from_bytes(public_key_bytes.try_into()?)?;
         let signature = Signature::from_slice(&self.header.signature)?;
         
@@ -74,6 +96,17 @@ impl SignedPacket {
              return Ok(false);
         }
 
+        // 2. Timestamp (Anti-Replay Window)
+        if self.header.timestamp < now.saturating_sub(tolerance_secs) || self.header.timestamp > now.saturating_add(tolerance_secs) {
+            warn!(
+                "Protocol: REJECTED valid packet from {} for clock skew only: {}s off (tolerance {}s)",
+                self.header.node_id,
+                self.header.timestamp.abs_diff(now),
+                tolerance_secs
+            );
+            return Ok(false);
+        }
+
         info!("Protocol: Packet verified successfully from {}", self.header.node_id);
         Ok(true)
     }
@@ -118,14 +151,7 @@ pub enum HandshakeMessage {
     Ack { node_id: String, challenge_nonce: String, verifying_key: Vec<u8> },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum TrustLevel {
-    New,       // Handshake complete, but unproven
-    Probation, // N packets verified
-    Trusted,   // Long-term secure peer
-    System,    // Local node / explicitly pinned system node
-    Rejected,  // Security violation encountered (Terminal)
-}
+pub use nervous_system::TrustLevel;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerState {
@@ -135,10 +161,31 @@ pub struct PeerState {
     pub packet_count: u64,
 }
 
+/// Outcome of `AdmissionManager::admit_bytes`
+#[derive(Debug)]
+pub enum Admission {
+    Admitted(SignedPacket),
+    /// Parsed, but refused by `should_admit`
+    Rejected,
+    /// Not a SignedPacket at all
+    Malformed,
+}
+
+/// Inbound counters, kept apart so a framing bug is not mistaken for an attack
+#[derive(Debug, Default)]
+pub struct AdmissionStats {
+    pub admitted: AtomicU64,
+    pub rejected: AtomicU64,
+    pub malformed: AtomicU64,
+}
+
 pub struct AdmissionManager {
     pub local_node_id: String,
     pub replay_cache: ReplayCache,
     pub peer_registry: Mutex<HashMap<String, PeerState>>, // NodeID -> State
+    /// +/- window for packet timestamps; widen for drifting embedded/offline nodes
+    pub skew_tolerance_secs: u64,
+    pub stats: AdmissionStats,
 }
 
 impl AdmissionManager {
@@ -147,9 +194,17 @@ impl AdmissionManager {
             local_node_id: node_id,
             replay_cache: ReplayCache::new(),
             peer_registry: Mutex::new(HashMap::new()),
+            skew_tolerance_secs: DEFAULT_SKEW_TOLERANCE_SECS,
+            stats: AdmissionStats::default(),
         }
     }
 
+    #[allow(dead_code)]
+    pub fn with_skew_tolerance(mut self, secs: u64) -> Self {
+        self.skew_tolerance_secs = secs;
+        self
+    }
+
     /// Explicitly pin a key (System trust)
     pub fn pin_key(&self, node_id: String, public_key: Vec<u8>) {
         let mut registry = self.peer_registry.lock().unwrap();
@@ -167,7 +222,7 @@ impl AdmissionManager {
         let mut registry = self.peer_registry.lock().unwrap();
         registry.insert(node_id, PeerState {
             public_key,
-            trust_level: TrustLevel::New,
+            trust_level: TrustLevel::Authenticated,
             last_seen: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
             packet_count: 0,
         });
@@ -195,22 +250,27 @@ impl AdmissionManager {
         };
 
         // 3. Rejected State Check
-        if state.trust_level == TrustLevel::Rejected {
+        if state.trust_level == TrustLevel::Blacklisted {
             return false;
         }
 
         // 4. Signature Verification
-        if let Err(e) = packet.verify(&state.public_key) {
-            warn!("Protocol: REJECT. Sig Violation from {}: {}", packet.header.node_id, e);
-            state.trust_level = TrustLevel::Rejected; // Terminal downgrade
-            return false;
+        match packet.verify_with_tolerance(&state.public_key, self.skew_tolerance_secs) {
+            Ok(true) => {}
+            // Mismatch or out of window (logged by verify)
+            Ok(false) => return false,
+            Err(e) => {
+                warn!("Protocol: REJECT. Sig Violation from {}: {}", packet.header.node_id, e);
+                state.trust_level = TrustLevel::Blacklisted; // Terminal downgrade
+                return false;
+            }
         }
 
         // 5. Trust Promotion Logic
         state.packet_count += 1;
         state.last_seen = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
         
-        if state.trust_level == TrustLevel::New && state.packet_count >= 10 {
+        if state.trust_level == TrustLevel::Authenticated && state.packet_count >= 10 {
             info!("Protocol: PROMOTING {} to Probation", packet.header.node_id);
             state.trust_level = TrustLevel::Probation;
         }
@@ -218,11 +278,75 @@ impl AdmissionManager {
         true
     }
 
+    /// Decode a raw inbound payload and run it through `should_admit`
+    pub fn admit_bytes(&self, bytes: &[u8]) -> Admission {
+        let packet = match SignedPacket::from_bytes(bytes) {
+            Ok(packet) => packet,
+            Err(e) => {
+                let total = self.stats.malformed.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!("Protocol: UNPARSEABLE inbound payload ({} bytes): {} [{} total]", bytes.len(), e, total);
+                return Admission::Malformed;
+            }
+        };
+
+        if self.should_admit(&packet) {
+            self.stats.admitted.fetch_add(1, Ordering::Relaxed);
+            Admission::Admitted(packet)
+        } else {
+            let total = self.stats.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("Protocol: NOT ADMITTED packet from {} [{} total]", packet.header.node_id, total);
+            Admission::Rejected
+        }
+    }
+
     fn penalize(&self, node_id: &str) {
         let mut registry = self.peer_registry.lock().unwrap();
         if let Some(state) = registry.get_mut(node_id) {
             warn!("Protocol: Security violation. Downgrading trust for {}", node_id);
-            state.trust_level = TrustLevel::Rejected;
+            state.trust_level = TrustLevel::Blacklisted;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skewed_packet_passes_under_widened_window() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public = key.verifying_key().to_bytes();
+        let now = 1_700_000_000;
+        // Sender's clock runs 15 minutes slow
+        let packet = SignedPacket::sign_at("drifter", &key, b"hello".to_vec(), now - 900);
+
+        assert!(!packet.verify_at(&public, DEFAULT_SKEW_TOLERANCE_SECS, now).unwrap());
+        assert!(packet.verify_at(&public, 1200, now).unwrap());
+
+        // Widening the window does not excuse a bad signature
+        let mut forged = packet.clone();
+        forged.payload = b"tampered".to_vec();
+        assert!(!forged.verify_at(&public, 1200, now).unwrap());
+    }
+
+    #[test]
+    fn test_inbound_bytes_are_admitted_or_counted() {
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let admission = AdmissionManager::new("local".to_string());
+        admission.pin_key("peer".to_string(), key.verifying_key().to_bytes().to_vec());
+
+        let packet = SignedPacket::sign("peer", &key, b"thought".to_vec()).unwrap();
+        match admission.admit_bytes(&packet.to_bytes().unwrap()) {
+            Admission::Admitted(admitted) => assert_eq!(admitted.payload, b"thought"),
+            other => panic!("expected admission, got {:?}", other),
+        }
+
+        assert!(matches!(admission.admit_bytes(b"\xffnot a packet"), Admission::Malformed));
+        // Same packet again is a replay: parsed fine, refused
+        assert!(matches!(admission.admit_bytes(&packet.to_bytes().unwrap()), Admission::Rejected));
+
+        assert_eq!(admission.stats.admitted.load(Ordering::Relaxed), 1);
+        assert_eq!(admission.stats.malformed.load(Ordering::Relaxed), 1);
+        assert_eq!(admission.stats.rejected.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,20 +1,27 @@
 // Consolidated Resource Management System
 // Merges body economy controller with HAL budgeting semantics
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, oneshot, RwLock};
 use serde::{Deserialize, Serialize};
+use nervous_system::clock::{self, SharedClock};
 
 // Unified Resource Types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceAllocation {
+    /// Handle for watching or referring to this allocation
+    #[serde(default)]
+    pub id: String,
     pub component: String,
     pub resource_type: ResourceType,
     pub amount: f64,
     pub priority: Priority,
     pub allocated_at: u64,
     pub expires_at: Option<u64>,
+    /// May be revoked early to make room for a higher-priority request
+    #[serde(default)]
+    pub preemptible: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -35,6 +42,22 @@ pub enum Priority {
     Low,        // Background tasks
 }
 
+impl Priority {
+    fn rank(&self) -> u8 {
+        match self {
+            Priority::Critical => 3,
+            Priority::High => 2,
+            Priority::Medium => 1,
+            Priority::Low => 0,
+        }
+    }
+
+    /// Allowed to revoke preemptible lower-priority allocations
+    fn can_preempt(&self) -> bool {
+        matches!(self, Priority::Critical | Priority::High)
+    }
+}
+
 // Consolidated Economy System (combines body economy + HAL budgeting)
 #[derive(Debug)]
 pub struct UnifiedResourceManager {
@@ -42,6 +65,50 @@ pub struct UnifiedResourceManager {
     resource_pools: Arc<RwLock<ResourcePools>>,
     budget_ledger: Arc<RwLock<BudgetLedger>>,
     policies: Arc<RwLock<ResourcePolicies>>,
+    clock: SharedClock,
+    /// Holders waiting to hear that their allocation was reclaimed
+    watchers: Arc<RwLock<HashMap<String, Vec<oneshot::Sender<AllocationExpired>>>>>,
+    expirations: broadcast::Sender<AllocationExpired>,
+    /// The node's own standing, applied to every component it runs
+    node_reputation: Option<Arc<dyn ReputationSource>>,
+}
+
+/// Current economy reputation of this node, read on every allocation
+#[async_trait::async_trait]
+pub trait ReputationSource: Send + Sync {
+    async fn reputation(&self) -> f32;
+}
+
+impl std::fmt::Debug for dyn ReputationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReputationSource")
+    }
+}
+
+#[async_trait::async_trait]
+impl ReputationSource for RwLock<nervous_system::economy::EconomyController> {
+    async fn reputation(&self) -> f32 {
+        self.read().await.wallet.reputation
+    }
+}
+
+/// Sent to holders when an allocation is reclaimed, so they stop using it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AllocationExpired {
+    pub allocation_id: String,
+    pub component: String,
+    pub resource_type: ResourceType,
+    pub amount: f64,
+    pub reason: ExpiryReason,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryReason {
+    /// Its duration ran out
+    Expired,
+    /// Revoked to make room for a higher-priority request
+    Preempted,
 }
 
 #[derive(Debug, Default)]
@@ -61,6 +128,74 @@ pub struct BudgetLedger {
     pub allocations: HashMap<String, f64>, // component -> budget
     pub expenditures: HashMap<String, f64>, // component -> spent
     pub reputation_weights: HashMap<String, f64>, // component -> reputation multiplier
+    /// component -> grants still inside the quota window; quota checks read only these
+    pub allocation_history: HashMap<String, VecDeque<UsageSample>>,
+    /// component -> expenditures still inside the quota window
+    pub expenditure_history: HashMap<String, VecDeque<UsageSample>>,
+}
+
+/// Economy reputation that maps to a neutral weight of 1.0 (genesis wallet)
+const BASE_REPUTATION: f32 = 10.0;
+const MIN_REPUTATION_WEIGHT: f64 = 0.1;
+const MAX_REPUTATION_WEIGHT: f64 = 2.0;
+
+fn reputation_weight(reputation: f32) -> f64 {
+    ((reputation / BASE_REPUTATION) as f64).clamp(MIN_REPUTATION_WEIGHT, MAX_REPUTATION_WEIGHT)
+}
+
+/// One allocation or expenditure by a component
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UsageSample {
+    pub timestamp: u64,
+    pub resource_type: ResourceType,
+    pub allocated: f64,
+    pub spent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageHistory {
+    pub component: String,
+    pub window_secs: u64,
+    pub samples: Vec<UsageSample>,
+    pub allocated_in_window: f64,
+    pub spent_in_window: f64,
+}
+
+impl BudgetLedger {
+    /// Log a grant, dropping the component's grants older than `since`
+    fn record_allocation(&mut self, component: &str, sample: UsageSample, since: u64) {
+        Self::record_in(&mut self.allocation_history, component, sample, since);
+    }
+
+    /// Log an expenditure, dropping the component's expenditures older than `since`
+    fn record_expenditure(&mut self, component: &str, sample: UsageSample, since: u64) {
+        Self::record_in(&mut self.expenditure_history, component, sample, since);
+    }
+
+    // Pruned by age only: a count cap would let a burst of samples evict
+    // grants that still count against the quota
+    fn record_in(log: &mut HashMap<String, VecDeque<UsageSample>>, component: &str, sample: UsageSample, since: u64) {
+        let samples = log.entry(component.to_string()).or_default();
+        while samples.front().is_some_and(|s| s.timestamp < since) {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// Reputation multiplier for `component` (1.0 when unknown)
+    fn weight(&self, component: &str) -> f64 {
+        self.reputation_weights.get(component).copied().unwrap_or(1.0)
+    }
+
+    /// Amount of `resource_type` granted to `component` since `since`
+    fn allocated_since(&self, component: &str, resource_type: &ResourceType, since: u64) -> f64 {
+        self.allocation_history.get(component)
+            .map(|samples| samples.iter()
+                .filter(|s| s.timestamp >= since && &s.resource_type == resource_type)
+                .map(|s| s.allocated)
+                .sum())
+            .unwrap_or(0.0)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -69,6 +204,20 @@ pub struct ResourcePolicies {
     pub priority_multipliers: HashMap<Priority, f64>,
     pub debt_conservation_enabled: bool,
     pub auto_reclaim_threshold: f64, // percentage
+    /// Most any one component may be granted per rolling window
+    pub component_quotas: HashMap<ResourceType, f64>,
+    /// Per-component overrides of `component_quotas`
+    pub component_quota_overrides: HashMap<String, HashMap<ResourceType, f64>>,
+    pub quota_window_secs: u64,
+}
+
+impl ResourcePolicies {
+    fn quota_for(&self, component: &str, resource_type: &ResourceType) -> Option<f64> {
+        self.component_quota_overrides.get(component)
+            .and_then(|quotas| quotas.get(resource_type))
+            .or_else(|| self.component_quotas.get(resource_type))
+            .copied()
+    }
 }
 
 impl UnifiedResourceManager {
@@ -102,14 +251,63 @@ impl UnifiedResourceManager {
                 ].iter().cloned().collect(),
                 debt_conservation_enabled: true,
                 auto_reclaim_threshold: 0.8,
+                // Four full-size grants per hour
+                component_quotas: [
+                    (ResourceType::CpuCores, 16.0),
+                    (ResourceType::MemoryBytes, 32.0 * 1024.0 * 1024.0 * 1024.0),
+                    (ResourceType::NetworkBandwidth, 2000.0),
+                    (ResourceType::CognitiveTokens, 2000.0),
+                    (ResourceType::EconomicBudget, 20000.0),
+                ].iter().cloned().collect(),
+                component_quota_overrides: HashMap::new(),
+                quota_window_secs: 3600,
             })),
+            clock: clock::system(),
+            watchers: Arc::new(RwLock::new(HashMap::new())),
+            expirations: broadcast::channel(256).0,
+            node_reputation: None,
         }
     }
 
+    /// Weight every allocation by the node's reputation as well as its
+    /// component's own (see `set_reputation`)
+    pub fn with_reputation_source(mut self, source: Arc<dyn ReputationSource>) -> Self {
+        self.node_reputation = Some(source);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Weight `component`'s quota and reachable share of a pool by its
+    /// economy reputation (10.0 = neutral)
+    pub async fn set_reputation(&self, component: &str, reputation: f32) {
+        let weight = reputation_weight(reputation);
+        self.budget_ledger.write().await.reputation_weights.insert(component.to_string(), weight);
+    }
+
+    /// Cap what `component` may be granted of `resource_type` per quota window
+    pub async fn set_component_quota(&self, component: &str, resource_type: ResourceType, quota: f64) {
+        self.policies.write().await.component_quota_overrides
+            .entry(component.to_string())
+            .or_default()
+            .insert(resource_type, quota);
+    }
+
     // Allocate resources (combines body economy allocation + HAL budgeting)
     pub async fn allocate_resource(&self, request: ResourceRequest) -> Result<ResourceAllocation, AllocationError> {
+        // Read before taking any of our locks
+        let node_weight = match &self.node_reputation {
+            Some(source) => reputation_weight(source.reputation().await),
+            None => 1.0,
+        };
         let policies = self.policies.read().await;
+        // Same lock order as release_expired_allocations
+        let mut allocations = self.allocations.write().await;
         let mut pools = self.resource_pools.write().await;
+        let mut ledger = self.budget_ledger.write().await;
         
         // Check policy limits
         let max_allowed = policies.max_allocation_per_component.get(&request.resource_type)
@@ -125,75 +323,200 @@ impl UnifiedResourceManager {
             .unwrap_or(1.0);
         
         let adjusted_amount = request.amount * priority_multiplier;
+        let weight = (ledger.weight(&request.component) * node_weight)
+            .clamp(MIN_REPUTATION_WEIGHT, MAX_REPUTATION_WEIGHT);
+
+        // Check the component's rolling-window quota, scaled by reputation
+        let now = self.clock.unix_secs();
+        let since = now.saturating_sub(policies.quota_window_secs);
+        if let Some(quota) = policies.quota_for(&request.component, &request.resource_type) {
+            let used = ledger.allocated_since(&request.component, &request.resource_type, since);
+            if used + adjusted_amount > quota * weight {
+                return Err(AllocationError::QuotaExceeded);
+            }
+        }
 
-        // Check availability
-        let available = self.get_available_amount(&mut pools, &request.resource_type);
+        // Check availability, revoking preemptible lower-priority holders if allowed.
+        // Low-reputation components only reach a share of what's left.
+        let available = self.get_available_amount(&pools, &request.resource_type) * weight.min(1.0);
+        let mut preempted = Vec::new();
         if adjusted_amount > available {
-            return Err(AllocationError::InsufficientResources);
+            if !request.priority.can_preempt() {
+                return Err(AllocationError::InsufficientResources);
+            }
+            let victims = Self::preemption_victims(&allocations, &request, adjusted_amount - available)
+                .ok_or(AllocationError::InsufficientResources)?;
+            for id in victims {
+                if let Some(victim) = allocations.remove(&id) {
+                    self.return_resources(&mut pools, &victim.resource_type, victim.amount);
+                    println!("Preempted {} {:?} from {} for {} (priority: {:?})",
+                             victim.amount, victim.resource_type, victim.component, request.component, request.priority);
+                    preempted.push(victim);
+                }
+            }
         }
 
         // Allocate resources
         self.consume_resources(&mut pools, &request.resource_type, adjusted_amount);
 
         let allocation = ResourceAllocation {
+            id: format!("alloc_{}", uuid::Uuid::new_v4()),
             component: request.component.clone(),
             resource_type: request.resource_type.clone(),
             amount: adjusted_amount,
             priority: request.priority.clone(),
-            allocated_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            expires_at: request.duration.map(|d| {
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() + d
-            }),
+            allocated_at: now,
+            expires_at: request.duration.map(|d| now + d),
+            preemptible: request.preemptible,
         };
 
         // Store allocation
-        let allocation_id = format!("alloc_{}", uuid::Uuid::new_v4());
-        let mut allocations = self.allocations.write().await;
-        allocations.insert(allocation_id.clone(), allocation.clone());
+        allocations.insert(allocation.id.clone(), allocation.clone());
+        drop(allocations);
+        drop(pools);
 
         // Update budget ledger
-        let mut ledger = self.budget_ledger.write().await;
         *ledger.allocations.entry(allocation.component.clone())
             .or_insert(0.0) += adjusted_amount;
+        ledger.record_allocation(&allocation.component, UsageSample {
+            timestamp: now,
+            resource_type: allocation.resource_type.clone(),
+            allocated: adjusted_amount,
+            spent: 0.0,
+        }, since);
+        drop(ledger);
 
         println!("Allocated {} {:?} to {} (priority: {:?})", 
                  adjusted_amount, request.resource_type, request.component, request.priority);
 
+        for victim in preempted {
+            self.notify_expired(victim, ExpiryReason::Preempted).await;
+        }
+
         Ok(allocation)
     }
 
     // Release resources (automatic cleanup)
     pub async fn release_expired_allocations(&self) -> usize {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = self.clock.unix_secs();
+
+        let mut expired = Vec::new();
+        {
+            let mut allocations = self.allocations.write().await;
+            let mut pools = self.resource_pools.write().await;
+
+            allocations.retain(|_id, alloc| {
+                match alloc.expires_at {
+                    Some(expiry) if now >= expiry => {
+                        self.return_resources(&mut pools, &alloc.resource_type, alloc.amount);
+                        expired.push(alloc.clone());
+                        false // Remove from allocations
+                    }
+                    _ => true, // Keep live and indefinite allocations
+                }
+            });
+        }
 
-        let mut released_count = 0;
-        let mut allocations = self.allocations.write().await;
-        let mut pools = self.resource_pools.write().await;
+        let released_count = expired.len();
+        for alloc in expired {
+            self.notify_expired(alloc, ExpiryReason::Expired).await;
+        }
+        released_count
+    }
 
-        allocations.retain(|_id, alloc| {
-            if let Some(expiry) = alloc.expires_at {
-                if now >= expiry {
-                    self.return_resources(&mut pools, &alloc.resource_type, alloc.amount);
-                    released_count += 1;
-                    false // Remove from allocations
-                } else {
-                    true // Keep allocation
-                }
-            } else {
-                true // Keep indefinite allocations
+    /// Record what a component actually consumed
+    pub async fn record_expenditure(&self, component: &str, resource_type: ResourceType, amount: f64) {
+        let window_secs = self.policies.read().await.quota_window_secs;
+        let now = self.clock.unix_secs();
+        let mut ledger = self.budget_ledger.write().await;
+        *ledger.expenditures.entry(component.to_string()).or_insert(0.0) += amount;
+        ledger.record_expenditure(component, UsageSample {
+            timestamp: now,
+            resource_type,
+            allocated: 0.0,
+            spent: amount,
+        }, now.saturating_sub(window_secs));
+    }
+
+    /// Allocations and expenditures of `component` within the quota window
+    pub async fn usage_history(&self, component: &str) -> UsageHistory {
+        let window_secs = self.policies.read().await.quota_window_secs;
+        let since = self.clock.unix_secs().saturating_sub(window_secs);
+        let ledger = self.budget_ledger.read().await;
+
+        let mut samples: Vec<UsageSample> = [&ledger.allocation_history, &ledger.expenditure_history]
+            .into_iter()
+            .filter_map(|log| log.get(component))
+            .flatten()
+            .filter(|s| s.timestamp >= since)
+            .cloned()
+            .collect();
+        samples.sort_by_key(|s| s.timestamp);
+        UsageHistory {
+            component: component.to_string(),
+            window_secs,
+            allocated_in_window: samples.iter().map(|s| s.allocated).sum(),
+            spent_in_window: samples.iter().map(|s| s.spent).sum(),
+            samples,
+        }
+    }
+
+    /// Get told once when `allocation_id` is reclaimed. None if it isn't held.
+    pub async fn watch_allocation(&self, allocation_id: &str) -> Option<oneshot::Receiver<AllocationExpired>> {
+        if !self.allocations.read().await.contains_key(allocation_id) {
+            return None;
+        }
+        let (tx, rx) = oneshot::channel();
+        self.watchers.write().await.entry(allocation_id.to_string()).or_default().push(tx);
+        Some(rx)
+    }
+
+    /// Every reclaimed allocation, for consumers that track many at once
+    pub fn subscribe_expirations(&self) -> broadcast::Receiver<AllocationExpired> {
+        self.expirations.subscribe()
+    }
+
+    /// Youngest preemptible lower-priority allocations of the requested type
+    /// that together free at least `shortfall`; None if they can't
+    fn preemption_victims(
+        allocations: &HashMap<String, ResourceAllocation>,
+        request: &ResourceRequest,
+        shortfall: f64,
+    ) -> Option<Vec<String>> {
+        let mut candidates: Vec<&ResourceAllocation> = allocations.values()
+            .filter(|a| a.preemptible
+                && a.resource_type == request.resource_type
+                && a.priority.rank() < request.priority.rank())
+            .collect();
+        candidates.sort_by(|a, b| b.allocated_at.cmp(&a.allocated_at));
+
+        let mut freed = 0.0;
+        let mut victims = Vec::new();
+        for candidate in candidates {
+            if freed >= shortfall {
+                break;
             }
-        });
+            freed += candidate.amount;
+            victims.push(candidate.id.clone());
+        }
+        (freed >= shortfall).then_some(victims)
+    }
 
-        released_count
+    async fn notify_expired(&self, alloc: ResourceAllocation, reason: ExpiryReason) {
+        let event = AllocationExpired {
+            allocation_id: alloc.id,
+            component: alloc.component,
+            resource_type: alloc.resource_type,
+            amount: alloc.amount,
+            reason,
+        };
+        if let Some(watchers) = self.watchers.write().await.remove(&event.allocation_id) {
+            for watcher in watchers {
+                // The holder may have dropped its receiver already
+                let _ = watcher.send(event.clone());
+            }
+        }
+        let _ = self.expirations.send(event);
     }
 
     // Get system metrics (for monitoring)
@@ -256,12 +579,16 @@ pub struct ResourceRequest {
     pub amount: f64,
     pub priority: Priority,
     pub duration: Option<u64>, // seconds
+    /// Holder accepts being revoked for higher-priority work
+    pub preemptible: bool,
 }
 
 #[derive(Debug)]
 pub enum AllocationError {
     InsufficientResources,
     ExceedsPolicyLimit,
+    /// The component used up its rolling-window quota
+    QuotaExceeded,
     UnsupportedResource,
     InvalidRequest,
 }
@@ -287,10 +614,11 @@ impl UnifiedResourceManager {
             amount: bandwidth_mbps,
             priority: Priority::High,
             duration: Some(300), // 5 minutes
+            preemptible: false,
         };
         
         self.allocate_resource(request).await
-            .map(|_alloc| format!("network_alloc_{}", uuid::Uuid::new_v4()))
+            .map(|alloc| alloc.id)
     }
 
     // Interface for HAL cognitive components
@@ -301,9 +629,178 @@ impl UnifiedResourceManager {
             amount: tokens,
             priority: Priority::Medium,
             duration: Some(60), // 1 minute
+            preemptible: false,
         };
         
         self.allocate_resource(request).await
-            .map(|_alloc| format!("cog_alloc_{}", uuid::Uuid::new_v4()))
+            .map(|alloc| alloc.id)
+    }
+}
+
+// Cerebrum reserves its thinking tokens here; a refusal degrades it to memory-only
+#[async_trait::async_trait]
+impl cerebellum::degrade::CognitiveBudget for UnifiedResourceManager {
+    async fn reserve(&self, tokens: f64) -> bool {
+        self.allocate_cognitive_budget("cerebrum", tokens).await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nervous_system::MockClock;
+
+    #[tokio::test]
+    async fn test_expired_allocation_notifies_holder() {
+        let clock = MockClock::starting_now();
+        let manager = UnifiedResourceManager::new().with_clock(clock.clone());
+
+        let id = manager.allocate_cognitive_budget("cortex", 100.0).await.unwrap();
+        let mut expired = manager.watch_allocation(&id).await.expect("allocation is held");
+        let mut all = manager.subscribe_expirations();
+
+        assert_eq!(manager.release_expired_allocations().await, 0);
+        assert!(expired.try_recv().is_err());
+
+        clock.advance(chrono::Duration::seconds(61));
+        assert_eq!(manager.release_expired_allocations().await, 1);
+
+        let event = expired.await.unwrap();
+        assert_eq!(event.allocation_id, id);
+        assert_eq!(event.component, "cortex");
+        assert_eq!(event.resource_type, ResourceType::CognitiveTokens);
+        assert_eq!(event.reason, ExpiryReason::Expired);
+        assert_eq!(all.recv().await.unwrap(), event);
+        assert!(manager.watch_allocation(&id).await.is_none());
+    }
+
+    fn cpu_request(component: &str, amount: f64, priority: Priority, preemptible: bool) -> ResourceRequest {
+        ResourceRequest {
+            component: component.to_string(),
+            resource_type: ResourceType::CpuCores,
+            amount,
+            priority,
+            duration: None,
+            preemptible,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_critical_request_preempts_low_allocation() {
+        let manager = UnifiedResourceManager::new();
+
+        // 8 cores: 4 + 2.4 + 1.2 + 0.3 held, 0.1 left
+        manager.allocate_resource(cpu_request("kernel", 4.0, Priority::Critical, false)).await.unwrap();
+        manager.allocate_resource(cpu_request("cortex", 4.0, Priority::Medium, false)).await.unwrap();
+        let batch = manager.allocate_resource(cpu_request("indexer", 4.0, Priority::Low, true)).await.unwrap();
+        let pinned = manager.allocate_resource(cpu_request("archiver", 1.0, Priority::Low, false)).await.unwrap();
+        let mut revoked = manager.watch_allocation(&batch.id).await.unwrap();
+
+        // Without preemption rights the request just fails
+        let medium = manager.allocate_resource(cpu_request("dreamer", 4.0, Priority::Medium, false)).await;
+        assert!(matches!(medium, Err(AllocationError::InsufficientResources)));
+        assert!(revoked.try_recv().is_err());
+
+        let critical = manager.allocate_resource(cpu_request("immune", 1.0, Priority::Critical, false)).await;
+        assert!(critical.is_ok());
+
+        let event = revoked.await.unwrap();
+        assert_eq!(event.component, "indexer");
+        assert_eq!(event.reason, ExpiryReason::Preempted);
+        assert!(manager.watch_allocation(&batch.id).await.is_none());
+        assert!(manager.watch_allocation(&pinned.id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_windowed_quota_denies_greedy_component() {
+        let clock = MockClock::starting_now();
+        let manager = UnifiedResourceManager::new().with_clock(clock.clone());
+        manager.set_component_quota("indexer", ResourceType::CpuCores, 5.0).await;
+
+        // 2 x 4 cores at Critical = 8 > 5
+        let first = manager.allocate_resource(cpu_request("indexer", 4.0, Priority::Critical, false)).await.unwrap();
+        let second = manager.allocate_resource(cpu_request("indexer", 4.0, Priority::Critical, false)).await;
+        assert!(matches!(second, Err(AllocationError::QuotaExceeded)));
+
+        // Other components have their own budget
+        assert!(manager.allocate_resource(cpu_request("cortex", 1.0, Priority::Critical, false)).await.is_ok());
+
+        manager.record_expenditure("indexer", ResourceType::CpuCores, 2.5).await;
+        let usage = manager.usage_history("indexer").await;
+        assert_eq!(usage.samples.len(), 2);
+        assert_eq!(usage.allocated_in_window, first.amount);
+        assert_eq!(usage.spent_in_window, 2.5);
+
+        // Once the window rolls past, the quota is available again
+        clock.advance(chrono::Duration::seconds(3601));
+        assert!(manager.usage_history("indexer").await.samples.is_empty());
+        assert!(manager.allocate_resource(cpu_request("indexer", 2.0, Priority::Critical, false)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_node_reputation_from_economy_weights_allocations() {
+        use nervous_system::economy::EconomyController;
+
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = nervous_system::NodeSecrets::generate();
+        let economy = Arc::new(RwLock::new(EconomyController::new("node", dir.path(), &secrets).unwrap()));
+        economy.write().await.wallet.reputation = 5.0;
+        let manager = UnifiedResourceManager::new().with_reputation_source(economy.clone());
+
+        // 7 of 8 cores taken, one left
+        manager.allocate_resource(cpu_request("kernel", 4.0, Priority::Critical, false)).await.unwrap();
+        manager.allocate_resource(cpu_request("immune", 3.0, Priority::Critical, false)).await.unwrap();
+
+        // Half reputation only reaches half of what's left, for any component
+        let denied = manager.allocate_resource(cpu_request("cortex", 1.0, Priority::Critical, false)).await;
+        assert!(matches!(denied, Err(AllocationError::InsufficientResources)));
+
+        // Reputation is read per allocation, so recovery applies at once
+        economy.write().await.wallet.reputation = 10.0;
+        assert!(manager.allocate_resource(cpu_request("cortex", 1.0, Priority::Critical, false)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expenditures_never_evict_quota_samples() {
+        let manager = UnifiedResourceManager::new();
+        manager.set_component_quota("indexer", ResourceType::CpuCores, 5.0).await;
+        manager.allocate_resource(cpu_request("indexer", 4.0, Priority::Critical, false)).await.unwrap();
+
+        for _ in 0..2000 {
+            manager.record_expenditure("indexer", ResourceType::CpuCores, 0.001).await;
+        }
+        let again = manager.allocate_resource(cpu_request("indexer", 4.0, Priority::Critical, false)).await;
+        assert!(matches!(again, Err(AllocationError::QuotaExceeded)));
+    }
+
+    #[tokio::test]
+    async fn test_many_small_grants_all_count_toward_quota() {
+        let manager = UnifiedResourceManager::new();
+        manager.set_component_quota("indexer", ResourceType::CpuCores, 1.15).await;
+        for _ in 0..1100 {
+            manager.allocate_resource(cpu_request("indexer", 0.001, Priority::Critical, false)).await.unwrap();
+        }
+
+        // 1.1 granted; every grant is still in the window
+        let over = manager.allocate_resource(cpu_request("indexer", 0.06, Priority::Critical, false)).await;
+        assert!(matches!(over, Err(AllocationError::QuotaExceeded)));
+        assert_eq!(manager.usage_history("indexer").await.samples.len(), 1100);
+    }
+
+    #[tokio::test]
+    async fn test_higher_reputation_wins_last_unit() {
+        let manager = UnifiedResourceManager::new();
+        manager.set_reputation("gossip", 5.0).await;
+        manager.set_reputation("cortex", 20.0).await;
+
+        // 7 of 8 cores taken, one left
+        manager.allocate_resource(cpu_request("kernel", 4.0, Priority::Critical, false)).await.unwrap();
+        manager.allocate_resource(cpu_request("immune", 3.0, Priority::Critical, false)).await.unwrap();
+
+        let low = manager.allocate_resource(cpu_request("gossip", 1.0, Priority::Critical, false)).await;
+        assert!(matches!(low, Err(AllocationError::InsufficientResources)));
+
+        let high = manager.allocate_resource(cpu_request("cortex", 1.0, Priority::Critical, false)).await;
+        assert!(high.is_ok());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,426 @@
+// Restricted process execution for the /v1/execute tools
+// Commands run with a cleared environment, under CPU and address-space
+// rlimits and a wall-clock timeout, in their own process group so a timeout
+// takes down everything they spawned. On Linux each command gets a user +
+// mount namespace whose root is a small read-only tmpfs holding only the
+// system directories (/usr, /bin, /lib, /etc, ...), a few /dev nodes and the
+// sandbox dir at /sandbox; the rest of node_root and the host filesystem are
+// unreachable. Unless a tool needs the network it also gets a fresh network
+// namespace, so only a downed loopback exists. Where that isolation can't be
+// set up the command is refused rather than run unjailed.
+
+use anyhow::{anyhow, Result};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Where the sandbox dir appears inside the jail
+pub const JAIL_SANDBOX: &str = "/sandbox";
+
+/// Host directories exposed read-only inside the jail, when they exist
+const SYSTEM_DIRS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc"];
+const DEVICES: &[&str] = &["/dev/null", "/dev/zero", "/dev/urandom"];
+
+#[derive(Debug, Clone)]
+pub struct SandboxLimits {
+    /// RLIMIT_CPU: seconds of CPU time
+    pub cpu_secs: u64,
+    /// RLIMIT_AS: bytes of address space
+    pub memory_bytes: u64,
+    /// Killed after this long regardless of CPU use
+    pub wall_clock: Duration,
+    pub allow_network: bool,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            cpu_secs: 10,
+            memory_bytes: 512 * 1024 * 1024,
+            wall_clock: Duration::from_secs(30),
+            allow_network: false,
+        }
+    }
+}
+
+impl SandboxLimits {
+    pub fn with_network(mut self) -> Self {
+        self.allow_network = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SandboxOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// None when killed by a signal (rlimit or timeout)
+    pub status: Option<i32>,
+    pub timed_out: bool,
+}
+
+impl SandboxOutput {
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
+}
+
+pub struct Sandbox {
+    root: PathBuf,
+    // Empty mount point the jail's tmpfs root is built on; lives outside `root`
+    jail: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new(node_root: &Path) -> Result<Self> {
+        let root = node_root.join("sandbox");
+        let jail = node_root.join(".sandbox-jail");
+        std::fs::create_dir_all(&root)?;
+        std::fs::create_dir_all(&jail)?;
+        Ok(Self { root, jail })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Resolve a caller-supplied relative path inside the jail, as the host sees it
+    pub fn path(&self, relative: &str) -> Result<PathBuf> {
+        Ok(self.root.join(checked_relative(relative)?))
+    }
+
+    // The same path as a jailed command sees it
+    pub fn jailed_path(&self, relative: &str) -> Result<PathBuf> {
+        Ok(Path::new(JAIL_SANDBOX).join(checked_relative(relative)?))
+    }
+
+    pub async fn run(&self, program: &str, args: &[String], limits: &SandboxLimits) -> Result<SandboxOutput> {
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .env_clear()
+            .env("PATH", "/usr/local/bin:/usr/bin:/bin")
+            .env("HOME", JAIL_SANDBOX)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        restrict(&mut cmd, &self.root, &self.jail, limits)?;
+
+        let child = cmd.spawn().map_err(|e| anyhow!("Sandbox could not start {}: {}", program, e))?;
+        let pid = child.id();
+        match tokio::time::timeout(limits.wall_clock, child.wait_with_output()).await {
+            Ok(output) => {
+                let output = output?;
+                Ok(SandboxOutput {
+                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                    status: output.status.code(),
+                    timed_out: false,
+                })
+            }
+            // Kill the whole group first; dropping the future then drops the
+            // child, and kill_on_drop reaps it
+            Err(_) => {
+                if let Some(pid) = pid {
+                    kill_group(pid);
+                }
+                Ok(SandboxOutput {
+                    stdout: String::new(),
+                    stderr: format!("killed after {:?}", limits.wall_clock),
+                    status: None,
+                    timed_out: true,
+                })
+            }
+        }
+    }
+}
+
+fn checked_relative(relative: &str) -> Result<&Path> {
+    let rel = Path::new(relative);
+    if rel.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(anyhow!("Path must stay inside the sandbox: {}", relative));
+    }
+    Ok(rel)
+}
+
+#[cfg(target_os = "linux")]
+fn kill_group(pid: u32) {
+    // The child called setpgid(0, 0), so its pid is the group id
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kill_group(_pid: u32) {}
+
+#[cfg(target_os = "linux")]
+mod jail {
+    use anyhow::{anyhow, Result};
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    struct Bind {
+        source: CString,
+        target: CString,
+        // Flags the host mount already carries; a remount inside a user
+        // namespace must keep them or the kernel refuses it
+        locked: libc::c_ulong,
+    }
+
+    /// Everything the child needs, built before fork so the pre_exec hook
+    /// only makes syscalls
+    pub struct Plan {
+        root: CString,
+        old_root: CString,
+        dev: CString,
+        sandbox: Bind,
+        system: Vec<Bind>,
+        devices: Vec<Bind>,
+        // Our own uid/gid mapped to itself; unmapped ids can't create files
+        uid_map: String,
+        gid_map: String,
+        cpu: libc::rlimit,
+        memory: libc::rlimit,
+        namespaces: libc::c_int,
+    }
+
+    fn cstring(path: &Path) -> Result<CString> {
+        CString::new(path.as_os_str().as_bytes()).map_err(|_| anyhow!("Path contains NUL: {:?}", path))
+    }
+
+    fn locked_flags(path: &Path) -> Result<libc::c_ulong> {
+        let c = cstring(path)?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c.as_ptr(), &mut stat) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let pairs = [
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ];
+        Ok(pairs.iter().filter(|(st, _)| stat.f_flag & st != 0).fold(0, |acc, (_, ms)| acc | ms))
+    }
+
+    fn bind(source: &Path, jail: &Path) -> Result<Bind> {
+        let relative = source.strip_prefix("/").unwrap_or(source);
+        Ok(Bind {
+            source: cstring(source)?,
+            target: cstring(&jail.join(relative))?,
+            locked: locked_flags(source)?,
+        })
+    }
+
+    impl Plan {
+        pub fn new(sandbox: &Path, jail: &Path, cpu_secs: u64, memory_bytes: u64, isolate_network: bool) -> Result<Self> {
+            let system = super::SYSTEM_DIRS.iter()
+                .map(Path::new)
+                .filter(|p| p.is_dir())
+                .map(|p| bind(p, jail))
+                .collect::<Result<Vec<_>>>()?;
+            let devices = super::DEVICES.iter()
+                .map(Path::new)
+                .filter(|p| p.exists())
+                .map(|p| bind(p, jail))
+                .collect::<Result<Vec<_>>>()?;
+            let mut sandbox_bind = bind(sandbox, jail)?;
+            sandbox_bind.target = cstring(&jail.join(super::JAIL_SANDBOX.trim_start_matches('/')))?;
+
+            let mut namespaces = libc::CLONE_NEWUSER | libc::CLONE_NEWNS;
+            if isolate_network {
+                namespaces |= libc::CLONE_NEWNET;
+            }
+            Ok(Self {
+                root: cstring(jail)?,
+                old_root: cstring(&jail.join(".old"))?,
+                dev: cstring(&jail.join("dev"))?,
+                sandbox: sandbox_bind,
+                system,
+                devices,
+                cpu: libc::rlimit { rlim_cur: cpu_secs as libc::rlim_t, rlim_max: cpu_secs as libc::rlim_t },
+                memory: libc::rlimit { rlim_cur: memory_bytes as libc::rlim_t, rlim_max: memory_bytes as libc::rlim_t },
+                uid_map: format!("{0} {0} 1", unsafe { libc::getuid() }),
+                gid_map: format!("{0} {0} 1", unsafe { libc::getgid() }),
+                namespaces,
+            })
+        }
+
+        /// Runs in the forked child before exec: only async-signal-safe calls
+        pub fn enter(&self) -> std::io::Result<()> {
+            fn check(ret: libc::c_int) -> std::io::Result<()> {
+                if ret != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            }
+            fn write(path: &std::ffi::CStr, contents: &[u8]) -> std::io::Result<()> {
+                unsafe {
+                    let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                    if fd < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+                    libc::close(fd);
+                    if written != contents.len() as isize {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            }
+            let none = std::ptr::null::<libc::c_char>();
+            let mount_dir = |bind: &Bind, flags: libc::c_ulong| unsafe {
+                check(libc::mkdir(bind.target.as_ptr(), 0o755))?;
+                check(libc::mount(bind.source.as_ptr(), bind.target.as_ptr(), none, libc::MS_BIND | libc::MS_REC, none.cast()))?;
+                if flags != 0 {
+                    check(libc::mount(none, bind.target.as_ptr(), none, libc::MS_REMOUNT | libc::MS_BIND | flags | bind.locked, none.cast()))?;
+                }
+                Ok::<_, std::io::Error>(())
+            };
+
+            unsafe {
+                check(libc::setpgid(0, 0))?;
+                check(libc::setrlimit(libc::RLIMIT_CPU, &self.cpu))?;
+                check(libc::setrlimit(libc::RLIMIT_AS, &self.memory))?;
+                check(libc::unshare(self.namespaces))?;
+                write(c"/proc/self/setgroups", b"deny")?;
+                write(c"/proc/self/uid_map", self.uid_map.as_bytes())?;
+                write(c"/proc/self/gid_map", self.gid_map.as_bytes())?;
+
+                // Nothing below may propagate back to the host
+                check(libc::mount(none, c"/".as_ptr(), none, libc::MS_REC | libc::MS_PRIVATE, none.cast()))?;
+                check(libc::mount(c"tmpfs".as_ptr(), self.root.as_ptr(), c"tmpfs".as_ptr(),
+                    libc::MS_NOSUID | libc::MS_NODEV, c"size=64k,mode=0755".as_ptr().cast()))?;
+
+                for bind in &self.system {
+                    mount_dir(bind, libc::MS_RDONLY | libc::MS_NOSUID)?;
+                }
+                mount_dir(&self.sandbox, libc::MS_NOSUID)?;
+                check(libc::mkdir(self.dev.as_ptr(), 0o755))?;
+                for device in &self.devices {
+                    let fd = libc::open(device.target.as_ptr(), libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC, 0o644);
+                    if fd < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    libc::close(fd);
+                    check(libc::mount(device.source.as_ptr(), device.target.as_ptr(), none, libc::MS_BIND, none.cast()))?;
+                }
+
+                // pivot_root rather than chroot: a chroot can be climbed out
+                // of with CAP_SYS_CHROOT, which the namespace grants
+                check(libc::mkdir(self.old_root.as_ptr(), 0o700))?;
+                if libc::syscall(libc::SYS_pivot_root, self.root.as_ptr(), self.old_root.as_ptr()) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                check(libc::chdir(c"/".as_ptr()))?;
+                check(libc::umount2(c"/.old".as_ptr(), libc::MNT_DETACH))?;
+                check(libc::rmdir(c"/.old".as_ptr()))?;
+                check(libc::mount(none, c"/".as_ptr(), none,
+                    libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV, none.cast()))?;
+                check(libc::chdir(c"/sandbox".as_ptr()))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn restrict(cmd: &mut Command, sandbox: &Path, jail: &Path, limits: &SandboxLimits) -> Result<()> {
+    let plan = jail::Plan::new(sandbox, jail, limits.cpu_secs, limits.memory_bytes, !limits.allow_network)?;
+    unsafe {
+        cmd.pre_exec(move || plan.enter());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn restrict(_cmd: &mut Command, _sandbox: &Path, _jail: &Path, _limits: &SandboxLimits) -> Result<()> {
+    Err(anyhow!("Filesystem isolation is unavailable on this platform; refusing to run"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_sandbox() -> (Sandbox, PathBuf) {
+        let base = std::env::temp_dir().join(format!("sandbox_{}", uuid::Uuid::new_v4()));
+        (Sandbox::new(&base).unwrap(), base)
+    }
+
+    // No unprivileged namespaces here: refused, never run unjailed
+    fn assert_refused(e: anyhow::Error) {
+        assert!(e.to_string().contains("could not start"), "{}", e);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_network_is_denied() {
+        let (sandbox, base) = temp_sandbox();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let args = vec!["-c".to_string(), format!("exec 3<>/dev/tcp/127.0.0.1/{}", port)];
+        match sandbox.run("bash", &args, &SandboxLimits::default()).await {
+            // Fresh namespace: the host's loopback listener is unreachable
+            Ok(out) => assert!(!out.success(), "connected out of the sandbox"),
+            Err(e) => assert_refused(e),
+        }
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_node_root_is_hidden() {
+        let (sandbox, base) = temp_sandbox();
+        std::fs::write(base.join("identity.key"), "secret").unwrap();
+        std::fs::write(sandbox.path("visible.txt").unwrap(), "inside").unwrap();
+
+        let outside = vec![base.join("identity.key").to_string_lossy().to_string()];
+        match sandbox.run("cat", &outside, &SandboxLimits::default()).await {
+            Ok(out) => {
+                assert!(!out.success());
+                assert!(!out.stdout.contains("secret"));
+
+                let inside = vec!["visible.txt".to_string()];
+                let out = sandbox.run("cat", &inside, &SandboxLimits::default()).await.unwrap();
+                assert!(out.success(), "{}", out.stderr);
+                assert_eq!(out.stdout, "inside");
+            }
+            Err(e) => assert_refused(e),
+        }
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_wall_clock_timeout_kills_group() {
+        let (sandbox, base) = temp_sandbox();
+        let limits = SandboxLimits { wall_clock: Duration::from_millis(200), ..Default::default() }.with_network();
+        // The backgrounded subshell outlives its parent unless the group dies
+        let args = vec!["-c".to_string(), "(sleep 1; touch escaped) & sleep 5".to_string()];
+        match sandbox.run("sh", &args, &limits).await {
+            Ok(out) => {
+                assert!(out.timed_out);
+                assert!(!out.success());
+                tokio::time::sleep(Duration::from_millis(1500)).await;
+                assert!(!sandbox.path("escaped").unwrap().exists());
+            }
+            Err(e) => assert_refused(e),
+        }
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn test_paths_stay_inside() {
+        let (sandbox, base) = temp_sandbox();
+        assert_eq!(sandbox.path("repos/x").unwrap(), sandbox.root().join("repos/x"));
+        assert_eq!(sandbox.jailed_path("repos/x").unwrap(), Path::new("/sandbox/repos/x"));
+        assert!(sandbox.path("../escape").is_err());
+        assert!(sandbox.path("/etc").is_err());
+        assert!(sandbox.jailed_path("../escape").is_err());
+        let _ = std::fs::remove_dir_all(base);
+    }
+}
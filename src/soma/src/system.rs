@@ -0,0 +1,132 @@
+//! IPPOC - Integrated Persistent Programmable Organism Core
+//!
+//! One-call assembly of a node's subsystems: the mesh (which owns the
+//! economy), long-term memory and the Cerebrum built on it. Memory and
+//! cognition need the `memory` feature; without it only the mesh is built.
+
+use nervous_system::{AiMesh, MeshConfig};
+
+#[cfg(feature = "memory")]
+use std::sync::Arc;
+#[cfg(feature = "memory")]
+use hidb::MemoryStore;
+
+#[cfg(feature = "memory")]
+const DEFAULT_DATABASE_URL: &str = "postgresql://localhost/ippoc";
+#[cfg(feature = "memory")]
+const DEFAULT_REDIS_URL: &str = "redis://localhost";
+
+/// Main IPPOC system entry point
+pub struct IPPOC {
+    pub network: AiMesh,
+    #[cfg(feature = "memory")]
+    pub memory: Arc<dyn MemoryStore>,
+    #[cfg(feature = "memory")]
+    pub brain: cerebellum::Cerebrum,
+}
+
+impl IPPOC {
+    /// Initialize the complete IPPOC system against local Postgres/Redis
+    pub async fn new() -> Result<Self, anyhow::Error> {
+        Self::builder().build().await
+    }
+
+    /// Assemble a system from injected parts; unset parts use production defaults
+    pub fn builder() -> IPPOCBuilder {
+        IPPOCBuilder::default()
+    }
+
+    /// Start all system components
+    pub async fn start(&self) -> Result<(), anyhow::Error> {
+        self.network.start_networking().await?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct IPPOCBuilder {
+    mesh_config: Option<MeshConfig>,
+    networking: Option<bool>,
+    #[cfg(feature = "memory")]
+    memory: Option<Arc<dyn MemoryStore>>,
+    #[cfg(feature = "memory")]
+    database_url: Option<String>,
+    #[cfg(feature = "memory")]
+    redis_url: Option<String>,
+}
+
+impl IPPOCBuilder {
+    pub fn mesh_config(mut self, config: MeshConfig) -> Self {
+        self.mesh_config = Some(config);
+        self
+    }
+
+    /// `false` keeps the mesh offline: `start` binds no ports
+    pub fn networking(mut self, enabled: bool) -> Self {
+        self.networking = Some(enabled);
+        self
+    }
+
+    /// Use this store instead of connecting to HiDB (e.g. `InMemoryStore` in tests)
+    #[cfg(feature = "memory")]
+    pub fn memory(mut self, store: Arc<dyn MemoryStore>) -> Self {
+        self.memory = Some(store);
+        self
+    }
+
+    #[cfg(feature = "memory")]
+    pub fn database_urls(mut self, database_url: impl Into<String>, redis_url: impl Into<String>) -> Self {
+        self.database_url = Some(database_url.into());
+        self.redis_url = Some(redis_url.into());
+        self
+    }
+
+    pub async fn build(self) -> Result<IPPOC, anyhow::Error> {
+        let mut mesh_config = self.mesh_config.unwrap_or_default();
+        if let Some(enabled) = self.networking {
+            mesh_config.networking = enabled;
+        }
+        let network = AiMesh::new(mesh_config)?.0;
+
+        #[cfg(feature = "memory")]
+        let memory: Arc<dyn MemoryStore> = match self.memory {
+            Some(store) => store,
+            None => {
+                let database_url = self.database_url.as_deref().unwrap_or(DEFAULT_DATABASE_URL);
+                let redis_url = self.redis_url.as_deref().unwrap_or(DEFAULT_REDIS_URL);
+                Arc::new(hidb::init(database_url, redis_url).await?)
+            }
+        };
+        #[cfg(feature = "memory")]
+        let brain = cerebellum::Cerebrum::new(memory.clone());
+
+        Ok(IPPOC {
+            network,
+            #[cfg(feature = "memory")]
+            memory,
+            #[cfg(feature = "memory")]
+            brain,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_offline_bootstrap_without_services() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let builder = IPPOC::builder()
+            .mesh_config(MeshConfig {
+                data_dir: data_dir.path().to_path_buf(),
+                ..Default::default()
+            })
+            .networking(false);
+        #[cfg(feature = "memory")]
+        let builder = builder.memory(Arc::new(hidb::InMemoryStore::new()));
+
+        let ippoc = builder.build().await.unwrap();
+        ippoc.start().await.unwrap();
+    }
+}
@@ -4,40 +4,161 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use ed25519_dalek::{SigningKey, VerifyingKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use sha2::{Sha256, Digest};
+use serde::{Deserialize, Serialize};
+use nervous_system::persist::{atomic_write, atomic_write_private};
+
+/// Set to boot on hardware that doesn't match the stored fingerprint (re-seals it)
+pub const HARDWARE_OVERRIDE_ENV: &str = "IPPOC_ALLOW_HARDWARE_CHANGE";
+const IDENTITY_FILE: &str = "unified_identity.json";
 
 // Consolidated Identity System
+// Our own identity: the only kind that holds a private key
 #[derive(Debug, Clone)]
-pub struct UnifiedIdentity {
+pub struct LocalIdentity {
     pub node_id: String,
     pub signing_key: SigningKey,
     pub verifying_key: VerifyingKey,
     pub hardware_fingerprint: String,
+    pub creation_timestamp: u64,
+    pub hardware_binding: HardwareBinding,
+}
+
+// How the identity's hardware binding was established on this boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HardwareBinding {
+    // First boot: identity created on this machine
+    New,
+    // Stored fingerprint matched the running hardware
+    Verified,
+    // Hardware differed and the override re-bound it
+    Rebound,
+}
+
+impl LocalIdentity {
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.signing_key.sign(message)
+    }
+
+    // How the trust table sees us
+    pub fn as_peer(&self, trust_level: TrustLevel) -> PeerIdentity {
+        PeerIdentity {
+            node_id: self.node_id.clone(),
+            verifying_key: self.verifying_key,
+            hardware_fingerprint: self.hardware_fingerprint.clone(),
+            trust_level,
+            creation_timestamp: self.creation_timestamp,
+        }
+    }
+}
+
+// Identity in the trust table: verify-only, never a private key
+#[derive(Debug, Clone)]
+pub struct PeerIdentity {
+    pub node_id: String,
+    pub verifying_key: VerifyingKey,
+    pub hardware_fingerprint: String,
     pub trust_level: TrustLevel,
     pub creation_timestamp: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum TrustLevel {
-    New,        // Fresh identity, minimal privileges
-    Probation,  // Handshake completed, limited access
-    Trusted,    // Established trust, full privileges
-    System,     // Local/system identity
-    Rejected,   // Security violation, blocked
+impl PeerIdentity {
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        self.verifying_key.verify(message, signature).is_ok()
+    }
 }
 
-impl Default for TrustLevel {
-    fn default() -> Self {
-        TrustLevel::New
+pub use nervous_system::TrustLevel;
+
+// Hardware the identity is bound to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardwareProfile {
+    pub hostname: String,
+    pub cpu_brand: String,
+    pub memory_bytes: u64,
+}
+
+impl HardwareProfile {
+    pub fn current() -> Self {
+        let mut sys = sysinfo::System::new_all();
+        sys.refresh_all();
+        Self {
+            hostname: sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            cpu_brand: sys.cpus().first().map(|c| c.brand()).unwrap_or("unknown").to_string(),
+            memory_bytes: sys.total_memory(),
+        }
+    }
+
+    // Same machine: memory may differ by up to 5% (firmware reservations, rounding)
+    pub fn matches(&self, other: &Self) -> bool {
+        let memory_slack = self.memory_bytes.max(other.memory_bytes) / 20;
+        self.hostname == other.hostname
+            && self.cpu_brand == other.cpu_brand
+            && self.memory_bytes.abs_diff(other.memory_bytes) <= memory_slack
+    }
+
+    pub fn fingerprint(&self) -> String {
+        let memory_gb = (self.memory_bytes as f64 / (1024.0 * 1024.0 * 1024.0)).round() as u64;
+        format!("{}-{}-{}", self.hostname, self.cpu_brand, memory_gb)
+    }
+}
+
+// On-disk form of the local identity
+#[derive(Serialize, Deserialize)]
+struct PersistedIdentity {
+    signing_key: String, // hex
+    hardware: HardwareProfile,
+    creation_timestamp: u64,
+}
+
+// On-disk form of a trust table entry
+#[derive(Serialize, Deserialize)]
+struct PersistedPeer {
+    node_id: String,
+    verifying_key: String, // hex
+    hardware_fingerprint: String,
+    trust_level: TrustLevel,
+    creation_timestamp: u64,
+}
+
+impl From<&PeerIdentity> for PersistedPeer {
+    fn from(peer: &PeerIdentity) -> Self {
+        Self {
+            node_id: peer.node_id.clone(),
+            verifying_key: hex::encode(peer.verifying_key.as_bytes()),
+            hardware_fingerprint: peer.hardware_fingerprint.clone(),
+            trust_level: peer.trust_level,
+            creation_timestamp: peer.creation_timestamp,
+        }
+    }
+}
+
+impl TryFrom<PersistedPeer> for PeerIdentity {
+    type Error = anyhow::Error;
+
+    fn try_from(peer: PersistedPeer) -> anyhow::Result<Self> {
+        let key_bytes: [u8; 32] = hex::decode(&peer.verifying_key)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("verifying key of {} must be 32 bytes", peer.node_id))?;
+        Ok(Self {
+            node_id: peer.node_id,
+            verifying_key: VerifyingKey::from_bytes(&key_bytes)?,
+            hardware_fingerprint: peer.hardware_fingerprint,
+            trust_level: peer.trust_level,
+            creation_timestamp: peer.creation_timestamp,
+        })
     }
 }
 
 // Unified Trust Manager (combines body AdmissionManager + HAL TrustStateMachine)
 pub struct UnifiedTrustManager {
-    identities: Arc<RwLock<HashMap<String, UnifiedIdentity>>>,
+    identities: Arc<RwLock<HashMap<String, PeerIdentity>>>,
     trust_policies: Arc<RwLock<TrustPolicies>>,
     replay_cache: Arc<RwLock<ReplayCache>>,
+    // Trust table file; in-memory only when None
+    store_path: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Default)]
@@ -61,7 +182,7 @@ impl UnifiedTrustManager {
             trust_policies: Arc::new(RwLock::new(TrustPolicies {
                 minimum_trust_for_network: TrustLevel::Probation,
                 packet_rate_limits: [
-                    (TrustLevel::New, 5),
+                    (TrustLevel::Authenticated, 5),
                     (TrustLevel::Probation, 50),
                     (TrustLevel::Trusted, 1000),
                     (TrustLevel::System, 10000),
@@ -73,53 +194,139 @@ impl UnifiedTrustManager {
                 nonces: HashMap::new(),
                 max_age: 300, // 5 minutes
             })),
+            store_path: None,
+        }
+    }
+
+    // Persist identities and trust levels to `path` after every change
+    pub fn with_store(mut self, path: std::path::PathBuf) -> Self {
+        self.store_path = Some(path);
+        self
+    }
+
+    // Restore the trust table from the store; returns how many entries were loaded
+    pub async fn load(&self) -> anyhow::Result<usize> {
+        let Some(path) = &self.store_path else { return Ok(0) };
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let persisted: Vec<PersistedPeer> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let mut identities = self.identities.write().await;
+        for entry in persisted {
+            let peer = PeerIdentity::try_from(entry)?;
+            identities.insert(peer.node_id.clone(), peer);
         }
+        Ok(identities.len())
     }
 
-    // Create new identity (consolidates NodeIdentity::pre_determine)
-    pub fn create_identity(&self, storage_base: &std::path::Path) -> anyhow::Result<UnifiedIdentity> {
-        let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.store_path else { return Ok(()) };
+
+        let persisted: Vec<PersistedPeer> = self.identities.read().await.values().map(PersistedPeer::from).collect();
+        atomic_write(path, serde_json::to_string_pretty(&persisted)?.as_bytes())?;
+        Ok(())
+    }
+
+    // Load the local identity, or create and persist one (consolidates NodeIdentity::pre_determine).
+    // Refuses to boot on different hardware unless IPPOC_ALLOW_HARDWARE_CHANGE is set,
+    // since a key that moved machines may have been stolen.
+    pub async fn create_identity(&self, storage_base: &std::path::Path) -> anyhow::Result<LocalIdentity> {
+        let allow_change = std::env::var(HARDWARE_OVERRIDE_ENV).map(|v| v == "1" || v == "true").unwrap_or(false);
+        self.load_or_create_identity(storage_base, HardwareProfile::current(), allow_change).await
+    }
+
+    async fn load_or_create_identity(
+        &self,
+        storage_base: &std::path::Path,
+        hardware: HardwareProfile,
+        allow_change: bool,
+    ) -> anyhow::Result<LocalIdentity> {
+        let path = storage_base.join(IDENTITY_FILE);
+        let mut binding = HardwareBinding::Verified;
+        let persisted = if path.exists() {
+            let mut persisted: PersistedIdentity = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            if !persisted.hardware.matches(&hardware) {
+                if !allow_change {
+                    return Err(anyhow::anyhow!(
+                        "Hardware mismatch: identity is bound to {}, but running on {}. Set {}=1 to re-bind it.",
+                        persisted.hardware.fingerprint(), hardware.fingerprint(), HARDWARE_OVERRIDE_ENV
+                    ));
+                }
+                tracing::warn!(
+                    "Hardware changed from {} to {}; re-binding identity ({} set)",
+                    persisted.hardware.fingerprint(), hardware.fingerprint(), HARDWARE_OVERRIDE_ENV
+                );
+                persisted.hardware = hardware;
+                Self::save_identity(&path, &persisted)?;
+                binding = HardwareBinding::Rebound;
+            }
+            persisted
+        } else {
+            binding = HardwareBinding::New;
+            let persisted = PersistedIdentity {
+                signing_key: hex::encode(SigningKey::generate(&mut rand::rngs::OsRng).to_bytes()),
+                hardware,
+                creation_timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs(),
+            };
+            Self::save_identity(&path, &persisted)?;
+            persisted
+        };
+
+        let key_bytes: [u8; 32] = hex::decode(&persisted.signing_key)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Corrupt identity file {:?}: signing key must be 32 bytes", path))?;
+        let signing_key = SigningKey::from_bytes(&key_bytes);
         let verifying_key: VerifyingKey = (&signing_key).into();
         let node_id = hex::encode(Sha256::digest(verifying_key.as_bytes()));
-        
-        let hardware_fingerprint = self.generate_hardware_fingerprint();
-        
-        let identity = UnifiedIdentity {
+
+        let identity = LocalIdentity {
             node_id: node_id.clone(),
             signing_key,
             verifying_key,
-            hardware_fingerprint,
-            trust_level: TrustLevel::New,
-            creation_timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs(),
+            hardware_fingerprint: persisted.hardware.fingerprint(),
+            creation_timestamp: persisted.creation_timestamp,
+            hardware_binding: binding,
         };
 
-        // Register self as system identity
-        let mut identities = self.identities.blocking_write();
-        identities.insert(node_id, identity.clone());
+        // Register self as system identity (public half only), keeping any
+        // trust level restored from the store for this same key
+        {
+            let mut identities = self.identities.write().await;
+            let known = identities.get(&node_id)
+                .filter(|peer| peer.verifying_key == identity.verifying_key)
+                .map(|peer| peer.trust_level);
+            identities.insert(node_id, identity.as_peer(known.unwrap_or(TrustLevel::Authenticated)));
+        }
+        self.save().await?;
 
         Ok(identity)
     }
 
+    fn save_identity(path: &std::path::Path, persisted: &PersistedIdentity) -> anyhow::Result<()> {
+        atomic_write_private(path, serde_json::to_string_pretty(persisted)?.as_bytes())
+    }
+
     // Register peer identity (combines AdmissionManager::register_handshake)
     pub async fn register_peer(&self, node_id: String, public_key: Vec<u8>) -> anyhow::Result<()> {
-        let verifying_key = VerifyingKey::from_bytes(public_key[..32].try_into()?)?;
+        let key_bytes = <[u8; 32]>::try_from(public_key.as_slice())
+            .map_err(|_| anyhow::anyhow!("public_key must be 32 bytes, got {}", public_key.len()))?;
+        let verifying_key = VerifyingKey::from_bytes(&key_bytes)?;
         
-        let identity = UnifiedIdentity {
+        let identity = PeerIdentity {
             node_id: node_id.clone(),
-            signing_key: SigningKey::from_bytes(&[0u8; 32]), // Placeholder
             verifying_key,
             hardware_fingerprint: "remote_peer".to_string(),
-            trust_level: TrustLevel::New,
+            trust_level: TrustLevel::Authenticated,
             creation_timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs(),
         };
 
-        let mut identities = self.identities.write().await;
-        identities.insert(node_id, identity);
-        Ok(())
+        self.identities.write().await.insert(node_id, identity);
+        self.save().await
     }
 
     // Evaluate trust for packet (combines AdmissionManager::should_admit + TrustStateMachine logic)
@@ -133,13 +340,13 @@ impl UnifiedTrustManager {
         let policies = self.trust_policies.read().await;
         
         // Check minimum trust level
-        if self.trust_level_rank(&identity.trust_level) < self.trust_level_rank(&policies.minimum_trust_for_network) {
+        if identity.trust_level < policies.minimum_trust_for_network {
             return TrustEvaluation::reject("Insufficient trust level");
         }
 
         // Packet type restrictions based on trust level
         match &identity.trust_level {
-            TrustLevel::New if packet_type != "SYN" => {
+            TrustLevel::Authenticated if packet_type != "SYN" => {
                 return TrustEvaluation::reject("New peers can only send SYN packets");
             }
             TrustLevel::Probation if !["SYN", "SYN-ACK", "HEARTBEAT"].contains(&packet_type) => {
@@ -153,23 +360,25 @@ impl UnifiedTrustManager {
 
     // Promote trust level (combines TrustStateMachine::promote)
     pub async fn promote_trust(&self, node_id: &str) -> anyhow::Result<()> {
-        let mut identities = self.identities.write().await;
-        let identity = identities.get_mut(node_id).ok_or_else(|| anyhow::anyhow!("Identity not found"))?;
-        
-        match identity.trust_level {
-            TrustLevel::New => {
-                identity.trust_level = TrustLevel::Probation;
-                println!("Promoted {} to Probation", node_id);
-            }
-            TrustLevel::Probation => {
-                // Check promotion threshold (simplified)
-                identity.trust_level = TrustLevel::Trusted;
-                println!("Promoted {} to Trusted", node_id);
+        {
+            let mut identities = self.identities.write().await;
+            let identity = identities.get_mut(node_id).ok_or_else(|| anyhow::anyhow!("Identity not found"))?;
+
+            match identity.trust_level {
+                TrustLevel::Authenticated => {
+                    identity.trust_level = TrustLevel::Probation;
+                    println!("Promoted {} to Probation", node_id);
+                }
+                TrustLevel::Probation => {
+                    // Check promotion threshold (simplified)
+                    identity.trust_level = TrustLevel::Trusted;
+                    println!("Promoted {} to Trusted", node_id);
+                }
+                _ => {} // Already at highest level
             }
-            _ => {} // Already at highest level
         }
-        
-        Ok(())
+
+        self.save().await
     }
 
     // Replay protection (consolidates ReplayCache functionality)
@@ -193,26 +402,6 @@ impl UnifiedTrustManager {
             false
         }
     }
-
-    // Utility functions
-    fn generate_hardware_fingerprint(&self) -> String {
-        let mut sys = sysinfo::System::new_all();
-        sys.refresh_all();
-        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
-        let cpu_info = sys.cpus().first().map(|c| c.brand()).unwrap_or("unknown").to_string();
-        let mem_total = sys.total_memory();
-        format!("{}-{}-{}", hostname, cpu_info, mem_total)
-    }
-
-    fn trust_level_rank(&self, level: &TrustLevel) -> u8 {
-        match level {
-            TrustLevel::Rejected => 0,
-            TrustLevel::New => 1,
-            TrustLevel::Probation => 2,
-            TrustLevel::Trusted => 3,
-            TrustLevel::System => 4,
-        }
-    }
 }
 
 #[derive(Debug)]
@@ -226,7 +415,7 @@ impl TrustEvaluation {
     pub fn allow(level: &TrustLevel) -> Self {
         Self {
             allowed: true,
-            trust_level: level.clone(),
+            trust_level: *level,
             reason: None,
         }
     }
@@ -234,7 +423,7 @@ impl TrustEvaluation {
     pub fn reject(reason: &str) -> Self {
         Self {
             allowed: false,
-            trust_level: TrustLevel::Rejected,
+            trust_level: TrustLevel::Blacklisted,
             reason: Some(reason.to_string()),
         }
     }
@@ -254,11 +443,199 @@ impl UnifiedTrustManager {
         evaluation.allowed
     }
     
+    pub async fn peer_identity(&self, node_id: &str) -> Option<PeerIdentity> {
+        self.identities.read().await.get(node_id).cloned()
+    }
+
     // Interface for HAL integration
     pub async fn get_trust_level(&self, node_id: &str) -> TrustLevel {
         let identities = self.identities.read().await;
         identities.get(node_id)
-            .map(|id| id.trust_level.clone())
-            .unwrap_or(TrustLevel::New)
+            .map(|id| id.trust_level)
+            .unwrap_or(TrustLevel::Unknown)
+    }
+}
+
+// Public description of this node for GET /v1/identity: the mesh identity peers
+// pin, plus the hardware binding. Only public keys, never secret material.
+pub fn identity_document(node: &nervous_system::NodeIdentity, local: &LocalIdentity) -> serde_json::Value {
+    serde_json::json!({
+        "id": node.id,
+        "name": node.name,
+        "role": node.role,
+        "signing_public": hex::encode(node.signing_public),
+        "exchange_public": hex::encode(node.exchange_public),
+        "hardware": {
+            "node_id": local.node_id,
+            "verifying_key": hex::encode(local.verifying_key.as_bytes()),
+            "fingerprint": local.hardware_fingerprint,
+            "binding": local.hardware_binding,
+        },
+        "created_at": local.creation_timestamp,
+    })
+}
+
+/// Share of a memory's confidence kept on recall, by the trust of the peer it
+/// came from; None means the memory is ignored and purged
+fn recall_weight(level: TrustLevel) -> Option<f32> {
+    match level {
+        TrustLevel::Blacklisted => None,
+        TrustLevel::Unknown => Some(0.2),
+        TrustLevel::Discovered => Some(0.3),
+        TrustLevel::Authenticated => Some(0.5),
+        TrustLevel::Probation => Some(0.7),
+        TrustLevel::Trusted => Some(0.9),
+        TrustLevel::System => Some(1.0),
+    }
+}
+
+#[async_trait::async_trait]
+impl hidb::SourceTrust for UnifiedTrustManager {
+    async fn weight(&self, source: &hidb::Provenance) -> Option<f32> {
+        match source {
+            hidb::Provenance::Peer(node_id) => recall_weight(self.get_trust_level(node_id).await),
+            // Unvetted web content
+            hidb::Provenance::Search(_) => Some(0.6),
+            hidb::Provenance::SelfLearned | hidb::Provenance::User => Some(1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn laptop() -> HardwareProfile {
+        HardwareProfile {
+            hostname: "soma-01".into(),
+            cpu_brand: "AMD Ryzen 7 7840U".into(),
+            memory_bytes: 32 * 1024 * 1024 * 1024,
+        }
+    }
+
+    fn temp_storage() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("unified_identity_{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_identity_reloads_on_same_hardware() {
+        let storage = temp_storage();
+        let first = UnifiedTrustManager::new().load_or_create_identity(&storage, laptop(), false).await.unwrap();
+
+        // Slightly less memory reported after a firmware update is still the same machine
+        let mut same = laptop();
+        same.memory_bytes -= 200 * 1024 * 1024;
+        let second = UnifiedTrustManager::new().load_or_create_identity(&storage, same, false).await.unwrap();
+
+        assert_eq!(first.node_id, second.node_id);
+        assert_eq!(first.creation_timestamp, second.creation_timestamp);
+        assert_eq!(first.hardware_binding, HardwareBinding::New);
+        assert_eq!(second.hardware_binding, HardwareBinding::Verified);
+        let _ = std::fs::remove_dir_all(storage);
+    }
+
+    #[tokio::test]
+    async fn test_identity_refuses_other_hardware() {
+        let storage = temp_storage();
+        let original = UnifiedTrustManager::new().load_or_create_identity(&storage, laptop(), false).await.unwrap();
+
+        let mut other = laptop();
+        other.cpu_brand = "Intel Xeon E5-2680".into();
+        let err = UnifiedTrustManager::new().load_or_create_identity(&storage, other.clone(), false).await.unwrap_err();
+        assert!(err.to_string().contains("Hardware mismatch"));
+
+        // The override re-binds the same key to the new machine
+        let moved = UnifiedTrustManager::new().load_or_create_identity(&storage, other.clone(), true).await.unwrap();
+        assert_eq!(moved.node_id, original.node_id);
+        assert_eq!(moved.hardware_binding, HardwareBinding::Rebound);
+        assert!(UnifiedTrustManager::new().load_or_create_identity(&storage, other, false).await.is_ok());
+        let _ = std::fs::remove_dir_all(storage);
+    }
+
+    #[tokio::test]
+    async fn test_create_identity_inside_runtime() {
+        let storage = temp_storage();
+        let manager = UnifiedTrustManager::new();
+
+        // Runs on the runtime's worker thread, like main does
+        let identity = manager.create_identity(&storage).await.unwrap();
+        assert_eq!(manager.get_trust_level(&identity.node_id).await, TrustLevel::Authenticated);
+        let _ = std::fs::remove_dir_all(storage);
+    }
+
+    #[tokio::test]
+    async fn test_trust_table_round_trip() {
+        let storage = temp_storage();
+        let table = storage.join("trust_table.json");
+        let peer_key = SigningKey::generate(&mut rand::rngs::OsRng);
+
+        let manager = UnifiedTrustManager::new().with_store(table.clone());
+        let local = manager.load_or_create_identity(&storage, laptop(), false).await.unwrap();
+        manager.register_peer("peer".into(), peer_key.verifying_key().to_bytes().to_vec()).await.unwrap();
+        manager.promote_trust("peer").await.unwrap();
+        manager.promote_trust("peer").await.unwrap();
+        manager.promote_trust(&local.node_id).await.unwrap();
+
+        let restarted = UnifiedTrustManager::new().with_store(table);
+        assert_eq!(restarted.load().await.unwrap(), 2);
+        let relocal = restarted.load_or_create_identity(&storage, laptop(), false).await.unwrap();
+
+        assert_eq!(relocal.node_id, local.node_id);
+        assert_eq!(restarted.get_trust_level(&local.node_id).await, TrustLevel::Probation);
+        assert_eq!(restarted.get_trust_level("peer").await, TrustLevel::Trusted);
+        let peer = restarted.peer_identity("peer").await.unwrap();
+        assert!(peer.verify(b"ping", &peer_key.sign(b"ping")));
+        let _ = std::fs::remove_dir_all(storage);
+    }
+
+    #[tokio::test]
+    async fn test_identity_document_is_public_only() {
+        let storage = temp_storage();
+        let local = UnifiedTrustManager::new().load_or_create_identity(&storage, laptop(), false).await.unwrap();
+        let mesh_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        let node = nervous_system::NodeIdentity {
+            id: nervous_system::node_id(mesh_key.verifying_key().as_bytes()),
+            exchange_public: [7u8; 32],
+            signing_public: mesh_key.verifying_key().to_bytes(),
+            role: "reasoning".into(),
+            name: "soma-01".into(),
+        };
+
+        let doc = identity_document(&node, &local);
+
+        let signing_public = hex::decode(doc["signing_public"].as_str().unwrap()).unwrap();
+        assert_eq!(doc["id"], hex::encode(Sha256::digest(&signing_public)));
+        assert_eq!(doc["hardware"]["binding"], "new");
+        assert_eq!(doc["created_at"], local.creation_timestamp);
+
+        // Neither signing key leaks
+        let text = doc.to_string();
+        for secret in [mesh_key.to_bytes(), local.signing_key.to_bytes()] {
+            assert!(!text.contains(&hex::encode(secret)));
+        }
+        let _ = std::fs::remove_dir_all(storage);
+    }
+
+    #[tokio::test]
+    async fn test_peer_identity_is_verify_only() {
+        let manager = UnifiedTrustManager::new();
+        let peer_key = SigningKey::generate(&mut rand::rngs::OsRng);
+        manager.register_peer("peer".into(), peer_key.verifying_key().to_bytes().to_vec()).await.unwrap();
+
+        let peer = manager.peer_identity("peer").await.unwrap();
+        let message = b"heartbeat";
+        assert!(peer.verify(message, &peer_key.sign(message)));
+
+        // Nothing in the table holds a key that can speak for the peer,
+        // including the all-zero placeholder it used to carry
+        let placeholder = SigningKey::from_bytes(&[0u8; 32]);
+        assert!(!peer.verify(message, &placeholder.sign(message)));
+        assert!(manager.register_peer("short".into(), vec![0u8; 8]).await.is_err());
+
+        // Trailing bytes are not silently dropped
+        let mut long = peer_key.verifying_key().to_bytes().to_vec();
+        long.push(0);
+        assert!(manager.register_peer("long".into(), long).await.is_err());
+        assert!(manager.peer_identity("long").await.is_none());
     }
-}
\ No newline at end of file
+}